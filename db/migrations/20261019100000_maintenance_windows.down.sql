@@ -0,0 +1 @@
+DROP TABLE IF EXISTS MaintenanceWindows;
\ No newline at end of file
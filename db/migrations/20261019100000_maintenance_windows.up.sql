@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS MaintenanceWindows (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    fief_id INTEGER NOT NULL,
+    weekday INTEGER,
+    start_min INTEGER NOT NULL,
+    duration_min INTEGER NOT NULL,
+    mode INTEGER NOT NULL,
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_fief_id ON MaintenanceWindows (fief_id);
@@ -0,0 +1,3 @@
+ALTER TABLE Fiefs DROP COLUMN clean_streak;
+ALTER TABLE Fiefs DROP COLUMN effective_interval_min;
+ALTER TABLE Fiefs DROP COLUMN adaptive;
\ No newline at end of file
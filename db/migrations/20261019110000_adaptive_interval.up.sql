@@ -0,0 +1,5 @@
+ALTER TABLE Fiefs ADD COLUMN adaptive BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE Fiefs ADD COLUMN effective_interval_min INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE Fiefs ADD COLUMN clean_streak INTEGER NOT NULL DEFAULT 0;
+
+UPDATE Fiefs SET effective_interval_min = check_interval_min;
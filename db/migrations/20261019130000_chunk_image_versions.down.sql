@@ -0,0 +1,3 @@
+ALTER TABLE Fiefs DROP COLUMN image_retention;
+
+DROP TABLE IF EXISTS ChunkImageVersions;
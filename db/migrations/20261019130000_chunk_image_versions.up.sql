@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS ChunkImageVersions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chunk_id INTEGER NOT NULL,
+    kind INTEGER NOT NULL,
+    img BLOB,
+    author_id INTEGER,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (chunk_id) REFERENCES Chunks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_chunk_image_versions_chunk_id ON ChunkImageVersions (chunk_id, kind);
+
+ALTER TABLE Fiefs ADD COLUMN image_retention INTEGER NOT NULL DEFAULT 10;
+
+INSERT INTO ChunkImageVersions (chunk_id, kind, img, author_id, created_at)
+SELECT id, 0, img_ref, NULL, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+FROM Chunks WHERE img_ref IS NOT NULL;
+
+INSERT INTO ChunkImageVersions (chunk_id, kind, img, author_id, created_at)
+SELECT id, 1, img_mask, NULL, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+FROM Chunks WHERE img_mask IS NOT NULL;
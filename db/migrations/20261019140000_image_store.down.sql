@@ -0,0 +1,15 @@
+-- 仅能恢复保存在 ImageBlobs 中的图片，使用文件系统存储时需要先迁移回 SQLite
+UPDATE Chunks SET img_ref = (SELECT img FROM ImageBlobs WHERE hash = ref_key) WHERE ref_key IS NOT NULL;
+UPDATE Chunks SET img_mask = (SELECT img FROM ImageBlobs WHERE hash = mask_key) WHERE mask_key IS NOT NULL;
+UPDATE Chunks SET img_diff = (SELECT img FROM ImageBlobs WHERE hash = diff_key) WHERE diff_key IS NOT NULL;
+UPDATE Chunks SET img_result = (SELECT img FROM ImageBlobs WHERE hash = result_key) WHERE result_key IS NOT NULL;
+UPDATE ChunkImageVersions SET img = (SELECT img FROM ImageBlobs WHERE hash = img_key) WHERE img_key IS NOT NULL;
+
+ALTER TABLE ChunkImageVersions DROP COLUMN img_key;
+ALTER TABLE Chunks DROP COLUMN curr_key;
+ALTER TABLE Chunks DROP COLUMN result_key;
+ALTER TABLE Chunks DROP COLUMN diff_key;
+ALTER TABLE Chunks DROP COLUMN mask_key;
+ALTER TABLE Chunks DROP COLUMN ref_key;
+
+DROP TABLE IF EXISTS ImageBlobs;
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS ImageBlobs (
+    hash TEXT PRIMARY KEY,
+    img BLOB NOT NULL
+);
+
+ALTER TABLE Chunks ADD COLUMN ref_key TEXT;
+ALTER TABLE Chunks ADD COLUMN mask_key TEXT;
+ALTER TABLE Chunks ADD COLUMN diff_key TEXT;
+ALTER TABLE Chunks ADD COLUMN result_key TEXT;
+ALTER TABLE Chunks ADD COLUMN curr_key TEXT;
+ALTER TABLE ChunkImageVersions ADD COLUMN img_key TEXT;
+
+-- 旧的 BLOB 列中的图片在启动时由 `SqlxChunkRepo::migrate_legacy_blobs` 移入图片存储并清空
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS RoleGrants (
+    role_id INTEGER NOT NULL,
+    fief_id INTEGER NOT NULL,
+    permissions INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (role_id, fief_id),
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_role_grants_fief_id ON RoleGrants (fief_id);
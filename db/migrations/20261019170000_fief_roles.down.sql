@@ -0,0 +1,16 @@
+-- SQLite 不能删除带外键约束的列，需要重建 Members
+CREATE TABLE Members_old (
+    user_id INTEGER NOT NULL,
+    fief_id INTEGER NOT NULL,
+    permissions INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, fief_id),
+    FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE,
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
+INSERT INTO Members_old (user_id, fief_id, permissions)
+    SELECT user_id, fief_id, permissions FROM Members;
+DROP TABLE Members;
+ALTER TABLE Members_old RENAME TO Members;
+CREATE INDEX IF NOT EXISTS idx_members_user_id ON Members (user_id);
+
+DROP TABLE IF EXISTS FiefRoles;
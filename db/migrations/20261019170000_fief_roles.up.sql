@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS FiefRoles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    fief_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    permissions INTEGER NOT NULL DEFAULT 0,
+    UNIQUE (fief_id, name),
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_fief_roles_fief_id ON FiefRoles (fief_id);
+
+ALTER TABLE Members ADD COLUMN role_id INTEGER REFERENCES FiefRoles(id) ON DELETE SET NULL;
@@ -0,0 +1 @@
+ALTER TABLE Fiefs DROP COLUMN owner_id;
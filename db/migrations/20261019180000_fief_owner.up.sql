@@ -0,0 +1,8 @@
+ALTER TABLE Fiefs ADD COLUMN owner_id INTEGER;
+
+-- 已有的领地以最早获得全部权限的成员（通常是创建者）作为所有者
+UPDATE Fiefs SET owner_id = (
+    SELECT user_id FROM Members
+    WHERE fief_id = Fiefs.id AND permissions = 255
+    ORDER BY rowid LIMIT 1
+);
@@ -0,0 +1,13 @@
+-- 领地被删除后仍然保留操作记录，因此 fief_id 不使用外键
+CREATE TABLE IF NOT EXISTS AuditLog (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    time TEXT NOT NULL,
+    actor_id INTEGER,
+    fief_id INTEGER,
+    action INTEGER NOT NULL,
+    target TEXT,
+    before_value TEXT,
+    after_value TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_fief_id ON AuditLog (fief_id, id);
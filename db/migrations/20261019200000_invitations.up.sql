@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS Invitations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    fief_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    inviter_id INTEGER,
+    permissions INTEGER,
+    fief_role_id INTEGER,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    UNIQUE (fief_id, user_id),
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE,
+    FOREIGN KEY (fief_role_id) REFERENCES FiefRoles(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_invitations_user_id ON Invitations (user_id);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS Dashboards;
+ALTER TABLE Fiefs DROP COLUMN last_check_failed;
@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS ImageBlobs;
+DROP TABLE IF EXISTS ChunkImageVersions;
+DROP TABLE IF EXISTS MaintenanceWindows;
+DROP TABLE IF EXISTS Chunks;
+DROP TABLE IF EXISTS Members;
+DROP TABLE IF EXISTS Fiefs;
+DROP TABLE IF EXISTS Users;
@@ -0,0 +1,71 @@
+CREATE TABLE IF NOT EXISTS Users (
+    id BIGINT PRIMARY KEY,
+    is_admin BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE IF NOT EXISTS Fiefs (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT UNIQUE NOT NULL,
+    check_interval_min BIGINT NOT NULL,
+    last_check TIMESTAMPTZ NOT NULL,
+    skip_check_until TIMESTAMPTZ NOT NULL,
+    should_check_now BOOLEAN NOT NULL,
+    adaptive BOOLEAN NOT NULL DEFAULT FALSE,
+    effective_interval_min BIGINT NOT NULL DEFAULT 0,
+    clean_streak BIGINT NOT NULL DEFAULT 0,
+    image_retention BIGINT NOT NULL DEFAULT 10
+);
+
+CREATE TABLE IF NOT EXISTS Members (
+    user_id BIGINT NOT NULL,
+    fief_id BIGINT NOT NULL,
+    permissions BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, fief_id),
+    FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE,
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS Chunks (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    fief_id BIGINT NOT NULL,
+    pos_x BIGINT NOT NULL,
+    pos_y BIGINT NOT NULL,
+    ref_key TEXT,
+    mask_key TEXT,
+    diff_key TEXT,
+    result_key TEXT,
+    curr_key TEXT,
+    diff_count BIGINT NOT NULL,
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS MaintenanceWindows (
+    id BIGSERIAL PRIMARY KEY,
+    fief_id BIGINT NOT NULL,
+    weekday BIGINT,
+    start_min BIGINT NOT NULL,
+    duration_min BIGINT NOT NULL,
+    mode BIGINT NOT NULL,
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS ChunkImageVersions (
+    id BIGSERIAL PRIMARY KEY,
+    chunk_id BIGINT NOT NULL,
+    kind BIGINT NOT NULL,
+    img_key TEXT,
+    author_id BIGINT,
+    created_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (chunk_id) REFERENCES Chunks(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS ImageBlobs (
+    hash TEXT PRIMARY KEY,
+    img BYTEA NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_members_user_id ON Members (user_id);
+CREATE INDEX IF NOT EXISTS idx_chunks_fief_id ON Chunks (fief_id);
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_fief_id ON MaintenanceWindows (fief_id);
+CREATE INDEX IF NOT EXISTS idx_chunk_image_versions_chunk_id ON ChunkImageVersions (chunk_id, kind);
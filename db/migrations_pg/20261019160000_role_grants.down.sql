@@ -0,0 +1 @@
+DROP TABLE IF EXISTS RoleGrants;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE Members DROP COLUMN IF EXISTS role_id;
+DROP TABLE IF EXISTS FiefRoles;
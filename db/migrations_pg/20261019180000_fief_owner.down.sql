@@ -0,0 +1 @@
+ALTER TABLE Fiefs DROP COLUMN IF EXISTS owner_id;
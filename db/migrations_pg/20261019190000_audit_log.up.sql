@@ -0,0 +1,13 @@
+-- 领地被删除后仍然保留操作记录，因此 fief_id 不使用外键
+CREATE TABLE IF NOT EXISTS AuditLog (
+    id BIGSERIAL PRIMARY KEY,
+    time TIMESTAMPTZ NOT NULL,
+    actor_id BIGINT,
+    fief_id BIGINT,
+    action BIGINT NOT NULL,
+    target TEXT,
+    before_value TEXT,
+    after_value TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_fief_id ON AuditLog (fief_id, id);
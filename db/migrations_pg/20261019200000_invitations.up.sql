@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS Invitations (
+    id BIGSERIAL PRIMARY KEY,
+    fief_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    inviter_id BIGINT,
+    permissions BIGINT,
+    fief_role_id BIGINT,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (fief_id, user_id),
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES Users(id) ON DELETE CASCADE,
+    FOREIGN KEY (fief_role_id) REFERENCES FiefRoles(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_invitations_user_id ON Invitations (user_id);
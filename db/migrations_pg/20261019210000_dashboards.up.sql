@@ -0,0 +1,11 @@
+-- 最近一次检查是否失败，状态面板据此显示领地的状态
+ALTER TABLE Fiefs ADD COLUMN last_check_failed BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- fief_id 为空时显示所有领地
+CREATE TABLE IF NOT EXISTS Dashboards (
+    id BIGSERIAL PRIMARY KEY,
+    fief_id BIGINT,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    FOREIGN KEY (fief_id) REFERENCES Fiefs(id) ON DELETE CASCADE
+);
@@ -0,0 +1,636 @@
+# English messages of WMonitor, every id in `zh-CN.ftl` must also exist here
+# Command descriptions at the end are only needed by non-Chinese locales
+
+## Localized option and choice names, matched by the Chinese names in `zh-CN.ftl`
+
+options =
+    .fief = fief
+    .chunk = chunk
+    .user = user
+    .role = role
+    .permissions = permissions
+    .new-name = new-name
+    .interval = interval
+    .duration = hours
+    .enabled = enabled
+    .count = count
+    .template = template
+    .archive = archive
+    .on-conflict = on-conflict
+    .weekday = weekday
+    .start = start
+    .end = end
+    .mode = mode
+    .id = id
+    .role-name = role-name
+    .expiry = hours
+    .fix = fix
+    .kind = kind
+    .version = version
+    .argument = argument
+    .image = image
+    .reference-image = reference
+    .mask-image = mask
+
+choices =
+    .everyday = Every day
+    .mon = Monday
+    .tue = Tuesday
+    .wed = Wednesday
+    .thu = Thursday
+    .fri = Friday
+    .sat = Saturday
+    .sun = Sunday
+    .skip-check = Skip checks
+    .mute-alerts = Mute alerts
+    .abort = Abort
+    .rename = Rename automatically
+    .replace = Replace the existing fief
+    .opaque = Opaque pixels
+    .colors = Specific colors
+    .flood-fill = Flood fill
+    .rect = Rectangle
+    .polygon = Polygon
+    .reference = Reference image
+    .mask = Mask image
+
+## Common
+
+error-permission-denied = Error: permission denied.
+error-grant-missing-perms = Error: you cannot grant permissions you do not have.
+error-invalid-perms = Error: `{ $perms }` contains invalid permission names.
+error-fief-not-found = Error: fief **{ $name }** does not exist.
+error-network = Network error, please try again later.
+error-mention-user = Invalid argument: please @mention a user.
+error-store-user = Error: failed to store user information: { $reason }.
+error-repo = Error: { $reason }.
+button-confirm = Confirm
+button-cancel = Cancel
+confirm-timeout = Timed out, the operation has been cancelled.
+none = None
+
+reason-not-found = the record does not exist and may have been deleted
+reason-conflict = the name is already taken
+reason-foreign-key = the related fief or user does not exist
+reason-invalid = invalid argument
+reason-storage = the database is temporarily unavailable, please try again later
+reason-decode = the stored data is corrupted, please contact an administrator
+
+## Paginated lists
+
+pages-footer = Page { $page }/{ $pages }, { $count } items{ $problems ->
+    [yes] , problems only
+   *[no] {""}
+}
+pages-empty = Nothing matches the current filter.
+pages-prev = Previous
+pages-next = Next
+pages-filter = { $problems ->
+    [yes] Show all
+   *[no] Problems only
+}
+pages-sort = Sort: { $sort ->
+    [status] status
+    [diff] diff count
+   *[default] default
+}
+status-ok = :white_check_mark: OK
+status-failed = :x: check failed
+status-damaged = :warning: damaged
+status-missing-ref = :grey_question: missing reference
+status-paused = :pause_button: paused
+
+## Basic commands
+
+fetch-waiting = Fetching the image from wplace.live, please wait...
+permissions-help =
+    # Permissions
+    ## Fief
+    - `FIEF_EDIT`: edit fief settings
+    - `FIEF_DELETE`: delete the fief
+    - `FIEF_ALL`: all fief permissions, same as `FIEF_EDIT` + `FIEF_DELETE`
+    ## Chunk
+    - `CHUNK_ADD`: add chunks to the fief
+    - `CHUNK_EDIT`: edit chunks of the fief
+    - `CHUNK_DELETE`: delete chunks of the fief
+    - `CHUNK_ALL`: all chunk permissions, as above
+    ## Member
+    - `MEMBER_INVITE`: invite members to the fief
+    - `MEMBER_EDIT_PERMS`: edit permissions of members in the fief
+    - `MEMBER_KICK`: remove members from the fief
+    - `MEMBER_ALL`: all member permissions, as above
+    ## Other
+    - `NONE`: no permissions
+    - `ALL`: all of the above
+
+## Audit log
+
+audit-truncated = *Showing the latest { $shown } of { $total } entries*
+audit-system = System
+audit-changed = : { $before } → { $after }
+audit-removed = { " " }(was { $before })
+audit-set = : { $after }
+audit-fief-create = created fief
+audit-fief-delete = deleted fief
+audit-fief-rename = renamed fief
+audit-fief-transfer = transferred fief
+audit-fief-settings = changed fief settings
+audit-fief-check = changed scheduled checks
+audit-chunk-add = added chunk
+audit-chunk-delete = deleted chunk
+audit-chunk-edit = edited chunk
+audit-chunk-image = updated chunk images
+audit-member-join = added member
+audit-member-leave = removed member
+audit-permission-change = changed permissions
+audit-admin-change = changed administrators
+audit-checker-pause = changed checker state
+audit-value-accept-invitation = accepted the invitation
+audit-value-dashboard = status dashboard
+audit-value-check-interval = check interval
+audit-value-minutes = { $minutes } minutes
+audit-value-enabled = enabled
+audit-value-disabled = disabled
+audit-value-disabled-for = disabled for { $hours } hours
+audit-value-adaptive = adaptive check interval
+audit-value-on = on
+audit-value-off = off
+audit-value-retention = retained history versions
+audit-value-restored = restored from archive
+audit-value-upload-ref = uploaded reference image
+audit-value-upload-mask = uploaded mask image
+audit-value-automask = generated mask image
+audit-value-accept-current = accepted the current state as reference
+audit-value-ref-current = updated reference to the current state
+audit-value-rollback = rolled back to version #{ $id }
+audit-value-fief-role = role { $name }
+audit-value-no-role = none
+audit-value-maintenance = maintenance window
+audit-value-pause = paused
+audit-value-resume = resumed
+audit-value-admin = administrator
+audit-value-not-admin = not an administrator
+
+## Fief
+
+fief-add-exists = Fief **{ $name }** already exists, please choose another name.
+fief-add-done = Created fief **{ $name }** (id: `{ $id }`).
+fief-remove-done = Deleted fief **{ $name }**.
+fief-rename-done = Renamed fief **{ $name }** to **{ $new_name }**.
+fief-rename-exists = Error: fief **{ $new_name }** already exists, please choose another name.
+fief-transfer-not-owner = Error: only the owner of the fief or an administrator can transfer it.
+fief-transfer-already-owner = Error: { $user } is already the owner of fief **{ $name }**.
+fief-transfer-not-member = Error: { $user } is not a member of fief **{ $name }**, please add them to the fief first.
+fief-transfer-confirm = Transfer fief **{ $name }** to { $user }? The current owner will lose the protection of ownership.
+fief-transfer-cancelled = Cancelled transferring fief **{ $name }**.
+fief-transfer-done = Transferred fief **{ $name }** to { $user }.
+fief-transfer-failed = Error: failed to transfer fief **{ $name }**: { $reason }.
+fief-settime-done = Changed the check interval of fief **{ $name }** to { $interval } minutes.
+fief-check-done = Done, fief **{ $name }** will be checked within a minute.
+fief-check-failed = Failed to schedule the check.
+fief-enable-done = Enabled scheduled checks of fief **{ $name }**.
+fief-disable-for = Disabled scheduled checks of fief **{ $name }** for { $hours } hours.
+fief-disable-done = Disabled scheduled checks of fief **{ $name }**.
+fief-adaptive-on = Enabled the adaptive check interval of fief **{ $name }**.
+fief-adaptive-off = Disabled the adaptive check interval of fief **{ $name }**, the configured interval is used again.
+fief-retention-invalid = Invalid argument: at least 1 previous version must be kept.
+fief-retention-done = Fief **{ $name }** now keeps { $retention } previous image versions besides the current one, older versions are removed on the next update.
+fief-import-empty = Error: no templates found in the file.
+fief-import-parse-failed = Error: failed to parse the template file: { $error }.
+fief-import-failed = Error: failed to import templates into fief **{ $name }**: { $error }.
+fief-import-done = Imported { $count } templates into fief **{ $name }**:
+fief-import-created = Created chunk *{ $chunk }* (`{ $x }`, `{ $y }`), { $pixels } pixels
+fief-import-merged = Merged into chunk *{ $chunk }* (`{ $x }`, `{ $y }`), { $pixels } pixels
+fief-export-failed = Error: failed to export fief **{ $name }**: { $error }.
+fief-export-done = Exported fief **{ $name }**.
+fief-restore-read-failed = Error: failed to read the archive: { $error }.
+fief-restore-replace-denied = Error: permission denied to replace fief **{ $name }**.
+fief-restore-conflict = Error: the fief name is already taken, please choose another name or conflict handling.
+fief-restore-failed = Error: failed to restore the fief: { $error }.
+fief-restore-done = Restored fief **{ $name }** with { $chunks } chunks and invited { $invited } archived members to rejoin, they can accept with `/wmuser invitations`.
+fief-info =
+    # Fief
+    Name: **{ $name }**
+    Owner: { $owner }
+    Last check: { $last_check }
+    Scheduled checks: { $check }
+    Check interval: every { $interval } minutes
+    Image versions kept: { $retention }
+fief-info-minutes-ago = { $minutes } minutes ago
+fief-info-enabled = :white_check_mark: enabled
+fief-info-disabled = :negative_squared_cross_mark: disabled
+fief-info-disabled-for = :negative_squared_cross_mark: disabled, enabled again in { $minutes } minutes
+fief-info-adaptive = Adaptive check interval: :white_check_mark: on, currently every { $interval } minutes
+fief-info-maintenance = Maintenance windows:
+fief-info-chunks = # Chunks
+fief-info-chunk =
+    - Chunk: *{ $chunk }*
+      Position: `({ $x }, { $y })`
+      Status: { $status }, diff pixels: { $diff }
+fief-info-members = # Members
+fief-info-user =
+    - User: { $user }
+      Permissions: { $perms }
+fief-info-role =
+    - Role: { $role }
+      Permissions: { $perms }
+fief-audit-empty = Fief **{ $name }** has no audit log entries yet.
+fief-audit-title = # Audit log of fief { $name }
+
+## Maintenance windows
+
+maintenance-everyday = every day
+maintenance-mon = every Monday
+maintenance-tue = every Tuesday
+maintenance-wed = every Wednesday
+maintenance-thu = every Thursday
+maintenance-fri = every Friday
+maintenance-sat = every Saturday
+maintenance-sun = every Sunday
+maintenance-skip-check = skip checks
+maintenance-mute-alerts = mute alerts
+maintenance-window = `#{ $id }` { $weekday } { $start }~{ $end } (UTC), { $mode }
+maintenance-invalid-time = Invalid argument: the time should be formatted as `HH:MM`, e.g. `20:00`.
+maintenance-add-done = Added a maintenance window to fief **{ $name }**: { $window }.
+maintenance-add-failed = Error: failed to add a maintenance window to fief **{ $name }**.
+maintenance-add-error = Error: failed to add a maintenance window to fief **{ $name }**: { $reason }.
+maintenance-list-empty = Fief **{ $name }** has no maintenance windows.
+maintenance-list-title = # Maintenance windows of fief { $name }
+maintenance-active = { " " }(active)
+maintenance-remove-done = Removed maintenance window `#{ $id }` of fief **{ $name }**.
+maintenance-remove-not-found = Error: fief **{ $name }** has no maintenance window `#{ $id }`.
+maintenance-remove-failed = Error: failed to remove the maintenance window of fief **{ $name }**: { $reason }.
+
+## Fief roles
+
+fief-role-not-found = Error: fief **{ $name }** has no role *{ $role }*.
+fief-role-create-done = Created role *{ $role }* in fief **{ $name }** with permissions: { $perms }.
+fief-role-create-exists = Error: fief **{ $name }** already has a role *{ $role }*.
+fief-role-create-failed = Error: failed to create the role in fief **{ $name }**: { $reason }.
+fief-role-edit-done = Changed the permissions of role *{ $role }* in fief **{ $name }** to: { $perms }.
+fief-role-edit-failed = Error: failed to edit role *{ $role }* of fief **{ $name }**: { $reason }.
+fief-role-remove-done = Removed role *{ $role }* from fief **{ $name }**.
+fief-role-remove-failed = Error: failed to remove role *{ $role }* from fief **{ $name }**: { $reason }.
+fief-role-list-empty = Fief **{ $name }** has no roles.
+fief-role-list-title = # Roles of fief { $name }
+fief-role-list-item =
+    - Role: *{ $role }*
+      Permissions: { $perms }
+
+## Discord roles
+
+role-not-in-fief = Error: role { $role } does not belong to fief **{ $name }**.
+role-join-done = Added role { $role } to fief **{ $name }**.
+role-join-exists = Error: role { $role } already belongs to fief **{ $name }**.
+role-join-failed = Error: failed to add role { $role } to fief **{ $name }**: { $reason }.
+role-leave-done = Removed role { $role } from fief **{ $name }**.
+role-leave-not-member = Error: role { $role } is not in fief **{ $name }** or has already been removed.
+role-leave-failed = Error: failed to remove role { $role } from fief **{ $name }**: { $reason }.
+role-allow-exists = Error: role { $role } already has permission `{ $perms }`.
+role-allow-done = Granted permission `{ $perms }` in fief **{ $name }** to role { $role }.
+role-allow-failed = Error: failed to grant permissions in fief **{ $name }** to role { $role }: { $reason }.
+role-deny-missing = Error: role { $role } does not have permission `{ $perms }`.
+role-deny-done = Revoked permission `{ $perms }` in fief **{ $name }** from role { $role }.
+role-deny-failed = Error: failed to revoke permissions in fief **{ $name }** from role { $role }: { $reason }.
+
+## Chunks
+
+chunk-not-found = Error: chunk *{ $chunk }* was not found in fief **{ $name }**.
+chunk-add-done = Created chunk *{ $chunk }* (id: `{ $id }`) in fief **{ $name }**.
+chunk-add-exists = Error: chunk *{ $chunk }* already exists in fief **{ $name }**.
+chunk-add-failed = Error: failed to create chunk *{ $chunk }* in fief **{ $name }**: { $reason }.
+chunk-remove-done = Removed chunk *{ $chunk }* from fief **{ $name }**.
+chunk-remove-failed = Error: failed to remove chunk *{ $chunk }* from fief **{ $name }**: { $reason }.
+chunk-rename-done = Renamed chunk *{ $chunk }* of fief **{ $name }** to *{ $new_name }*.
+chunk-edit-failed = Error: failed to edit chunk *{ $chunk }* of fief **{ $name }**: { $reason }.
+chunk-setref-done = Updated the reference image of chunk *{ $chunk }* in fief **{ $name }**.
+chunk-setmask-done = Updated the mask image of chunk *{ $chunk }* in fief **{ $name }**.
+chunk-refnow-done = Set the reference image of chunk *{ $chunk }* in fief **{ $name }** to its current state.
+chunk-no-ref = Error: chunk *{ $chunk }* of fief **{ $name }** has no reference image.
+chunk-rollback-done = Restored chunk *{ $chunk }* of fief **{ $name }** to version `#{ $id }`.
+chunk-rollback-not-found = Error: chunk *{ $chunk }* of fief **{ $name }** has no version `#{ $id }`.
+chunk-setpos-done = Moved chunk *{ $chunk }* of fief **{ $name }** to `({ $x }, { $y })`.
+chunk-info =
+    # Chunk
+    Name: *{ $chunk }*
+    Fief: **{ $name }**
+    Position: (`{ $x }`, `{ $y }`)
+    Reference image: { $ref_img }
+chunk-info-ref-by = Reference image updated by { $user } { $minutes } minutes ago
+chunk-info-mask = Mask image: { $mask_img }
+chunk-info-set = :white_check_mark: set
+chunk-info-unset = :negative_squared_cross_mark: not set
+
+## Image uploads and validation
+
+image-kind = { $kind ->
+    [mask] mask image
+   *[ref] reference image
+}
+upload-prompt =
+    Please send the following PNG images in this channel: { $pending }
+    Multiple images in one message are used in order (received { $received }/{ $total }).
+upload-cancelled = Upload cancelled.
+upload-timeout = Timed out, please run the command again.
+upload-no-attachment = No attachment found, please upload again.
+upload-not-png = Attachment *{ $file }* is not a PNG image.
+upload-too-large = Attachment *{ $file }* exceeds the size limit of { $max } MiB.
+upload-too-many-failures = Error: failed three times, please run the command again.
+image-decode-failed = Error: failed to decode the image: { $error }.
+image-fixed = The image had the following problems, which were fixed automatically:{ $issues }
+image-fixable =
+    Error: the image has the following problems:{ $issues }
+    Set `fix` to true and upload again to fix them automatically.
+image-unfixable = Error: the image has the following problems, which cannot be fixed automatically:{ $issues }
+issue-wrong-size = the image is `{ $width }x{ $height }`, it should be `{ $expected_width }x{ $expected_height }`
+issue-non-binary-mask = { $pixels } pixels are neither pure black nor pure white
+issue-off-palette = { $pixels } pixels ({ $colors } colors) are not in the wplace palette
+automask-invalid-arg = Invalid argument: use `#ed1c24,#ff7f27` for colors, `x,y` for flood fill, `x1,y1,x2,y2` for a rectangle and at least three vertices `x1,y1 x2,y2 x3,y3` for a polygon.
+automask-failed = Error: failed to generate the mask image: { $error }.
+automask-confirm = The generated mask covers { $pixels } pixels, save it as the mask image of chunk *{ $chunk }*?
+automask-cancelled = Mask generation cancelled.
+
+## Image history
+
+history-empty = Chunk *{ $chunk }* of fief **{ $name }** has no { image-kind } history.
+history-title = # History of the { image-kind } of chunk { $chunk }
+history-item = - `#{ $id }` { $time } { $action ->
+    [clear] cleared
+   *[set] set
+} by { $author }
+history-current = { " " }(current)
+
+## Users
+
+user-unknown = unknown
+user-not-member = Error: user { $user } does not belong to fief **{ $name }**.
+user-join-exists = Error: user { $user } is already a member of fief **{ $name }**.
+user-join-pending = Error: user { $user } already has a pending invitation to fief **{ $name }**.
+user-join-failed = Error: failed to invite user { $user } to fief **{ $name }**: { $reason }.
+user-invitation = { $user }, { $author } invited you to join fief **{ $name }**, members receive alerts about the fief. The invitation expires { $expires }.
+user-invitation-dm = { $author } invited you to join fief **{ $name }**, the invitation expires { $expires }. You can also use `/wmuser accept` or `/wmuser decline` to respond.
+user-invitation-not-found = Error: you have no invitation to fief **{ $name }**, or it has expired.
+user-invitations-empty = You have no pending invitations.
+user-invitations-title = # Pending invitations
+user-invitations-item =
+    - Fief: **{ $name }**
+      Invited by: { $inviter }
+      Expires: { $expires }
+user-accept-done = Joined fief **{ $name }**.
+user-accept-member = You are already a member of fief **{ $name }**.
+user-decline-done = Declined the invitation to fief **{ $name }**.
+user-leave-owner = Error: the owner cannot be removed from the fief, transfer the fief first.
+user-leave-done = Removed user { $user } from fief **{ $name }**.
+user-leave-not-member = Error: user { $user } is not in fief **{ $name }** or has already been removed.
+user-leave-failed = Error: failed to remove user { $user } from fief **{ $name }**: { $reason }.
+user-allow-exists = Error: user { $user } already has permission `{ $perms }`.
+user-allow-done = Granted permission `{ $perms }` in fief **{ $name }** to user { $user }.
+user-allow-failed = Error: failed to grant permissions in fief **{ $name }** to user { $user }: { $reason }.
+user-deny-owner = Error: permissions of the owner cannot be revoked.
+user-deny-missing = Error: user { $user } does not have permission `{ $perms }`.
+user-deny-done = Revoked permission `{ $perms }` in fief **{ $name }** from user { $user }.
+user-deny-failed = Error: failed to revoke permissions in fief **{ $name }** from user { $user }: { $reason }.
+user-setrole-done = Set the role of user { $user } in fief **{ $name }** to *{ $role }*.
+user-setrole-cleared = Cleared the role of user { $user } in fief **{ $name }**.
+user-setrole-failed = Error: failed to set the role of user { $user } in fief **{ $name }**: { $reason }.
+user-info =
+    # User
+    Discord ID: `{ $id }`
+    WMonitor administrator: `{ $admin ->
+        [yes] yes
+       *[no] no
+    }`
+
+    # Fiefs
+user-info-fief =
+    - Fief: **{ $name }**{ $owner ->
+        [yes] { " " }(owner)
+       *[no] {""}
+    }
+      Role: { $role }
+      Own permissions: { $perms }
+      Effective permissions: { $effective }
+
+## Administrators
+
+op-pause-done = Paused scheduled checks, a running check stops after the current pass.
+op-pause-already = Error: the checker is already paused.
+op-resume-done = Resumed scheduled checks.
+op-resume-not-paused = Error: the checker is not paused.
+op-runonce-done = Asked the checker to run a pass now.
+op-status-title = # Checker status
+op-status-paused = State: :pause_button: paused
+op-status-running = State: :arrow_forward: running
+op-status-queue = Queue:{ " " }
+op-status-queue-empty = empty
+op-status-checking = **{ $name }** (checking)
+op-status-last-pass = Last pass:{ " " }
+op-status-last-pass-at = took { $seconds } seconds, { $minutes } minutes ago
+op-status-next = Next scheduled check:{ " " }
+op-status-next-at = **{ $name }** (in { $minutes } minutes)
+op-listop-title = # Administrators
+op-listop-failed = Error: failed to list administrators: { $reason }.
+op-set-already = Error: { $user } { $admin ->
+    [yes] is already an administrator
+   *[no] is not an administrator
+}.
+op-set-done = { $admin ->
+    [yes] Made { $user } an administrator
+   *[no] { $user } is no longer an administrator
+}.
+op-set-failed = Error: failed to { $admin ->
+    [yes] make { $user } an administrator
+   *[no] remove { $user } from administrators
+}: { $reason }.
+op-fiefs-failed = Error: failed to fetch fiefs.
+op-fiefs-title = # Fiefs
+op-fiefs-item = { $enabled ->
+    [yes] enabled
+   *[no] disabled
+} | chunks: { $chunks } | diff: { $diff } | interval: { $interval } min
+op-fiefs-adaptive = { " " }(adaptive: { $interval } min)
+op-fiefs-members = `Members:`
+op-audit-empty = There are no audit entries yet.
+op-audit-title = # Audit log
+
+## Notifications
+
+notify-check-failed = Failed to check fief **{ $name }** (retries: { $times }/{ $max }).
+notify-check-success = Fief **{ $name }** is fine now.
+notify-diff-found =
+    # Unexpected pixels found
+    Fief: **{ $name }**
+    Chunks: { $chunks }
+    Unexpected pixels: { $pixels }
+notify-network-error = Network error: { $error }.
+notify-ref-missing = Warning: chunk *{ $chunk }* of fief **{ $name }** has no reference image.
+notify-mask-missing = Warning: chunk *{ $chunk }* of fief **{ $name }** has no mask image.
+
+## Dashboards
+
+dashboard-title = # Dashboard
+dashboard-title-fief = # Dashboard of { $name }
+dashboard-fief = **{ $name }** { $status }
+    Last check: { $last } | Next check: { $next }
+    Chunks: { $chunks } | With problems: { $problems } | Diff pixels: { $diff }
+dashboard-chunk = - { $status } *{ $chunk }*, diff pixels: { $diff }
+dashboard-more = …and { $count } more
+dashboard-updated = -# Updated <t:{ $time }:R>
+dashboard-create-done = Sent a dashboard to this channel. It is updated after every check; delete the message to stop updating it.{ $pinned ->
+    [yes] {""}
+   *[no] { " " }The bot cannot manage messages, please pin it manually.
+}
+dashboard-create-failed = Error: failed to send the dashboard: { $error }.
+
+## Buttons on notifications and invitations
+
+diff-select-placeholder = Choose chunks whose current state becomes the reference image
+diff-accept-all = Accept all
+diff-ignore = Ignore
+diff-grief = Mark as griefing
+diff-ignored = { $user } ignored this alert.
+diff-griefed = { $user } marked this alert as griefing, please repair it soon.
+diff-accepted = { $user } accepted the current state of chunks { $chunks }as the reference image.
+diff-accept-failed = Failed to update the reference image of chunks { $chunks }, please try again later.
+diff-accept-expired = The checked images of chunks { $chunks }have been cleaned up and can no longer be accepted, please wait for the next check.
+invitation-accept = Join fief
+invitation-decline = Decline
+invitation-gone = Error: the invitation has already been handled or is no longer valid.
+invitation-not-invitee = Error: only the invited user can respond to this invitation.
+invitation-expired = The invitation has expired.
+invitation-accepted = { $user } joined fief **{ $name }**.
+invitation-declined = { $user } declined to join fief **{ $name }**.
+
+## Command descriptions, the id is the command path joined by `-`
+## and each attribute describes the option with the same id in `options`
+
+wmhelp = Show all commands
+    .command = The command to show help for
+wmfetch = Fetch the image of a chunk from wplace.live by its coordinates
+    .x = X coordinate of the chunk on Wplace
+    .y = Y coordinate of the chunk on Wplace
+wmpermissions = List all kinds of permissions
+
+wmfief = Fief commands
+wmfief-add = Create a fief
+    .fief = Name of the new fief
+wmfief-remove = Delete a fief
+wmfief-check = Check a fief now
+wmfief-rename = Rename a fief
+    .fief = Current name of the fief
+    .new-name = New name of the fief
+wmfief-transfer = Transfer the fief to another member, the previous owner keeps their own permissions
+    .user = The new owner, who must be a member of the fief
+wmfief-settime = Set the check interval of a fief
+    .interval = Check interval in minutes
+wmfief-enable = Enable scheduled checks of a fief (enabled when the fief is created)
+wmfief-disable = Disable scheduled checks of a fief
+    .duration = Hours until checks are enabled again
+wmfief-adaptive = Turn the adaptive check interval of a fief on or off
+    .enabled = Whether to use the adaptive check interval
+wmfief-setretention = Set how many previous versions of reference and mask images each chunk keeps
+    .count = Number of versions to keep, at least 1
+wmfief-import = Create chunks, reference and mask images from Blue Marble or Overlay Pro templates
+    .template = A JSON file, or PNG images whose names end with `chunkX_chunkY_pixelX_pixelY`
+wmfief-export = Export the settings, chunks, images and members of a fief as an archive
+wmfief-restore = Restore a fief from an archive made by `/wmfief export`
+    .archive = The tar file made by `/wmfief export`
+    .fief = Name of the restored fief, defaults to the name in the archive
+    .on-conflict = What to do when the name is taken, defaults to abort
+wmfief-info = Show information about a fief
+wmfief-audit = Show the audit log of a fief, newest first
+wmfief-dashboard = Send a dashboard of the fief to this channel, updated after every check
+wmfief-maintenance = Scheduled maintenance windows of a fief
+wmfief-maintenance-add = Add a maintenance window (UTC) that skips checks or mutes alerts
+    .weekday = A day of the week, or every day
+    .start = Start time (UTC) as HH:MM
+    .end = End time (UTC) as HH:MM, the window crosses midnight if it is before the start
+    .mode = Skip checks during the window, or check as usual without alerts
+wmfief-maintenance-list = List the maintenance windows of a fief
+wmfief-maintenance-remove = Remove a maintenance window of a fief
+    .id = The id of the window, see `/wmfief maintenance list`
+wmfief-role = Fief roles, members holding a role get all of its permissions
+wmfief-role-create = Create a role
+    .permissions = Permissions separated by spaces, see `/wmpermissions` for all of them
+wmfief-role-edit = Change the permissions of a role, members holding it are affected too
+    .permissions = Permissions separated by spaces, replacing the current ones
+wmfief-role-remove = Delete a role, members holding it keep their own permissions
+wmfief-role-list = List the roles of a fief
+
+wmchunk = Chunk commands
+wmchunk-add = Add a chunk to a fief
+    .fief = The fief the chunk belongs to
+    .chunk = Name of the new chunk
+    .x = X coordinate of the chunk on Wplace
+    .y = Y coordinate of the chunk on Wplace
+wmchunk-remove = Remove a chunk from a fief
+wmchunk-rename = Rename a chunk
+    .chunk = Current name of the chunk
+    .new-name = New name of the chunk
+wmchunk-setref = Upload the reference image of the chunk
+    .chunk = Name of the chunk
+    .image = The reference image as PNG, send it after the command if left empty
+    .fix = Whether to fix fixable problems automatically, defaults to no
+wmchunk-setimages = Upload both the reference and the mask image of the chunk
+    .chunk = Name of the chunk
+    .reference-image = The reference image as PNG, send it after the command if left empty
+    .mask-image = The mask image as PNG, send it after the command if left empty
+    .fix = Whether to fix fixable problems automatically, defaults to no
+wmchunk-refnow = Use the current state of the chunk as its reference image
+    .chunk = Name of the chunk
+wmchunk-refhistory = Show previous versions of the reference or mask image of the chunk
+    .chunk = Name of the chunk
+    .kind = Reference or mask image history, defaults to the reference image
+wmchunk-rollback = Restore the reference or mask image of the chunk to a previous version
+    .chunk = Name of the chunk
+    .version = The version id, see `/wmchunk refhistory`
+wmchunk-setmask = Upload the mask image of the chunk (marks which pixels are checked)
+    .chunk = Name of the chunk
+    .image = The mask image as PNG, send it after the command if left empty
+    .fix = Whether to fix fixable problems automatically, defaults to no
+wmchunk-automask = Generate a mask image from the reference image and save it after a preview
+    .chunk = Name of the chunk
+    .mode = How to generate the mask
+    .argument = Colors: #RRGGBB,…; flood fill: x,y; rectangle: x1,y1,x2,y2; polygon: x1,y1 x2,y2 …
+wmchunk-setpos = Change the coordinates of a chunk
+    .chunk = Name of the chunk
+    .x = X coordinate of the chunk on Wplace
+    .y = Y coordinate of the chunk on Wplace
+wmchunk-info = Show information about a chunk
+    .chunk = Name of the chunk
+
+wmuser = User commands
+wmuser-join = Invite a user to a fief, they become a member after accepting
+    .expiry = Hours until the invitation expires, defaults to 72, at most 30 days
+wmuser-leave = Remove a user from a fief, members can always remove themselves
+wmuser-invitations = Show your pending fief invitations
+wmuser-accept = Accept a fief invitation
+wmuser-decline = Decline a fief invitation
+wmuser-allow = Grant fief permissions to a user
+    .permissions = Permissions separated by spaces, see `/wmpermissions` for all of them
+wmuser-deny = Revoke fief permissions from a user
+    .permissions = Permissions separated by spaces, see `/wmpermissions` for all of them
+wmuser-setrole = Set the role of a user in a fief
+    .role-name = Leave empty to clear the role, see `/wmfief role list` for all roles
+wmuser-info = Show information about a user
+    .fief = Leave empty to show all fiefs of the user
+
+wmrole = Discord role commands, members of a role share the fief permissions granted to it
+wmrole-join = Add a Discord role to a fief, its members receive the fief's alerts
+wmrole-leave = Remove a Discord role from a fief
+wmrole-allow = Grant fief permissions to a Discord role
+    .permissions = Permissions separated by spaces, see `/wmpermissions` for all of them
+wmrole-deny = Revoke fief permissions from a Discord role
+    .permissions = Permissions separated by spaces, see `/wmpermissions` for all of them
+
+wmop = Administrator commands
+wmop-op = Make a user an administrator
+wmop-deop = Remove a user from administrators
+wmop-listop = List all administrators
+wmop-pause = Pause scheduled checks
+wmop-resume = Resume scheduled checks
+wmop-runonce = Run a check pass now (also works while paused)
+wmop-status = Show the checker status
+wmop-fiefs = List all fiefs
+wmop-dashboard = Send a dashboard of all fiefs to this channel, updated after every check
+wmop-audit = Show the audit log of all fiefs and administrators, newest first
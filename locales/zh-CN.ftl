@@ -0,0 +1,502 @@
+# WMonitor 的中文文本，这里的每个 id 在其他语言中都必须存在
+# 指令的描述直接写在代码中，不在这里重复
+
+## 指令的参数名与选项名，用于查找其他语言中对应的翻译
+
+options =
+    .fief = 领地名
+    .chunk = 区块名
+    .user = 用户
+    .role = 身份组
+    .permissions = 权限
+    .new-name = 新名字
+    .interval = 间隔
+    .duration = 禁用时长
+    .enabled = 开启
+    .count = 数量
+    .template = 模板
+    .archive = 归档
+    .on-conflict = 冲突处理
+    .weekday = 星期
+    .start = 开始
+    .end = 结束
+    .mode = 模式
+    .id = 编号
+    .role-name = 职位名
+    .expiry = 有效期
+    .fix = 自动修复
+    .kind = 类型
+    .version = 版本编号
+    .argument = 参数
+    .image = 图片
+    .reference-image = 参考图
+    .mask-image = 遮罩图
+
+choices =
+    .everyday = 每天
+    .mon = 周一
+    .tue = 周二
+    .wed = 周三
+    .thu = 周四
+    .fri = 周五
+    .sat = 周六
+    .sun = 周日
+    .skip-check = 跳过检查
+    .mute-alerts = 不发送警报
+    .abort = 放弃导入
+    .rename = 自动重命名
+    .replace = 覆盖已有领地
+    .opaque = 不透明像素
+    .colors = 指定颜色
+    .flood-fill = 填充
+    .rect = 矩形
+    .polygon = 多边形
+    .reference = 参考图
+    .mask = 遮罩图
+
+## 通用
+
+error-permission-denied = 错误：操作失败，权限不足。
+error-grant-missing-perms = 错误：不能授予自己没有的权限。
+error-invalid-perms = 错误：`{ $perms }` 包含无效的权限名称。
+error-fief-not-found = 错误：领地 **{ $name }** 不存在。
+error-network = 网络异常，请稍后重试。
+error-mention-user = 参数错误：请@用户作为输入。
+error-store-user = 错误：无法存储用户信息: { $reason }。
+error-repo = 错误：{ $reason }。
+button-confirm = 确认
+button-cancel = 取消
+confirm-timeout = 等待已超时，操作已取消。
+none = 无
+
+reason-not-found = 记录不存在，可能已被删除
+reason-conflict = 名字已被占用
+reason-foreign-key = 关联的领地或用户不存在
+reason-invalid = 参数不合法
+reason-storage = 数据库暂时无法访问，请稍后重试
+reason-decode = 数据库中的数据已损坏，请联系管理员
+
+## 分页列表
+
+pages-footer = 第 { $page }/{ $pages } 页，共 { $count } 项{ $problems ->
+    [yes] ，仅显示异常
+   *[no] {""}
+}
+pages-empty = 没有符合条件的项。
+pages-prev = 上一页
+pages-next = 下一页
+pages-filter = { $problems ->
+    [yes] 显示全部
+   *[no] 只看异常
+}
+pages-sort = 排序：{ $sort ->
+    [status] 状态
+    [diff] 差异数量
+   *[default] 默认
+}
+status-ok = :white_check_mark: 正常
+status-failed = :x: 检查失败
+status-damaged = :warning: 受损
+status-missing-ref = :grey_question: 缺少参考图
+status-paused = :pause_button: 已暂停
+
+## 基本指令
+
+fetch-waiting = 正在从 wplace.live 获取图片，请稍等……
+permissions-help =
+    # 权限说明
+    ## 领地相关
+    - `FIEF_EDIT`: 编辑领地信息
+    - `FIEF_DELETE`: 删除领地
+    - `FIEF_ALL`: 领地的全部权限，等同于 `FIEF_EDIT` + `FIEF_DELETE`
+    ## 区块相关
+    - `CHUNK_ADD`: 在领地内添加区块
+    - `CHUNK_EDIT`: 编辑领地内区块信息
+    - `CHUNK_DELETE`: 删除领地内的区块
+    - `CHUNK_ALL`: 区块的全部权限，详细说明同上
+    ## 成员相关
+    - `MEMBER_INVITE`: 邀请成员至领地
+    - `MEMBER_EDIT_PERMS`: 编辑用户在领地内的权限
+    - `MEMBER_KICK`: 将成员移出领地
+    - `MEMBER_ALL`: 成员的全部权限，详细说明同上
+    ## 其他
+    - `NONE`: 无任何权限
+    - `ALL`: 拥有上述所有权限
+
+## 操作记录
+
+audit-truncated = *只显示最近的 { $shown } 条，共 { $total } 条*
+audit-system = 系统
+audit-changed = ：{ $before } → { $after }
+audit-removed = （原为 { $before }）
+audit-set = ：{ $after }
+audit-fief-create = 创建领地
+audit-fief-delete = 删除领地
+audit-fief-rename = 重命名领地
+audit-fief-transfer = 转让领地
+audit-fief-settings = 修改领地设置
+audit-fief-check = 修改定期检查
+audit-chunk-add = 添加区块
+audit-chunk-delete = 删除区块
+audit-chunk-edit = 修改区块
+audit-chunk-image = 更新区块图片
+audit-member-join = 添加成员
+audit-member-leave = 移出成员
+audit-permission-change = 修改权限
+audit-admin-change = 修改管理员
+audit-checker-pause = 修改检查器状态
+audit-value-accept-invitation = 接受邀请
+audit-value-dashboard = 状态面板
+audit-value-check-interval = 检查间隔
+audit-value-minutes = { $minutes } 分钟
+audit-value-enabled = 启用
+audit-value-disabled = 禁用
+audit-value-disabled-for = 禁用 { $hours } 小时
+audit-value-adaptive = 自适应检查间隔
+audit-value-on = 开启
+audit-value-off = 关闭
+audit-value-retention = 历史版本保留数量
+audit-value-restored = 从归档恢复
+audit-value-upload-ref = 上传参考图
+audit-value-upload-mask = 上传遮罩图
+audit-value-automask = 自动生成遮罩图
+audit-value-accept-current = 接受当前状态为参考图
+audit-value-ref-current = 参考图更新为当前状态
+audit-value-rollback = 恢复为版本 #{ $id }
+audit-value-fief-role = 职位 { $name }
+audit-value-no-role = 无
+audit-value-maintenance = 维护时间段
+audit-value-pause = 暂停
+audit-value-resume = 恢复
+audit-value-admin = 管理员
+audit-value-not-admin = 非管理员
+
+## 领地
+
+fief-add-exists = 领地 **{ $name }** 早已存在，请换个名字重新创建。
+fief-add-done = 成功创建领地 **{ $name }**（id: `{ $id }`）。
+fief-remove-done = 成功删除领地 **{ $name }**。
+fief-rename-done = 已将领地 **{ $name }** 的名字变更为 **{ $new_name }**。
+fief-rename-exists = 错误：领地 **{ $new_name }** 早已存在，请换个名字。
+fief-transfer-not-owner = 错误：只有领地所有者或管理员才能转让领地。
+fief-transfer-already-owner = 错误：用户 { $user } 已经是领地 **{ $name }** 的所有者。
+fief-transfer-not-member = 错误：用户 { $user } 并不属于领地 **{ $name }**，请先将其添加进领地。
+fief-transfer-confirm = 确定要将领地 **{ $name }** 转让给 { $user } 吗？转让后原所有者将失去所有者的保护。
+fief-transfer-cancelled = 已取消转让领地 **{ $name }**。
+fief-transfer-done = 已将领地 **{ $name }** 转让给 { $user }。
+fief-transfer-failed = 错误：无法转让领地 **{ $name }**: { $reason }。
+fief-settime-done = 已变更 **{ $name }** 领地的检查间隔时间为 { $interval } 分钟。
+fief-check-done = 设置成功，领地 **{ $name }** 将在一分钟内被执行检查。
+fief-check-failed = 设置失败。
+fief-enable-done = 已启用对领地 **{ $name }** 的定期自动检查。
+fief-disable-for = 已禁用对领地 **{ $name }** 的定期自动检查（持续时间: { $hours } 小时）。
+fief-disable-done = 已禁用对领地 **{ $name }** 的定期自动检查。
+fief-adaptive-on = 已开启领地 **{ $name }** 的自适应检查间隔。
+fief-adaptive-off = 已关闭领地 **{ $name }** 的自适应检查间隔，检查间隔已恢复为设置值。
+fief-retention-invalid = 参数错误：保留的历史版本数量至少为 1。
+fief-retention-done = 已将领地 **{ $name }** 的图片历史版本保留数量设置为 { $retention }（不含当前版本），超出的旧版本将在下次更新时清理。
+fief-import-empty = 错误：文件中没有找到模板。
+fief-import-parse-failed = 错误：无法解析模板文件: { $error }。
+fief-import-failed = 错误：无法将模板导入领地 **{ $name }**: { $error }。
+fief-import-done = 已将 { $count } 个模板导入领地 **{ $name }**：
+fief-import-created = 新建区块 *{ $chunk }* (`{ $x }`, `{ $y }`)，共 { $pixels } 个像素
+fief-import-merged = 合并至区块 *{ $chunk }* (`{ $x }`, `{ $y }`)，共 { $pixels } 个像素
+fief-export-failed = 错误：无法导出领地 **{ $name }**: { $error }。
+fief-export-done = 已导出领地 **{ $name }**。
+fief-restore-read-failed = 错误：无法读取归档文件: { $error }。
+fief-restore-replace-denied = 错误：无法覆盖领地 **{ $name }**，权限不足。
+fief-restore-conflict = 错误：领地名已被占用，请换个名字或选择其他冲突处理方式。
+fief-restore-failed = 错误：无法恢复领地: { $error }。
+fief-restore-done = 成功恢复领地 **{ $name }**，共 { $chunks } 个区块，已邀请归档内的 { $invited } 名成员重新加入，他们可以通过 `/wmuser invitations` 接受邀请。
+fief-info =
+    # 领地信息
+    名字：**{ $name }**
+    所有者：{ $owner }
+    上次检查：{ $last_check }
+    定期自动检查：{ $check }
+    定期自动检查间隔：{ $interval } 分钟一次
+    图片历史版本保留数量：{ $retention }
+fief-info-minutes-ago = { $minutes } 分钟之前
+fief-info-enabled = :white_check_mark: 启用中
+fief-info-disabled = :negative_squared_cross_mark: 禁用中
+fief-info-disabled-for = :negative_squared_cross_mark: 禁用中，{ $minutes } 分钟之后启用
+fief-info-adaptive = 自适应检查间隔：:white_check_mark: 开启，当前实际间隔为 { $interval } 分钟一次
+fief-info-maintenance = 维护时间段：
+fief-info-chunks = # 领地的区块信息
+fief-info-chunk =
+    - 区块名：*{ $chunk }*
+      位置：`({ $x }, { $y })`
+      状态：{ $status }，差异像素：{ $diff }
+fief-info-members = # 领地成员
+fief-info-user =
+    - 用户：{ $user }
+      权限：{ $perms }
+fief-info-role =
+    - 身份组：{ $role }
+      权限：{ $perms }
+fief-audit-empty = 领地 **{ $name }** 还没有操作记录。
+fief-audit-title = # 领地 { $name } 的操作记录
+
+## 领地的维护时间段
+
+maintenance-everyday = 每天
+maintenance-mon = 每周一
+maintenance-tue = 每周二
+maintenance-wed = 每周三
+maintenance-thu = 每周四
+maintenance-fri = 每周五
+maintenance-sat = 每周六
+maintenance-sun = 每周日
+maintenance-skip-check = 跳过检查
+maintenance-mute-alerts = 不发送警报
+maintenance-window = `#{ $id }` { $weekday } { $start }~{ $end } (UTC)，{ $mode }
+maintenance-invalid-time = 参数错误：时间格式应为 `HH:MM`，例如 `20:00`。
+maintenance-add-done = 已为领地 **{ $name }** 添加维护时间段：{ $window }。
+maintenance-add-failed = 错误：无法为领地 **{ $name }** 添加维护时间段。
+maintenance-add-error = 错误：无法为领地 **{ $name }** 添加维护时间段: { $reason }。
+maintenance-list-empty = 领地 **{ $name }** 没有设置维护时间段。
+maintenance-list-title = # 领地 { $name } 的维护时间段
+maintenance-active = （进行中）
+maintenance-remove-done = 已删除领地 **{ $name }** 的维护时间段 `#{ $id }`。
+maintenance-remove-not-found = 错误：领地 **{ $name }** 没有编号为 `#{ $id }` 的维护时间段。
+maintenance-remove-failed = 错误：无法删除领地 **{ $name }** 的维护时间段: { $reason }。
+
+## 领地的职位
+
+fief-role-not-found = 错误：领地 **{ $name }** 没有职位 *{ $role }*。
+fief-role-create-done = 已在领地 **{ $name }** 创建职位 *{ $role }*，权限：{ $perms }。
+fief-role-create-exists = 错误：领地 **{ $name }** 已有职位 *{ $role }*。
+fief-role-create-failed = 错误：无法在领地 **{ $name }** 创建职位: { $reason }。
+fief-role-edit-done = 已将领地 **{ $name }** 的职位 *{ $role }* 的权限修改为：{ $perms }。
+fief-role-edit-failed = 错误：无法修改领地 **{ $name }** 的职位 *{ $role }*: { $reason }。
+fief-role-remove-done = 已删除领地 **{ $name }** 的职位 *{ $role }*。
+fief-role-remove-failed = 错误：无法删除领地 **{ $name }** 的职位 *{ $role }*: { $reason }。
+fief-role-list-empty = 领地 **{ $name }** 没有设置职位。
+fief-role-list-title = # 领地 { $name } 的职位
+fief-role-list-item =
+    - 职位：*{ $role }*
+      权限：{ $perms }
+
+## 身份组
+
+role-not-in-fief = 错误：身份组 { $role } 并不属于领地 **{ $name }**。
+role-join-done = 已添加身份组 { $role } 至领地 **{ $name }**。
+role-join-exists = 错误：身份组 { $role } 已经属于领地 **{ $name }**。
+role-join-failed = 错误：无法添加身份组 { $role } 至领地 **{ $name }**: { $reason }。
+role-leave-done = 已将身份组 { $role } 从领地 **{ $name }** 中移出。
+role-leave-not-member = 错误：身份组 { $role } 不在领地 **{ $name }** 或已经被移出。
+role-leave-failed = 错误：无法将身份组 { $role } 从领地 **{ $name }** 中移出: { $reason }。
+role-allow-exists = 错误：身份组 { $role } 已有权限 `{ $perms }`。
+role-allow-done = 已经授予身份组 { $role } 在领地 **{ $name }** 的 `{ $perms }` 权限。
+role-allow-failed = 错误：无法在领地 **{ $name }** 为身份组 { $role } 添加权限: { $reason }。
+role-deny-missing = 错误：身份组 { $role } 未有权限 `{ $perms }`。
+role-deny-done = 已经收回身份组 { $role } 在领地 **{ $name }** 的 `{ $perms }` 权限。
+role-deny-failed = 错误：无法在领地 **{ $name }** 为身份组 { $role } 收回权限: { $reason }。
+
+## 区块
+
+chunk-not-found = 错误：无法从领地 **{ $name }** 中找到区块 *{ $chunk }*。
+chunk-add-done = 成功在领地 **{ $name }** 内创建区块 *{ $chunk }*(id: `{ $id }`)。
+chunk-add-exists = 错误：区块 *{ $chunk }* 早已存在于领地 **{ $name }**。
+chunk-add-failed = 错误：无法在领地 **{ $name }** 内创建区块 *{ $chunk }*: { $reason }。
+chunk-remove-done = 成功将区块 *{ $chunk }* 从领地 **{ $name }** 中删除。
+chunk-remove-failed = 错误：无法将区块 *{ $chunk }* 从领地 **{ $name }** 中删除: { $reason }。
+chunk-rename-done = 成功将领地 **{ $name }** 内的区块 *{ $chunk }* 更名为 *{ $new_name }*。
+chunk-edit-failed = 错误：无法修改领地 **{ $name }** 内的区块 *{ $chunk }*: { $reason }。
+chunk-setref-done = 成功更新领地 **{ $name }** 内区块 *{ $chunk }* 的参考图。
+chunk-setmask-done = 成功更新领地 **{ $name }** 内区块 *{ $chunk }* 的遮罩图。
+chunk-refnow-done = 成功将领地 **{ $name }** 内区块 *{ $chunk }* 的参考图更新为当前状态。
+chunk-no-ref = 错误：领地 **{ $name }** 内区块 *{ $chunk }* 未设置参考图。
+chunk-rollback-done = 成功将领地 **{ $name }** 内区块 *{ $chunk }* 恢复为版本 `#{ $id }`。
+chunk-rollback-not-found = 错误：领地 **{ $name }** 内区块 *{ $chunk }* 没有编号为 `#{ $id }` 的历史版本。
+chunk-setpos-done = 成功将领地 **{ $name }** 内的区块 *{ $chunk }* 坐标改为 `({ $x }, { $y })`。
+chunk-info =
+    # 区块信息
+    区块名：*{ $chunk }*
+    属于领地：**{ $name }**
+    位置：(`{ $x }`, `{ $y }`)
+    参考图：{ $ref_img }
+chunk-info-ref-by = 参考图由 { $user } 于 { $minutes } 分钟之前更新
+chunk-info-mask = 遮罩图：{ $mask_img }
+chunk-info-set = :white_check_mark: 已设置
+chunk-info-unset = :negative_squared_cross_mark: 未设置
+
+## 图片上传与检查
+
+image-kind = { $kind ->
+    [mask] 遮罩图
+   *[ref] 参考图
+}
+upload-prompt =
+    请在此频道发送以下 PNG 图片：{ $pending }
+    一条消息中的多张图片会按顺序使用（已收到 { $received }/{ $total }）。
+upload-cancelled = 已取消上传。
+upload-timeout = 等待已超时，请重新输入指令。
+upload-no-attachment = 找不到附件，请再次上传。
+upload-not-png = 附件 *{ $file }* 不是 PNG 图片。
+upload-too-large = 附件 *{ $file }* 超过了 { $max } MiB 的大小限制。
+upload-too-many-failures = 错误：失败超过三次，请重新输入指令。
+image-decode-failed = 错误：无法解码图片: { $error }。
+image-fixed = 图片存在以下问题，已自动修复：{ $issues }
+image-fixable =
+    错误：图片存在以下问题：{ $issues }
+    可以将 `自动修复` 设置为是后重新上传。
+image-unfixable = 错误：图片存在以下问题，无法自动修复：{ $issues }
+issue-wrong-size = 图片尺寸为 `{ $width }x{ $height }`，应为 `{ $expected_width }x{ $expected_height }`
+issue-non-binary-mask = 有 { $pixels } 个像素不是纯黑或纯白
+issue-off-palette = 有 { $pixels } 个像素（共 { $colors } 种颜色）不属于 wplace 调色板
+automask-invalid-arg = 参数错误：颜色模式应为 `#ed1c24,#ff7f27`，填充模式应为 `x,y`，矩形模式应为 `x1,y1,x2,y2`，多边形模式应为至少三个顶点 `x1,y1 x2,y2 x3,y3`。
+automask-failed = 错误：无法生成遮罩图: { $error }。
+automask-confirm = 生成的遮罩图共覆盖 { $pixels } 个像素，是否保存为区块 *{ $chunk }* 的遮罩图？
+automask-cancelled = 已取消生成遮罩图。
+
+## 图片的历史版本
+
+history-empty = 领地 **{ $name }** 内区块 *{ $chunk }* 没有{ image-kind }的历史版本。
+history-title = # 区块 { $chunk } 的{ image-kind }历史
+history-item = - `#{ $id }` { $time } 由 { $author } { $action ->
+    [clear] 清除
+   *[set] 设置
+}
+history-current = （当前）
+
+## 用户
+
+user-unknown = 未知
+user-not-member = 错误：用户 { $user } 并不属于领地 **{ $name }**。
+user-join-exists = 错误：用户 { $user } 已经是领地 **{ $name }** 的成员。
+user-join-pending = 错误：用户 { $user } 已有加入领地 **{ $name }** 的邀请，请等待其处理。
+user-join-failed = 错误：无法邀请用户 { $user } 加入领地 **{ $name }**: { $reason }。
+user-invitation = { $user }，{ $author } 邀请你加入领地 **{ $name }**，加入后会收到领地的异常通知。邀请将在 { $expires }过期。
+user-invitation-dm = { $author } 邀请你加入领地 **{ $name }**，邀请将在 { $expires }过期。也可以使用 `/wmuser accept` 或 `/wmuser decline` 处理邀请。
+user-invitation-not-found = 错误：你没有加入领地 **{ $name }** 的邀请，或邀请已过期。
+user-invitations-empty = 你没有待处理的领地邀请。
+user-invitations-title = # 待处理的领地邀请
+user-invitations-item =
+    - 领地：**{ $name }**
+      邀请者：{ $inviter }
+      过期时间：{ $expires }
+user-accept-done = 已加入领地 **{ $name }**。
+user-accept-member = 你已经是领地 **{ $name }** 的成员。
+user-decline-done = 已拒绝加入领地 **{ $name }** 的邀请。
+user-leave-owner = 错误：不能将领地所有者移出领地，请先转让领地。
+user-leave-done = 已将用户 { $user } 从领地 **{ $name }** 中移出。
+user-leave-not-member = 错误：用户 { $user } 不在领地 **{ $name }** 或已经被移出。
+user-leave-failed = 错误：无法将用户 { $user } 从领地 **{ $name }** 中移出: { $reason }。
+user-allow-exists = 错误：用户 { $user } 已有权限 `{ $perms }`。
+user-allow-done = 已经授予用户 { $user } 在领地 **{ $name }** 的 `{ $perms }` 权限。
+user-allow-failed = 错误：无法在领地 **{ $name }** 为用户 { $user } 添加权限: { $reason }。
+user-deny-owner = 错误：不能收回领地所有者的权限。
+user-deny-missing = 错误：用户 { $user } 未有权限 `{ $perms }`。
+user-deny-done = 已经收回用户 { $user } 在领地 **{ $name }** 的 `{ $perms }` 权限。
+user-deny-failed = 错误：无法在领地 **{ $name }** 为用户 { $user } 收回权限: { $reason }。
+user-setrole-done = 已将用户 { $user } 在领地 **{ $name }** 的职位设为 *{ $role }*。
+user-setrole-cleared = 已取消用户 { $user } 在领地 **{ $name }** 的职位。
+user-setrole-failed = 错误：无法设置用户 { $user } 在领地 **{ $name }** 的职位: { $reason }。
+user-info =
+    # 用户基本信息
+    Discord ID：`{ $id }`
+    WMonitor 管理员：`{ $admin ->
+        [yes] 是
+       *[no] 否
+    }`
+
+    # 所属领地
+user-info-fief =
+    - 领地名: **{ $name }**{ $owner ->
+        [yes] （所有者）
+       *[no] {""}
+    }
+      职位：{ $role }
+      自身权限：{ $perms }
+      实际权限：{ $effective }
+
+## 管理员
+
+op-pause-done = 已暂停定期检查，正在进行的检查会在本轮结束后停止。
+op-pause-already = 错误：检查器已经处于暂停状态。
+op-resume-done = 已恢复定期检查。
+op-resume-not-paused = 错误：检查器并未暂停。
+op-runonce-done = 已请求检查器立即执行一轮检查。
+op-status-title = # 检查器状态
+op-status-paused = 状态：:pause_button: 已暂停
+op-status-running = 状态：:arrow_forward: 运行中
+op-status-queue = 当前队列：
+op-status-queue-empty = 空
+op-status-checking = **{ $name }**（检查中）
+op-status-last-pass = 上轮检查：
+op-status-last-pass-at = 耗时 { $seconds } 秒，{ $minutes } 分钟之前
+op-status-next = 下一个计划检查：
+op-status-next-at = **{ $name }**（{ $minutes } 分钟后）
+op-listop-title = # 管理员列表
+op-listop-failed = 错误：无法获取管理员列表: { $reason }。
+op-set-already = 错误：{ $user } 已经是{ $admin ->
+    [yes] {""}
+   *[no] 非
+}管理员。
+op-set-done = 已设置 { $user } 为{ $admin ->
+    [yes] {""}
+   *[no] 非
+}管理员。
+op-set-failed = 错误：无法设置 { $user } 为{ $admin ->
+    [yes] {""}
+   *[no] 非
+}管理员: { $reason }。
+op-fiefs-failed = 错误：无法获取领地信息。
+op-fiefs-title = # 领地列表
+op-fiefs-item = { $enabled ->
+    [yes] 启用中
+   *[no] 禁用中
+} | 区块: { $chunks } | 差异: { $diff } | 间隔: { $interval } 分钟
+op-fiefs-adaptive = （自适应: { $interval } 分钟）
+op-fiefs-members = `成员:`
+op-audit-empty = 还没有操作记录。
+op-audit-title = # 操作记录
+
+## 通知
+
+notify-check-failed = 领地 **{ $name }** 检查失败（重试次数: { $times }/{ $max }）。
+notify-check-success = 领地 **{ $name }** 目前正常。
+notify-diff-found =
+    # 发现异常像素
+    领地: **{ $name }**
+    异常区块：{ $chunks }
+    异常像素数量：{ $pixels } 个
+notify-network-error = 网络异常：{ $error }。
+notify-ref-missing = 警告：领地 **{ $name }** 的区块 *{ $chunk }* 未设置参考图。
+notify-mask-missing = 警告：领地 **{ $name }** 的区块 *{ $chunk }* 未设置遮罩图。
+
+## 状态面板
+
+dashboard-title = # 状态面板
+dashboard-title-fief = # 领地 { $name } 的状态面板
+dashboard-fief = **{ $name }** { $status }
+    上次检查：{ $last } | 下次检查：{ $next }
+    区块：{ $chunks } | 异常区块：{ $problems } | 差异像素：{ $diff }
+dashboard-chunk = - { $status } *{ $chunk }*，差异像素：{ $diff }
+dashboard-more = …以及其他 { $count } 项
+dashboard-updated = -# 更新于 <t:{ $time }:R>
+dashboard-create-done = 已在当前频道发送状态面板，每次检查后会自动更新，删除该消息即可停止更新。{ $pinned ->
+    [yes] {""}
+   *[no] 机器人没有管理消息的权限，请手动置顶。
+}
+dashboard-create-failed = 错误：无法发送状态面板: { $error }。
+
+## 通知与邀请上的按钮
+
+diff-select-placeholder = 选择要接受当前状态为参考图的区块
+diff-accept-all = 全部接受
+diff-ignore = 忽略
+diff-grief = 标记为恶意破坏
+diff-ignored = { $user } 已忽略此次异常。
+diff-griefed = { $user } 已将此次异常标记为恶意破坏，请尽快修复。
+diff-accepted = { $user } 已接受区块 { $chunks }的当前状态为参考图。
+diff-accept-failed = 无法更新区块 { $chunks }的参考图，请稍后重试。
+diff-accept-expired = 区块 { $chunks }检查时的图片已被清理，无法接受，请等待下一次检查。
+invitation-accept = 加入领地
+invitation-decline = 拒绝
+invitation-gone = 错误：邀请已被处理或已失效。
+invitation-not-invitee = 错误：只有被邀请的用户才能处理此邀请。
+invitation-expired = 邀请已过期。
+invitation-accepted = { $user } 已加入领地 **{ $name }**。
+invitation-declined = { $user } 已拒绝加入领地 **{ $name }**。
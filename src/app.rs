@@ -1,10 +1,4 @@
-use std::{
-    sync::{
-        Arc,
-        atomic::{AtomicBool, Ordering},
-    },
-    time::Duration,
-};
+use std::time::Duration;
 
 use anyhow::Result;
 use tokio::{sync::Mutex, time::sleep};
@@ -24,25 +18,31 @@ pub struct WMonitor {
 impl WMonitor {
     pub async fn run(self) -> Result<()> {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let should_close_atomic = Arc::new(AtomicBool::new(false));
         let repo = Box::leak(Box::new(self.repo));
 
-        let should_close = should_close_atomic.clone();
         let mut checker = Checker::new(repo, tx);
+        let checker_handle = checker.handle();
+        let handle = checker_handle.clone();
         let check_task = tokio::spawn(async move {
             info!("running checker");
-            while !should_close.load(Ordering::SeqCst) {
-                if let Err(e) = checker.check_all().await {
+            let mut forced = false;
+            loop {
+                if (forced || !handle.is_paused())
+                    && let Err(e) = checker.check_all().await
+                {
                     error!("{e}");
                 }
-                sleep(Duration::from_secs(60)).await;
+                forced = tokio::select!(
+                    _ = sleep(Duration::from_secs(60)) => false,
+                    _ = handle.run_once_requested() => true,
+                );
             }
         });
 
         let data = bot::Data {
             repo,
             event_rx: Mutex::new(Some(rx)),
-            should_close: should_close_atomic.clone(),
+            checker: checker_handle,
         };
         let mut bot = bot::new_client(&self.discord_token, data).await?;
         let bot_task = tokio::spawn(async move {
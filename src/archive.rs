@@ -0,0 +1,396 @@
+use std::{collections::HashMap, io::Read};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Repositories,
+    core::{ImagePng, Position},
+    domains::{FiefId, FiefRoleId, MaintenanceRule, Permissions, RoleId, UserId},
+};
+
+/// 归档格式的版本，格式发生不兼容的变化时递增，仍然可以读取旧版本的归档
+pub const ARCHIVE_VERSION: u32 = 2;
+const MANIFEST_PATH: &str = "manifest.json";
+/// 恢复领地时发给归档内成员的邀请的有效期
+const INVITATION_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub fief: FiefEntry,
+    pub chunks: Vec<ChunkEntry>,
+    pub members: Vec<MemberEntry>,
+    /// 领地内自定义的职位，版本 1 的归档没有记录
+    #[serde(default)]
+    pub roles: Vec<RoleEntry>,
+    /// 旧版本的归档没有记录身份组的授权
+    #[serde(default)]
+    pub role_grants: Vec<RoleGrantEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FiefEntry {
+    pub name: String,
+    pub check_interval_min: i64,
+    /// 暂停检查的截止时间，没有暂停检查时为空
+    #[serde(default)]
+    pub skip_check_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// 版本 1 的归档只记录了是否暂停检查，不再写入
+    #[serde(default, skip_serializing)]
+    pub check_enabled: Option<bool>,
+    pub adaptive: bool,
+    pub image_retention: usize,
+    pub maintenance_windows: Vec<MaintenanceRule>,
+    /// 旧版本的归档没有记录所有者
+    #[serde(default)]
+    pub owner: Option<UserId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkEntry {
+    pub name: String,
+    pub position: Position,
+    /// 参考图在归档内的路径
+    pub ref_img: Option<String>,
+    /// 遮罩图在归档内的路径
+    pub mask_img: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberEntry {
+    pub user_id: UserId,
+    pub permissions: i64,
+    /// 担任的职位的名字
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleEntry {
+    pub name: String,
+    pub permissions: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleGrantEntry {
+    pub role_id: RoleId,
+    pub permissions: i64,
+}
+
+/// 导入时领地名已被占用的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OnConflict {
+    /// 放弃导入
+    Abort,
+    /// 在名字后添加编号
+    Rename,
+    /// 删除已有的领地
+    Replace,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Restored {
+    pub fief_id: FiefId,
+    pub name: String,
+    pub chunks: usize,
+    pub members: usize,
+    /// 收到邀请、同意后才会重新加入的成员数量
+    pub invited: usize,
+}
+
+fn append(builder: &mut tar::Builder<Vec<u8>>, path: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp() as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// 将领地的设置、区块、参考图、遮罩图、成员、职位和身份组的授权导出为 tar 归档
+pub async fn export(repo: &Repositories, id: FiefId) -> Result<Vec<u8>> {
+    let fief = repo.fief().fief_by_id(id).await?;
+    let maintenance_windows = repo
+        .fief()
+        .maintenance_windows(id)
+        .await?
+        .into_iter()
+        .map(|w| w.rule)
+        .collect();
+
+    let mut builder = tar::Builder::new(vec![]);
+    let mut chunks = vec![];
+    let summaries = repo.chunk().summaries_by_fief(id).await?;
+    for (i, summary) in summaries.into_iter().enumerate() {
+        let chunk = summary.chunk;
+        let mut entry = ChunkEntry {
+            name: chunk.name,
+            position: chunk.position,
+            ref_img: None,
+            mask_img: None,
+        };
+        let ref_img = if summary.has_ref {
+            repo.chunk().ref_img(chunk.id).await?
+        } else {
+            None
+        };
+        if let Some(img) = ref_img {
+            let path = format!("chunks/{i}/ref.png");
+            append(&mut builder, &path, &img.into_inner())?;
+            entry.ref_img = Some(path);
+        }
+        let mask_img = if summary.has_mask {
+            repo.chunk().mask_img(chunk.id).await?
+        } else {
+            None
+        };
+        if let Some(img) = mask_img {
+            let path = format!("chunks/{i}/mask.png");
+            append(&mut builder, &path, &img.into_inner())?;
+            entry.mask_img = Some(path);
+        }
+        chunks.push(entry);
+    }
+
+    let mut members = vec![];
+    for m in repo.fief().members_with_perms(id).await? {
+        let role = repo.user().fief_role_in(m.user_id, id).await?;
+        members.push(MemberEntry {
+            user_id: m.user_id,
+            permissions: m.permissions.bits(),
+            role: role.map(|r| r.name),
+        });
+    }
+    let roles = repo
+        .fief()
+        .fief_roles(id)
+        .await?
+        .into_iter()
+        .map(|r| RoleEntry {
+            name: r.name,
+            permissions: r.permissions.bits(),
+        })
+        .collect();
+
+    let role_grants = repo
+        .fief()
+        .role_grants(id)
+        .await?
+        .into_iter()
+        .map(|g| RoleGrantEntry {
+            role_id: g.role_id,
+            permissions: g.permissions.bits(),
+        })
+        .collect();
+
+    let manifest = Manifest {
+        version: ARCHIVE_VERSION,
+        fief: FiefEntry {
+            name: fief.name,
+            check_interval_min: fief.check_interval.num_minutes(),
+            skip_check_until: (fief.skip_check_until > chrono::Utc::now())
+                .then_some(fief.skip_check_until),
+            check_enabled: None,
+            adaptive: fief.adaptive,
+            image_retention: fief.image_retention,
+            owner: fief.owner,
+            maintenance_windows,
+        },
+        chunks,
+        members,
+        roles,
+        role_grants,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+    append(&mut builder, MANIFEST_PATH, &manifest)?;
+    Ok(builder.into_inner()?)
+}
+
+/// 读取归档内的清单和所有文件
+pub fn read(data: &[u8]) -> Result<(Manifest, HashMap<String, Vec<u8>>)> {
+    let mut files = HashMap::new();
+    for entry in tar::Archive::new(data).entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let mut content = vec![];
+        entry.read_to_end(&mut content)?;
+        files.insert(path, content);
+    }
+
+    let manifest = files
+        .get(MANIFEST_PATH)
+        .ok_or_else(|| anyhow!("failed to read archive: missing {MANIFEST_PATH}"))?;
+    let manifest: Manifest = serde_json::from_slice(manifest)?;
+    if !(1..=ARCHIVE_VERSION).contains(&manifest.version) {
+        return Err(anyhow!(
+            "failed to read archive: unsupported version {}",
+            manifest.version
+        ));
+    }
+    Ok((manifest, files))
+}
+
+/// 从归档重新创建领地，`name` 为空时使用归档内的领地名，
+/// 名字已被占用并且选择放弃导入时返回 `None`，失败时不会留下导入了一半的领地
+///
+/// `owner` 为恢复领地的用户，会成为领地的所有者并拥有全部权限，归档内的
+/// 成员会收到保留原有权限的邀请；为空时（在命令行中恢复）沿用归档内记录的
+/// 所有者并直接加入成员
+pub async fn restore(
+    repo: &Repositories,
+    data: &[u8],
+    name: Option<&str>,
+    on_conflict: OnConflict,
+    owner: Option<UserId>,
+) -> Result<Option<Restored>> {
+    let uow = repo.begin().await?;
+    let restored = restore_in(&uow, data, name, on_conflict, owner).await?;
+    if restored.is_some() {
+        uow.commit().await?;
+    }
+    Ok(restored)
+}
+
+async fn restore_in(
+    repo: &Repositories,
+    data: &[u8],
+    name: Option<&str>,
+    on_conflict: OnConflict,
+    owner: Option<UserId>,
+) -> Result<Option<Restored>> {
+    let (manifest, mut files) = read(data)?;
+    let mut take = |path: &Option<String>| -> Result<Option<ImagePng>> {
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        let img = files
+            .remove(path)
+            .ok_or_else(|| anyhow!("failed to read archive: missing {path}"))?;
+        Ok(Some(ImagePng::new(img)))
+    };
+    let mut chunks = vec![];
+    for c in &manifest.chunks {
+        chunks.push((c, take(&c.ref_img)?, take(&c.mask_img)?));
+    }
+
+    let base = name.unwrap_or(&manifest.fief.name);
+    let interval = Some(chrono::Duration::minutes(manifest.fief.check_interval_min));
+    let exists = repo.fief().id(base).await.is_ok();
+    let (fief_id, name) = match (exists, on_conflict) {
+        (true, OnConflict::Abort) => return Ok(None),
+        (true, OnConflict::Rename) => {
+            let mut n = 2;
+            loop {
+                let name = format!("{base}-{n}");
+                if let Some(id) = repo.fief().create(&name, interval).await? {
+                    break (id, name);
+                }
+                n += 1;
+            }
+        }
+        (exists, _) => {
+            if exists {
+                repo.fief().remove_by_name(base).await?;
+            }
+            let id = repo
+                .fief()
+                .create(base, interval)
+                .await?
+                .ok_or_else(|| anyhow!("failed to create fief {base}"))?;
+            (id, base.to_owned())
+        }
+    };
+
+    let fief = &manifest.fief;
+    repo.fief().set_adaptive(fief_id, fief.adaptive).await?;
+    repo.fief()
+        .set_image_retention(fief_id, fief.image_retention)
+        .await?;
+    match (fief.skip_check_until, fief.check_enabled) {
+        (Some(until), _) => {
+            let skip = repo
+                .fief()
+                .skip_check_for(fief_id, chrono::Duration::zero(), Some(until));
+            skip.await?;
+        }
+        // 版本 1 的归档不知道暂停到何时，只能一直暂停
+        (None, Some(false)) => repo.fief().skip_check(fief_id).await?,
+        (None, _) => {}
+    }
+    for rule in &fief.maintenance_windows {
+        repo.fief().add_maintenance_window(fief_id, *rule).await?;
+    }
+
+    for (c, ref_, mask) in chunks {
+        let id = repo
+            .chunk()
+            .create(&c.name, fief_id, c.position)
+            .await?
+            .ok_or_else(|| anyhow!("failed to create chunk {}", c.name))?;
+        if ref_.is_some() {
+            repo.chunk().update_ref_img(id, ref_, None).await?;
+        }
+        if mask.is_some() {
+            repo.chunk().update_mask_img(id, mask, None).await?;
+        }
+    }
+
+    let mut roles = HashMap::new();
+    for r in &manifest.roles {
+        let perms = Permissions::from_bits_truncate(r.permissions);
+        let role_id = repo
+            .fief()
+            .create_fief_role(fief_id, &r.name, perms)
+            .await?
+            .ok_or_else(|| anyhow!("failed to create role {}", r.name))?;
+        roles.insert(r.name.as_str(), role_id);
+    }
+    let role_of = |m: &MemberEntry| -> Option<FiefRoleId> {
+        m.role.as_deref().and_then(|name| roles.get(name)).copied()
+    };
+
+    let (mut members, mut invited) = (0, 0);
+    if let Some(owner) = owner {
+        repo.user().create(owner, false).await?;
+        repo.user()
+            .join(owner, fief_id, Some(Permissions::ALL))
+            .await?;
+        repo.user()
+            .set_permissions_in(owner, fief_id, Permissions::ALL)
+            .await?;
+        members += 1;
+    }
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(INVITATION_DAYS);
+    for m in manifest.members.iter().filter(|m| Some(m.user_id) != owner) {
+        repo.user().create(m.user_id, false).await?;
+        let perms = Some(Permissions::from_bits_truncate(m.permissions));
+        if owner.is_some() {
+            let invitation =
+                repo.user()
+                    .invite(m.user_id, fief_id, perms, role_of(m), owner, expires_at);
+            if invitation.await?.is_some() {
+                invited += 1;
+            }
+        } else if repo.user().join(m.user_id, fief_id, perms).await? {
+            repo.user()
+                .set_fief_role_in(m.user_id, fief_id, role_of(m))
+                .await?;
+            members += 1;
+        }
+    }
+    repo.fief().set_owner(fief_id, owner.or(fief.owner)).await?;
+    for g in &manifest.role_grants {
+        let perms = Some(Permissions::from_bits_truncate(g.permissions));
+        repo.fief().grant_role(fief_id, g.role_id, perms).await?;
+    }
+
+    Ok(Some(Restored {
+        fief_id,
+        name,
+        chunks: manifest.chunks.len(),
+        members,
+        invited,
+    }))
+}
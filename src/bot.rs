@@ -1,18 +1,22 @@
 mod commands;
+pub mod dashboard;
+pub mod i18n;
+mod interactions;
 mod notification;
+pub mod pages;
+pub mod perms;
 
-use std::{
-    sync::{Arc, atomic::AtomicBool},
-    time::Duration,
-};
+use std::{sync::Arc, time::Duration};
 
-use poise::serenity_prelude::{self as serenity, CacheHttp, ChannelId, Http};
+use poise::serenity_prelude::{
+    self as serenity, CacheHttp, ChannelId, FullEvent, Http, Interaction,
+};
 use tokio::sync::{Mutex, mpsc::Receiver};
 
 use crate::{
     bot::commands::start_with,
     cfg,
-    check::Event,
+    check::{CheckerHandle, Event},
     core::{
         get_or_env,
         log::{error, info, warn},
@@ -27,7 +31,7 @@ type Context<'a> = poise::Context<'a, Data, Error>;
 pub struct Data {
     pub repo: &'static crate::Repositories,
     pub event_rx: Mutex<Option<Receiver<Event>>>,
-    pub should_close: Arc<AtomicBool>,
+    pub checker: CheckerHandle,
 }
 
 static CHANNEL_ID: std::sync::LazyLock<ChannelId> = std::sync::LazyLock::new(|| {
@@ -51,6 +55,14 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
         }
         poise::FrameworkError::Command { error, ctx, .. } => {
             warn!("Error in command `{}`: {:?}", ctx.command().name, error,);
+            // 数据库出错时告知用户具体原因，而不是让指令没有任何回应
+            if let Some(e) = error.downcast_ref::<crate::RepoError>() {
+                let msg = i18n::tr!(ctx, "error-repo", reason = commands::reason(ctx, e));
+                let reply = poise::CreateReply::default().content(msg).ephemeral(true);
+                if let Err(e) = ctx.send(reply).await {
+                    warn!("Error while handling error: {}", e)
+                }
+            }
         }
         error => {
             if let Err(e) = poise::builtins::on_error(error).await {
@@ -60,11 +72,32 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
     }
 }
 
+async fn event_handler(
+    ctx: &serenity::Context,
+    event: &FullEvent,
+    data: &Data,
+) -> Result<(), Error> {
+    if let FullEvent::InteractionCreate {
+        interaction: Interaction::Component(ci),
+    } = event
+        && let Err(e) = interactions::handle_component(ctx, ci, data).await
+    {
+        warn!(
+            "Error while handling interaction `{}`: {:?}",
+            ci.data.custom_id, e
+        );
+    }
+    Ok(())
+}
+
 pub async fn new_client(token: &impl AsRef<str>, data: Data) -> anyhow::Result<serenity::Client> {
+    let mut commands = commands::all();
+    i18n::localize_commands(&mut commands);
+
     // FrameworkOptions contains all of poise's configuration option in one struct
     // Every option can be omitted to use its default value
     let options = poise::FrameworkOptions {
-        commands: commands::all(),
+        commands,
         prefix_options: poise::PrefixFrameworkOptions {
             prefix: Some(".".into()),
             edit_tracker: Some(Arc::new(poise::EditTracker::for_timespan(
@@ -88,6 +121,7 @@ pub async fn new_client(token: &impl AsRef<str>, data: Data) -> anyhow::Result<s
         },
         // The global error handler for all error cases that may occur
         on_error: |error| Box::pin(on_error(error)),
+        event_handler: |ctx, event, _framework, data| Box::pin(event_handler(ctx, event, data)),
         // Enforce command checks even for owners (enforced by default)
         // Set to true to bypass checks, which is useful for testing
         skip_checks_for_owners: false,
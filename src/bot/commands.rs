@@ -1,26 +1,51 @@
+use std::{sync::Arc, time::Duration};
+
+use fluent::FluentArgs;
 use poise::{
     CreateReply,
-    serenity_prelude::{ChannelId, CreateAttachment, Http, MessageBuilder},
+    serenity_prelude::{
+        self as serenity, ButtonStyle, ChannelId, ComponentInteractionCollector, CreateActionRow,
+        CreateAttachment, CreateButton, CreateInteractionResponse,
+        CreateInteractionResponseMessage, Http, Mention,
+    },
 };
 use tokio::sync::mpsc::Receiver;
 
+pub(super) use super::perms::has_perms;
 use super::{Context, Data, Error};
 use crate::{
-    Repositories,
-    bot::notification::notification_message,
+    RepoError, Repositories,
+    bot::{
+        dashboard,
+        i18n::{self, Locale, tr},
+        notification::notification_message,
+    },
     check::Event,
-    domains::{FiefId, Permissions, UserId},
+    core::log::warn,
+    domains::{
+        AuditAction, AuditEntry, AuditRecord, AuditValue, FiefId, Invitation, Permissions, RoleId,
+        UserId,
+    },
     net,
 };
 
 mod admin;
+mod autocomplete;
 mod chunk;
 mod fief;
+mod role;
+mod upload;
 mod user;
 
+/// 回复只有调用者可见的消息，字面量参数为 `locales/` 中文本的 id
 macro_rules! say {
-    ($ctx:expr, $fmt:literal $($args:tt)*) => {
-        $ctx.send(poise::CreateReply::default().content(format!($fmt $($args)*)).ephemeral(true)).await?
+    ($ctx:expr, $id:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $ctx.send(
+            poise::CreateReply::default()
+                .content($crate::bot::i18n::tr!($ctx, $id $(, $name = $value)*))
+                .ephemeral(true),
+        )
+        .await?
     };
     ($ctx:expr, $str:expr) => {
         $ctx.send(poise::CreateReply::default().content($str).ephemeral(true)).await?
@@ -36,6 +61,7 @@ pub(super) fn all() -> Vec<poise::Command<Data, Error>> {
         fief::wmfief(),
         chunk::wmchunk(),
         user::wmuser(),
+        role::wmrole(),
         admin::wmop(),
     ]
 }
@@ -71,9 +97,9 @@ pub async fn wmfetch(
     #[description = "区块在 Wplace 上的 Y 坐标"]
     y: usize,
 ) -> Result<(), Error> {
-    say!(ctx, "正在从 wplace.live 获取图片，请稍等……");
+    say!(ctx, "fetch-waiting");
     let Ok((_, img)) = net::fetch_current_image([x, y]).await else {
-        say!(ctx, "网络异常，请稍后重试。");
+        say!(ctx, "error-network");
         return Ok(());
     };
     let file_name = format!("chunk_{x}_{y}.png");
@@ -89,46 +115,292 @@ pub async fn wmfetch(
 /// 列出用户权限种类
 #[poise::command(prefix_command, slash_command, category = "基本指令")]
 pub async fn wmpermissions(ctx: Context<'_>) -> Result<(), Error> {
-    let mut msg = MessageBuilder::new();
-    msg.push("# 权限说明\n")
-        .push("## 领地相关\n")
-        .push("- `FIEF_EDIT`: 编辑领地信息\n")
-        .push("- `FIEF_DELETE`: 删除领地\n")
-        .push("- `FIEF_ALL`: 领地的全部权限，等同于 `FIEF_EDIT` + `FIEF_DELETE`\n")
-        .push("## 区块相关\n")
-        .push("- `CHUNK_ADD`: 在领地内添加区块\n")
-        .push("- `CHUNK_EDIT`: 编辑领地内区块信息\n")
-        .push("- `CHUNK_DELETE`: 删除领地内的区块\n")
-        .push("- `CHUNK_ALL`: 区块的全部权限，详细说明同上\n")
-        .push("## 成员相关\n")
-        .push("- `MEMBER_INVITE`: 邀请成员至领地\n")
-        .push("- `MEMBER_EDIT_PERMS`: 编辑用户在领地内的权限\n")
-        .push("- `MEMBER_KICK`: 将成员移出领地\n")
-        .push("- `MEMBER_ALL`: 成员的全部权限，详细说明同上\n")
-        .push("## 其他\n")
-        .push("- `NONE`: 无任何权限\n")
-        .push("- `ALL`: 拥有上述所有权限\n");
-
-    say!(ctx, msg.build());
+    say!(ctx, "permissions-help");
     Ok(())
 }
 
+/// 解析以空格、逗号或 `|` 分隔的多个权限名称
+fn parse_perms(s: &str) -> Option<Permissions> {
+    s.split([' ', ',', '|'])
+        .filter(|s| !s.is_empty())
+        .try_fold(Permissions::NONE, |p, name| {
+            Some(p | Permissions::from_name(name)?)
+        })
+}
+
+fn perms_str(p: Permissions) -> String {
+    let s = p
+        .iter_names()
+        .map(|(s, _)| s)
+        .fold(String::new(), |a, s| a + "`" + s + "` ");
+    if s.is_empty() { "`NONE`".into() } else { s }
+}
+
 fn id_of(user: &poise::serenity_prelude::User) -> UserId {
     UserId(user.id.get() as i64)
 }
 
-async fn has_perms(repo: &Repositories, id: UserId, fief_id: FiefId, perms: Permissions) -> bool {
-    let Ok(user) = repo.user().user_by_id(id).await else {
-        return false;
+pub(super) fn role_ids(roles: &[serenity::RoleId]) -> Vec<RoleId> {
+    roles.iter().map(|r| RoleId(r.get() as i64)).collect()
+}
+
+/// 检查指令的调用者是否拥有权限，私信中调用时只考虑用户自身的权限
+pub(super) async fn author_has_perms(
+    ctx: Context<'_>,
+    fief_id: FiefId,
+    perms: Permissions,
+) -> Result<bool, Error> {
+    let roles = author_roles(ctx).await;
+    has_perms(ctx.data().repo, id_of(ctx.author()), &roles, fief_id, perms).await
+}
+
+/// 调用者在当前服务器中的身份组，私信中为空
+pub(super) async fn author_roles(ctx: Context<'_>) -> Vec<RoleId> {
+    match ctx.author_member().await {
+        Some(member) => role_ids(&member.roles),
+        None => vec![],
+    }
+}
+
+/// 成员不能授予自己没有的权限，不满足时提示用户并返回 `false`
+pub(super) async fn author_can_grant(
+    ctx: Context<'_>,
+    fief_id: FiefId,
+    perms: Permissions,
+) -> Result<bool, Error> {
+    if author_has_perms(ctx, fief_id, perms).await? {
+        return Ok(true);
+    }
+    say!(ctx, "error-grant-missing-perms");
+    Ok(false)
+}
+
+pub(super) async fn is_owner(
+    repo: &Repositories,
+    fief_id: FiefId,
+    id: UserId,
+) -> Result<bool, Error> {
+    Ok(repo.fief().fief_by_id(fief_id).await?.owner == Some(id))
+}
+
+/// 按名字查找领地，不存在时提示用户并返回 `None`
+pub(super) async fn fief_id_of(ctx: Context<'_>, name: &str) -> Result<Option<FiefId>, Error> {
+    match ctx.data().repo.fief().id(name).await {
+        Ok(id) => Ok(Some(id)),
+        Err(RepoError::NotFound) => {
+            say!(ctx, "error-fief-not-found", name = name);
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 写入操作记录，失败时只记录日志，不影响已经完成的操作
+pub(super) async fn record_audit(repo: &Repositories, actor: UserId, record: AuditRecord) {
+    let record = AuditRecord {
+        actor: Some(actor),
+        ..record
     };
-    if user.is_admin {
-        return true;
+    if let Err(e) = repo.audit().record(record).await {
+        warn!("failed to record audit entry: {e}");
     }
-    let Ok(p) = repo.user().permissions_in(id, fief_id).await else {
-        return false;
+}
+
+/// 以指令的调用者为操作者写入操作记录
+pub(super) async fn audit(ctx: Context<'_>, record: AuditRecord) {
+    record_audit(ctx.data().repo, id_of(ctx.author()), record).await
+}
+
+/// 接受邀请并加入领地，已经是领地成员时只删除邀请并返回 `false`
+pub(super) async fn accept_invitation(
+    repo: &Repositories,
+    invitation: &Invitation,
+) -> Result<bool, Error> {
+    let (user_id, fief_id) = (invitation.user_id, invitation.fief_id);
+    let uow = repo.begin().await?;
+    let joined = uow
+        .user()
+        .join(user_id, fief_id, invitation.permissions)
+        .await?;
+    if joined && invitation.fief_role.is_some() {
+        uow.user()
+            .set_fief_role_in(user_id, fief_id, invitation.fief_role)
+            .await?;
+    }
+    uow.user().remove_invitation(invitation.id).await?;
+    uow.commit().await?;
+
+    if joined {
+        let record = AuditRecord::new(AuditAction::MemberJoin)
+            .fief(fief_id)
+            .target(Mention::User((user_id.0 as u64).into()).to_string())
+            .after(AuditValue::msg("audit-value-accept-invitation"));
+        record_audit(repo, user_id, record).await;
+    }
+    Ok(joined)
+}
+
+fn action_str(locale: Locale, action: AuditAction) -> String {
+    match action {
+        AuditAction::FiefCreate => tr!(locale, "audit-fief-create"),
+        AuditAction::FiefDelete => tr!(locale, "audit-fief-delete"),
+        AuditAction::FiefRename => tr!(locale, "audit-fief-rename"),
+        AuditAction::FiefTransfer => tr!(locale, "audit-fief-transfer"),
+        AuditAction::FiefSettings => tr!(locale, "audit-fief-settings"),
+        AuditAction::FiefCheck => tr!(locale, "audit-fief-check"),
+        AuditAction::ChunkAdd => tr!(locale, "audit-chunk-add"),
+        AuditAction::ChunkDelete => tr!(locale, "audit-chunk-delete"),
+        AuditAction::ChunkEdit => tr!(locale, "audit-chunk-edit"),
+        AuditAction::ChunkImage => tr!(locale, "audit-chunk-image"),
+        AuditAction::MemberJoin => tr!(locale, "audit-member-join"),
+        AuditAction::MemberLeave => tr!(locale, "audit-member-leave"),
+        AuditAction::PermissionChange => tr!(locale, "audit-permission-change"),
+        AuditAction::AdminChange => tr!(locale, "audit-admin-change"),
+        AuditAction::CheckerPause => tr!(locale, "audit-checker-pause"),
+    }
+}
+
+/// 操作记录中的值，文本的 id 按照 `locale` 翻译
+pub(super) fn audit_value_str(locale: Locale, value: &AuditValue) -> String {
+    match value {
+        AuditValue::Text(s) => s.clone(),
+        AuditValue::Message { id, args } => {
+            let mut fluent_args = FluentArgs::new();
+            for (name, value) in args {
+                fluent_args.set(name.clone(), audit_value_str(locale, value));
+            }
+            i18n::get(locale, id, &fluent_args)
+        }
+    }
+}
+
+/// 操作记录一直增长，查看时只加载最近的这么多条
+const AUDIT_LIMIT: usize = 200;
+
+/// 操作记录的标题，记录多于 `AUDIT_LIMIT` 条时注明只显示了最近的部分
+fn audit_title(locale: Locale, title: String, total: usize) -> String {
+    if total <= AUDIT_LIMIT {
+        return title;
+    }
+    let note = tr!(
+        locale,
+        "audit-truncated",
+        shown = AUDIT_LIMIT,
+        total = total
+    );
+    format!("{title}\n{note}")
+}
+
+/// 一条操作记录的描述：时间、操作者、操作、对象以及修改前后的值
+fn describe_audit(locale: Locale, e: &AuditEntry) -> String {
+    let r = &e.record;
+    let actor = r.actor.map_or(tr!(locale, "audit-system"), |u| {
+        Mention::User((u.0 as u64).into()).to_string()
+    });
+    let mut s = format!(
+        "<t:{}:f> {actor} {}",
+        e.time.timestamp(),
+        action_str(locale, r.action)
+    );
+    if let Some(target) = &r.target {
+        s += &format!(" {}", audit_value_str(locale, target));
+    }
+    let value = |v: &Option<AuditValue>| v.as_ref().map(|v| audit_value_str(locale, v));
+    match (value(&r.before), value(&r.after)) {
+        (Some(before), Some(after)) => {
+            s += &tr!(locale, "audit-changed", before = before, after = after)
+        }
+        (Some(before), None) => s += &tr!(locale, "audit-removed", before = before),
+        (None, Some(after)) => s += &tr!(locale, "audit-set", after = after),
+        (None, None) => {}
+    }
+    s
+}
+
+/// 仓库错误对应的提示
+pub(super) fn reason(locale: impl Into<Locale>, e: &RepoError) -> String {
+    let locale = locale.into();
+    match e {
+        RepoError::NotFound => tr!(locale, "reason-not-found"),
+        RepoError::Conflict => tr!(locale, "reason-conflict"),
+        RepoError::ForeignKey => tr!(locale, "reason-foreign-key"),
+        RepoError::Invalid(_) => tr!(locale, "reason-invalid"),
+        RepoError::Storage(_) => tr!(locale, "reason-storage"),
+        RepoError::Decode(_) => tr!(locale, "reason-decode"),
+    }
+}
+
+/// 在当前频道发送并置顶状态面板，`fief_id` 为 `None` 时显示所有领地
+async fn create_dashboard(ctx: Context<'_>, fief_id: Option<FiefId>) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    // 状态面板所有人可见，使用服务器的语言
+    let msg = dashboard::dashboard_message(repo, Locale::of_guild(ctx), fief_id).await?;
+    let msg = match ctx.channel_id().send_message(ctx, msg).await {
+        Ok(msg) => msg,
+        Err(e) => {
+            say!(ctx, "dashboard-create-failed", error = e.to_string());
+            return Ok(());
+        }
+    };
+    // 缺少管理消息的权限时无法置顶，状态面板仍然会更新
+    let pinned = msg.pin(ctx).await.is_ok();
+
+    let channel_id = ctx.channel_id().get() as i64;
+    repo.dashboard()
+        .create(fief_id, channel_id, msg.id.get() as i64)
+        .await?;
+    let record = AuditRecord::new(AuditAction::FiefSettings)
+        .target(AuditValue::msg("audit-value-dashboard"));
+    let record = match fief_id {
+        Some(id) => record.fief(id),
+        None => record,
+    };
+    audit(ctx, record).await;
+    say!(
+        ctx,
+        "dashboard-create-done",
+        pinned = if pinned { "yes" } else { "no" }
+    );
+    Ok(())
+}
+
+/// 发送带有确认和取消按钮的消息并等待用户选择，超时视为取消
+async fn confirm(ctx: Context<'_>, reply: CreateReply) -> Result<bool, Error> {
+    let id = ctx.id();
+    let (yes, no) = (format!("{id}:confirm"), format!("{id}:cancel"));
+    let buttons = vec![
+        CreateButton::new(&yes)
+            .style(ButtonStyle::Success)
+            .label(tr!(ctx, "button-confirm")),
+        CreateButton::new(&no)
+            .style(ButtonStyle::Secondary)
+            .label(tr!(ctx, "button-cancel")),
+    ];
+    let reply = reply
+        .components(vec![CreateActionRow::Buttons(buttons)])
+        .ephemeral(true);
+    let handle = ctx.send(reply).await?;
+
+    let prefix = format!("{id}:");
+    let Some(ci) = ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .filter(move |ci| ci.data.custom_id.starts_with(&prefix))
+        .timeout(Duration::from_secs(60))
+        .await
+    else {
+        let timeout = CreateReply::default()
+            .content(tr!(ctx, "confirm-timeout"))
+            .components(vec![]);
+        handle.edit(ctx, timeout).await?;
+        return Ok(false);
     };
 
-    p.contains(perms)
+    let confirmed = ci.data.custom_id == yes;
+    let msg = CreateInteractionResponseMessage::new().components(vec![]);
+    ci.create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
+        .await?;
+    Ok(confirmed)
 }
 
 pub(super) async fn start_with(
@@ -139,9 +411,24 @@ pub(super) async fn start_with(
 ) -> Result<(), Error> {
     // channel.say(&http, "WMonitor 已在当前频道开启。").await?;
 
+    // 通知发送到公共频道，使用服务器的语言
+    let locale = match channel.to_channel(&http).await {
+        Ok(serenity::Channel::Guild(c)) => match c.guild_id.to_partial_guild(&http).await {
+            Ok(guild) => Locale::pick(None, Some(&guild.preferred_locale)),
+            Err(_) => Locale::fallback(),
+        },
+        _ => Locale::fallback(),
+    };
+
+    let http = Arc::new(http);
+    let dashboards = dashboard::spawn_refresher(http.clone(), repo, locale);
     tokio::spawn(async move {
         while let Some(event) = tx.recv().await {
-            if let Ok(msg) = notification_message(repo, event).await {
+            if let Event::Checked(fief_id) = event {
+                dashboards.send(fief_id).ok();
+                continue;
+            }
+            if let Ok(Some(msg)) = notification_message(repo, locale, event).await {
                 channel.send_message(&http, msg).await.ok();
             }
         }
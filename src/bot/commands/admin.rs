@@ -1,11 +1,17 @@
-use std::sync::atomic::Ordering;
+use std::collections::HashMap;
 
-use poise::serenity_prelude::{Http, Mention, MessageBuilder};
+use poise::serenity_prelude::{Mention, MessageBuilder};
 
 use super::{Context, Error, say};
 use crate::{
-    bot::commands::{id_of, start_with},
-    domains::{User, UserId},
+    bot::{
+        commands::{
+            AUDIT_LIMIT, audit, audit_title, create_dashboard, describe_audit, id_of, reason,
+        },
+        i18n::{Locale, tr},
+        pages::{Pages, Row, Status},
+    },
+    domains::{AuditAction, AuditRecord, AuditValue, FiefId, User, UserId},
 };
 
 /// 管理员指令
@@ -13,48 +19,161 @@ use crate::{
     prefix_command,
     slash_command,
     category = "管理员",
-    subcommands("op", "deop", "listop", "stop", "start", "fiefs")
+    subcommands(
+        "op",
+        "deop",
+        "listop",
+        "pause",
+        "resume",
+        "runonce",
+        "status",
+        "fiefs",
+        "dashboard",
+        "audit_log"
+    )
 )]
 pub(super) async fn wmop(_: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-/// 启用 WMonitor
+/// 暂停检查器的定期检查
 #[poise::command(prefix_command, slash_command, category = "管理员")]
-pub(super) async fn start(ctx: Context<'_>) -> Result<(), Error> {
+pub(super) async fn pause(ctx: Context<'_>) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
     let author = repo.user().user_by_id(id_of(ctx.author())).await;
     let Ok(User { is_admin: true, .. }) = author else {
-        say!(ctx, "错误：操作失败，权限不足。");
+        say!(ctx, "error-permission-denied");
         return Ok(());
     };
 
-    let channel = ctx.channel_id();
-    let Some(tx) = ctx.data().event_rx.lock().await.take() else {
-        say!(ctx, "通知频道设置后无法更改，请重启程序并再次设置。");
+    if ctx.data().checker.pause() {
+        audit(
+            ctx,
+            AuditRecord::new(AuditAction::CheckerPause).after(AuditValue::msg("audit-value-pause")),
+        )
+        .await;
+        say!(ctx, "op-pause-done");
+    } else {
+        say!(ctx, "op-pause-already");
+    }
+    Ok(())
+}
+
+/// 恢复检查器的定期检查
+#[poise::command(prefix_command, slash_command, category = "管理员")]
+pub(super) async fn resume(ctx: Context<'_>) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let author = repo.user().user_by_id(id_of(ctx.author())).await;
+    let Ok(User { is_admin: true, .. }) = author else {
+        say!(ctx, "error-permission-denied");
         return Ok(());
     };
-    let http = Http::new(ctx.http().token());
-    start_with(http, ctx.data().repo, tx, channel).await
+
+    if ctx.data().checker.resume() {
+        audit(
+            ctx,
+            AuditRecord::new(AuditAction::CheckerPause)
+                .after(AuditValue::msg("audit-value-resume")),
+        )
+        .await;
+        say!(ctx, "op-resume-done");
+    } else {
+        say!(ctx, "op-resume-not-paused");
+    }
+    Ok(())
 }
 
-/// 关闭 WMonitor
+/// 立即执行一轮检查（暂停状态下同样有效）
 #[poise::command(prefix_command, slash_command, category = "管理员")]
-pub(super) async fn stop(ctx: Context<'_>) -> Result<(), Error> {
+pub(super) async fn runonce(ctx: Context<'_>) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
     let author = repo.user().user_by_id(id_of(ctx.author())).await;
     let Ok(User { is_admin: true, .. }) = author else {
-        say!(ctx, "错误：操作失败，权限不足。");
+        say!(ctx, "error-permission-denied");
         return Ok(());
     };
 
-    ctx.data().should_close.store(true, Ordering::SeqCst);
-    say!(
-        ctx,
-        "已发送关闭信号，请等待检查器完成工作（大概需要一分钟）。"
-    );
+    ctx.data().checker.run_once();
+    say!(ctx, "op-runonce-done");
+    Ok(())
+}
+
+/// 查看检查器状态
+#[poise::command(prefix_command, slash_command, category = "管理员")]
+pub(super) async fn status(ctx: Context<'_>) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let author = repo.user().user_by_id(id_of(ctx.author())).await;
+    let Ok(User { is_admin: true, .. }) = author else {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    };
+
+    let checker = &ctx.data().checker;
+    let status = checker.status();
+    let now = chrono::Utc::now();
+
+    let locale = Locale::from(ctx);
+    let mut builder = MessageBuilder::new();
+    builder
+        .push(tr!(locale, "op-status-title"))
+        .push("\n")
+        .push(if checker.is_paused() {
+            tr!(locale, "op-status-paused")
+        } else {
+            tr!(locale, "op-status-running")
+        })
+        .push("\n");
+
+    builder.push(tr!(locale, "op-status-queue"));
+    if status.queue.is_empty() {
+        builder.push(tr!(locale, "op-status-queue-empty"));
+    } else {
+        for fief_id in status.queue {
+            let name = repo.fief().name(fief_id).await.unwrap_or_default();
+            if status.checking == Some(fief_id) {
+                builder.push(tr!(locale, "op-status-checking", name = &name));
+            } else {
+                builder.push(format!("**{name}**"));
+            }
+            builder.push(" ");
+        }
+    }
+    builder.push("\n");
+
+    builder.push(tr!(locale, "op-status-last-pass"));
+    match (status.last_pass_duration, status.last_pass_at) {
+        (Some(dur), Some(at)) => builder.push(tr!(
+            locale,
+            "op-status-last-pass-at",
+            seconds = dur.as_secs(),
+            minutes = (now - at).num_minutes()
+        )),
+        _ => builder.push(tr!(locale, "none")),
+    };
+    builder.push("\n");
+
+    let fiefs = repo.fief().all().await.unwrap_or_default();
+    let next = fiefs.into_iter().min_by_key(|f| f.next_check());
+    builder.push(tr!(locale, "op-status-next"));
+    match next {
+        Some(fief) if fief.next_check() - now < chrono::Duration::weeks(100) => {
+            let min = (fief.next_check() - now).num_minutes().max(0);
+            builder.push(tr!(
+                locale,
+                "op-status-next-at",
+                name = &fief.name,
+                minutes = min
+            ))
+        }
+        _ => builder.push(tr!(locale, "none")),
+    };
+    builder.push("\n");
+
+    say!(ctx, builder.build());
     Ok(())
 }
 
@@ -65,7 +184,7 @@ pub(super) async fn listop(ctx: Context<'_>) -> Result<(), Error> {
 
     let author = repo.user().user_by_id(id_of(ctx.author())).await;
     let Ok(User { is_admin: true, .. }) = author else {
-        say!(ctx, "错误：操作失败，权限不足。");
+        say!(ctx, "error-permission-denied");
         return Ok(());
     };
 
@@ -74,12 +193,12 @@ pub(super) async fn listop(ctx: Context<'_>) -> Result<(), Error> {
             let mentions = admins
                 .into_iter()
                 .map(|u| Mention::User((u.id.0 as u64).into()))
-                .fold("# 管理员列表\n".to_string(), |s, m| {
+                .fold(tr!(ctx, "op-listop-title") + "\n", |s, m| {
                     s + m.to_string().as_ref() + "\n"
                 });
             say!(ctx, mentions)
         }
-        Err(e) => say!(ctx, "错误：无法获取管理员列表: {e}"),
+        Err(e) => say!(ctx, "op-listop-failed", reason = reason(ctx, &e)),
     };
     Ok(())
 }
@@ -103,30 +222,55 @@ async fn set_admin(ctx: Context<'_>, user: Mention, is_admin: bool) -> Result<()
 
     let author = repo.user().user_by_id(id_of(ctx.author())).await;
     let Ok(User { is_admin: true, .. }) = author else {
-        say!(ctx, "错误：操作失败，权限不足。");
+        say!(ctx, "error-permission-denied");
         return Ok(());
     };
 
     let Mention::User(user_id) = user else {
-        say!(ctx, "参数错误：请@用户作为输入。");
+        say!(ctx, "error-mention-user");
         return Ok(());
     };
 
     let user_id = UserId(user_id.get() as i64);
     if let Err(e) = repo.user().create(user_id, false).await {
-        say!(ctx, "无法存储用户信息: {e}。");
+        say!(ctx, "error-store-user", reason = reason(ctx, &e));
     }
 
-    let a = if is_admin { "" } else { "非" };
+    let admin = if is_admin { "yes" } else { "no" };
     let is_admin_old = repo.user().user_by_id(user_id).await?.is_admin;
     if is_admin_old == is_admin {
-        say!(ctx, "错误：{user} 已经是{a}管理员。");
+        say!(
+            ctx,
+            "op-set-already",
+            user = user.to_string(),
+            admin = admin
+        );
         return Ok(());
     }
 
     match repo.user().set_admin(user_id, is_admin).await {
-        Ok(_) => say!(ctx, "已设置 {user} 为{a}管理员。"),
-        Err(e) => say!(ctx, "错误：无法设置 {user} 为{a}管理员: {e}。"),
+        Ok(_) => {
+            let status = |is_admin| {
+                AuditValue::msg(if is_admin {
+                    "audit-value-admin"
+                } else {
+                    "audit-value-not-admin"
+                })
+            };
+            let record = AuditRecord::new(AuditAction::AdminChange)
+                .target(user.to_string())
+                .before(status(is_admin_old))
+                .after(status(is_admin));
+            audit(ctx, record).await;
+            say!(ctx, "op-set-done", user = user.to_string(), admin = admin)
+        }
+        Err(e) => say!(
+            ctx,
+            "op-set-failed",
+            user = user.to_string(),
+            admin = admin,
+            reason = reason(ctx, &e)
+        ),
     };
     Ok(())
 }
@@ -138,44 +282,123 @@ pub(super) async fn fiefs(ctx: Context<'_>) -> Result<(), Error> {
 
     let author = repo.user().user_by_id(id_of(ctx.author())).await;
     let Ok(User { is_admin: true, .. }) = author else {
-        say!(ctx, "错误：操作失败，权限不足。");
+        say!(ctx, "error-permission-denied");
         return Ok(());
     };
 
-    let Ok(fiefs) = repo.fief().all().await else {
-        say!(ctx, "错误：无法获取领地信息。");
+    let (Ok(summaries), Ok(members)) = (
+        repo.fief().summaries().await,
+        repo.fief().all_members().await,
+    ) else {
+        say!(ctx, "op-fiefs-failed");
         return Ok(());
     };
+    let mut members_of = HashMap::<FiefId, Vec<UserId>>::new();
+    for m in members {
+        members_of.entry(m.fief_id).or_default().push(m.user_id);
+    }
 
-    let mut builder = MessageBuilder::new();
-    builder.push("# 领地列表\n");
+    let locale = Locale::from(ctx);
+    let mut pages = Pages::new(tr!(locale, "op-fiefs-title"));
     let now = chrono::Utc::now();
-    for fief in fiefs {
+    for summary in summaries {
+        let fief = summary.fief;
+        let members = members_of.remove(&fief.id).unwrap_or_default();
         let enabled = fief.skip_check_until < now;
 
-        builder.push("`[").push(fief.name).push("] ");
-        builder.push(if enabled {
-            "启用中 | "
-        } else {
-            "禁用中 | "
-        });
-
-        let chunks = repo.fief().chunk_count(fief.id).await.unwrap_or(0);
-        builder.push("区块: ").push(chunks.to_string()).push(" | ");
-        let min = fief.check_interval.num_minutes().to_string();
-        builder.push("间隔: ").push(min).push(" 分钟`\n");
-        let members = repo.fief().members(fief.id).await.unwrap_or(vec![]);
+        let mut builder = MessageBuilder::new();
+        builder.push("`[").push(&fief.name).push("] ");
+        builder.push(tr!(
+            locale,
+            "op-fiefs-item",
+            enabled = if enabled { "yes" } else { "no" },
+            chunks = summary.chunk_count,
+            diff = summary.diff_count,
+            interval = fief.check_interval.num_minutes()
+        ));
+        if fief.adaptive {
+            builder.push(tr!(
+                locale,
+                "op-fiefs-adaptive",
+                interval = fief.effective_interval.num_minutes()
+            ));
+        }
+        builder.push("`\n");
         let mentions = members
             .into_iter()
             .map(|u| Mention::User((u.0 as u64).into()))
-            .fold("`成员:` ".to_string(), |s, m| {
+            .fold(tr!(locale, "op-fiefs-members") + " ", |s, m| {
                 s + m.to_string().as_str() + " "
             });
+        builder.push(mentions).push("\n");
 
-        builder.push(mentions);
-        builder.push("\n\n");
+        let status = match (enabled, summary.diff_count) {
+            (false, _) => Status::Paused,
+            (true, 0) => Status::Ok,
+            (true, _) => Status::Damaged,
+        };
+        pages.push(
+            Row::new(builder.build())
+                .status(status)
+                .diff(summary.diff_count),
+        );
     }
 
-    say!(ctx, builder.build());
-    Ok(())
+    pages.sortable().send(ctx).await
+}
+
+/// 在当前频道发送所有领地的状态面板，每次检查后自动更新
+#[poise::command(prefix_command, slash_command, category = "管理员")]
+pub(super) async fn dashboard(ctx: Context<'_>) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let author = repo.user().user_by_id(id_of(ctx.author())).await;
+    let Ok(User { is_admin: true, .. }) = author else {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    };
+
+    create_dashboard(ctx, None).await
+}
+
+/// 查看所有领地和管理员的操作记录，从新到旧排列
+#[poise::command(prefix_command, slash_command, rename = "audit", category = "管理员")]
+pub(super) async fn audit_log(ctx: Context<'_>) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let author = repo.user().user_by_id(id_of(ctx.author())).await;
+    let Ok(User { is_admin: true, .. }) = author else {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    };
+
+    let total = repo.audit().count().await?;
+    if total == 0 {
+        say!(ctx, "op-audit-empty");
+        return Ok(());
+    }
+
+    let entries = repo.audit().all(0, AUDIT_LIMIT).await?;
+    let names = repo
+        .fief()
+        .all()
+        .await?
+        .into_iter()
+        .map(|f| (f.id, f.name))
+        .collect::<HashMap<_, _>>();
+    let locale = Locale::from(ctx);
+    let title = audit_title(locale, tr!(locale, "op-audit-title"), total);
+    let mut pages = Pages::new(title);
+    for e in entries {
+        // 领地可能已被删除，此时只显示 id
+        let fief = match e.record.fief_id {
+            Some(id) => match names.get(&id) {
+                Some(n) => format!("[**{n}**] "),
+                None => format!("[#{}] ", id.0),
+            },
+            None => String::new(),
+        };
+        pages.push(Row::new(format!("- {fief}{}", describe_audit(locale, &e))));
+    }
+    pages.send(ctx).await
 }
@@ -0,0 +1,91 @@
+use poise::serenity_prelude::ResolvedValue;
+
+use super::{Context, author_roles, id_of};
+use crate::{RepoError, core::log::warn};
+
+/// Discord 最多显示 25 个候选项
+const MAX_CHOICES: usize = 25;
+
+fn filter(names: Vec<String>, partial: &str) -> Vec<String> {
+    let partial = partial.to_lowercase();
+    names
+        .into_iter()
+        .filter(|n| n.to_lowercase().contains(&partial))
+        .take(MAX_CHOICES)
+        .collect()
+}
+
+/// 调用者可见的领地：管理员可以看到所有领地，其他用户只能看到所属的领地
+async fn visible_fief_names(ctx: Context<'_>) -> Result<Vec<String>, RepoError> {
+    let repo = &ctx.data().repo;
+    let id = id_of(ctx.author());
+    match repo.user().user_by_id(id).await {
+        Ok(user) if user.is_admin => return repo.fief().names().await,
+        Ok(_) | Err(RepoError::NotFound) => {}
+        Err(e) => return Err(e),
+    }
+    repo.fief().names_of(id, &author_roles(ctx).await).await
+}
+
+/// 补全调用者所属的领地名
+pub(super) async fn fief_names(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    match visible_fief_names(ctx).await {
+        Ok(names) => filter(names, partial),
+        Err(e) => {
+            warn!("failed to autocomplete fief names: {e}");
+            vec![]
+        }
+    }
+}
+
+/// 补全调用者收到邀请的领地名
+pub(super) async fn invited_fief_names(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let repo = &ctx.data().repo;
+    let result = async {
+        let mut names = vec![];
+        for invitation in repo.user().invitations(id_of(ctx.author())).await? {
+            names.push(repo.fief().name(invitation.fief_id).await?);
+        }
+        Ok::<_, RepoError>(names)
+    };
+    match result.await {
+        Ok(names) => filter(names, partial),
+        Err(e) => {
+            warn!("failed to autocomplete invited fief names: {e}");
+            vec![]
+        }
+    }
+}
+
+/// 同一指令中已经填写的领地名
+fn selected_fief_name(ctx: Context<'_>) -> Option<String> {
+    let poise::Context::Application(ctx) = ctx else {
+        return None;
+    };
+    ctx.args.iter().find_map(|o| match &o.value {
+        ResolvedValue::String(s) if o.name == "领地名" => Some(s.to_string()),
+        _ => None,
+    })
+}
+
+/// 补全已选择的领地中的区块名，调用者不属于该领地时不给出候选项
+pub(super) async fn chunk_names(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Some(fief_name) = selected_fief_name(ctx) else {
+        return vec![];
+    };
+    let repo = &ctx.data().repo;
+    let result = async {
+        if !visible_fief_names(ctx).await?.contains(&fief_name) {
+            return Ok(vec![]);
+        }
+        let fief_id = repo.fief().id(&fief_name).await?;
+        repo.chunk().names_by_fief(fief_id).await
+    };
+    match result.await {
+        Ok(names) => filter(names, partial),
+        Err(e) => {
+            warn!("failed to autocomplete chunk names: {e}");
+            vec![]
+        }
+    }
+}
@@ -1,14 +1,26 @@
-use std::time::Duration;
-
 use poise::{
     CreateReply,
-    serenity_prelude::{CreateAttachment, MessageBuilder, MessageCollector},
+    serenity_prelude::{Attachment, CreateAttachment, Mention, MessageBuilder},
 };
 
-use super::{Context, Error, has_perms, id_of, say};
+use super::{
+    Context, Error, audit, author_has_perms, autocomplete, confirm, fief_id_of, id_of, reason, say,
+    upload,
+};
 use crate::{
-    core::{ImagePng, Position},
-    domains::{Chunk, FiefId, Permissions},
+    RepoError,
+    bot::{
+        i18n::{Locale, tr},
+        pages::{Pages, Row},
+    },
+    check::{
+        automask::{self, MaskShape},
+        validation::{self, Issue, Validated},
+    },
+    core::{ImagePng, Position, WPLACE_CHUNK_HEIGHT, WPLACE_CHUNK_WIDTH},
+    domains::{
+        AuditAction, AuditRecord, AuditValue, Chunk, FiefId, ImageKind, ImageVersionId, Permissions,
+    },
     net,
 };
 
@@ -18,7 +30,18 @@ use crate::{
     slash_command,
     category = "区块",
     subcommands(
-        "add", "remove", "rename", "setref", "refnow", "setmask", "setpos", "info"
+        "add",
+        "remove",
+        "rename",
+        "setref",
+        "setimages",
+        "refnow",
+        "refhistory",
+        "rollback",
+        "setmask",
+        "automask",
+        "setpos",
+        "info"
     )
 )]
 pub(super) async fn wmchunk(_: Context<'_>) -> Result<(), Error> {
@@ -32,23 +55,22 @@ async fn _try(
     perms: Permissions,
 ) -> Result<Option<(FiefId, Chunk)>, Error> {
     let repo = &ctx.data().repo;
-    let Ok(fief_id) = repo.fief().id(fief_name).await else {
-        say!(ctx, "错误：领地 **{fief_name}** 不存在。");
+    let Some(fief_id) = fief_id_of(ctx, fief_name).await? else {
         return Ok(None);
     };
 
-    let author_id = id_of(ctx.author());
-    if !has_perms(repo, author_id, fief_id, perms).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, fief_id, perms).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(None);
     }
 
-    let Ok(chunk) = repo.chunk().chunk_by_name(fief_id, name).await else {
-        say!(
-            ctx,
-            "错误：无法从领地 **{fief_name}** 中找到区块 *{name}*。"
-        );
-        return Ok(None);
+    let chunk = match repo.chunk().chunk_by_name(fief_id, name).await {
+        Ok(chunk) => chunk,
+        Err(RepoError::NotFound) => {
+            say!(ctx, "chunk-not-found", name = fief_name, chunk = name);
+            return Ok(None);
+        }
+        Err(e) => return Err(e.into()),
     };
 
     Ok(Some((fief_id, chunk)))
@@ -59,6 +81,7 @@ async fn _try(
 pub(super) async fn add(
     ctx: Context<'_>,
     #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
     #[description = "区块所在领地的名字"]
     fief_name: String,
 
@@ -75,27 +98,41 @@ pub(super) async fn add(
     y: usize,
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
-    let Ok(fief_id) = repo.fief().id(&fief_name).await else {
-        say!(ctx, "错误：领地 **{fief_name}** 不存在。");
+    let Some(fief_id) = fief_id_of(ctx, &fief_name).await? else {
         return Ok(());
     };
 
-    let author_id = id_of(ctx.author());
-    if !has_perms(repo, author_id, fief_id, Permissions::CHUNK_ADD).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, fief_id, Permissions::CHUNK_ADD).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(());
     }
 
     let msg = match repo.chunk().create(&name, fief_id, [x, y].into()).await {
-        Ok(Some(id)) => format!(
-            "成功在领地 **{fief_name}** 内创建区块 *{name}*(id: `{}`)。",
-            id.0
-        ),
+        Ok(Some(id)) => {
+            let record = AuditRecord::new(AuditAction::ChunkAdd)
+                .fief(fief_id)
+                .target(&name)
+                .after(format!("({x}, {y})"));
+            audit(ctx, record).await;
+            tr!(
+                ctx,
+                "chunk-add-done",
+                name = &fief_name,
+                chunk = &name,
+                id = id.0
+            )
+        }
         Ok(None) => {
-            format!("错误：区块 *{name}* 早已存在于领地 **{fief_name}**。")
+            tr!(ctx, "chunk-add-exists", name = &fief_name, chunk = &name)
         }
         Err(e) => {
-            format!("错误：无法在领地 **{fief_name}** 内创建区块 *{name}*: {e}")
+            tr!(
+                ctx,
+                "chunk-add-failed",
+                name = &fief_name,
+                chunk = &name,
+                reason = reason(ctx, &e)
+            )
         }
     };
 
@@ -107,23 +144,38 @@ pub(super) async fn add(
 #[poise::command(prefix_command, slash_command, category = "区块")]
 pub(super) async fn remove(
     ctx: Context<'_>,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
-    #[rename = "区块名"] name: String,
+    #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
+    name: String,
 ) -> Result<(), Error> {
-    let Some((_, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_DELETE).await? else {
+    let Some((fief_id, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_DELETE).await?
+    else {
         return Ok(());
     };
     let repo = &ctx.data().repo;
 
     let msg = match repo.chunk().remove_by_id(chunk.id).await {
         Ok(true) => {
-            format!("成功将区块 *{name}* 从领地 **{fief_name}** 中删除。")
+            let record = AuditRecord::new(AuditAction::ChunkDelete)
+                .fief(fief_id)
+                .target(&name);
+            audit(ctx, record).await;
+            tr!(ctx, "chunk-remove-done", name = &fief_name, chunk = &name)
         }
         Ok(false) => {
-            format!("错误：无法从领地 **{fief_name}** 中找到区块 *{name}*。")
+            tr!(ctx, "chunk-not-found", name = &fief_name, chunk = &name)
         }
-        Err(e) => format!("错误：无法将区块 *{name}* 从领地 **{fief_name}** 中删除: {e}。"),
+        Err(e) => tr!(
+            ctx,
+            "chunk-remove-failed",
+            name = &fief_name,
+            chunk = &name,
+            reason = reason(ctx, &e)
+        ),
     };
     say!(ctx, msg);
     Ok(())
@@ -133,9 +185,12 @@ pub(super) async fn remove(
 #[poise::command(prefix_command, slash_command, category = "区块")]
 pub(super) async fn rename(
     ctx: Context<'_>,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
     #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
     #[description = "区块的原名字"]
     name: String,
 
@@ -143,172 +198,614 @@ pub(super) async fn rename(
     #[description = "给区块起个新名字"]
     new_name: String,
 ) -> Result<(), Error> {
-    let Some((_, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await? else {
+    let Some((fief_id, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await?
+    else {
         return Ok(());
     };
     let repo = &ctx.data().repo;
 
     let msg = match repo.chunk().rename(chunk.id, &new_name).await {
-        Ok(_) => format!("成功将领地 **{fief_name}** 内的区块 *{name}* 更名为 *{new_name}*。"),
-        Err(e) => format!("错误：无法修改领地 **{fief_name}** 内的区块 *{name}*: {e}。"),
+        Ok(_) => {
+            let record = AuditRecord::new(AuditAction::ChunkEdit)
+                .fief(fief_id)
+                .target(&name)
+                .before(&name)
+                .after(&new_name);
+            audit(ctx, record).await;
+            tr!(
+                ctx,
+                "chunk-rename-done",
+                name = &fief_name,
+                chunk = &name,
+                new_name = &new_name
+            )
+        }
+        Err(RepoError::Conflict) => {
+            tr!(
+                ctx,
+                "chunk-add-exists",
+                name = &fief_name,
+                chunk = &new_name
+            )
+        }
+        Err(e) => tr!(
+            ctx,
+            "chunk-edit-failed",
+            name = &fief_name,
+            chunk = &name,
+            reason = reason(ctx, &e)
+        ),
     };
     say!(ctx, msg);
     Ok(())
 }
 
+fn describe_issue(locale: Locale, issue: &Issue) -> String {
+    match issue {
+        Issue::WrongSize { width, height } => tr!(
+            locale,
+            "issue-wrong-size",
+            width = width,
+            height = height,
+            expected_width = WPLACE_CHUNK_WIDTH,
+            expected_height = WPLACE_CHUNK_HEIGHT
+        ),
+        Issue::NonBinaryMask { pixels } => tr!(locale, "issue-non-binary-mask", pixels = pixels),
+        Issue::OffPalette { pixels, colors } => {
+            tr!(
+                locale,
+                "issue-off-palette",
+                pixels = pixels,
+                colors = colors
+            )
+        }
+    }
+}
+
+/// 报告上传图片的检查结果，返回可以保存的图片
+async fn _report(
+    ctx: Context<'_>,
+    validated: anyhow::Result<Validated>,
+) -> Result<Option<ImagePng>, Error> {
+    let validated = match validated {
+        Ok(validated) => validated,
+        Err(e) => {
+            say!(ctx, "image-decode-failed", error = e.to_string());
+            return Ok(None);
+        }
+    };
+
+    let locale = Locale::from(ctx);
+    let issues = validated.issues.iter().fold(String::new(), |s, i| {
+        s + "\n- " + &describe_issue(locale, i)
+    });
+    match validated.img {
+        Some(img) => {
+            if validated.fixed {
+                say!(ctx, "image-fixed", issues = &issues);
+            }
+            Ok(Some(img))
+        }
+        None if validated.issues.iter().all(Issue::is_fixable) => {
+            say!(ctx, "image-fixable", issues = &issues);
+            Ok(None)
+        }
+        None => {
+            say!(ctx, "image-unfixable", issues = &issues);
+            Ok(None)
+        }
+    }
+}
+
+/// `image-kind` 文本的参数
+fn kind_str(kind: ImageKind) -> &'static str {
+    match kind {
+        ImageKind::Reference => "ref",
+        ImageKind::Mask => "mask",
+    }
+}
+
+/// 上传并保存区块的参考图和（或）遮罩图，任意一张图片无法使用时都不会保存
+async fn _set_images(
+    ctx: Context<'_>,
+    fief_name: String,
+    name: String,
+    fix: Option<bool>,
+    files: Vec<(ImageKind, Option<Attachment>)>,
+) -> Result<(), Error> {
+    let Some((fief_id, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await?
+    else {
+        return Ok(());
+    };
+    let repo = &ctx.data().repo;
+
+    let locale = Locale::from(ctx);
+    let (kinds, slots): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .map(|(kind, file)| {
+            let name = tr!(locale, "image-kind", kind = kind_str(kind));
+            (kind, upload::Slot { name, file })
+        })
+        .unzip();
+    let Some(data) = upload::images(ctx, &slots).await? else {
+        return Ok(());
+    };
+
+    let fix = fix.unwrap_or(false);
+    let mut imgs = vec![];
+    for (kind, data) in kinds.into_iter().zip(data) {
+        let img = ImagePng::new(data);
+        let validated = match kind {
+            ImageKind::Reference => validation::validate_ref(img, fix),
+            ImageKind::Mask => validation::validate_mask(img, fix),
+        };
+        let Some(img) = _report(ctx, validated).await? else {
+            return Ok(());
+        };
+        imgs.push((kind, img));
+    }
+
+    // 参考图和遮罩图在同一个事务中保存，不会只更新其中一张
+    let author = Some(id_of(ctx.author()));
+    let saved = async {
+        let uow = repo.begin().await?;
+        for (kind, img) in &imgs {
+            let img = Some(img.clone());
+            match kind {
+                ImageKind::Reference => uow.chunk().update_ref_img(chunk.id, img, author).await?,
+                ImageKind::Mask => uow.chunk().update_mask_img(chunk.id, img, author).await?,
+            }
+        }
+        uow.commit().await
+    };
+    if let Err(e) = saved.await {
+        say!(
+            ctx,
+            "chunk-edit-failed",
+            name = &fief_name,
+            chunk = &name,
+            reason = reason(locale, &e)
+        );
+        return Ok(());
+    }
+
+    for (kind, _) in imgs {
+        let after = match kind {
+            ImageKind::Reference => AuditValue::msg("audit-value-upload-ref"),
+            ImageKind::Mask => AuditValue::msg("audit-value-upload-mask"),
+        };
+        let record = AuditRecord::new(AuditAction::ChunkImage)
+            .fief(fief_id)
+            .target(&name)
+            .after(after);
+        audit(ctx, record).await;
+        match kind {
+            ImageKind::Reference => {
+                say!(ctx, "chunk-setref-done", name = &fief_name, chunk = &name)
+            }
+            ImageKind::Mask => {
+                say!(ctx, "chunk-setmask-done", name = &fief_name, chunk = &name)
+            }
+        };
+    }
+    Ok(())
+}
+
 /// 上传该区块的参考图
 #[poise::command(prefix_command, slash_command, category = "区块")]
 pub(super) async fn setref(
     ctx: Context<'_>,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
+    #[description = "区块的原名字"]
+    name: String,
+
+    #[rename = "图片"]
+    #[description = "PNG 格式的参考图，不填则在指令之后发送"]
+    image: Option<Attachment>,
+
+    #[rename = "自动修复"]
+    #[description = "是否自动修复可以修复的问题，默认为否"]
+    fix: Option<bool>,
+) -> Result<(), Error> {
+    let files = vec![(ImageKind::Reference, image)];
+    _set_images(ctx, fief_name, name, fix, files).await
+}
+
+/// 上传该区块的遮罩图（用于划定哪些像素需要检查）
+#[poise::command(prefix_command, slash_command, category = "区块")]
+pub(super) async fn setmask(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
+    #[description = "区块的原名字"]
+    name: String,
+
+    #[rename = "图片"]
+    #[description = "PNG 格式的遮罩图，不填则在指令之后发送"]
+    image: Option<Attachment>,
+
+    #[rename = "自动修复"]
+    #[description = "是否自动修复可以修复的问题，默认为否"]
+    fix: Option<bool>,
+) -> Result<(), Error> {
+    let files = vec![(ImageKind::Mask, image)];
+    _set_images(ctx, fief_name, name, fix, files).await
+}
+
+/// 同时上传该区块的参考图和遮罩图
+#[poise::command(prefix_command, slash_command, category = "区块")]
+pub(super) async fn setimages(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
     #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
     #[description = "区块的原名字"]
     name: String,
+
+    #[rename = "参考图"]
+    #[description = "PNG 格式的参考图，不填则在指令之后发送"]
+    ref_: Option<Attachment>,
+
+    #[rename = "遮罩图"]
+    #[description = "PNG 格式的遮罩图，不填则在指令之后发送"]
+    mask: Option<Attachment>,
+
+    #[rename = "自动修复"]
+    #[description = "是否自动修复可以修复的问题，默认为否"]
+    fix: Option<bool>,
 ) -> Result<(), Error> {
-    let Some((_, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await? else {
+    let files = vec![(ImageKind::Reference, ref_), (ImageKind::Mask, mask)];
+    _set_images(ctx, fief_name, name, fix, files).await
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+enum MaskModeChoice {
+    #[name = "不透明像素"]
+    Opaque,
+    #[name = "指定颜色"]
+    Colors,
+    #[name = "填充"]
+    FloodFill,
+    #[name = "矩形"]
+    Rect,
+    #[name = "多边形"]
+    Polygon,
+}
+
+fn parse_points(s: &str) -> Option<Vec<Position>> {
+    let nums = s
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|n| !n.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<usize>, _>>()
+        .ok()?;
+    if nums.len() % 2 != 0 {
+        return None;
+    }
+    Some(nums.chunks(2).map(|p| Position::new(p[0], p[1])).collect())
+}
+
+fn parse_colors(s: &str) -> Option<Vec<[u8; 3]>> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|c| !c.is_empty())
+        .map(|c| {
+            let c = c.trim_start_matches('#');
+            if c.len() != 6 {
+                return None;
+            }
+            let rgb = u32::from_str_radix(c, 16).ok()?;
+            Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|colors| !colors.is_empty())
+}
+
+fn parse_shape(mode: MaskModeChoice, arg: &str) -> Option<MaskShape> {
+    let points = || parse_points(arg);
+    match mode {
+        MaskModeChoice::Opaque => Some(MaskShape::Opaque),
+        MaskModeChoice::Colors => parse_colors(arg).map(MaskShape::Colors),
+        MaskModeChoice::FloodFill => match points()?.as_slice() {
+            [p] => Some(MaskShape::FloodFill(*p)),
+            _ => None,
+        },
+        MaskModeChoice::Rect => match points()?.as_slice() {
+            [a, b] => Some(MaskShape::Rect(*a, *b)),
+            _ => None,
+        },
+        MaskModeChoice::Polygon => points().filter(|v| v.len() >= 3).map(MaskShape::Polygon),
+    }
+}
+
+/// 根据参考图生成遮罩图，预览确认后保存
+#[poise::command(prefix_command, slash_command, category = "区块")]
+pub(super) async fn automask(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
+    #[description = "区块的原名字"]
+    name: String,
+
+    #[rename = "模式"]
+    #[description = "生成遮罩的方式"]
+    mode: MaskModeChoice,
+
+    #[rename = "参数"]
+    #[description = "颜色：#RRGGBB,…；填充：x,y；矩形：x1,y1,x2,y2；多边形：x1,y1 x2,y2 …"]
+    arg: Option<String>,
+) -> Result<(), Error> {
+    let Some((fief_id, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await?
+    else {
         return Ok(());
     };
     let repo = &ctx.data().repo;
-    say!(ctx, "请发送图片以上传参考图。").message().await?;
-
-    let mut img: Option<Vec<u8>> = None;
-    for _ in 0..3 {
-        let Some(mut msg) = MessageCollector::new(ctx)
-            .channel_id(ctx.channel_id())
-            .author_id(ctx.author().id)
-            .timeout(Duration::from_secs(60))
-            .await
-        else {
-            say!(ctx, "等待已超时，请重新输入指令。");
-            return Ok(());
-        };
 
-        if msg.attachments.is_empty() {
-            say!(ctx, "找不到附件，请再次上传。");
-            msg.delete(ctx).await?;
-            continue;
-        }
-        let file = msg.attachments.remove(0);
-        if file
-            .content_type
-            .as_ref()
-            .map(|ty| ty == "image/png")
-            .unwrap_or(false)
-        {
-            img = Some(file.download().await?);
-            msg.delete(ctx).await?;
-            break;
-        } else {
-            say!(ctx, "附件类型只能是 PNG 图片，请再次上传。");
-            msg.delete(ctx).await?;
-        }
-    }
+    let Some(shape) = parse_shape(mode, arg.as_deref().unwrap_or_default()) else {
+        say!(ctx, "automask-invalid-arg");
+        return Ok(());
+    };
 
-    let Some(img) = img.map(ImagePng::new) else {
-        say!(ctx, "错误：失败超过三次，请重新输入指令。");
+    let Some(ref_) = repo.chunk().ref_img(chunk.id).await? else {
+        say!(ctx, "chunk-no-ref", name = &fief_name, chunk = &name);
         return Ok(());
     };
+    let ref_ = ref_.try_to_rgba()?;
+    let built = automask::build_mask(&ref_, &shape)
+        .and_then(|mask| Ok((automask::preview_mask(&ref_, &mask)?, mask)));
+    let (preview, mask) = match built {
+        Ok(built) => built,
+        Err(e) => {
+            say!(ctx, "automask-failed", error = e.to_string());
+            return Ok(());
+        }
+    };
 
-    let msg = match repo.chunk().update_ref_img(chunk.id, Some(img)).await {
+    let pixels = mask.pixels().filter(|px| px.0[0] == 0xFF).count();
+    let preview = ImagePng::try_from_rgba(preview)?;
+    let reply = CreateReply::default()
+        .content(tr!(ctx, "automask-confirm", pixels = pixels, chunk = &name))
+        .attachment(CreateAttachment::bytes(
+            preview.into_inner(),
+            "mask_preview.png",
+        ));
+    if !confirm(ctx, reply).await? {
+        say!(ctx, "automask-cancelled");
+        return Ok(());
+    }
+
+    let mask = ImagePng::try_from_gray(mask)?;
+    let author = Some(id_of(ctx.author()));
+    let msg = match repo
+        .chunk()
+        .update_mask_img(chunk.id, Some(mask), author)
+        .await
+    {
         Ok(_) => {
-            format!("成功更新领地 **{fief_name}** 内区块 *{name}* 的参考图。")
+            let record = AuditRecord::new(AuditAction::ChunkImage)
+                .fief(fief_id)
+                .target(&name)
+                .after(AuditValue::msg("audit-value-automask"));
+            audit(ctx, record).await;
+            tr!(ctx, "chunk-setmask-done", name = &fief_name, chunk = &name)
         }
-        Err(e) => format!("错误：无法修改领地 **{fief_name}** 内的区块 *{name}*: {e}。"),
+        Err(e) => tr!(
+            ctx,
+            "chunk-edit-failed",
+            name = &fief_name,
+            chunk = &name,
+            reason = reason(ctx, &e)
+        ),
     };
     say!(ctx, msg);
     Ok(())
 }
 
-/// 上传该区块的遮罩图（用于划定哪些像素需要检查）
+/// 设置该区块的参考图为当前状态
 #[poise::command(prefix_command, slash_command, category = "区块")]
-pub(super) async fn setmask(
+pub(super) async fn refnow(
     ctx: Context<'_>,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
     #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
     #[description = "区块的原名字"]
     name: String,
 ) -> Result<(), Error> {
-    let Some((_, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await? else {
+    let Some((fief_id, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await?
+    else {
         return Ok(());
     };
     let repo = &ctx.data().repo;
-    say!(ctx, "请发送图片以上传遮罩图。").message().await?;
-
-    let mut img: Option<Vec<u8>> = None;
-    for _ in 0..3 {
-        let Some(mut msg) = MessageCollector::new(ctx)
-            .channel_id(ctx.channel_id())
-            .author_id(ctx.author().id)
-            .timeout(Duration::from_secs(60))
-            .await
-        else {
-            say!(ctx, "等待已超时，请重新输入指令。");
-            return Ok(());
-        };
-
-        if msg.attachments.is_empty() {
-            say!(ctx, "找不到附件，请再次上传。");
-            msg.delete(ctx).await?;
-            continue;
-        }
-        let file = msg.attachments.remove(0);
-        if file
-            .content_type
-            .as_ref()
-            .map(|ty| ty == "image/png")
-            .unwrap_or(false)
-        {
-            img = Some(file.download().await?);
-            msg.delete(ctx).await?;
-            break;
-        } else {
-            say!(ctx, "附件类型只能是 PNG 图片，请再次上传。");
-            msg.delete(ctx).await?;
-        }
-    }
 
-    let Some(img) = img.map(ImagePng::new) else {
-        say!(ctx, "错误：失败超过三次，请重新输入指令。");
+    say!(ctx, "fetch-waiting");
+    let Position { x, y } = chunk.position;
+    let Ok((_, img)) = net::fetch_current_image([x, y]).await else {
+        say!(ctx, "error-network");
         return Ok(());
     };
 
-    let msg = match repo.chunk().update_mask_img(chunk.id, Some(img)).await {
+    let msg = match repo
+        .chunk()
+        .update_ref_img(chunk.id, Some(img), Some(id_of(ctx.author())))
+        .await
+    {
         Ok(_) => {
-            format!("成功更新领地 **{fief_name}** 内区块 *{name}* 的遮罩图。")
+            let record = AuditRecord::new(AuditAction::ChunkImage)
+                .fief(fief_id)
+                .target(&name)
+                .after(AuditValue::msg("audit-value-ref-current"));
+            audit(ctx, record).await;
+            tr!(ctx, "chunk-refnow-done", name = &fief_name, chunk = &name)
         }
-        Err(e) => format!("错误：无法修改领地 **{fief_name}** 内的区块 *{name}*: {e}。"),
+        Err(e) => tr!(
+            ctx,
+            "chunk-edit-failed",
+            name = &fief_name,
+            chunk = &name,
+            reason = reason(ctx, &e)
+        ),
     };
     say!(ctx, msg);
     Ok(())
 }
 
-/// 设置该区块的参考图为当前状态
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+enum ImageKindChoice {
+    #[name = "参考图"]
+    Reference,
+    #[name = "遮罩图"]
+    Mask,
+}
+
+impl From<ImageKindChoice> for ImageKind {
+    fn from(value: ImageKindChoice) -> Self {
+        match value {
+            ImageKindChoice::Reference => ImageKind::Reference,
+            ImageKindChoice::Mask => ImageKind::Mask,
+        }
+    }
+}
+
+/// 查看该区块参考图或遮罩图的历史版本
 #[poise::command(prefix_command, slash_command, category = "区块")]
-pub(super) async fn refnow(
+pub(super) async fn refhistory(
     ctx: Context<'_>,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
     #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
     #[description = "区块的原名字"]
     name: String,
+
+    #[rename = "类型"]
+    #[description = "查看参考图还是遮罩图的历史，默认为参考图"]
+    kind: Option<ImageKindChoice>,
 ) -> Result<(), Error> {
-    let Some((_, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await? else {
+    let Some((_, chunk)) = _try(ctx, &fief_name, &name, Permissions::NONE).await? else {
         return Ok(());
     };
     let repo = &ctx.data().repo;
 
-    say!(ctx, "正在从 wplace.live 获取图片，请稍等……");
-    let Position { x, y } = chunk.position;
-    let Ok((_, img)) = net::fetch_current_image([x, y]).await else {
-        say!(ctx, "网络异常，请稍后重试。");
+    let kind = kind.map(ImageKind::from).unwrap_or(ImageKind::Reference);
+    let kind_name = kind_str(kind);
+    let versions = repo.chunk().image_versions(chunk.id, kind).await?;
+    if versions.is_empty() {
+        say!(
+            ctx,
+            "history-empty",
+            name = &fief_name,
+            chunk = &name,
+            kind = kind_name
+        );
+        return Ok(());
+    }
+
+    let locale = Locale::from(ctx);
+    let mut pages = Pages::new(tr!(
+        locale,
+        "history-title",
+        chunk = &name,
+        kind = kind_name
+    ));
+    for (i, v) in versions.iter().enumerate() {
+        let author = v.author.map_or(tr!(locale, "audit-system"), |author| {
+            Mention::User((author.0 as u64).into()).to_string()
+        });
+        let mut text = tr!(
+            locale,
+            "history-item",
+            id = v.id.0,
+            time = v.created_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            author = author,
+            action = if v.is_empty { "clear" } else { "set" }
+        );
+        if i == 0 {
+            text += &tr!(locale, "history-current");
+        }
+        pages.push(Row::new(text));
+    }
+
+    pages.send(ctx).await
+}
+
+/// 将该区块的参考图或遮罩图恢复为指定的历史版本
+#[poise::command(prefix_command, slash_command, category = "区块")]
+pub(super) async fn rollback(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
+    #[description = "区块的原名字"]
+    name: String,
+
+    #[rename = "版本编号"]
+    #[description = "历史版本的编号，可通过 `/wmchunk refhistory` 查看"]
+    version_id: i64,
+) -> Result<(), Error> {
+    let Some((fief_id, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await?
+    else {
         return Ok(());
     };
+    let repo = &ctx.data().repo;
 
-    let msg = match repo.chunk().update_ref_img(chunk.id, Some(img)).await {
-        Ok(_) => format!("成功将领地 **{fief_name}** 内区块 *{name}* 的参考图更新为当前状态。"),
-        Err(e) => format!("错误：无法修改领地 **{fief_name}** 内的区块 *{name}*: {e}。"),
+    let version_id = ImageVersionId(version_id);
+    let author = Some(id_of(ctx.author()));
+    let msg = match repo
+        .chunk()
+        .rollback_img(chunk.id, version_id, author)
+        .await
+    {
+        Ok(true) => {
+            let record = AuditRecord::new(AuditAction::ChunkImage)
+                .fief(fief_id)
+                .target(&name)
+                .after(AuditValue::msg("audit-value-rollback").arg("id", version_id.0.to_string()));
+            audit(ctx, record).await;
+            tr!(
+                ctx,
+                "chunk-rollback-done",
+                name = &fief_name,
+                chunk = &name,
+                id = version_id.0
+            )
+        }
+        Ok(false) => tr!(
+            ctx,
+            "chunk-rollback-not-found",
+            name = &fief_name,
+            chunk = &name,
+            id = version_id.0
+        ),
+        Err(e) => tr!(
+            ctx,
+            "chunk-edit-failed",
+            name = &fief_name,
+            chunk = &name,
+            reason = reason(ctx, &e)
+        ),
     };
     say!(ctx, msg);
     Ok(())
@@ -318,9 +815,12 @@ pub(super) async fn refnow(
 #[poise::command(prefix_command, slash_command, category = "区块")]
 pub(super) async fn setpos(
     ctx: Context<'_>,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
     #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
     #[description = "区块的原名字"]
     name: String,
 
@@ -332,14 +832,37 @@ pub(super) async fn setpos(
     #[description = "区块在 Wplace 上的 Y 坐标"]
     y: usize,
 ) -> Result<(), Error> {
-    let Some((_, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await? else {
+    let Some((fief_id, chunk)) = _try(ctx, &fief_name, &name, Permissions::CHUNK_EDIT).await?
+    else {
         return Ok(());
     };
     let repo = &ctx.data().repo;
 
     let msg = match repo.chunk().set_position(chunk.id, [x, y].into()).await {
-        Ok(_) => format!("成功将领地 **{fief_name}** 内的区块 *{name}* 坐标改为 `({x}, {y})`。"),
-        Err(e) => format!("错误：无法修改领地 **{fief_name}** 内的区块 *{name}*: {e}。"),
+        Ok(_) => {
+            let before = chunk.position;
+            let record = AuditRecord::new(AuditAction::ChunkEdit)
+                .fief(fief_id)
+                .target(&name)
+                .before(format!("({}, {})", before.x, before.y))
+                .after(format!("({x}, {y})"));
+            audit(ctx, record).await;
+            tr!(
+                ctx,
+                "chunk-setpos-done",
+                name = &fief_name,
+                chunk = &name,
+                x = x,
+                y = y
+            )
+        }
+        Err(e) => tr!(
+            ctx,
+            "chunk-edit-failed",
+            name = &fief_name,
+            chunk = &name,
+            reason = reason(ctx, &e)
+        ),
     };
     say!(ctx, msg);
     Ok(())
@@ -349,9 +872,12 @@ pub(super) async fn setpos(
 #[poise::command(prefix_command, slash_command, category = "区块")]
 pub(super) async fn info(
     ctx: Context<'_>,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
     #[rename = "区块名"]
+    #[autocomplete = "autocomplete::chunk_names"]
     #[description = "区块的原名字"]
     name: String,
 ) -> Result<(), Error> {
@@ -359,33 +885,51 @@ pub(super) async fn info(
         return Ok(());
     };
     let repo = &ctx.data().repo;
+    let locale = Locale::from(ctx);
+    let status = |set: bool| {
+        if set {
+            tr!(locale, "chunk-info-set")
+        } else {
+            tr!(locale, "chunk-info-unset")
+        }
+    };
+    let ref_ = repo.chunk().ref_img(chunk.id).await?;
     let mut builder = MessageBuilder::new();
     builder
-        .push("# 区块信息\n")
-        .push("区块名：*")
-        .push(chunk.name)
-        .push("*\n属于领地：**")
-        .push(fief_name)
-        .push("**\n位置：")
-        .push("(`")
-        .push(chunk.position.x.to_string())
-        .push("`, `")
-        .push(chunk.position.y.to_string())
-        .push("`)\n");
-
-    let ref_ = repo.chunk().ref_img(chunk.id).await?;
-    builder.push("参考图：").push(if ref_.is_none() {
-        ":negative_squared_cross_mark: 未设置\n"
-    } else {
-        ":white_check_mark: 已设置\n"
-    });
+        .push(tr!(
+            locale,
+            "chunk-info",
+            chunk = &chunk.name,
+            name = &fief_name,
+            x = chunk.position.x,
+            y = chunk.position.y,
+            ref_img = status(ref_.is_some())
+        ))
+        .push("\n");
+    let versions = repo.chunk().image_versions(chunk.id, ImageKind::Reference);
+    if let Some(latest) = versions.await?.first()
+        && let Some(author) = latest.author
+    {
+        let user = Mention::User((author.0 as u64).into());
+        let ago = (chrono::Utc::now() - latest.created_at).num_minutes();
+        builder
+            .push(tr!(
+                locale,
+                "chunk-info-ref-by",
+                user = user.to_string(),
+                minutes = ago
+            ))
+            .push("\n");
+    }
 
     let mask = repo.chunk().mask_img(chunk.id).await?;
-    builder.push("遮罩图：").push(if mask.is_none() {
-        ":negative_squared_cross_mark: 未设置\n"
-    } else {
-        ":white_check_mark: 已设置\n"
-    });
+    builder
+        .push(tr!(
+            locale,
+            "chunk-info-mask",
+            mask_img = status(mask.is_some())
+        ))
+        .push("\n");
 
     let result = repo.chunk().result_img(chunk.id).await?;
     let mut reply = CreateReply::default()
@@ -1,21 +1,51 @@
 use poise::{
     CreateReply,
-    serenity_prelude::{Mention, MessageBuilder},
+    serenity_prelude::{Attachment, CreateAttachment, Mention, MessageBuilder},
 };
 
-use super::{Context, Error, say};
+use super::{Context, Error, autocomplete, confirm, say, upload};
 use crate::{
-    bot::commands::{has_perms, id_of},
-    domains::Permissions,
+    RepoError,
+    archive::{self, OnConflict},
+    bot::{
+        commands::{
+            AUDIT_LIMIT, audit, audit_title, author_has_perms, create_dashboard, describe_audit,
+            fief_id_of, id_of, is_owner, perms_str, reason,
+        },
+        i18n::{Locale, tr},
+        pages::{Pages, Row, Status},
+    },
+    domains::{AuditAction, AuditRecord, AuditValue, Permissions, User, UserId},
+    templates,
 };
 
+mod maintenance;
+mod role;
+
 /// 领地操作
 #[poise::command(
     prefix_command,
     slash_command,
     category = "领地",
     subcommands(
-        "add", "remove", "check", "rename", "settime", "enable", "disable", "info"
+        "add",
+        "remove",
+        "check",
+        "rename",
+        "transfer",
+        "settime",
+        "enable",
+        "disable",
+        "adaptive",
+        "setretention",
+        "import",
+        "export",
+        "restore",
+        "info",
+        "audit_log",
+        "dashboard",
+        "maintenance::maintenance",
+        "role::role"
     )
 )]
 pub(super) async fn wmfief(_: Context<'_>) -> Result<(), Error> {
@@ -33,21 +63,28 @@ pub(super) async fn add(
     let repo = &ctx.data().repo;
 
     let user_id = id_of(ctx.author());
-    if let Err(e) = repo.user().create(user_id, false).await {
-        say!(ctx, "错误：无法存储用户信息: {e}。");
+    let uow = repo.begin().await?;
+    if let Err(e) = uow.user().create(user_id, false).await {
+        say!(ctx, "error-store-user", reason = reason(ctx, &e));
     }
 
-    let Some(id) = repo.fief().create(&name, None).await? else {
-        let msg = format!("领地 **{name}** 早已存在，请换个名字重新创建。");
-        say!(ctx, msg);
+    let Some(id) = uow.fief().create(&name, None).await? else {
+        say!(ctx, "fief-add-exists", name = &name);
         return Ok(());
     };
 
-    repo.user()
-        .join(user_id, id, Some(Permissions::ALL))
-        .await?;
+    uow.user().join(user_id, id, Some(Permissions::ALL)).await?;
+    uow.fief().set_owner(id, Some(user_id)).await?;
+    uow.commit().await?;
+    audit(
+        ctx,
+        AuditRecord::new(AuditAction::FiefCreate)
+            .fief(id)
+            .after(&name),
+    )
+    .await;
 
-    say!(ctx, "成功创建领地 **{name}**（id: `{}`）。", id.0);
+    say!(ctx, "fief-add-done", name = &name, id = id.0);
     Ok(())
 }
 
@@ -55,23 +92,33 @@ pub(super) async fn add(
 #[poise::command(prefix_command, slash_command, category = "领地")]
 pub(super) async fn remove(
     ctx: Context<'_>,
-    #[rename = "领地名"] name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
-    let Ok(id) = repo.fief().id(&name).await else {
-        say!(ctx, "错误：领地 **{name}** 不存在。");
+    let Some(id) = fief_id_of(ctx, &name).await? else {
         return Ok(());
     };
 
-    let user_id = id_of(ctx.author());
-    if !has_perms(repo, user_id, id, Permissions::FIEF_DELETE).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, id, Permissions::FIEF_DELETE).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(());
     }
 
-    repo.fief().remove_by_name(&name).await?;
-    say!(ctx, "成功删除领地 **{name}**。");
+    let uow = repo.begin().await?;
+    uow.chunk().remove_all_by_fief(id).await?;
+    uow.fief().remove_by_id(id).await?;
+    uow.commit().await?;
+    audit(
+        ctx,
+        AuditRecord::new(AuditAction::FiefDelete)
+            .fief(id)
+            .before(&name),
+    )
+    .await;
+    say!(ctx, "fief-remove-done", name = &name);
     Ok(())
 }
 
@@ -80,6 +127,7 @@ pub(super) async fn remove(
 pub(super) async fn rename(
     ctx: Context<'_>,
     #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
     #[description = "领地的原名字"]
     name: String,
 
@@ -89,18 +137,118 @@ pub(super) async fn rename(
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
-    let Ok(id) = repo.fief().id(&name).await else {
-        say!(ctx, "错误：领地 **{name}** 不存在。");
+    let Some(id) = fief_id_of(ctx, &name).await? else {
         return Ok(());
     };
-    let user_id = id_of(ctx.author());
-    if !has_perms(repo, user_id, id, Permissions::FIEF_EDIT).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    }
+
+    match repo.fief().rename(id, &new_name).await {
+        Ok(_) => {
+            let record = AuditRecord::new(AuditAction::FiefRename)
+                .fief(id)
+                .before(&name)
+                .after(&new_name);
+            audit(ctx, record).await;
+            say!(ctx, "fief-rename-done", name = &name, new_name = &new_name)
+        }
+        Err(RepoError::Conflict) => {
+            say!(ctx, "fief-rename-exists", new_name = &new_name)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    Ok(())
+}
+
+/// 将领地转让给其他成员，转让后原所有者保留自身的权限
+#[poise::command(prefix_command, slash_command, category = "领地")]
+pub(super) async fn transfer(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
+
+    #[rename = "用户"]
+    #[description = "新的所有者，必须是领地的成员"]
+    user: Mention,
+) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+    let Some(id) = fief_id_of(ctx, &name).await? else {
+        return Ok(());
+    };
+
+    let author_id = id_of(ctx.author());
+    let fief = repo.fief().fief_by_id(id).await?;
+    let is_admin = match repo.user().user_by_id(author_id).await {
+        Ok(u) => u.is_admin,
+        Err(RepoError::NotFound) => false,
+        Err(e) => return Err(e.into()),
+    };
+    if fief.owner != Some(author_id) && !is_admin {
+        say!(ctx, "fief-transfer-not-owner");
         return Ok(());
     }
 
-    repo.fief().rename(id, &new_name).await?;
-    say!(ctx, "已将领地 **{name}** 的名字变更为 **{new_name}**。");
+    let Mention::User(user_id) = user else {
+        say!(ctx, "error-mention-user");
+        return Ok(());
+    };
+    let user_id = UserId(user_id.get() as i64);
+    if fief.owner == Some(user_id) {
+        say!(
+            ctx,
+            "fief-transfer-already-owner",
+            user = user.to_string(),
+            name = &name
+        );
+        return Ok(());
+    }
+    if !repo.user().is_member_of(user_id, id).await? {
+        say!(
+            ctx,
+            "fief-transfer-not-member",
+            user = user.to_string(),
+            name = &name
+        );
+        return Ok(());
+    }
+
+    let reply = CreateReply::default().content(tr!(
+        ctx,
+        "fief-transfer-confirm",
+        name = &name,
+        user = user.to_string()
+    ));
+    if !confirm(ctx, reply).await? {
+        say!(ctx, "fief-transfer-cancelled", name = &name);
+        return Ok(());
+    }
+
+    match repo.fief().set_owner(id, Some(user_id)).await {
+        Ok(_) => {
+            let mut record = AuditRecord::new(AuditAction::FiefTransfer)
+                .fief(id)
+                .after(user.to_string());
+            if let Some(owner) = fief.owner {
+                record = record.before(Mention::User((owner.0 as u64).into()).to_string());
+            }
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "fief-transfer-done",
+                name = &name,
+                user = user.to_string()
+            )
+        }
+        Err(e) => say!(
+            ctx,
+            "fief-transfer-failed",
+            name = &name,
+            reason = reason(ctx, &e)
+        ),
+    };
     Ok(())
 }
 
@@ -108,7 +256,9 @@ pub(super) async fn rename(
 #[poise::command(prefix_command, slash_command, category = "领地")]
 pub(super) async fn settime(
     ctx: Context<'_>,
-    #[rename = "领地名"] name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
 
     #[rename = "间隔"]
     #[description = "检查间隔（分钟）"]
@@ -116,23 +266,27 @@ pub(super) async fn settime(
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
-    let Ok(id) = repo.fief().id(&name).await else {
-        say!(ctx, "错误：领地 **{name}** 不存在。");
+    let Some(id) = fief_id_of(ctx, &name).await? else {
         return Ok(());
     };
-    let user_id = id_of(ctx.author());
-    if !has_perms(repo, user_id, id, Permissions::FIEF_EDIT).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(());
     }
 
+    let before = repo.fief().fief_by_id(id).await?.check_interval;
     repo.fief()
         .set_check_interval(id, chrono::Duration::minutes(interval as i64))
         .await?;
-    say!(
-        ctx,
-        "已变更 **{name}** 领地的检查间隔时间为 {interval} 分钟。"
-    );
+    let record = AuditRecord::new(AuditAction::FiefSettings)
+        .fief(id)
+        .target(AuditValue::msg("audit-value-check-interval"))
+        .before(
+            AuditValue::msg("audit-value-minutes").arg("minutes", before.num_minutes().to_string()),
+        )
+        .after(AuditValue::msg("audit-value-minutes").arg("minutes", interval.to_string()));
+    audit(ctx, record).await;
+    say!(ctx, "fief-settime-done", name = &name, interval = interval);
     Ok(())
 }
 
@@ -140,25 +294,25 @@ pub(super) async fn settime(
 #[poise::command(prefix_command, slash_command, category = "领地")]
 pub(super) async fn check(
     ctx: Context<'_>,
-    #[rename = "领地名"] name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
-    let Ok(id) = repo.fief().id(&name).await else {
-        say!(ctx, "错误：领地 **{name}** 不存在。");
+    let Some(id) = fief_id_of(ctx, &name).await? else {
         return Ok(());
     };
-    let user_id = id_of(ctx.author());
-    if !has_perms(repo, user_id, id, Permissions::FIEF_EDIT).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(());
     }
 
     match repo.fief().mark_should_check_now(id).await {
         Ok(_) => {
-            say!(ctx, "设置成功，领地 **{name}** 将在一分钟内被执行检查。")
+            say!(ctx, "fief-check-done", name = &name)
         }
-        Err(_) => say!(ctx, "设置失败。"),
+        Err(_) => say!(ctx, "fief-check-failed"),
     };
 
     Ok(())
@@ -168,22 +322,29 @@ pub(super) async fn check(
 #[poise::command(prefix_command, slash_command, category = "管理员")]
 pub(super) async fn enable(
     ctx: Context<'_>,
-    #[rename = "领地名"] name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
-    let Ok(id) = repo.fief().id(&name).await else {
-        say!(ctx, "错误：领地 **{name}** 不存在。");
+    let Some(id) = fief_id_of(ctx, &name).await? else {
         return Ok(());
     };
-    let user_id = id_of(ctx.author());
-    if !has_perms(repo, user_id, id, Permissions::FIEF_EDIT).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(());
     }
 
     repo.fief().keep_check(id).await?;
-    say!(ctx, "已启用对领地 **{name}** 的定期自动检查。");
+    audit(
+        ctx,
+        AuditRecord::new(AuditAction::FiefCheck)
+            .fief(id)
+            .after(AuditValue::msg("audit-value-enabled")),
+    )
+    .await;
+    say!(ctx, "fief-enable-done", name = &name);
     Ok(())
 }
 
@@ -191,20 +352,20 @@ pub(super) async fn enable(
 #[poise::command(prefix_command, slash_command, category = "领地")]
 pub(super) async fn disable(
     ctx: Context<'_>,
-    #[rename = "领地名"] name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
     #[rename = "禁用时长"]
     #[description = "多少小时后重新启用"]
     dur_hours: Option<usize>,
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
-    let Ok(id) = repo.fief().id(&name).await else {
-        say!(ctx, "错误：领地 **{name}** 不存在。");
+    let Some(id) = fief_id_of(ctx, &name).await? else {
         return Ok(());
     };
-    let user_id = id_of(ctx.author());
-    if !has_perms(repo, user_id, id, Permissions::FIEF_EDIT).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(());
     }
 
@@ -212,114 +373,518 @@ pub(super) async fn disable(
         Some(d) => {
             let dur = chrono::Duration::hours(d as i64);
             repo.fief().skip_check_for(id, dur, None).await?;
+            let record = AuditRecord::new(AuditAction::FiefCheck)
+                .fief(id)
+                .after(AuditValue::msg("audit-value-disabled-for").arg("hours", d.to_string()));
+            audit(ctx, record).await;
+            say!(ctx, "fief-disable-for", name = &name, hours = d);
+        }
+        _ => {
+            repo.fief().skip_check(id).await?;
+            audit(
+                ctx,
+                AuditRecord::new(AuditAction::FiefCheck)
+                    .fief(id)
+                    .after(AuditValue::msg("audit-value-disabled")),
+            )
+            .await;
+            say!(ctx, "fief-disable-done", name = &name);
+        }
+    }
+    Ok(())
+}
+
+/// 开启或关闭领地的自适应检查间隔
+#[poise::command(prefix_command, slash_command, category = "领地")]
+pub(super) async fn adaptive(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
+    #[rename = "开启"]
+    #[description = "是否开启自适应检查间隔"]
+    enabled: bool,
+) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let Some(id) = fief_id_of(ctx, &name).await? else {
+        return Ok(());
+    };
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    }
+
+    repo.fief().set_adaptive(id, enabled).await?;
+    let record = AuditRecord::new(AuditAction::FiefSettings)
+        .fief(id)
+        .target(AuditValue::msg("audit-value-adaptive"))
+        .after(AuditValue::msg(if enabled {
+            "audit-value-on"
+        } else {
+            "audit-value-off"
+        }));
+    audit(ctx, record).await;
+    if enabled {
+        say!(ctx, "fief-adaptive-on", name = &name);
+    } else {
+        say!(ctx, "fief-adaptive-off", name = &name);
+    }
+    Ok(())
+}
+
+/// 设置领地内每个区块除当前版本外保留的参考图和遮罩图历史版本数量
+#[poise::command(prefix_command, slash_command, category = "领地")]
+pub(super) async fn setretention(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
+    #[rename = "数量"]
+    #[description = "当前版本之外保留的历史版本数量，至少为 1"]
+    retention: usize,
+) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let Some(id) = fief_id_of(ctx, &name).await? else {
+        return Ok(());
+    };
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    }
+
+    if retention == 0 {
+        say!(ctx, "fief-retention-invalid");
+        return Ok(());
+    }
+
+    let before = repo.fief().fief_by_id(id).await?.image_retention;
+    repo.fief().set_image_retention(id, retention).await?;
+    let record = AuditRecord::new(AuditAction::FiefSettings)
+        .fief(id)
+        .target(AuditValue::msg("audit-value-retention"))
+        .before(before.to_string())
+        .after(retention.to_string());
+    audit(ctx, record).await;
+    say!(
+        ctx,
+        "fief-retention-done",
+        name = &name,
+        retention = retention
+    );
+    Ok(())
+}
+
+/// 从 Blue Marble 或 Overlay Pro 导出的模板创建区块、参考图和遮罩图
+#[poise::command(prefix_command, slash_command, category = "领地")]
+pub(super) async fn import(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
+    #[rename = "模板"]
+    #[description = "JSON 文件，或文件名以 `区块X_区块Y_像素X_像素Y` 结尾的 PNG 图片"]
+    file: Attachment,
+) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let Some(id) = fief_id_of(ctx, &name).await? else {
+        return Ok(());
+    };
+    let user_id = id_of(ctx.author());
+    let perms = Permissions::CHUNK_ADD | Permissions::CHUNK_EDIT;
+    if !author_has_perms(ctx, id, perms).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    }
+
+    if !upload::check_size(ctx, &file, upload::MAX_TEMPLATE_MIB).await? {
+        return Ok(());
+    }
+    let data = file.download().await?;
+    let templates = match templates::parse(&file.filename, &data) {
+        Ok(templates) if !templates.is_empty() => templates,
+        Ok(_) => {
+            say!(ctx, "fief-import-empty");
+            return Ok(());
+        }
+        Err(e) => {
+            say!(ctx, "fief-import-parse-failed", error = e.to_string());
+            return Ok(());
+        }
+    };
+
+    let imported = match templates::import(repo, id, &templates, Some(user_id)).await {
+        Ok(imported) => imported,
+        Err(e) => {
             say!(
                 ctx,
-                "已禁用对领地 **{name}** 的定期自动检查（持续时间: {d} 小时）。",
+                "fief-import-failed",
+                name = &name,
+                error = e.to_string()
             );
+            return Ok(());
         }
-        _ => {
-            repo.fief().skip_check(id).await?;
-            say!(ctx, "已禁用对领地 **{name}** 的定期自动检查。");
+    };
+
+    let mut pages = Pages::new(tr!(
+        ctx,
+        "fief-import-done",
+        count = templates.len(),
+        name = &name
+    ));
+    for i in imported {
+        let action = if i.created {
+            AuditAction::ChunkAdd
+        } else {
+            AuditAction::ChunkImage
+        };
+        audit(ctx, AuditRecord::new(action).fief(id).target(&i.name)).await;
+
+        let line = if i.created {
+            tr!(
+                ctx,
+                "fief-import-created",
+                chunk = &i.name,
+                x = i.tile.x,
+                y = i.tile.y,
+                pixels = i.pixels
+            )
+        } else {
+            tr!(
+                ctx,
+                "fief-import-merged",
+                chunk = &i.name,
+                x = i.tile.x,
+                y = i.tile.y,
+                pixels = i.pixels
+            )
+        };
+        pages.push(Row::new(format!("- {line}")));
+    }
+    pages.send(ctx).await
+}
+
+/// 将领地的设置、区块、图片和成员导出为归档文件
+#[poise::command(prefix_command, slash_command, category = "领地")]
+pub(super) async fn export(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
+) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let Some(id) = fief_id_of(ctx, &name).await? else {
+        return Ok(());
+    };
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    }
+
+    let data = match archive::export(repo, id).await {
+        Ok(data) => data,
+        Err(e) => {
+            say!(
+                ctx,
+                "fief-export-failed",
+                name = &name,
+                error = e.to_string()
+            );
+            return Ok(());
+        }
+    };
+    ctx.send(
+        CreateReply::default()
+            .content(tr!(ctx, "fief-export-done", name = &name))
+            .attachment(CreateAttachment::bytes(data, format!("{name}.tar")))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+enum ConflictChoice {
+    #[name = "放弃导入"]
+    Abort,
+    #[name = "自动重命名"]
+    Rename,
+    #[name = "覆盖已有领地"]
+    Replace,
+}
+
+impl From<ConflictChoice> for OnConflict {
+    fn from(value: ConflictChoice) -> Self {
+        match value {
+            ConflictChoice::Abort => OnConflict::Abort,
+            ConflictChoice::Rename => OnConflict::Rename,
+            ConflictChoice::Replace => OnConflict::Replace,
         }
     }
+}
+
+/// 从 `/wmfief export` 导出的归档文件恢复领地
+#[poise::command(prefix_command, slash_command, category = "领地")]
+pub(super) async fn restore(
+    ctx: Context<'_>,
+    #[rename = "归档"]
+    #[description = "`/wmfief export` 导出的 tar 文件"]
+    file: Attachment,
+    #[rename = "领地名"]
+    #[description = "恢复后的领地名，默认使用归档内的名字"]
+    name: Option<String>,
+    #[rename = "冲突处理"]
+    #[description = "领地名已被占用时的处理方式，默认放弃导入"]
+    on_conflict: Option<ConflictChoice>,
+) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+    let user_id = id_of(ctx.author());
+    let data = file.download().await?;
+
+    let on_conflict = on_conflict.map_or(OnConflict::Abort, OnConflict::from);
+    if on_conflict == OnConflict::Replace {
+        let target = match &name {
+            Some(name) => name.clone(),
+            None => match archive::read(&data) {
+                Ok((manifest, _)) => manifest.fief.name,
+                Err(e) => {
+                    say!(ctx, "fief-restore-read-failed", error = e.to_string());
+                    return Ok(());
+                }
+            },
+        };
+        // 覆盖已有的领地会改变其所有者，只有所有者和管理员可以这样做
+        if let Ok(id) = repo.fief().id(&target).await {
+            let author = repo.user().user_by_id(user_id).await;
+            if !matches!(author, Ok(User { is_admin: true, .. }))
+                && !is_owner(repo, id, user_id).await?
+            {
+                say!(ctx, "fief-restore-replace-denied", name = &target);
+                return Ok(());
+            }
+        }
+    }
+
+    let restored = archive::restore(repo, &data, name.as_deref(), on_conflict, Some(user_id));
+    let restored = match restored.await {
+        Ok(Some(restored)) => restored,
+        Ok(None) => {
+            say!(ctx, "fief-restore-conflict");
+            return Ok(());
+        }
+        Err(e) => {
+            say!(ctx, "fief-restore-failed", error = e.to_string());
+            return Ok(());
+        }
+    };
+
+    let record = AuditRecord::new(AuditAction::FiefCreate)
+        .fief(restored.fief_id)
+        .target(AuditValue::msg("audit-value-restored"))
+        .after(&restored.name);
+    audit(ctx, record).await;
+
+    say!(
+        ctx,
+        "fief-restore-done",
+        name = &restored.name,
+        chunks = restored.chunks,
+        invited = restored.invited
+    );
     Ok(())
 }
 
 /// 获取领地信息
 #[poise::command(prefix_command, slash_command, category = "领地")]
 pub(super) async fn info(
-    ctx: Context<'_>, #[rename = "领地名"] name: String
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
-    let Ok(fief) = repo.fief().fief_by_name(&name).await else {
-        say!(ctx, "错误：领地 **{name}** 不存在。");
-        return Ok(());
+    let fief = match repo.fief().fief_by_name(&name).await {
+        Ok(fief) => fief,
+        Err(RepoError::NotFound) => {
+            say!(ctx, "error-fief-not-found", name = &name);
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
     };
 
+    let locale = Locale::from(ctx);
     let mut builder = MessageBuilder::new();
 
     let now = chrono::Utc::now();
     let last_check = now - fief.last_check;
     let last_check = if last_check > chrono::Duration::weeks(100) {
-        "无".to_string()
+        tr!(locale, "none")
     } else {
-        format!("{} 分钟之前", last_check.num_minutes())
+        tr!(
+            locale,
+            "fief-info-minutes-ago",
+            minutes = last_check.num_minutes()
+        )
     };
 
     let skip_check_until = if fief.skip_check_until < now {
-        ":white_check_mark: 启用中".to_string()
+        tr!(locale, "fief-info-enabled")
     } else {
         let skip_check_until = fief.skip_check_until - now;
         if skip_check_until > chrono::Duration::weeks(100) {
-            ":negative_squared_cross_mark: 禁用中".to_string()
+            tr!(locale, "fief-info-disabled")
         } else {
-            format!(
-                ":negative_squared_cross_mark: 禁用中，{} 分钟之后启用",
-                skip_check_until.num_minutes()
+            tr!(
+                locale,
+                "fief-info-disabled-for",
+                minutes = skip_check_until.num_minutes()
             )
         }
     };
 
+    let owner = fief.owner.map_or(tr!(locale, "none"), |u| {
+        Mention::User((u.0 as u64).into()).to_string()
+    });
     builder
-        .push("# 领地信息")
-        .push("\n名字：**")
-        .push(fief.name)
-        .push("**\n上次检查：")
-        .push(last_check)
-        .push("\n定期自动检查：")
-        .push(skip_check_until)
-        .push("\n定期自动检查间隔：")
-        .push(fief.check_interval.num_minutes().to_string())
-        .push(" 分钟一次\n");
-
-    let mut chunks = vec![];
-    for chunk_id in repo.fief().chunks(fief.id).await? {
-        let Ok(chunk) = repo.chunk().chunk_by_id(chunk_id).await else {
-            continue;
-        };
-        chunks.push((chunk.name, chunk.position));
+        .push(tr!(
+            locale,
+            "fief-info",
+            name = &fief.name,
+            owner = owner,
+            last_check = last_check,
+            check = skip_check_until,
+            interval = fief.check_interval.num_minutes(),
+            retention = fief.image_retention
+        ))
+        .push("\n");
+
+    if fief.adaptive {
+        builder
+            .push(tr!(
+                locale,
+                "fief-info-adaptive",
+                interval = fief.effective_interval.num_minutes()
+            ))
+            .push("\n");
+    }
+
+    let windows = repo.fief().maintenance_windows(fief.id).await?;
+    if !windows.is_empty() {
+        builder
+            .push(tr!(locale, "fief-info-maintenance"))
+            .push("\n");
+        for w in windows {
+            builder
+                .push("- ")
+                .push(maintenance::describe(locale, &w))
+                .push("\n");
+        }
     }
 
+    let mut pages = Pages::new(builder.build().trim_end());
+    let chunks = repo.chunk().summaries_by_fief(fief.id).await?;
     if !chunks.is_empty() {
-        builder.push("# 领地的区块信息\n");
-        for (name, pos) in chunks {
-            builder.push(format!(
-                "- 区块名：*{name}*\n  位置：`({}, {})`\n",
-                pos.x, pos.y
-            ));
+        pages.section(tr!(locale, "fief-info-chunks"));
+        for c in chunks {
+            let status = match (c.has_ref, c.diff_count) {
+                (false, _) => Status::MissingRef,
+                (true, 0) => Status::Ok,
+                (true, _) => Status::Damaged,
+            };
+            let pos = c.chunk.position;
+            let text = tr!(
+                locale,
+                "fief-info-chunk",
+                chunk = &c.chunk.name,
+                x = pos.x,
+                y = pos.y,
+                status = status.label(locale),
+                diff = c.diff_count
+            );
+            pages.push(Row::new(text).status(status).diff(c.diff_count));
         }
     }
 
     let mut members = vec![];
-    for member_id in repo.fief().members(fief.id).await? {
-        let perms = repo.user().permissions_in(member_id, fief.id).await?;
-        let perms_str = perms
-            .iter_names()
-            .map(|(s, _)| s)
-            .fold(String::new(), |a, s| a + "`" + s + "` ");
-        members.push((
-            Mention::User((member_id.0 as u64).into()),
-            if perms_str.is_empty() {
-                "无".into()
-            } else {
-                perms_str
-            },
+    for m in repo.fief().members_with_perms(fief.id).await? {
+        members.push(tr!(
+            locale,
+            "fief-info-user",
+            user = Mention::User((m.user_id.0 as u64).into()).to_string(),
+            perms = perms_str(m.permissions)
+        ));
+    }
+    for g in repo.fief().role_grants(fief.id).await? {
+        members.push(tr!(
+            locale,
+            "fief-info-role",
+            role = Mention::Role((g.role_id.0 as u64).into()).to_string(),
+            perms = perms_str(g.permissions)
         ));
     }
 
     if !members.is_empty() {
-        builder.push("# 领地成员\n");
-        for (name, perms) in members {
-            builder.push(format!("- 用户：{name}\n  权限：{perms}\n"));
+        pages.section(tr!(locale, "fief-info-members"));
+        for m in members {
+            pages.push(Row::new(m));
         }
     }
 
-    ctx.send(
-        CreateReply::default()
-            .content(builder.build())
-            .ephemeral(true),
-    )
-    .await?;
-    Ok(())
+    pages.sortable().send(ctx).await
+}
+
+/// 查看领地的操作记录，从新到旧排列
+#[poise::command(prefix_command, slash_command, rename = "audit", category = "领地")]
+pub(super) async fn audit_log(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
+) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let Some(id) = fief_id_of(ctx, &name).await? else {
+        return Ok(());
+    };
+    if !author_has_perms(ctx, id, Permissions::NONE).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    }
+
+    let total = repo.audit().count_by_fief(id).await?;
+    if total == 0 {
+        say!(ctx, "fief-audit-empty", name = &name);
+        return Ok(());
+    }
+
+    let locale = Locale::from(ctx);
+    let title = tr!(locale, "fief-audit-title", name = &name);
+    let mut pages = Pages::new(audit_title(locale, title, total));
+    for e in repo.audit().by_fief(id, 0, AUDIT_LIMIT).await? {
+        pages.push(Row::new(format!("- {}", describe_audit(locale, &e))));
+    }
+    pages.send(ctx).await
+}
+
+/// 在当前频道发送领地的状态面板，每次检查后自动更新
+#[poise::command(prefix_command, slash_command, category = "领地")]
+pub(super) async fn dashboard(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    name: String,
+) -> Result<(), Error> {
+    let Some(id) = fief_id_of(ctx, &name).await? else {
+        return Ok(());
+    };
+    if !author_has_perms(ctx, id, Permissions::FIEF_EDIT).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(());
+    }
+
+    create_dashboard(ctx, Some(id)).await
 }
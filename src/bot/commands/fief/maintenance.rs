@@ -0,0 +1,289 @@
+use super::super::{
+    Context, Error, audit, audit_value_str, author_has_perms, autocomplete, fief_id_of, reason, say,
+};
+use crate::{
+    bot::{
+        i18n::{Locale, tr},
+        pages::{Pages, Row},
+    },
+    domains::{
+        AuditAction, AuditRecord, AuditValue, FiefId, MaintenanceMode, MaintenanceRule,
+        MaintenanceWindow, MaintenanceWindowId, Permissions,
+    },
+};
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+enum WeekdayChoice {
+    #[name = "每天"]
+    Everyday,
+    #[name = "周一"]
+    Mon,
+    #[name = "周二"]
+    Tue,
+    #[name = "周三"]
+    Wed,
+    #[name = "周四"]
+    Thu,
+    #[name = "周五"]
+    Fri,
+    #[name = "周六"]
+    Sat,
+    #[name = "周日"]
+    Sun,
+}
+
+impl From<WeekdayChoice> for Option<chrono::Weekday> {
+    fn from(value: WeekdayChoice) -> Self {
+        use chrono::Weekday;
+        match value {
+            WeekdayChoice::Everyday => None,
+            WeekdayChoice::Mon => Some(Weekday::Mon),
+            WeekdayChoice::Tue => Some(Weekday::Tue),
+            WeekdayChoice::Wed => Some(Weekday::Wed),
+            WeekdayChoice::Thu => Some(Weekday::Thu),
+            WeekdayChoice::Fri => Some(Weekday::Fri),
+            WeekdayChoice::Sat => Some(Weekday::Sat),
+            WeekdayChoice::Sun => Some(Weekday::Sun),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+enum ModeChoice {
+    #[name = "跳过检查"]
+    SkipCheck,
+    #[name = "不发送警报"]
+    MuteAlerts,
+}
+
+impl From<ModeChoice> for MaintenanceMode {
+    fn from(value: ModeChoice) -> Self {
+        match value {
+            ModeChoice::SkipCheck => MaintenanceMode::SkipCheck,
+            ModeChoice::MuteAlerts => MaintenanceMode::MuteAlerts,
+        }
+    }
+}
+
+pub(super) fn describe(locale: Locale, w: &MaintenanceWindow) -> String {
+    audit_value_str(locale, &audit_value(w))
+}
+
+/// 维护时间段的描述，在操作记录中保存为文本的 id 与参数
+fn audit_value(w: &MaintenanceWindow) -> AuditValue {
+    let weekday = match w.rule.weekday {
+        None => "maintenance-everyday",
+        Some(chrono::Weekday::Mon) => "maintenance-mon",
+        Some(chrono::Weekday::Tue) => "maintenance-tue",
+        Some(chrono::Weekday::Wed) => "maintenance-wed",
+        Some(chrono::Weekday::Thu) => "maintenance-thu",
+        Some(chrono::Weekday::Fri) => "maintenance-fri",
+        Some(chrono::Weekday::Sat) => "maintenance-sat",
+        Some(chrono::Weekday::Sun) => "maintenance-sun",
+    };
+    let end = w.rule.start + w.rule.duration;
+    let mode = match w.rule.mode {
+        MaintenanceMode::SkipCheck => "maintenance-skip-check",
+        MaintenanceMode::MuteAlerts => "maintenance-mute-alerts",
+    };
+    AuditValue::msg("maintenance-window")
+        .arg("id", w.id.0.to_string())
+        .arg("weekday", AuditValue::msg(weekday))
+        .arg("start", w.rule.start.format("%H:%M").to_string())
+        .arg("end", end.format("%H:%M").to_string())
+        .arg("mode", AuditValue::msg(mode))
+}
+
+async fn _try(
+    ctx: Context<'_>,
+    fief_name: &str,
+    perms: Permissions,
+) -> Result<Option<FiefId>, Error> {
+    let Some(id) = fief_id_of(ctx, fief_name).await? else {
+        return Ok(None);
+    };
+
+    if !author_has_perms(ctx, id, perms).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(None);
+    }
+
+    Ok(Some(id))
+}
+
+/// 领地的定期维护时间段
+#[poise::command(
+    prefix_command,
+    slash_command,
+    category = "领地",
+    subcommands("add", "list", "remove")
+)]
+pub(super) async fn maintenance(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// 添加定期维护时间段（UTC），期间跳过检查或不发送警报
+#[poise::command(prefix_command, slash_command, category = "领地")]
+async fn add(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "星期"]
+    #[description = "每周的哪一天，或者每天"]
+    weekday: WeekdayChoice,
+
+    #[rename = "开始"]
+    #[description = "开始时间（UTC），格式为 HH:MM"]
+    start: String,
+
+    #[rename = "结束"]
+    #[description = "结束时间（UTC），格式为 HH:MM，早于开始时间则跨越午夜"]
+    end: String,
+
+    #[rename = "模式"]
+    #[description = "维护期间跳过检查，或者照常检查但不发送警报"]
+    mode: ModeChoice,
+) -> Result<(), Error> {
+    let Some(id) = _try(ctx, &fief_name, Permissions::FIEF_EDIT).await? else {
+        return Ok(());
+    };
+    let repo = &ctx.data().repo;
+
+    let parse = |s: &str| chrono::NaiveTime::parse_from_str(s, "%H:%M");
+    let (Ok(start), Ok(end)) = (parse(&start), parse(&end)) else {
+        say!(ctx, "maintenance-invalid-time");
+        return Ok(());
+    };
+
+    let mut duration = end - start;
+    if duration <= chrono::Duration::zero() {
+        duration += chrono::Duration::days(1);
+    }
+
+    let rule = MaintenanceRule {
+        weekday: weekday.into(),
+        start,
+        duration,
+        mode: mode.into(),
+    };
+    match repo.fief().add_maintenance_window(id, rule).await {
+        Ok(Some(window_id)) => {
+            let w = MaintenanceWindow {
+                id: window_id,
+                fief_id: id,
+                rule,
+            };
+            let record = AuditRecord::new(AuditAction::FiefSettings)
+                .fief(id)
+                .target(AuditValue::msg("audit-value-maintenance"))
+                .after(audit_value(&w));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "maintenance-add-done",
+                name = &fief_name,
+                window = describe(ctx.into(), &w)
+            )
+        }
+        Ok(None) => say!(ctx, "maintenance-add-failed", name = &fief_name),
+        Err(e) => say!(
+            ctx,
+            "maintenance-add-error",
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+    Ok(())
+}
+
+/// 列出领地的定期维护时间段
+#[poise::command(prefix_command, slash_command, category = "领地")]
+async fn list(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+) -> Result<(), Error> {
+    let Some(id) = _try(ctx, &fief_name, Permissions::NONE).await? else {
+        return Ok(());
+    };
+    let repo = &ctx.data().repo;
+
+    let windows = repo.fief().maintenance_windows(id).await?;
+    if windows.is_empty() {
+        say!(ctx, "maintenance-list-empty", name = &fief_name);
+        return Ok(());
+    }
+
+    let locale = Locale::from(ctx);
+    let mut pages = Pages::new(tr!(locale, "maintenance-list-title", name = &fief_name));
+    let now = chrono::Utc::now();
+    for w in windows {
+        let mut text = format!("- {}", describe(locale, &w));
+        if w.rule.contains(now) {
+            text += &tr!(locale, "maintenance-active");
+        }
+        pages.push(Row::new(text));
+    }
+
+    pages.send(ctx).await
+}
+
+/// 删除领地的定期维护时间段
+#[poise::command(prefix_command, slash_command, category = "领地")]
+async fn remove(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "编号"]
+    #[description = "维护时间段的编号，可通过 `/wmfief maintenance list` 查看"]
+    window_id: i64,
+) -> Result<(), Error> {
+    let Some(id) = _try(ctx, &fief_name, Permissions::FIEF_EDIT).await? else {
+        return Ok(());
+    };
+    let repo = &ctx.data().repo;
+
+    let window_id = MaintenanceWindowId(window_id);
+    let before = repo
+        .fief()
+        .maintenance_windows(id)
+        .await?
+        .into_iter()
+        .find(|w| w.id == window_id)
+        .map(|w| audit_value(&w));
+    match repo.fief().remove_maintenance_window(id, window_id).await {
+        Ok(true) => {
+            let mut record = AuditRecord::new(AuditAction::FiefSettings)
+                .fief(id)
+                .target(AuditValue::msg("audit-value-maintenance"));
+            if let Some(before) = before {
+                record = record.before(before);
+            }
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "maintenance-remove-done",
+                name = &fief_name,
+                id = window_id.0
+            )
+        }
+        Ok(false) => say!(
+            ctx,
+            "maintenance-remove-not-found",
+            name = &fief_name,
+            id = window_id.0
+        ),
+        Err(e) => say!(
+            ctx,
+            "maintenance-remove-failed",
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+    Ok(())
+}
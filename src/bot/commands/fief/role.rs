@@ -0,0 +1,255 @@
+use super::super::{
+    Context, Error, audit, author_can_grant, author_has_perms, autocomplete, fief_id_of,
+    parse_perms, perms_str, reason, say,
+};
+use crate::{
+    RepoError,
+    bot::{
+        i18n::{Locale, tr},
+        pages::{Pages, Row},
+    },
+    domains::{AuditAction, AuditRecord, AuditValue, FiefId, FiefRole, Permissions},
+};
+
+async fn _try(
+    ctx: Context<'_>,
+    fief_name: &str,
+    perms: Permissions,
+) -> Result<Option<FiefId>, Error> {
+    let Some(id) = fief_id_of(ctx, fief_name).await? else {
+        return Ok(None);
+    };
+
+    if !author_has_perms(ctx, id, perms).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(None);
+    }
+
+    Ok(Some(id))
+}
+
+/// 按名字查找职位，不存在时提示用户并返回 `None`
+async fn role_of(
+    ctx: Context<'_>,
+    id: FiefId,
+    fief_name: &str,
+    role_name: &str,
+) -> Result<Option<FiefRole>, Error> {
+    let repo = &ctx.data().repo;
+    match repo.fief().fief_role_by_name(id, role_name).await {
+        Ok(role) => Ok(Some(role)),
+        Err(RepoError::NotFound) => {
+            say!(
+                ctx,
+                "fief-role-not-found",
+                name = fief_name,
+                role = role_name
+            );
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 领地的职位，担任职位的成员拥有职位的全部权限
+#[poise::command(
+    prefix_command,
+    slash_command,
+    category = "领地",
+    subcommands("create", "edit", "remove", "list")
+)]
+pub(super) async fn role(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// 创建职位
+#[poise::command(prefix_command, slash_command, category = "领地")]
+async fn create(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+    #[rename = "职位名"] role_name: String,
+
+    #[rename = "权限"]
+    #[description = "以空格分隔的多个权限，可以通过 `/wmpermissions` 了解所有权限"]
+    permissions: String,
+) -> Result<(), Error> {
+    let Some(id) = _try(ctx, &fief_name, Permissions::MEMBER_EDIT_PERMS).await? else {
+        return Ok(());
+    };
+    let Some(p) = parse_perms(&permissions) else {
+        say!(ctx, "error-invalid-perms", perms = &permissions);
+        return Ok(());
+    };
+    if !author_can_grant(ctx, id, p).await? {
+        return Ok(());
+    }
+
+    let repo = &ctx.data().repo;
+    match repo.fief().create_fief_role(id, &role_name, p).await {
+        Ok(Some(_)) => {
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(id)
+                .target(AuditValue::msg("audit-value-fief-role").arg("name", &role_name))
+                .after(perms_str(p));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "fief-role-create-done",
+                name = &fief_name,
+                role = &role_name,
+                perms = perms_str(p)
+            )
+        }
+        Ok(None) => say!(
+            ctx,
+            "fief-role-create-exists",
+            name = &fief_name,
+            role = &role_name
+        ),
+        Err(e) => say!(
+            ctx,
+            "fief-role-create-failed",
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+    Ok(())
+}
+
+/// 修改职位的权限，担任该职位的成员会同时受到影响
+#[poise::command(prefix_command, slash_command, category = "领地")]
+async fn edit(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+    #[rename = "职位名"] role_name: String,
+
+    #[rename = "权限"]
+    #[description = "以空格分隔的多个权限，会替换职位原有的权限"]
+    permissions: String,
+) -> Result<(), Error> {
+    let Some(id) = _try(ctx, &fief_name, Permissions::MEMBER_EDIT_PERMS).await? else {
+        return Ok(());
+    };
+    let Some(role) = role_of(ctx, id, &fief_name, &role_name).await? else {
+        return Ok(());
+    };
+    let Some(p) = parse_perms(&permissions) else {
+        say!(ctx, "error-invalid-perms", perms = &permissions);
+        return Ok(());
+    };
+    if !author_can_grant(ctx, id, p).await? {
+        return Ok(());
+    }
+
+    let repo = &ctx.data().repo;
+    match repo.fief().set_fief_role_permissions(role.id, p).await {
+        Ok(_) => {
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(id)
+                .target(AuditValue::msg("audit-value-fief-role").arg("name", &role_name))
+                .before(perms_str(role.permissions))
+                .after(perms_str(p));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "fief-role-edit-done",
+                name = &fief_name,
+                role = &role_name,
+                perms = perms_str(p)
+            )
+        }
+        Err(e) => say!(
+            ctx,
+            "fief-role-edit-failed",
+            name = &fief_name,
+            role = &role_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+    Ok(())
+}
+
+/// 删除职位，担任该职位的成员保留自身的权限
+#[poise::command(prefix_command, slash_command, category = "领地")]
+async fn remove(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+    #[rename = "职位名"] role_name: String,
+) -> Result<(), Error> {
+    let Some(id) = _try(ctx, &fief_name, Permissions::MEMBER_EDIT_PERMS).await? else {
+        return Ok(());
+    };
+    let Some(role) = role_of(ctx, id, &fief_name, &role_name).await? else {
+        return Ok(());
+    };
+
+    let repo = &ctx.data().repo;
+    match repo.fief().remove_fief_role(role.id).await {
+        Ok(true) => {
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(id)
+                .target(AuditValue::msg("audit-value-fief-role").arg("name", &role_name))
+                .before(perms_str(role.permissions));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "fief-role-remove-done",
+                name = &fief_name,
+                role = &role_name
+            )
+        }
+        Ok(false) => say!(
+            ctx,
+            "fief-role-not-found",
+            name = &fief_name,
+            role = &role_name
+        ),
+        Err(e) => say!(
+            ctx,
+            "fief-role-remove-failed",
+            name = &fief_name,
+            role = &role_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+    Ok(())
+}
+
+/// 列出领地的职位
+#[poise::command(prefix_command, slash_command, category = "领地")]
+async fn list(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+) -> Result<(), Error> {
+    let Some(id) = _try(ctx, &fief_name, Permissions::NONE).await? else {
+        return Ok(());
+    };
+    let repo = &ctx.data().repo;
+
+    let roles = repo.fief().fief_roles(id).await?;
+    if roles.is_empty() {
+        say!(ctx, "fief-role-list-empty", name = &fief_name);
+        return Ok(());
+    }
+
+    let locale = Locale::from(ctx);
+    let mut pages = Pages::new(tr!(locale, "fief-role-list-title", name = &fief_name));
+    for r in roles {
+        pages.push(Row::new(tr!(
+            locale,
+            "fief-role-list-item",
+            role = &r.name,
+            perms = perms_str(r.permissions)
+        )));
+    }
+
+    pages.send(ctx).await
+}
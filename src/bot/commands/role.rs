@@ -0,0 +1,298 @@
+use poise::serenity_prelude::{Mention, Role};
+
+use super::{Context, Error, autocomplete, say};
+use crate::{
+    bot::commands::{
+        audit, author_can_grant, author_has_perms, fief_id_of, parse_perms, perms_str, reason,
+    },
+    domains::{AuditAction, AuditRecord, FiefId, Permissions, RoleId},
+};
+
+/// 身份组操作，身份组的成员共享授予该身份组的领地权限
+#[poise::command(
+    prefix_command,
+    slash_command,
+    category = "身份组",
+    subcommands("join", "leave", "allow", "deny")
+)]
+pub(super) async fn wmrole(_: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+async fn _try(
+    ctx: Context<'_>,
+    fief_name: &str,
+    perms: Permissions,
+) -> Result<Option<FiefId>, Error> {
+    let Some(fief_id) = fief_id_of(ctx, fief_name).await? else {
+        return Ok(None);
+    };
+
+    if !author_has_perms(ctx, fief_id, perms).await? {
+        say!(ctx, "error-permission-denied");
+        return Ok(None);
+    }
+
+    Ok(Some(fief_id))
+}
+
+/// 身份组在领地中已有的权限，未加入领地时提示用户并返回 `None`
+async fn role_perms(
+    ctx: Context<'_>,
+    role_id: RoleId,
+    fief_id: FiefId,
+    role: &Mention,
+    fief_name: &str,
+) -> Result<Option<Permissions>, Error> {
+    let grants = ctx.data().repo.fief().role_grants(fief_id).await?;
+    let Some(grant) = grants.into_iter().find(|g| g.role_id == role_id) else {
+        say!(
+            ctx,
+            "role-not-in-fief",
+            role = role.to_string(),
+            name = fief_name
+        );
+        return Ok(None);
+    };
+    Ok(Some(grant.permissions))
+}
+
+fn role_id_of(role: &Role) -> (RoleId, Mention) {
+    (RoleId(role.id.get() as i64), Mention::Role(role.id))
+}
+
+/// 将身份组添加进领地，其成员会收到领地的通知
+#[poise::command(prefix_command, slash_command, category = "身份组")]
+pub(super) async fn join(
+    ctx: Context<'_>,
+    #[rename = "身份组"] role: Role,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+) -> Result<(), Error> {
+    let Some(fief_id) = _try(ctx, &fief_name, Permissions::MEMBER_INVITE).await? else {
+        return Ok(());
+    };
+    let (role_id, role) = role_id_of(&role);
+
+    let repo = &ctx.data().repo;
+    match repo.fief().grant_role(fief_id, role_id, None).await {
+        Ok(true) => {
+            let record = AuditRecord::new(AuditAction::MemberJoin)
+                .fief(fief_id)
+                .target(role.to_string());
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "role-join-done",
+                role = role.to_string(),
+                name = &fief_name
+            )
+        }
+        Ok(false) => {
+            say!(
+                ctx,
+                "role-join-exists",
+                role = role.to_string(),
+                name = &fief_name
+            )
+        }
+        Err(e) => say!(
+            ctx,
+            "role-join-failed",
+            role = role.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+
+    Ok(())
+}
+
+/// 将身份组从领地移出
+#[poise::command(prefix_command, slash_command, category = "身份组")]
+pub(super) async fn leave(
+    ctx: Context<'_>,
+    #[rename = "身份组"] role: Role,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+) -> Result<(), Error> {
+    let Some(fief_id) = _try(ctx, &fief_name, Permissions::MEMBER_KICK).await? else {
+        return Ok(());
+    };
+    let (role_id, role) = role_id_of(&role);
+
+    let repo = &ctx.data().repo;
+    match repo.fief().revoke_role(fief_id, role_id).await {
+        Ok(true) => {
+            let record = AuditRecord::new(AuditAction::MemberLeave)
+                .fief(fief_id)
+                .target(role.to_string());
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "role-leave-done",
+                role = role.to_string(),
+                name = &fief_name
+            )
+        }
+        Ok(false) => say!(
+            ctx,
+            "role-leave-not-member",
+            role = role.to_string(),
+            name = &fief_name
+        ),
+        Err(e) => say!(
+            ctx,
+            "role-leave-failed",
+            role = role.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+
+    Ok(())
+}
+
+/// 给予身份组对领地的权限
+#[poise::command(prefix_command, slash_command, category = "身份组")]
+pub(super) async fn allow(
+    ctx: Context<'_>,
+    #[rename = "身份组"] role: Role,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "权限"]
+    #[description = "以空格分隔的多个权限，可以通过 `/wmpermissions` 了解所有权限"]
+    permission: String,
+) -> Result<(), Error> {
+    let Some(fief_id) = _try(ctx, &fief_name, Permissions::MEMBER_EDIT_PERMS).await? else {
+        return Ok(());
+    };
+    let (role_id, role) = role_id_of(&role);
+    let Some(perms) = role_perms(ctx, role_id, fief_id, &role, &fief_name).await? else {
+        return Ok(());
+    };
+
+    let Some(p) = parse_perms(&permission) else {
+        say!(ctx, "error-invalid-perms", perms = &permission);
+        return Ok(());
+    };
+
+    if perms.contains(p) {
+        say!(
+            ctx,
+            "role-allow-exists",
+            role = role.to_string(),
+            perms = &permission
+        );
+        return Ok(());
+    }
+
+    if !author_can_grant(ctx, fief_id, p).await? {
+        return Ok(());
+    }
+
+    let repo = &ctx.data().repo;
+    match repo
+        .fief()
+        .set_role_permissions(fief_id, role_id, p | perms)
+        .await
+    {
+        Ok(_) => {
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(fief_id)
+                .target(role.to_string())
+                .before(perms_str(perms))
+                .after(perms_str(p | perms));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "role-allow-done",
+                role = role.to_string(),
+                name = &fief_name,
+                perms = &permission
+            )
+        }
+        Err(e) => say!(
+            ctx,
+            "role-allow-failed",
+            role = role.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+
+    Ok(())
+}
+
+/// 收回身份组对领地的权限
+#[poise::command(prefix_command, slash_command, category = "身份组")]
+pub(super) async fn deny(
+    ctx: Context<'_>,
+    #[rename = "身份组"] role: Role,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "权限"]
+    #[description = "以空格分隔的多个权限，可以通过 `/wmpermissions` 了解所有权限"]
+    permission: String,
+) -> Result<(), Error> {
+    let Some(fief_id) = _try(ctx, &fief_name, Permissions::MEMBER_EDIT_PERMS).await? else {
+        return Ok(());
+    };
+    let (role_id, role) = role_id_of(&role);
+    let Some(perms) = role_perms(ctx, role_id, fief_id, &role, &fief_name).await? else {
+        return Ok(());
+    };
+
+    let Some(p) = parse_perms(&permission) else {
+        say!(ctx, "error-invalid-perms", perms = &permission);
+        return Ok(());
+    };
+
+    if perms.intersection(p) == Permissions::NONE {
+        say!(
+            ctx,
+            "role-deny-missing",
+            role = role.to_string(),
+            perms = &permission
+        );
+        return Ok(());
+    }
+
+    let repo = &ctx.data().repo;
+    match repo
+        .fief()
+        .set_role_permissions(fief_id, role_id, perms - p)
+        .await
+    {
+        Ok(_) => {
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(fief_id)
+                .target(role.to_string())
+                .before(perms_str(perms))
+                .after(perms_str(perms - p));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "role-deny-done",
+                role = role.to_string(),
+                name = &fief_name,
+                perms = &permission
+            )
+        }
+        Err(e) => say!(
+            ctx,
+            "role-deny-failed",
+            role = role.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::{future::IntoFuture, time::Duration};
+
+use poise::{
+    CreateReply,
+    serenity_prelude::{
+        Attachment, ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton,
+        CreateInteractionResponse, CreateInteractionResponseMessage, MessageCollector,
+    },
+};
+
+use super::{Context, Error, say};
+use crate::bot::i18n::{Locale, tr};
+
+/// 单张图片的大小上限（MiB），wplace 的区块图片通常远小于此
+const MAX_IMAGE_MIB: u32 = 4;
+/// 模板文件的大小上限（MiB），Overlay Pro 导出的 JSON 中可能包含多张图片
+pub(super) const MAX_TEMPLATE_MIB: u32 = 16;
+/// 等待用户发送图片的时间
+const WAIT: Duration = Duration::from_secs(60);
+/// 发送错误的消息超过该次数后放弃上传
+const MAX_FAILURES: usize = 3;
+
+/// 需要上传的一张图片
+pub(super) struct Slot {
+    /// 显示给用户的名字，例如“参考图”
+    pub(super) name: String,
+    /// 斜杠指令参数中已经附带的图片
+    pub(super) file: Option<Attachment>,
+}
+
+/// 检查附件的类型与大小，不满足时提示用户并返回 `false`
+async fn check(ctx: Context<'_>, file: &Attachment) -> Result<bool, Error> {
+    let is_png = file.content_type.as_deref() == Some("image/png");
+    if !is_png {
+        say!(ctx, "upload-not-png", file = &file.filename);
+        return Ok(false);
+    }
+    check_size(ctx, file, MAX_IMAGE_MIB).await
+}
+
+/// 在下载之前检查附件的大小，超过 `max_mib` 时提示用户并返回 `false`
+pub(super) async fn check_size(
+    ctx: Context<'_>,
+    file: &Attachment,
+    max_mib: u32,
+) -> Result<bool, Error> {
+    if file.size > max_mib * 1024 * 1024 {
+        say!(
+            ctx,
+            "upload-too-large",
+            file = &file.filename,
+            max = max_mib
+        );
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn progress(locale: Locale, slots: &[Slot], images: &[Option<Vec<u8>>]) -> String {
+    let pending = slots
+        .iter()
+        .zip(images)
+        .filter(|(_, img)| img.is_none())
+        .fold(String::new(), |s, (slot, _)| s + "*" + &slot.name + "* ");
+    let received = images.iter().filter(|img| img.is_some()).count();
+    tr!(
+        locale,
+        "upload-prompt",
+        pending = pending,
+        received = received,
+        total = images.len()
+    )
+}
+
+/// 按顺序获取每张图片：斜杠指令直接使用参数中的附件，缺少的图片通过
+/// 当前频道中的消息收集，一条消息可以包含多张图片。
+/// 等待期间可以点击按钮取消，取消、超时或失败次数过多时返回 `None`
+pub(super) async fn images(
+    ctx: Context<'_>,
+    slots: &[Slot],
+) -> Result<Option<Vec<Vec<u8>>>, Error> {
+    let mut images = Vec::with_capacity(slots.len());
+    for slot in slots {
+        match &slot.file {
+            Some(file) if !check(ctx, file).await? => return Ok(None),
+            Some(file) => images.push(Some(file.download().await?)),
+            None => images.push(None),
+        }
+    }
+    if images.iter().all(Option::is_some) {
+        return Ok(Some(images.into_iter().flatten().collect()));
+    }
+
+    let locale = Locale::from(ctx);
+    let cancel = format!("{}:upload-cancel", ctx.id());
+    let buttons = vec![
+        CreateButton::new(&cancel)
+            .style(ButtonStyle::Secondary)
+            .label(tr!(locale, "button-cancel")),
+    ];
+    let prompt = CreateReply::default()
+        .content(progress(locale, slots, &images))
+        .components(vec![CreateActionRow::Buttons(buttons)])
+        .ephemeral(true);
+    let handle = ctx.send(prompt).await?;
+    let finish = |content| CreateReply::default().content(content).components(vec![]);
+
+    let mut failures = 0;
+    while images.iter().any(Option::is_none) {
+        if failures >= MAX_FAILURES {
+            handle
+                .edit(ctx, finish(tr!(locale, "upload-too-many-failures")))
+                .await?;
+            return Ok(None);
+        }
+
+        let messages = MessageCollector::new(ctx)
+            .channel_id(ctx.channel_id())
+            .author_id(ctx.author().id)
+            .timeout(WAIT);
+        let id = cancel.clone();
+        let cancels = ComponentInteractionCollector::new(ctx)
+            .author_id(ctx.author().id)
+            .filter(move |ci| ci.data.custom_id == id)
+            .timeout(WAIT);
+        let msg = tokio::select! {
+            msg = messages.into_future() => msg,
+            Some(ci) = cancels.into_future() => {
+                let msg = CreateInteractionResponseMessage::new()
+                    .content(tr!(locale, "upload-cancelled"))
+                    .components(vec![]);
+                ci.create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
+                    .await?;
+                return Ok(None);
+            }
+        };
+        let Some(msg) = msg else {
+            handle
+                .edit(ctx, finish(tr!(locale, "upload-timeout")))
+                .await?;
+            return Ok(None);
+        };
+
+        if msg.attachments.is_empty() {
+            failures += 1;
+            say!(ctx, "upload-no-attachment");
+        }
+        for file in &msg.attachments {
+            let Some(img) = images.iter_mut().find(|img| img.is_none()) else {
+                break;
+            };
+            if check(ctx, file).await? {
+                *img = Some(file.download().await?);
+            } else {
+                failures += 1;
+            }
+        }
+        msg.delete(ctx).await?;
+
+        let reply = CreateReply::default().content(progress(locale, slots, &images));
+        handle.edit(ctx, reply).await?;
+    }
+
+    let done = CreateReply::default()
+        .content(progress(locale, slots, &images))
+        .components(vec![]);
+    handle.edit(ctx, done).await?;
+    Ok(Some(images.into_iter().flatten().collect()))
+}
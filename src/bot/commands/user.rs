@@ -1,9 +1,24 @@
-use poise::serenity_prelude::{Mention, MessageBuilder};
+use poise::{
+    CreateReply,
+    serenity_prelude::{CreateMessage, Mention},
+};
 
-use super::{Context, Error, say};
+use super::{Context, Error, autocomplete, say};
 use crate::{
-    bot::commands::{has_perms, id_of},
-    domains::{FiefId, Permissions, UserId},
+    RepoError,
+    bot::{
+        commands::{
+            accept_invitation, audit, author_can_grant, author_has_perms, fief_id_of, id_of,
+            is_owner, parse_perms, perms_str, reason,
+        },
+        i18n::{Locale, tr},
+        interactions::invitation_components,
+        pages::{Pages, Row},
+    },
+    core::log::info,
+    domains::{
+        AuditAction, AuditRecord, AuditValue, FiefId, FiefRole, Invitation, Permissions, UserId,
+    },
 };
 
 /// 用户操作
@@ -11,7 +26,17 @@ use crate::{
     prefix_command,
     slash_command,
     category = "用户",
-    subcommands("join", "leave", "allow", "deny", "info")
+    subcommands(
+        "join",
+        "leave",
+        "invitations",
+        "accept",
+        "decline",
+        "allow",
+        "deny",
+        "setrole",
+        "info"
+    )
 )]
 pub(super) async fn wmuser(_: Context<'_>) -> Result<(), Error> {
     Ok(())
@@ -24,36 +49,45 @@ async fn _try(
     perms: Permissions,
 ) -> Result<Option<(UserId, FiefId)>, Error> {
     let repo = &ctx.data().repo;
-    let Ok(fief_id) = repo.fief().id(fief_name).await else {
-        say!(ctx, "错误：领地 **{fief_name}** 不存在。");
+    let Some(fief_id) = fief_id_of(ctx, fief_name).await? else {
         return Ok(None);
     };
 
-    let author_id = id_of(ctx.author());
-    if !has_perms(repo, author_id, fief_id, perms).await {
-        say!(ctx, "错误：操作失败，权限不足。");
+    if !author_has_perms(ctx, fief_id, perms).await? {
+        say!(ctx, "error-permission-denied");
         return Ok(None);
     }
 
     let Mention::User(user_id) = user else {
-        say!(ctx, "参数错误：请@用户作为输入。");
+        say!(ctx, "error-mention-user");
         return Ok(None);
     };
 
     let user_id = UserId(user_id.get() as i64);
     if let Err(e) = repo.user().create(user_id, false).await {
-        say!(ctx, "无法存储用户信息: {e}。");
+        say!(ctx, "error-store-user", reason = reason(ctx, &e));
     }
 
     Ok(Some((user_id, fief_id)))
 }
 
-/// 将用户添加进领地
+/// 邀请默认的有效期（小时）
+const DEFAULT_INVITATION_HOURS: usize = 72;
+/// 邀请最长的有效期（小时），超过时按最长有效期处理
+const MAX_INVITATION_HOURS: usize = 30 * 24;
+
+/// 邀请用户加入领地，用户同意后才会成为领地成员
 #[poise::command(prefix_command, slash_command, category = "用户")]
 pub(super) async fn join(
     ctx: Context<'_>,
     #[rename = "用户"] user: Mention,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "有效期"]
+    #[description = "邀请的有效期（小时），默认为 72 小时，最长 30 天"]
+    hours: Option<usize>,
 ) -> Result<(), Error> {
     let Some((user_id, fief_id)) = _try(ctx, &user, &fief_name, Permissions::MEMBER_INVITE).await?
     else {
@@ -61,59 +95,235 @@ pub(super) async fn join(
     };
 
     let repo = &ctx.data().repo;
-    match repo.user().join(user_id, fief_id, None).await {
-        Ok(true) => say!(ctx, "已添加用户 {user} 至领地 **{fief_name}**。"),
-        Ok(false) => {
-            say!(ctx, "错误：用户 {user} 已经是领地 **{fief_name}** 的成员。")
-        }
-        Err(e) => say!(
+    if repo.user().is_member_of(user_id, fief_id).await? {
+        say!(
             ctx,
-            "错误：无法添加用户 {user} 至领地 **{fief_name}**: {e}。"
-        ),
+            "user-join-exists",
+            user = user.to_string(),
+            name = &fief_name
+        );
+        return Ok(());
+    }
+
+    let hours = hours
+        .unwrap_or(DEFAULT_INVITATION_HOURS)
+        .clamp(1, MAX_INVITATION_HOURS);
+    let expires_at = chrono::Utc::now()
+        .checked_add_signed(chrono::Duration::hours(hours as i64))
+        .ok_or_else(|| anyhow::anyhow!("invalid invitation expiry: {hours} hours"))?;
+    let inviter = id_of(ctx.author());
+    let id = match repo
+        .user()
+        .invite(user_id, fief_id, None, None, Some(inviter), expires_at)
+        .await
+    {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            say!(
+                ctx,
+                "user-join-pending",
+                user = user.to_string(),
+                name = &fief_name
+            );
+            return Ok(());
+        }
+        Err(e) => {
+            say!(
+                ctx,
+                "user-join-failed",
+                user = user.to_string(),
+                name = &fief_name,
+                reason = reason(ctx, &e)
+            );
+            return Ok(());
+        }
     };
 
+    // 邀请消息所有人可见，使用服务器的语言
+    let locale = Locale::of_guild(ctx);
+    let author = Mention::User(ctx.author().id).to_string();
+    let expires = format!("<t:{}:R>", expires_at.timestamp());
+    ctx.send(
+        CreateReply::default()
+            .content(tr!(
+                locale,
+                "user-invitation",
+                user = user.to_string(),
+                author = &author,
+                name = &fief_name,
+                expires = &expires
+            ))
+            .components(invitation_components(locale, id)),
+    )
+    .await?;
+
+    // 用户可能关闭了私信，此时只能通过频道中的消息或指令处理邀请
+    let dm = CreateMessage::new()
+        .content(tr!(
+            locale,
+            "user-invitation-dm",
+            author = &author,
+            name = &fief_name,
+            expires = &expires
+        ))
+        .components(invitation_components(locale, id));
+    if let Mention::User(discord_id) = user
+        && let Err(e) = discord_id.direct_message(ctx, dm).await
+    {
+        info!("failed to send invitation to {}: {e}", user_id.0);
+    }
+
     Ok(())
 }
 
-/// 将用户从领地移出
+/// 将用户从领地移出，成员可以不需要权限地将自己移出领地
 #[poise::command(prefix_command, slash_command, category = "用户")]
 pub(super) async fn leave(
     ctx: Context<'_>,
     #[rename = "用户"] user: Mention,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 ) -> Result<(), Error> {
-    let Some((user_id, fief_id)) = _try(ctx, &user, &fief_name, Permissions::MEMBER_KICK).await?
-    else {
+    let perms = match user {
+        Mention::User(id) if id == ctx.author().id => Permissions::NONE,
+        _ => Permissions::MEMBER_KICK,
+    };
+    let Some((user_id, fief_id)) = _try(ctx, &user, &fief_name, perms).await? else {
         return Ok(());
     };
     let repo = &ctx.data().repo;
 
+    if is_owner(repo, fief_id, user_id).await? {
+        say!(ctx, "user-leave-owner");
+        return Ok(());
+    }
+
     match repo.user().leave(user_id, fief_id).await {
         Ok(true) => {
-            say!(ctx, "已将用户 {user} 从领地 **{fief_name}** 中移出。")
+            let record = AuditRecord::new(AuditAction::MemberLeave)
+                .fief(fief_id)
+                .target(user.to_string());
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "user-leave-done",
+                user = user.to_string(),
+                name = &fief_name
+            )
         }
         Ok(false) => say!(
             ctx,
-            "错误：用户 {user} 不在领地 **{fief_name}** 或已经被移出。"
+            "user-leave-not-member",
+            user = user.to_string(),
+            name = &fief_name
         ),
         Err(e) => say!(
             ctx,
-            "错误：无法将用户 {user} 从领地 **{fief_name}** 中移出: {e}。"
+            "user-leave-failed",
+            user = user.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
         ),
     };
 
     Ok(())
 }
 
+/// 查看自己尚未处理的领地邀请
+#[poise::command(prefix_command, slash_command, category = "用户")]
+pub(super) async fn invitations(ctx: Context<'_>) -> Result<(), Error> {
+    let repo = &ctx.data().repo;
+
+    let invitations = repo.user().invitations(id_of(ctx.author())).await?;
+    if invitations.is_empty() {
+        say!(ctx, "user-invitations-empty");
+        return Ok(());
+    }
+
+    let locale = Locale::from(ctx);
+    let mut pages = Pages::new(tr!(locale, "user-invitations-title"));
+    for i in invitations {
+        let name = repo.fief().name(i.fief_id).await?;
+        let inviter = i.inviter.map_or(tr!(locale, "user-unknown"), |u| {
+            Mention::User((u.0 as u64).into()).to_string()
+        });
+        pages.push(Row::new(tr!(
+            locale,
+            "user-invitations-item",
+            name = &name,
+            inviter = &inviter,
+            expires = format!("<t:{}:R>", i.expires_at.timestamp())
+        )));
+    }
+
+    pages.send(ctx).await
+}
+
+/// 按领地名查找调用者未过期的邀请，不存在时提示用户并返回 `None`
+async fn invitation_of(ctx: Context<'_>, fief_name: &str) -> Result<Option<Invitation>, Error> {
+    let Some(fief_id) = fief_id_of(ctx, fief_name).await? else {
+        return Ok(None);
+    };
+    let repo = &ctx.data().repo;
+    let invitations = repo.user().invitations(id_of(ctx.author())).await?;
+    let Some(invitation) = invitations.into_iter().find(|i| i.fief_id == fief_id) else {
+        say!(ctx, "user-invitation-not-found", name = fief_name);
+        return Ok(None);
+    };
+    Ok(Some(invitation))
+}
+
+/// 接受领地的邀请
+#[poise::command(prefix_command, slash_command, category = "用户")]
+pub(super) async fn accept(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::invited_fief_names"]
+    fief_name: String,
+) -> Result<(), Error> {
+    let Some(invitation) = invitation_of(ctx, &fief_name).await? else {
+        return Ok(());
+    };
+
+    let repo = &ctx.data().repo;
+    if accept_invitation(repo, &invitation).await? {
+        say!(ctx, "user-accept-done", name = &fief_name);
+    } else {
+        say!(ctx, "user-accept-member", name = &fief_name);
+    }
+    Ok(())
+}
+
+/// 拒绝领地的邀请
+#[poise::command(prefix_command, slash_command, category = "用户")]
+pub(super) async fn decline(
+    ctx: Context<'_>,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::invited_fief_names"]
+    fief_name: String,
+) -> Result<(), Error> {
+    let Some(invitation) = invitation_of(ctx, &fief_name).await? else {
+        return Ok(());
+    };
+
+    let repo = &ctx.data().repo;
+    repo.user().remove_invitation(invitation.id).await?;
+    say!(ctx, "user-decline-done", name = &fief_name);
+    Ok(())
+}
+
 /// 给予用户对领地的权限
 #[poise::command(prefix_command, slash_command, category = "用户")]
 pub(super) async fn allow(
     ctx: Context<'_>,
     #[rename = "用户"] user: Mention,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
 
     #[rename = "权限"]
-    #[description = "可以通过 `/wmpermissions` 了解所有权限"]
+    #[description = "以空格分隔的多个权限，可以通过 `/wmpermissions` 了解所有权限"]
     permission: String,
 ) -> Result<(), Error> {
     let Some((user_id, fief_id)) =
@@ -123,18 +333,36 @@ pub(super) async fn allow(
     };
     let repo = &ctx.data().repo;
 
-    let Ok(perms) = repo.user().permissions_in(user_id, fief_id).await else {
-        say!(ctx, "错误：用户 {user} 并不属于领地 **{fief_name}**。");
-        return Ok(());
+    let perms = match repo.user().permissions_in(user_id, fief_id).await {
+        Ok(perms) => perms,
+        Err(RepoError::NotFound) => {
+            say!(
+                ctx,
+                "user-not-member",
+                user = user.to_string(),
+                name = &fief_name
+            );
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
     };
 
-    let Some(p) = Permissions::from_name(&permission) else {
-        say!(ctx, "错误：`{permission}` 不是有效的权限名称。");
+    let Some(p) = parse_perms(&permission) else {
+        say!(ctx, "error-invalid-perms", perms = &permission);
         return Ok(());
     };
 
     if perms.contains(p) {
-        say!(ctx, "错误：用户 {user} 已有权限 `{permission}`。");
+        say!(
+            ctx,
+            "user-allow-exists",
+            user = user.to_string(),
+            perms = &permission
+        );
+        return Ok(());
+    }
+
+    if !author_can_grant(ctx, fief_id, p).await? {
         return Ok(());
     }
 
@@ -143,13 +371,27 @@ pub(super) async fn allow(
         .set_permissions_in(user_id, fief_id, p | perms)
         .await
     {
-        Ok(_) => say!(
-            ctx,
-            "已经授予用户 {user} 在领地 **{fief_name}** 的 `{permission}` 权限。"
-        ),
+        Ok(_) => {
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(fief_id)
+                .target(user.to_string())
+                .before(perms_str(perms))
+                .after(perms_str(p | perms));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "user-allow-done",
+                user = user.to_string(),
+                name = &fief_name,
+                perms = &permission
+            )
+        }
         Err(e) => say!(
             ctx,
-            "错误：无法在领地 **{fief_name}** 为用户 {user} 添加权限: {e}。"
+            "user-allow-failed",
+            user = user.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
         ),
     };
 
@@ -161,9 +403,11 @@ pub(super) async fn allow(
 pub(super) async fn deny(
     ctx: Context<'_>,
     #[rename = "用户"] user: Mention,
-    #[rename = "领地名"] fief_name: String,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
     #[rename = "权限"]
-    #[description = "可以通过 `/wmpermissions` 了解所有权限"]
+    #[description = "以空格分隔的多个权限，可以通过 `/wmpermissions` 了解所有权限"]
     permission: String,
 ) -> Result<(), Error> {
     let Some((user_id, fief_id)) =
@@ -173,18 +417,37 @@ pub(super) async fn deny(
     };
     let repo = &ctx.data().repo;
 
-    let Ok(perms) = repo.user().permissions_in(user_id, fief_id).await else {
-        say!(ctx, "错误：用户 {user} 并不属于领地 **{fief_name}**。");
+    if is_owner(repo, fief_id, user_id).await? {
+        say!(ctx, "user-deny-owner");
         return Ok(());
+    }
+
+    let perms = match repo.user().permissions_in(user_id, fief_id).await {
+        Ok(perms) => perms,
+        Err(RepoError::NotFound) => {
+            say!(
+                ctx,
+                "user-not-member",
+                user = user.to_string(),
+                name = &fief_name
+            );
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
     };
 
-    let Some(p) = Permissions::from_name(&permission) else {
-        say!(ctx, "错误：`{permission}` 不是有效的权限名称。");
+    let Some(p) = parse_perms(&permission) else {
+        say!(ctx, "error-invalid-perms", perms = &permission);
         return Ok(());
     };
 
     if perms.intersection(p) == Permissions::NONE {
-        say!(ctx, "错误：用户 {user} 未有权限 `{permission}`。");
+        say!(
+            ctx,
+            "user-deny-missing",
+            user = user.to_string(),
+            perms = &permission
+        );
         return Ok(());
     }
 
@@ -193,13 +456,125 @@ pub(super) async fn deny(
         .set_permissions_in(user_id, fief_id, perms - p)
         .await
     {
-        Ok(_) => say!(
+        Ok(_) => {
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(fief_id)
+                .target(user.to_string())
+                .before(perms_str(perms))
+                .after(perms_str(perms - p));
+            audit(ctx, record).await;
+            say!(
+                ctx,
+                "user-deny-done",
+                user = user.to_string(),
+                name = &fief_name,
+                perms = &permission
+            )
+        }
+        Err(e) => say!(
             ctx,
-            "已经收回用户 {user} 在领地 **{fief_name}** 的 `{permission}` 权限。"
+            "user-deny-failed",
+            user = user.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
+        ),
+    };
+
+    Ok(())
+}
+
+/// 设置用户在领地中的职位
+#[poise::command(prefix_command, slash_command, category = "用户")]
+pub(super) async fn setrole(
+    ctx: Context<'_>,
+    #[rename = "用户"] user: Mention,
+    #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
+    fief_name: String,
+
+    #[rename = "职位名"]
+    #[description = "不填则取消用户的职位，可以通过 `/wmfief role list` 查看所有职位"]
+    role_name: Option<String>,
+) -> Result<(), Error> {
+    let Some((user_id, fief_id)) =
+        _try(ctx, &user, &fief_name, Permissions::MEMBER_EDIT_PERMS).await?
+    else {
+        return Ok(());
+    };
+    let repo = &ctx.data().repo;
+
+    let role = match &role_name {
+        Some(role_name) => match repo.fief().fief_role_by_name(fief_id, role_name).await {
+            Ok(role) => Some(role),
+            Err(RepoError::NotFound) => {
+                say!(
+                    ctx,
+                    "fief-role-not-found",
+                    name = &fief_name,
+                    role = role_name
+                );
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        },
+        None => None,
+    };
+
+    if let Some(role) = &role
+        && !author_can_grant(ctx, fief_id, role.permissions).await?
+    {
+        return Ok(());
+    }
+
+    let before = match repo.user().fief_role_in(user_id, fief_id).await {
+        Ok(before) => before,
+        Err(RepoError::NotFound) => None,
+        Err(e) => return Err(e.into()),
+    };
+    match repo
+        .user()
+        .set_fief_role_in(user_id, fief_id, role.as_ref().map(|r| r.id))
+        .await
+    {
+        Ok(true) => {
+            let role_str = |r: Option<&FiefRole>| match r {
+                Some(r) => AuditValue::msg("audit-value-fief-role").arg("name", &r.name),
+                None => AuditValue::msg("audit-value-no-role"),
+            };
+            let record = AuditRecord::new(AuditAction::PermissionChange)
+                .fief(fief_id)
+                .target(user.to_string())
+                .before(role_str(before.as_ref()))
+                .after(role_str(role.as_ref()));
+            audit(ctx, record).await;
+            match role {
+                Some(role) => say!(
+                    ctx,
+                    "user-setrole-done",
+                    user = user.to_string(),
+                    name = &fief_name,
+                    role = &role.name
+                ),
+                None => say!(
+                    ctx,
+                    "user-setrole-cleared",
+                    user = user.to_string(),
+                    name = &fief_name
+                ),
+            }
+        }
+        Ok(false) => say!(
+            ctx,
+            "user-not-member",
+            user = user.to_string(),
+            name = &fief_name
         ),
         Err(e) => say!(
             ctx,
-            "错误：无法在领地 **{fief_name}** 为用户 {user} 收回权限: {e}。"
+            "user-setrole-failed",
+            user = user.to_string(),
+            name = &fief_name,
+            reason = reason(ctx, &e)
         ),
     };
 
@@ -213,24 +588,24 @@ pub(super) async fn info(
     #[rename = "用户"] user: Mention,
 
     #[rename = "领地名"]
+    #[autocomplete = "autocomplete::fief_names"]
     #[description = "不填则显示用户在所有领地的信息"]
     fief_name: Option<String>,
 ) -> Result<(), Error> {
     let repo = &ctx.data().repo;
 
     let Mention::User(user_id) = user else {
-        say!(ctx, "参数错误：请@用户作为输入。");
+        say!(ctx, "error-mention-user");
         return Ok(());
     };
 
     let user_id = UserId(user_id.get() as i64);
     if let Err(e) = repo.user().create(user_id, false).await {
-        say!(ctx, "无法存储用户信息: {e}。");
+        say!(ctx, "error-store-user", reason = reason(ctx, &e));
     }
 
     let fief_ids = if let Some(fief_name) = fief_name {
-        let Ok(fief_id) = repo.fief().id(&fief_name).await else {
-            say!(ctx, "错误：领地 **{fief_name}** 不存在。");
+        let Some(fief_id) = fief_id_of(ctx, &fief_name).await? else {
             return Ok(());
         };
         vec![fief_id]
@@ -239,28 +614,37 @@ pub(super) async fn info(
     };
 
     let is_admin = repo.user().user_by_id(user_id).await?.is_admin;
-    let mut builder = MessageBuilder::new();
-    builder
-        .push("# 用户基本信息")
-        .push(format!("\nDiscord ID：`{}`\n", user_id.0))
-        .push(format!(
-            "WMonitor 管理员：`{}\n`",
-            if is_admin { "是" } else { "否" }
-        ))
-        .push("\n# 所属领地");
+    let locale = Locale::from(ctx);
+    let mut pages = Pages::new(tr!(
+        locale,
+        "user-info",
+        id = user_id.0.to_string(),
+        admin = if is_admin { "yes" } else { "no" }
+    ));
 
     for fief_id in fief_ids {
-        let name = repo.fief().name(fief_id).await?;
+        let fief = repo.fief().fief_by_id(fief_id).await?;
+        let name = fief.name;
         let perms = repo.user().permissions_in(user_id, fief_id).await?;
-        let perms_str = perms
-            .iter_names()
-            .map(|(s, _)| s)
-            .fold(String::new(), |a, s| a + "`" + s + "` ");
-        builder
-            .push(format!("\n- 领地名: **{name}**\n"))
-            .push(format!("  拥有权限：{}\n", perms_str));
+        let effective = repo
+            .user()
+            .effective_permissions_in(user_id, fief_id)
+            .await?;
+        let role = repo.user().fief_role_in(user_id, fief_id).await?;
+        pages.push(Row::new(tr!(
+            locale,
+            "user-info-fief",
+            name = &name,
+            owner = if fief.owner == Some(user_id) {
+                "yes"
+            } else {
+                "no"
+            },
+            role = role.map_or(tr!(locale, "none"), |r| format!("*{}*", r.name)),
+            perms = perms_str(perms),
+            effective = perms_str(effective)
+        )));
     }
 
-    say!(ctx, builder.build());
-    Ok(())
+    pages.send(ctx).await
 }
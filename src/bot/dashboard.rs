@@ -0,0 +1,328 @@
+//! 状态面板：置顶在频道中的消息，每次检查后原地编辑，而不是发送新的消息
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use image::{DynamicImage, Rgba, RgbaImage, imageops, imageops::FilterType};
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, CreateAttachment, CreateMessage, EditAttachments, EditMessage,
+    Http, MessageId,
+};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+use super::{
+    Error,
+    i18n::{Locale, tr},
+    pages::Status,
+};
+use crate::{
+    Repositories,
+    core::{
+        ImagePng,
+        log::{info, warn},
+    },
+    domains::{ChunkId, Fief, FiefId},
+};
+
+/// 正文的字符数上限，超出的区块或领地不再列出
+const MAX_CHARS: usize = 1900;
+/// 缩略图网格中最多显示的区块数量
+const MAX_THUMBS: usize = 64;
+const THUMB_SIZE: u32 = 96;
+/// 收到第一个待刷新的领地后等待的时间，期间检查过的领地合并为一次刷新
+const REFRESH_DELAY: Duration = Duration::from_secs(10);
+
+/// 缩略图边框的颜色
+fn color(status: Status) -> Rgba<u8> {
+    Rgba(match status {
+        Status::Failed => [0xed, 0x42, 0x45, 0xff],
+        Status::Damaged => [0xfa, 0xa6, 0x1a, 0xff],
+        Status::MissingRef => [0x80, 0x84, 0x8e, 0xff],
+        Status::Ok => [0x3b, 0xa5, 0x5d, 0xff],
+        Status::Paused => [0x58, 0x65, 0xf2, 0xff],
+    })
+}
+
+struct ChunkState {
+    id: ChunkId,
+    name: String,
+    status: Status,
+    diff: usize,
+}
+
+struct FiefState {
+    fief: Fief,
+    status: Status,
+    chunks: Vec<ChunkState>,
+}
+
+async fn fief_state(repo: &Repositories, fief: Fief) -> Result<FiefState, Error> {
+    let failed = fief.last_check_failed;
+    let chunks = repo
+        .chunk()
+        .summaries_by_fief(fief.id)
+        .await?
+        .into_iter()
+        .map(|c| {
+            let status = match (failed, c.has_ref, c.diff_count) {
+                (true, ..) => Status::Failed,
+                (false, false, _) => Status::MissingRef,
+                (false, true, 0) => Status::Ok,
+                (false, true, _) => Status::Damaged,
+            };
+            ChunkState {
+                id: c.chunk.id,
+                name: c.chunk.name,
+                status,
+                diff: c.diff_count,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // 状态按需要关注的程度排列，领地的状态取最需要关注的区块
+    let paused = fief.skip_check_until > chrono::Utc::now();
+    let status = match chunks.iter().map(|c| c.status).min() {
+        Some(Status::Failed) => Status::Failed,
+        _ if paused => Status::Paused,
+        status => status.unwrap_or(Status::Ok),
+    };
+    Ok(FiefState {
+        fief,
+        status,
+        chunks,
+    })
+}
+
+fn fief_line(locale: Locale, state: &FiefState) -> String {
+    let now = chrono::Utc::now();
+    let fief = &state.fief;
+    let timestamp = |t: chrono::DateTime<chrono::Utc>| format!("<t:{}:R>", t.timestamp());
+    let last = match now - fief.last_check {
+        d if d > chrono::Duration::weeks(100) => tr!(locale, "none"),
+        _ => timestamp(fief.last_check),
+    };
+    let next = match fief.next_check() - now {
+        d if d > chrono::Duration::weeks(100) => tr!(locale, "none"),
+        _ => timestamp(fief.next_check().max(now)),
+    };
+    tr!(
+        locale,
+        "dashboard-fief",
+        name = &fief.name,
+        status = state.status.label(locale),
+        last = last,
+        next = next,
+        chunks = state.chunks.len(),
+        problems = state
+            .chunks
+            .iter()
+            .filter(|c| c.status.is_problem())
+            .count(),
+        diff = state.chunks.iter().map(|c| c.diff).sum::<usize>()
+    )
+}
+
+/// 依次添加各项，超出字符数上限时省略剩余的项
+fn fit(locale: Locale, mut content: String, items: &[String], footer: &str) -> String {
+    for (i, item) in items.iter().enumerate() {
+        let more = tr!(locale, "dashboard-more", count = items.len() - i);
+        let len = content.chars().count() + item.chars().count() + footer.chars().count();
+        if len + more.chars().count() + 2 > MAX_CHARS {
+            content += &more;
+            content += "\n";
+            break;
+        }
+        content += item;
+        content += "\n";
+    }
+    content + footer
+}
+
+/// 将区块的缩略图排成接近正方形的网格，每张缩略图带有给定颜色的边框，
+/// 缩略图保持原有比例，缺少图片时留空
+pub fn thumbnail_grid(tiles: &[(Option<RgbaImage>, Rgba<u8>)], size: u32) -> RgbaImage {
+    const BORDER: u32 = 3;
+    const BACKGROUND: Rgba<u8> = Rgba([0x2b, 0x2d, 0x31, 0xff]);
+
+    let cols = (tiles.len() as f64).sqrt().ceil().max(1.0) as u32;
+    let rows = (tiles.len() as u32).div_ceil(cols).max(1);
+    let cell = size + BORDER * 2;
+    let mut out = RgbaImage::from_pixel(cols * cell, rows * cell, BACKGROUND);
+    for (i, (img, border)) in tiles.iter().enumerate() {
+        let (x, y) = (i as u32 % cols * cell, i as u32 / cols * cell);
+        let mut tile = RgbaImage::from_pixel(cell, cell, *border);
+        let inner = RgbaImage::from_pixel(size, size, BACKGROUND);
+        imageops::replace(&mut tile, &inner, BORDER as i64, BORDER as i64);
+        if let Some(img) = img {
+            let thumb = DynamicImage::ImageRgba8(img.clone())
+                .resize(size, size, FilterType::Nearest)
+                .to_rgba8();
+            let (dx, dy) = ((size - thumb.width()) / 2, (size - thumb.height()) / 2);
+            let (dx, dy) = ((BORDER + dx) as i64, (BORDER + dy) as i64);
+            imageops::overlay(&mut tile, &thumb, dx, dy);
+        }
+        imageops::replace(&mut out, &tile, x as i64, y as i64);
+    }
+    out
+}
+
+async fn thumbnails(repo: &Repositories, states: &[FiefState]) -> Result<Option<ImagePng>, Error> {
+    let mut tiles = vec![];
+    for c in states.iter().flat_map(|s| &s.chunks).take(MAX_THUMBS) {
+        let img = match repo.chunk().result_img(c.id).await? {
+            Some(img) => Some(img),
+            None => repo.chunk().ref_img(c.id).await?,
+        };
+        tiles.push((img, color(c.status)));
+    }
+    if tiles.is_empty() {
+        return Ok(None);
+    }
+
+    // 解码和缩放图片比较耗时，不在异步任务中进行
+    let grid = tokio::task::spawn_blocking(move || {
+        let tiles = tiles
+            .into_iter()
+            .map(|(img, color)| (img.and_then(|img| img.try_to_rgba().ok()), color))
+            .collect::<Vec<_>>();
+        ImagePng::try_from_rgba(thumbnail_grid(&tiles, THUMB_SIZE))
+    })
+    .await??;
+    Ok(Some(grid))
+}
+
+/// 状态面板的正文和区块缩略图，`fief_id` 为 `None` 时显示所有领地
+async fn content(
+    repo: &Repositories,
+    locale: Locale,
+    fief_id: Option<FiefId>,
+) -> Result<(String, Option<ImagePng>), Error> {
+    let footer = tr!(
+        locale,
+        "dashboard-updated",
+        time = chrono::Utc::now().timestamp().to_string()
+    );
+    let (content, states) = match fief_id {
+        Some(id) => {
+            let state = fief_state(repo, repo.fief().fief_by_id(id).await?).await?;
+            let mut header = tr!(locale, "dashboard-title-fief", name = &state.fief.name);
+            header += "\n";
+            header += &fief_line(locale, &state);
+            header += "\n";
+            let chunks = state
+                .chunks
+                .iter()
+                .map(|c| {
+                    tr!(
+                        locale,
+                        "dashboard-chunk",
+                        chunk = &c.name,
+                        status = c.status.label(locale),
+                        diff = c.diff
+                    )
+                })
+                .collect::<Vec<_>>();
+            (fit(locale, header, &chunks, &footer), vec![state])
+        }
+        None => {
+            let mut states = vec![];
+            for fief in repo.fief().all().await? {
+                states.push(fief_state(repo, fief).await?);
+            }
+            let header = tr!(locale, "dashboard-title") + "\n";
+            let fiefs = states
+                .iter()
+                .map(|s| fief_line(locale, s))
+                .collect::<Vec<_>>();
+            (fit(locale, header, &fiefs, &footer), states)
+        }
+    };
+    Ok((content, thumbnails(repo, &states).await?))
+}
+
+/// 新的状态面板消息
+pub async fn dashboard_message(
+    repo: &Repositories,
+    locale: Locale,
+    fief_id: Option<FiefId>,
+) -> Result<CreateMessage, Error> {
+    let (content, grid) = content(repo, locale, fief_id).await?;
+    let msg = CreateMessage::new().content(content);
+    Ok(match grid {
+        Some(grid) => msg.add_file(CreateAttachment::bytes(grid.into_inner(), "dashboard.png")),
+        None => msg,
+    })
+}
+
+fn is_not_found(e: &serenity::Error) -> bool {
+    matches!(
+        e,
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r))
+            if r.status_code.as_u16() == 404
+    )
+}
+
+/// 在单独的任务中刷新状态面板，返回的发送端用于提交检查过的领地
+///
+/// 一段时间内提交的领地合并为一次刷新，显示所有领地的状态面板每批只重新生成一次
+pub fn spawn_refresher(
+    http: Arc<Http>,
+    repo: &'static Repositories,
+    locale: Locale,
+) -> UnboundedSender<FiefId> {
+    let (tx, mut rx) = unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(fief_id) = rx.recv().await {
+            tokio::time::sleep(REFRESH_DELAY).await;
+            let mut fiefs = BTreeSet::from([fief_id]);
+            while let Ok(fief_id) = rx.try_recv() {
+                fiefs.insert(fief_id);
+            }
+            if let Err(e) = refresh(&http, repo, locale, &fiefs).await {
+                warn!("failed to refresh dashboards: {e}");
+            }
+        }
+    });
+    tx
+}
+
+/// 更新所有显示这些领地的状态面板，每个状态面板只更新一次，
+/// 消息已被删除的状态面板不再更新
+async fn refresh(
+    http: &Http,
+    repo: &Repositories,
+    locale: Locale,
+    fiefs: &BTreeSet<FiefId>,
+) -> Result<(), Error> {
+    let mut dashboards = BTreeMap::new();
+    for &fief_id in fiefs {
+        for d in repo.dashboard().showing(fief_id).await? {
+            dashboards.insert(d.id, d);
+        }
+    }
+    for d in dashboards.into_values() {
+        let (content, grid) = content(repo, locale, d.fief_id).await?;
+        // 替换原有的附件
+        let mut attachments = EditAttachments::new();
+        if let Some(grid) = grid {
+            let grid = CreateAttachment::bytes(grid.into_inner(), "dashboard.png");
+            attachments = attachments.add(grid);
+        }
+        let edit = EditMessage::new().content(content).attachments(attachments);
+
+        let channel = ChannelId::new(d.channel_id as u64);
+        let message = MessageId::new(d.message_id as u64);
+        match channel.edit_message(http, message, edit).await {
+            Ok(_) => {}
+            Err(e) if is_not_found(&e) => {
+                info!("dashboard {} has been deleted", d.id.0);
+                repo.dashboard().remove(d.id).await?;
+            }
+            Err(e) => warn!("failed to update dashboard {}: {e}", d.id.0),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,265 @@
+//! 指令与通知的多语言文本，文本保存在 `locales/` 下的 Fluent 文件中
+
+use std::{borrow::Cow, sync::LazyLock};
+
+use fluent::{FluentArgs, FluentResource, FluentValue, concurrent::FluentBundle};
+use fluent_syntax::ast;
+
+use super::{Context, Data, Error};
+use crate::{cfg, core::log::warn};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum Locale {
+    ZhCn,
+    EnUs,
+}
+
+impl Locale {
+    pub const ALL: [Self; 2] = [Self::ZhCn, Self::EnUs];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::ZhCn => "zh-CN",
+            Self::EnUs => "en-US",
+        }
+    }
+
+    /// 根据 Discord 的语言代码选择语言，没有对应的语言时返回 `None`
+    pub fn from_discord(code: &str) -> Option<Self> {
+        match code.split('-').next()? {
+            "zh" => Some(Self::ZhCn),
+            "en" => Some(Self::EnUs),
+            _ => None,
+        }
+    }
+
+    /// 配置中的默认语言，无法识别时使用中文
+    pub fn fallback() -> Self {
+        Self::from_discord(&cfg().common.locale).unwrap_or(Self::ZhCn)
+    }
+
+    /// 优先使用用户的语言，其次是服务器的语言
+    pub fn pick(user: Option<&str>, guild: Option<&str>) -> Self {
+        user.and_then(Self::from_discord)
+            .or_else(|| guild.and_then(Self::from_discord))
+            .unwrap_or_else(Self::fallback)
+    }
+
+    /// Discord 中使用该语言的所有语言代码
+    fn discord_codes(self) -> &'static [&'static str] {
+        match self {
+            Self::ZhCn => &["zh-CN"],
+            Self::EnUs => &["en-US", "en-GB"],
+        }
+    }
+}
+
+impl Locale {
+    /// 服务器的语言，用于所有人可见的消息
+    pub fn of_guild(ctx: Context<'_>) -> Self {
+        let guild = ctx.guild().map(|g| g.preferred_locale.clone());
+        Self::pick(None, guild.as_deref())
+    }
+}
+
+/// 指令的回复使用调用者的语言
+impl From<Context<'_>> for Locale {
+    fn from(ctx: Context<'_>) -> Self {
+        let guild = ctx.guild().map(|g| g.preferred_locale.clone());
+        Self::pick(ctx.locale(), guild.as_deref())
+    }
+}
+
+/// 可以作为文本参数的值
+pub trait Arg {
+    fn value(&self) -> FluentValue<'static>;
+}
+
+impl Arg for str {
+    fn value(&self) -> FluentValue<'static> {
+        FluentValue::String(Cow::Owned(self.to_owned()))
+    }
+}
+
+impl Arg for String {
+    fn value(&self) -> FluentValue<'static> {
+        self.as_str().value()
+    }
+}
+
+impl<T: Arg + ?Sized> Arg for &T {
+    fn value(&self) -> FluentValue<'static> {
+        (**self).value()
+    }
+}
+
+macro_rules! number_arg {
+    ($($num:ty),*) => {
+        $(impl Arg for $num {
+            fn value(&self) -> FluentValue<'static> {
+                FluentValue::from(*self)
+            }
+        })*
+    };
+}
+number_arg!(u8, u32, u64, usize, i32, i64, f64);
+
+/// 获取文本，例如 `tr!(ctx, "error-fief-not-found", name = &name)`
+macro_rules! tr {
+    ($locale:expr, $id:literal $(, $name:ident = $value:expr)* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut args = fluent::FluentArgs::new();
+        $(args.set(stringify!($name), $crate::bot::i18n::Arg::value(&$value));)*
+        $crate::bot::i18n::get($crate::bot::i18n::Locale::from($locale), $id, &args)
+    }};
+}
+pub(crate) use tr;
+
+type Bundle = FluentBundle<FluentResource>;
+
+fn bundle(locale: Locale, source: &str) -> Bundle {
+    let resource = FluentResource::try_new(source.to_owned()).unwrap_or_else(|(_, e)| {
+        panic!("failed to parse {} messages: {e:?}", locale.code());
+    });
+    let mut bundle = Bundle::new_concurrent(vec![locale.code().parse().unwrap()]);
+    // Discord 不需要双向文本的隔离字符
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).unwrap_or_else(|e| {
+        panic!("failed to load {} messages: {e:?}", locale.code());
+    });
+    bundle
+}
+
+/// 与 [`Locale::ALL`] 的顺序一致
+const SOURCES: [&str; 2] = [
+    include_str!("../../locales/zh-CN.ftl"),
+    include_str!("../../locales/en-US.ftl"),
+];
+
+static BUNDLES: LazyLock<Vec<Bundle>> = LazyLock::new(|| {
+    Locale::ALL
+        .into_iter()
+        .map(|l| bundle(l, SOURCES[l as usize]))
+        .collect()
+});
+
+fn bundle_of(locale: Locale) -> &'static Bundle {
+    &BUNDLES[locale as usize]
+}
+
+fn format(
+    bundle: &Bundle,
+    id: &str,
+    attr: Option<&str>,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let message = bundle.get_message(id)?;
+    let pattern = match attr {
+        Some(attr) => message.get_attribute(attr)?.value(),
+        None => message.value()?,
+    };
+    let mut errors = vec![];
+    let s = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        warn!("failed to format message `{id}`: {errors:?}");
+    }
+    Some(s.into_owned())
+}
+
+/// 获取文本，缺少翻译时使用中文，仍然缺少时返回文本的 id
+pub fn get(locale: Locale, id: &str, args: &FluentArgs) -> String {
+    format(bundle_of(locale), id, None, Some(args))
+        .or_else(|| format(bundle_of(Locale::ZhCn), id, None, Some(args)))
+        .unwrap_or_else(|| {
+            warn!("unknown message `{id}`");
+            id.to_owned()
+        })
+}
+
+/// 某个语言中所有文本的 id，用于检查翻译是否完整
+pub fn message_ids(locale: Locale) -> Vec<String> {
+    let Ok(resource) = FluentResource::try_new(SOURCES[locale as usize].to_owned()) else {
+        return vec![];
+    };
+    resource
+        .entries()
+        .filter_map(|e| match e {
+            ast::Entry::Message(m) => Some(m.id.name.to_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `options` 和 `choices` 中中文名与 id 的对应关系
+fn attr_ids(message: &str) -> Vec<(String, &'static str)> {
+    let zh = bundle_of(Locale::ZhCn);
+    let Some(message) = zh.get_message(message) else {
+        return vec![];
+    };
+    message
+        .attributes()
+        .map(|a| {
+            (
+                zh.format_pattern(a.value(), None, &mut vec![]).into_owned(),
+                a.id(),
+            )
+        })
+        .collect()
+}
+
+/// 为指令、参数及选项添加其他语言的名字和描述，指令本身的文本为中文，
+/// 描述的 id 为以 `-` 连接的指令名，例如 `wmfief-role-create`
+pub fn localize_commands(commands: &mut [poise::Command<Data, Error>]) {
+    let options = attr_ids("options");
+    let choices = attr_ids("choices");
+    let option_id = |name: &str| options.iter().find(|(n, _)| n == name).map(|(_, id)| *id);
+    let choice_id = |name: &str| choices.iter().find(|(n, _)| n == name).map(|(_, id)| *id);
+
+    // 此时 poise 尚未设置 `qualified_name`，需要自行拼接上级指令的名字
+    fn localize(
+        command: &mut poise::Command<Data, Error>,
+        parent: &str,
+        option_id: &dyn Fn(&str) -> Option<&'static str>,
+        choice_id: &dyn Fn(&str) -> Option<&'static str>,
+    ) {
+        let key = match parent {
+            "" => command.name.clone(),
+            _ => format!("{parent}-{}", command.name),
+        };
+        for locale in Locale::ALL.into_iter().filter(|l| *l != Locale::ZhCn) {
+            let bundle = bundle_of(locale);
+            for &code in locale.discord_codes() {
+                let code = code.to_owned();
+                if let Some(s) = format(bundle, &key, None, None) {
+                    command.description_localizations.insert(code.clone(), s);
+                }
+                for p in &mut command.parameters {
+                    // 英文参数名（例如 `x`）不需要翻译，直接作为 id
+                    let name = p.name.clone();
+                    let id = option_id(&name).unwrap_or(&name);
+                    if let Some(s) = format(bundle, "options", Some(id), None) {
+                        p.name_localizations.insert(code.clone(), s);
+                    }
+                    if let Some(s) = format(bundle, &key, Some(id), None) {
+                        p.description_localizations.insert(code.clone(), s);
+                    }
+                    for c in &mut p.choices {
+                        let Some(s) = choice_id(&c.name)
+                            .and_then(|id| format(bundle, "choices", Some(id), None))
+                        else {
+                            continue;
+                        };
+                        c.localizations.insert(code.clone(), s);
+                    }
+                }
+            }
+        }
+        for sub in &mut command.subcommands {
+            localize(sub, &key, option_id, choice_id);
+        }
+    }
+
+    for command in commands {
+        localize(command, "", &option_id, &choice_id);
+    }
+}
@@ -0,0 +1,336 @@
+use poise::serenity_prelude::{
+    self as serenity, ActionRowComponent, ButtonStyle, ComponentInteraction,
+    ComponentInteractionDataKind, CreateActionRow, CreateButton, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption, EditInteractionResponse, Mention,
+};
+
+use super::{
+    Data, Error,
+    commands::{accept_invitation, has_perms, record_audit, role_ids},
+    i18n::{Locale, tr},
+};
+use crate::{
+    RepoError,
+    core::log::{info, warn},
+    domains::{
+        AuditAction, AuditRecord, AuditValue, ChunkId, FiefId, ImageKey, InvitationId, Permissions,
+        UserId,
+    },
+};
+
+const DIFF_PREFIX: &str = "wmdiff";
+const INVITE_PREFIX: &str = "wminvite";
+/// Discord 选择菜单最多只能有 25 个选项
+const MAX_SELECT_OPTIONS: usize = 25;
+
+/// 发现差异的区块，以及检查时获取到的图片
+type DiffChunk = (ChunkId, ImageKey, String);
+
+/// 选项的值记录检查时的图片，接受时使用的是通知所报告的状态
+fn option_value(id: ChunkId, key: &ImageKey) -> String {
+    format!("{}:{}", id.0, key.0)
+}
+
+/// 附加在 `DiffFound` 通知上的操作组件
+pub(super) fn diff_components(
+    locale: Locale,
+    fief_id: FiefId,
+    chunks: &[DiffChunk],
+) -> Vec<CreateActionRow> {
+    if chunks.is_empty() {
+        return vec![];
+    }
+
+    let options = chunks
+        .iter()
+        .take(MAX_SELECT_OPTIONS)
+        .map(|(id, key, name)| CreateSelectMenuOption::new(name, option_value(*id, key)))
+        .collect::<Vec<_>>();
+    let len = options.len() as u8;
+    let select = CreateSelectMenu::new(
+        format!("{DIFF_PREFIX}:select:{}", fief_id.0),
+        CreateSelectMenuKind::String { options },
+    )
+    .placeholder(tr!(locale, "diff-select-placeholder"))
+    .min_values(1)
+    .max_values(len);
+
+    let buttons = vec![
+        CreateButton::new(format!("{DIFF_PREFIX}:accept:{}", fief_id.0))
+            .style(ButtonStyle::Success)
+            .label(tr!(locale, "diff-accept-all")),
+        CreateButton::new(format!("{DIFF_PREFIX}:ignore:{}", fief_id.0))
+            .style(ButtonStyle::Secondary)
+            .label(tr!(locale, "diff-ignore")),
+        CreateButton::new(format!("{DIFF_PREFIX}:grief:{}", fief_id.0))
+            .style(ButtonStyle::Danger)
+            .label(tr!(locale, "diff-grief")),
+    ];
+
+    vec![
+        CreateActionRow::SelectMenu(select),
+        CreateActionRow::Buttons(buttons),
+    ]
+}
+
+/// 附加在领地邀请上的按钮，只有被邀请的用户可以使用
+pub(super) fn invitation_components(locale: Locale, id: InvitationId) -> Vec<CreateActionRow> {
+    let buttons = vec![
+        CreateButton::new(format!("{INVITE_PREFIX}:accept:{}", id.0))
+            .style(ButtonStyle::Success)
+            .label(tr!(locale, "invitation-accept")),
+        CreateButton::new(format!("{INVITE_PREFIX}:decline:{}", id.0))
+            .style(ButtonStyle::Secondary)
+            .label(tr!(locale, "invitation-decline")),
+    ];
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+/// 列出通知消息中尚未处理的区块
+fn pending_chunks(ci: &ComponentInteraction) -> Vec<DiffChunk> {
+    ci.message
+        .components
+        .iter()
+        .flat_map(|row| row.components.iter())
+        .filter_map(|c| match c {
+            ActionRowComponent::SelectMenu(menu) => Some(menu),
+            _ => None,
+        })
+        .flat_map(|menu| menu.options.iter())
+        .filter_map(|o| {
+            let (id, key) = o.value.split_once(':')?;
+            let id = ChunkId(id.parse().ok()?);
+            Some((id, ImageKey(key.to_owned()), o.label.clone()))
+        })
+        .collect()
+}
+
+fn fmt_names<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    names.fold(String::new(), |s, n| s + "*" + n + "* ")
+}
+
+/// 只有点击者可见的回复使用点击者的语言
+fn user_locale(ci: &ComponentInteraction) -> Locale {
+    Locale::pick(Some(&ci.locale), ci.guild_locale.as_deref())
+}
+
+/// 追加在原消息上的文本所有人可见，使用服务器的语言
+fn guild_locale(ci: &ComponentInteraction) -> Locale {
+    Locale::pick(None, ci.guild_locale.as_deref())
+}
+
+async fn reply_ephemeral(
+    ctx: &serenity::Context,
+    ci: &ComponentInteraction,
+    content: impl Into<String>,
+) -> Result<(), Error> {
+    let msg = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+    ci.create_response(ctx, CreateInteractionResponse::Message(msg))
+        .await?;
+    Ok(())
+}
+
+pub(super) async fn handle_component(
+    ctx: &serenity::Context,
+    ci: &ComponentInteraction,
+    data: &Data,
+) -> Result<(), Error> {
+    let mut parts = ci.data.custom_id.split(':');
+    let (Some(prefix), Some(action), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    let Ok(id) = id.parse() else {
+        return Ok(());
+    };
+    match prefix {
+        DIFF_PREFIX => handle_diff(ctx, ci, data, action, FiefId(id)).await,
+        INVITE_PREFIX => handle_invitation(ctx, ci, data, action, InvitationId(id)).await,
+        _ => Ok(()),
+    }
+}
+
+async fn handle_diff(
+    ctx: &serenity::Context,
+    ci: &ComponentInteraction,
+    data: &Data,
+    action: &str,
+    fief_id: FiefId,
+) -> Result<(), Error> {
+    let user_id = UserId(ci.user.id.get() as i64);
+    let roles = ci.member.as_ref().map_or(vec![], |m| role_ids(&m.roles));
+    if !has_perms(data.repo, user_id, &roles, fief_id, Permissions::CHUNK_EDIT).await? {
+        let content = tr!(user_locale(ci), "error-permission-denied");
+        return reply_ephemeral(ctx, ci, content).await;
+    }
+
+    let locale = guild_locale(ci);
+    let user = Mention::User(ci.user.id).to_string();
+    let note = match action {
+        "ignore" => {
+            info!("diff of fief {} is ignored by {}", fief_id.0, user_id.0);
+            tr!(locale, "diff-ignored", user = &user)
+        }
+        "grief" => {
+            info!(
+                "diff of fief {} is marked as griefing by {}",
+                fief_id.0, user_id.0
+            );
+            tr!(locale, "diff-griefed", user = &user)
+        }
+        "accept" | "select" => return accept(ctx, ci, data, fief_id, user_id).await,
+        _ => return Ok(()),
+    };
+
+    let msg = CreateInteractionResponseMessage::new()
+        .content(format!("{}\n{note}", ci.message.content))
+        .components(vec![]);
+    ci.create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
+        .await?;
+    Ok(())
+}
+
+/// 处理邀请上的按钮，邀请可能已经通过其他消息或指令处理
+async fn handle_invitation(
+    ctx: &serenity::Context,
+    ci: &ComponentInteraction,
+    data: &Data,
+    action: &str,
+    id: InvitationId,
+) -> Result<(), Error> {
+    let repo = data.repo;
+    let invitation = match repo.user().invitation_by_id(id).await {
+        Ok(invitation) => invitation,
+        Err(RepoError::NotFound) => {
+            let content = tr!(user_locale(ci), "invitation-gone");
+            return reply_ephemeral(ctx, ci, content).await;
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if invitation.user_id.0 != ci.user.id.get() as i64 {
+        let content = tr!(user_locale(ci), "invitation-not-invitee");
+        return reply_ephemeral(ctx, ci, content).await;
+    }
+
+    let fief_name = repo.fief().name(invitation.fief_id).await?;
+    let locale = guild_locale(ci);
+    let user = Mention::User(ci.user.id).to_string();
+    let note = if invitation.is_expired(chrono::Utc::now()) {
+        repo.user().remove_invitation(id).await?;
+        tr!(locale, "invitation-expired")
+    } else {
+        match action {
+            "accept" => {
+                accept_invitation(repo, &invitation).await?;
+                info!(
+                    "{} accepted the invitation to fief {}",
+                    invitation.user_id.0, invitation.fief_id.0
+                );
+                tr!(
+                    locale,
+                    "invitation-accepted",
+                    user = &user,
+                    name = &fief_name
+                )
+            }
+            "decline" => {
+                repo.user().remove_invitation(id).await?;
+                tr!(
+                    locale,
+                    "invitation-declined",
+                    user = &user,
+                    name = &fief_name
+                )
+            }
+            _ => return Ok(()),
+        }
+    };
+
+    let msg = CreateInteractionResponseMessage::new()
+        .content(format!("{}\n{note}", ci.message.content))
+        .components(vec![]);
+    ci.create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
+        .await?;
+    Ok(())
+}
+
+/// 将选中区块（或全部未处理区块）的当前状态设置为参考图
+async fn accept(
+    ctx: &serenity::Context,
+    ci: &ComponentInteraction,
+    data: &Data,
+    fief_id: FiefId,
+    user_id: UserId,
+) -> Result<(), Error> {
+    let repo = data.repo;
+    let pending = pending_chunks(ci);
+    let selected = match &ci.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => pending
+            .iter()
+            .filter(|(id, key, _)| values.contains(&option_value(*id, key)))
+            .cloned()
+            .collect(),
+        _ => pending.clone(),
+    };
+
+    // 区块较多时保存图片可能需要较长时间，先确认交互以免超时
+    ci.create_response(ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let (mut accepted, mut expired, mut failed) = (vec![], vec![], vec![]);
+    for (id, key, name) in selected {
+        // 接受的是发现差异时检查到的状态，而不是点击时的最新状态
+        match repo.accept_checked_img(id, &key, user_id).await {
+            Ok(false) => expired.push(name),
+            Ok(true) => {
+                let record = AuditRecord::new(AuditAction::ChunkImage)
+                    .fief(fief_id)
+                    .target(&name)
+                    .after(AuditValue::msg("audit-value-accept-current"));
+                record_audit(repo, user_id, record).await;
+                accepted.push((id, name));
+            }
+            Err(e) => {
+                warn!("failed to accept reference image of chunk {}: {e}", id.0);
+                failed.push(name);
+            }
+        }
+    }
+
+    let locale = guild_locale(ci);
+    let mut content = ci.message.content.clone();
+    if !accepted.is_empty() {
+        info!("{} accepted current state of fief {}", user_id.0, fief_id.0);
+        let accepted_names = fmt_names(accepted.iter().map(|(_, n)| n));
+        let user = Mention::User(ci.user.id).to_string();
+        content += "\n";
+        content += &tr!(
+            locale,
+            "diff-accepted",
+            user = &user,
+            chunks = &accepted_names
+        );
+    }
+    if !expired.is_empty() {
+        let expired_names = fmt_names(expired.iter());
+        content += "\n";
+        content += &tr!(locale, "diff-accept-expired", chunks = &expired_names);
+    }
+    if !failed.is_empty() {
+        let failed_names = fmt_names(failed.iter());
+        content += "\n";
+        content += &tr!(locale, "diff-accept-failed", chunks = &failed_names);
+    }
+
+    let remaining = pending
+        .into_iter()
+        .filter(|(id, ..)| accepted.iter().all(|(a, _)| a != id))
+        .collect::<Vec<_>>();
+    let edit = EditInteractionResponse::new()
+        .content(content)
+        .components(diff_components(locale, fief_id, &remaining));
+    ci.edit_response(ctx, edit).await?;
+    Ok(())
+}
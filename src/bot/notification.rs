@@ -1,33 +1,61 @@
+use std::collections::HashMap;
+
 use poise::serenity_prelude::{
     CreateAttachment, CreateMessage, Mention, MessageBuilder, MessageFlags,
 };
 
-use super::Error;
+use super::{
+    Error,
+    i18n::{Locale, tr},
+    interactions::diff_components,
+};
 use crate::{
     Repositories,
     check::{Event, MAX_RETRY_TIMES, RetryTimes},
+    domains::FiefId,
 };
 
+/// 需要提醒的领地成员，包括被授予权限的身份组
+async fn member_mentions(repo: &Repositories, fief_id: FiefId) -> Result<String, Error> {
+    let users = repo
+        .fief()
+        .members(fief_id)
+        .await?
+        .into_iter()
+        .map(|u| Mention::User((u.0 as u64).into()));
+    let roles = repo
+        .fief()
+        .role_grants(fief_id)
+        .await?
+        .into_iter()
+        .map(|g| Mention::Role((g.role_id.0 as u64).into()));
+    Ok(users
+        .chain(roles)
+        .fold(String::new(), |s, m| s + m.to_string().as_str() + " "))
+}
+
 pub async fn notification_message(
     repo: &Repositories,
+    locale: Locale,
     event: Event,
-) -> Result<CreateMessage, Error> {
+) -> Result<Option<CreateMessage>, Error> {
     let result = CreateMessage::new();
 
-    Ok(match event {
+    Ok(Some(match event {
         Event::CheckFailed(fief_id, RetryTimes(times)) => {
             let name = repo.fief().name(fief_id).await?;
             let mut builder = MessageBuilder::new();
-            builder.push(format!(
-                "领地 **{name}** 检查失败（重试次数: {times}/{MAX_RETRY_TIMES}）。"
+            builder.push(tr!(
+                locale,
+                "notify-check-failed",
+                name = &name,
+                times = times,
+                max = MAX_RETRY_TIMES
             ));
             if times == MAX_RETRY_TIMES {
-                let users = repo.fief().members(fief_id).await?;
-                let mentions = users
-                    .into_iter()
-                    .map(|u| Mention::User((u.0 as u64).into()))
-                    .fold("\n".to_string(), |s, m| s + m.to_string().as_str() + " ");
-                builder.push(mentions);
+                builder
+                    .push("\n")
+                    .push(member_mentions(repo, fief_id).await?);
             }
             result.content(builder.build())
         }
@@ -36,58 +64,78 @@ pub async fn notification_message(
             let name = repo.fief().name(fief_id).await?;
             result
                 .flags(MessageFlags::SUPPRESS_NOTIFICATIONS)
-                .content(format!("领地 **{name}** 目前正常。"))
+                .content(tr!(locale, "notify-check-success", name = &name))
         }
 
-        Event::DiffFound(fief_id, chunk_ids) => {
-            let users = repo.fief().members(fief_id).await?;
-            let mentions = users
-                .into_iter()
-                .map(|u| Mention::User((u.0 as u64).into()))
-                .fold(String::new(), |s, m| s + m.to_string().as_str() + " ");
+        Event::DiffFound(fief_id, checked) => {
+            let mentions = member_mentions(repo, fief_id).await?;
 
             let name = repo.fief().name(fief_id).await?;
             let diff_count = repo.fief().diff_count(fief_id).await?;
 
+            let names = repo
+                .chunk()
+                .summaries_by_fief(fief_id)
+                .await?
+                .into_iter()
+                .map(|c| (c.chunk.id, c.chunk.name))
+                .collect::<HashMap<_, _>>();
+
             let mut chunk_result_imgs = vec![];
             let mut chunk_names = String::new();
-            for id in chunk_ids {
-                let name = repo.chunk().name(id).await?;
+            let mut chunks = vec![];
+            for (id, key) in checked {
+                let Some(name) = names.get(&id).cloned() else {
+                    continue;
+                };
                 let img = repo.chunk().result_img(id).await?;
                 chunk_names = chunk_names + "*" + name.as_str() + "* ";
                 chunk_result_imgs.push(img);
+                chunks.push((id, key, name));
             }
 
             let content = MessageBuilder::new()
-                .push("# 发现异常像素\n")
-                .push(format!("领地: **{name}**\n"))
-                .push(format!("异常区块：{chunk_names}\n"))
-                .push(format!("异常像素数量：{diff_count} 个\n"))
+                .push(tr!(
+                    locale,
+                    "notify-diff-found",
+                    name = &name,
+                    chunks = &chunk_names,
+                    pixels = diff_count
+                ))
+                .push("\n")
                 .push(mentions)
                 .build();
-            result.content(content).add_files(
-                chunk_result_imgs
-                    .into_iter()
-                    .flatten()
-                    .enumerate()
-                    .map(|(i, img)| {
-                        CreateAttachment::bytes(img.into_inner(), format!("diff_{i}.png"))
-                    }),
-            )
+            result
+                .content(content)
+                .components(diff_components(locale, fief_id, &chunks))
+                .add_files(
+                    chunk_result_imgs
+                        .into_iter()
+                        .flatten()
+                        .enumerate()
+                        .map(|(i, img)| {
+                            CreateAttachment::bytes(img.into_inner(), format!("diff_{i}.png"))
+                        }),
+                )
         }
 
-        Event::NetworkError(e) => result.content(format!("网络异常：{e}。")),
+        Event::NetworkError(e) => {
+            result.content(tr!(locale, "notify-network-error", error = e.to_string()))
+        }
 
         Event::ChunkRefMissing(fief_id, chunk_id) => {
             let f = repo.fief().name(fief_id).await?;
             let c = repo.chunk().name(chunk_id).await?;
-            result.content(format!("警告：领地 **{f}** 的区块 *{c}* 未设置参考图。"))
+            result.content(tr!(locale, "notify-ref-missing", name = &f, chunk = &c))
         }
 
         Event::ChunkMaskMissing(fief_id, chunk_id) => {
             let f = repo.fief().name(fief_id).await?;
             let c = repo.chunk().name(chunk_id).await?;
-            result.content(format!("警告：领地 **{f}** 的区块 *{c}* 未设置遮罩图。"))
+            result.content(tr!(locale, "notify-mask-missing", name = &f, chunk = &c))
         }
-    })
+
+        // 只用于更新状态面板
+        Event::Checked(..) => return Ok(None),
+    }))
 }
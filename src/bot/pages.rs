@@ -0,0 +1,311 @@
+//! 可以翻页的列表消息，避免内容超出 Discord 单条消息 2000 字符的限制
+
+use std::time::Duration;
+
+use poise::{
+    CreateReply,
+    serenity_prelude::{
+        ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton,
+        CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
+};
+
+use super::{
+    Context, Error,
+    i18n::{Locale, tr},
+};
+
+/// 每页正文（不含页脚）的字符数上限，为页脚留出空间
+const PAGE_CHARS: usize = 1900;
+/// 每页最多显示的行数
+const PAGE_ROWS: usize = 15;
+/// 按钮在最后一次点击之后保持可用的时间
+const WAIT: Duration = Duration::from_secs(300);
+
+/// 列表项的状态，按状态排序时越需要关注的越靠前
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Default)]
+pub enum Status {
+    /// 领地最近一次检查失败
+    Failed,
+    Damaged,
+    MissingRef,
+    #[default]
+    Ok,
+    Paused,
+}
+
+impl Status {
+    /// 需要成员处理的状态，用于“只看异常”
+    pub fn is_problem(self) -> bool {
+        matches!(self, Self::Failed | Self::Damaged | Self::MissingRef)
+    }
+
+    pub fn label(self, locale: Locale) -> String {
+        match self {
+            Self::Failed => tr!(locale, "status-failed"),
+            Self::Damaged => tr!(locale, "status-damaged"),
+            Self::MissingRef => tr!(locale, "status-missing-ref"),
+            Self::Ok => tr!(locale, "status-ok"),
+            Self::Paused => tr!(locale, "status-paused"),
+        }
+    }
+}
+
+/// 列表中的一项，可以包含多行文本
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Row {
+    pub text: String,
+    pub status: Status,
+    pub diff: usize,
+}
+
+impl Row {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            status: Status::Ok,
+            diff: 0,
+        }
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn diff(mut self, diff: usize) -> Self {
+        self.diff = diff;
+        self
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Default)]
+pub enum Sort {
+    /// 保持添加时的顺序
+    #[default]
+    Default,
+    Status,
+    /// 差异像素多的在前
+    Diff,
+}
+
+impl Sort {
+    fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Status,
+            Self::Status => Self::Diff,
+            Self::Diff => Self::Default,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Status => "status",
+            Self::Diff => "diff",
+        }
+    }
+}
+
+/// 用户当前看到的页码（从 0 开始）、筛选和排序方式
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Default)]
+pub struct View {
+    pub page: usize,
+    pub problems_only: bool,
+    pub sort: Sort,
+}
+
+/// 带有标题的分段列表，标题在每一页都会显示，
+/// 筛选和排序只在每一段之内进行
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Pages {
+    title: String,
+    sections: Vec<(Option<String>, Vec<Row>)>,
+    sortable: bool,
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max.saturating_sub(1)) {
+        Some((i, _)) if s.chars().count() > max => format!("{}…", &s[..i]),
+        _ => s.to_owned(),
+    }
+}
+
+impl Pages {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 开始新的一段，之后添加的项都显示在该小标题之下
+    pub fn section(&mut self, heading: impl Into<String>) -> &mut Self {
+        self.sections.push((Some(heading.into()), vec![]));
+        self
+    }
+
+    pub fn push(&mut self, row: Row) -> &mut Self {
+        match self.sections.last_mut() {
+            Some((_, rows)) => rows.push(row),
+            None => self.sections.push((None, vec![row])),
+        }
+        self
+    }
+
+    /// 显示筛选与排序按钮
+    pub fn sortable(&mut self) -> &mut Self {
+        self.sortable = true;
+        self
+    }
+
+    /// 筛选与排序之后的各段
+    fn visible(&self, view: &View) -> Vec<(Option<&str>, Vec<&Row>)> {
+        self.sections
+            .iter()
+            .map(|(heading, rows)| {
+                let mut rows = rows
+                    .iter()
+                    .filter(|r| !view.problems_only || r.status.is_problem())
+                    .collect::<Vec<_>>();
+                match view.sort {
+                    Sort::Default => {}
+                    Sort::Status => rows.sort_by_key(|r| r.status),
+                    Sort::Diff => rows.sort_by_key(|r| std::cmp::Reverse(r.diff)),
+                }
+                (heading.as_deref(), rows)
+            })
+            .filter(|(_, rows)| !rows.is_empty())
+            .collect()
+    }
+
+    /// 筛选之后剩余的项数
+    pub fn count(&self, view: &View) -> usize {
+        self.visible(view).iter().map(|(_, rows)| rows.len()).sum()
+    }
+
+    /// 按视图筛选、排序并分页，没有可显示的项时返回空列表
+    pub fn render(&self, view: &View) -> Vec<String> {
+        let title = truncate(&self.title, PAGE_CHARS / 2);
+        let budget = PAGE_CHARS - title.chars().count() - 1;
+        let mut pages = vec![];
+        let (mut page, mut rows) = (String::new(), 0);
+        for (heading, section) in self.visible(view) {
+            // 小标题在每一页中该段的第一项之前显示
+            let item = |row: &Row, with_heading: bool| match heading {
+                Some(h) if with_heading => format!("{h}\n{}\n", row.text),
+                _ => format!("{}\n", row.text),
+            };
+            for (i, row) in section.into_iter().enumerate() {
+                let mut text = item(row, i == 0);
+                let len = page.chars().count() + text.chars().count();
+                if rows > 0 && (rows >= PAGE_ROWS || len > budget) {
+                    pages.push(format!("{title}\n{page}"));
+                    (page, rows) = (String::new(), 0);
+                    text = item(row, true);
+                }
+                page += &truncate(&text, budget);
+                rows += 1;
+            }
+        }
+        if rows > 0 {
+            pages.push(format!("{title}\n{page}"));
+        }
+        pages
+    }
+
+    fn content(&self, locale: Locale, view: &View) -> (String, usize) {
+        let pages = self.render(view);
+        let Some(page) = pages.get(view.page) else {
+            let empty = tr!(locale, "pages-empty");
+            let title = truncate(&self.title, PAGE_CHARS / 2);
+            return (format!("{title}\n{empty}"), 1);
+        };
+        let footer = tr!(
+            locale,
+            "pages-footer",
+            page = view.page + 1,
+            pages = pages.len(),
+            count = self.count(view),
+            problems = if view.problems_only { "yes" } else { "no" }
+        );
+        (format!("{page}\n{footer}"), pages.len())
+    }
+
+    fn buttons(&self, locale: Locale, id: u64, view: &View, pages: usize) -> CreateActionRow {
+        let button = |name: &str| {
+            CreateButton::new(format!("{id}:page-{name}")).style(ButtonStyle::Secondary)
+        };
+        let mut buttons = vec![
+            button("prev")
+                .label(tr!(locale, "pages-prev"))
+                .disabled(view.page == 0),
+            button("next")
+                .label(tr!(locale, "pages-next"))
+                .disabled(view.page + 1 >= pages),
+        ];
+        if self.sortable {
+            let problems = if view.problems_only { "yes" } else { "no" };
+            buttons.push(button("filter").label(tr!(locale, "pages-filter", problems = problems)));
+            buttons.push(button("sort").label(tr!(
+                locale,
+                "pages-sort",
+                sort = view.sort.as_str()
+            )));
+        }
+        CreateActionRow::Buttons(buttons)
+    }
+
+    /// 发送只有调用者可见的列表，内容超过一页或可以排序时附带翻页按钮
+    pub async fn send(&self, ctx: Context<'_>) -> Result<(), Error> {
+        let locale = Locale::from(ctx);
+        let mut view = View::default();
+        let (content, pages) = self.content(locale, &view);
+        let mut reply = CreateReply::default().content(content).ephemeral(true);
+        if pages <= 1 && !self.sortable {
+            ctx.send(reply).await?;
+            return Ok(());
+        }
+
+        let id = ctx.id();
+        reply = reply.components(vec![self.buttons(locale, id, &view, pages)]);
+        let handle = ctx.send(reply).await?;
+        let prefix = format!("{id}:page-");
+        loop {
+            let p = prefix.clone();
+            let Some(ci) = ComponentInteractionCollector::new(ctx)
+                .author_id(ctx.author().id)
+                .filter(move |ci| ci.data.custom_id.starts_with(&p))
+                .timeout(WAIT)
+                .await
+            else {
+                let reply = CreateReply::default().components(vec![]);
+                handle.edit(ctx, reply).await?;
+                return Ok(());
+            };
+
+            match &ci.data.custom_id[prefix.len()..] {
+                "prev" => view.page = view.page.saturating_sub(1),
+                "next" => view.page += 1,
+                "filter" => {
+                    view.problems_only = !view.problems_only;
+                    view.page = 0;
+                }
+                "sort" => {
+                    view.sort = view.sort.next();
+                    view.page = 0;
+                }
+                _ => {}
+            }
+            let pages = self.render(&view).len().max(1);
+            view.page = view.page.min(pages - 1);
+
+            let (content, pages) = self.content(locale, &view);
+            let msg = CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(vec![self.buttons(locale, id, &view, pages)]);
+            ci.create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
+                .await?;
+        }
+    }
+}
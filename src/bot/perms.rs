@@ -0,0 +1,43 @@
+use crate::{
+    RepoError, Repositories,
+    domains::{FiefId, Permissions, RoleId, UserId},
+};
+
+/// 管理员和领地所有者拥有全部权限，其他用户的实际权限为自身权限、
+/// 职位权限与所属身份组权限的并集
+///
+/// 既不是领地成员、所属身份组也没有被授予领地权限的用户不属于这个领地，
+/// 即使要求的权限为空也返回 `false`
+pub async fn has_perms(
+    repo: &Repositories,
+    id: UserId,
+    roles: &[RoleId],
+    fief_id: FiefId,
+    perms: Permissions,
+) -> anyhow::Result<bool> {
+    match repo.user().user_by_id(id).await {
+        Ok(user) if user.is_admin => return Ok(true),
+        Ok(_) | Err(RepoError::NotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+    match repo.fief().fief_by_id(fief_id).await {
+        Ok(fief) if fief.owner == Some(id) => return Ok(true),
+        Ok(_) | Err(RepoError::NotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+    let (mut effective, mut belongs) = match repo.user().effective_permissions_in(id, fief_id).await
+    {
+        Ok(p) => (p, true),
+        Err(RepoError::NotFound) => (Permissions::NONE, false),
+        Err(e) => return Err(e.into()),
+    };
+    if !roles.is_empty() {
+        for grant in repo.fief().role_grants(fief_id).await? {
+            if roles.contains(&grant.role_id) {
+                effective |= grant.permissions;
+                belongs = true;
+            }
+        }
+    }
+    Ok(belongs && effective.contains(perms))
+}
@@ -1,5 +1,10 @@
+pub mod adaptive;
 pub mod algorithms;
+pub mod automask;
 mod checker;
-pub use checker::{Checker, MAX_RETRY_TIMES};
+pub use checker::{Checker, ImageSource, MAX_RETRY_TIMES};
+mod control;
+pub use control::{CheckerHandle, CheckerStatus};
 mod events;
 pub use events::*;
+pub mod validation;
@@ -0,0 +1,35 @@
+use crate::{cfg, domains::Fief};
+
+/// 根据本次检查的结果计算自适应领地新的实际检查间隔与连续无异常次数。
+///
+/// - 发现异常时，间隔减半（不低于 `minimum_interval_min`），
+///   已经放慢的间隔先恢复为设置值再减半；
+/// - 间隔低于设置值时，连续 `adaptive_clean_checks`
+///   次无异常后间隔翻倍，直到恢复设置值；
+/// - 持续 `adaptive_quiet_hours` 小时无异常后，间隔继续翻倍（不超过
+///   `adaptive_maximum_interval_min`）。
+pub fn next_interval(fief: &Fief, diff_found: bool) -> (chrono::Duration, usize) {
+    let (min, clean_checks, quiet_hours, max) = {
+        let cfg = &cfg().check;
+        (
+            chrono::Duration::minutes(cfg.minimum_interval_min as i64),
+            cfg.adaptive_clean_checks,
+            chrono::Duration::hours(cfg.adaptive_quiet_hours as i64),
+            chrono::Duration::minutes(cfg.adaptive_maximum_interval_min as i64),
+        )
+    };
+    let (base, curr) = (fief.check_interval, fief.effective_interval);
+
+    if diff_found {
+        return ((curr.min(base) / 2).max(min), 0);
+    }
+
+    let streak = fief.clean_streak + 1;
+    if curr < base && streak >= clean_checks {
+        return ((curr * 2).min(base), 0);
+    }
+    if curr >= base && curr < max && curr * streak as i32 >= quiet_hours {
+        return ((curr * 2).min(max).max(base), 0);
+    }
+    (curr, streak)
+}
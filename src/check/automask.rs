@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use image::{GrayImage, Luma, RgbaImage};
+use rayon::prelude::*;
+
+use super::algorithms::{find_diffs, gen_visual_result};
+use crate::core::{Position, WPLACE_CHUNK_HEIGHT, WPLACE_CHUNK_WIDTH};
+
+const MASKED: Luma<u8> = Luma([0xFF]);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MaskShape {
+    /// 参考图中所有不透明的像素
+    Opaque,
+    /// 参考图中颜色属于给定集合的不透明像素
+    Colors(Vec<[u8; 3]>),
+    /// 从给定像素开始，与其颜色相同且上下左右相连的区域
+    FloodFill(Position),
+    /// 由两个对角确定的矩形（包含边界）
+    Rect(Position, Position),
+    /// 由顶点依次连接而成的多边形
+    Polygon(Vec<Position>),
+}
+
+/// 根据参考图生成遮罩图
+pub fn build_mask(ref_: &RgbaImage, shape: &MaskShape) -> Result<GrayImage> {
+    let (w, h) = ref_.dimensions();
+    let mut mask = GrayImage::new(w, h);
+    let fill = |mask: &mut GrayImage, f: &(dyn Fn(u32, u32) -> bool + Sync)| {
+        mask.par_enumerate_pixels_mut()
+            .filter(|(x, y, _)| f(*x, *y))
+            .for_each(|(_, _, px)| *px = MASKED);
+    };
+
+    match shape {
+        MaskShape::Opaque => fill(&mut mask, &|x, y| ref_.get_pixel(x, y).0[3] != 0),
+        MaskShape::Colors(colors) => fill(&mut mask, &|x, y| {
+            let [r, g, b, a] = ref_.get_pixel(x, y).0;
+            a == 0xFF && colors.contains(&[r, g, b])
+        }),
+        MaskShape::FloodFill(start) => {
+            let (x, y) = (start.x as u32, start.y as u32);
+            if x >= w || y >= h {
+                return Err(anyhow::anyhow!(
+                    "failed to build mask: start point out of range"
+                ));
+            }
+            flood_fill(ref_, &mut mask, x, y);
+        }
+        MaskShape::Rect(a, b) => {
+            let (x1, x2) = (a.x.min(b.x) as u32, a.x.max(b.x) as u32);
+            let (y1, y2) = (a.y.min(b.y) as u32, a.y.max(b.y) as u32);
+            fill(&mut mask, &|x, y| {
+                (x1..=x2).contains(&x) && (y1..=y2).contains(&y)
+            });
+        }
+        MaskShape::Polygon(vertices) => {
+            if vertices.len() < 3 {
+                return Err(anyhow::anyhow!(
+                    "failed to build mask: polygon needs at least 3 vertices"
+                ));
+            }
+            fill(&mut mask, &|x, y| in_polygon(vertices, x, y));
+        }
+    }
+    Ok(mask)
+}
+
+fn flood_fill(ref_: &RgbaImage, mask: &mut GrayImage, x: u32, y: u32) {
+    let (w, h) = ref_.dimensions();
+    let color = *ref_.get_pixel(x, y);
+    let mut queue = VecDeque::from([(x, y)]);
+    mask.put_pixel(x, y, MASKED);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx >= w || ny >= h || mask.get_pixel(nx, ny) == &MASKED {
+                continue;
+            }
+            if ref_.get_pixel(nx, ny) == &color {
+                mask.put_pixel(nx, ny, MASKED);
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+}
+
+/// 使用奇偶规则判断像素中心是否在多边形内
+fn in_polygon(vertices: &[Position], x: u32, y: u32) -> bool {
+    let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+    let mut inside = false;
+    let mut j = vertices.len() - 1;
+    for (i, vi) in vertices.iter().enumerate() {
+        let vj = &vertices[j];
+        let (xi, yi, xj, yj) = (vi.x as f64, vi.y as f64, vj.x as f64, vj.y as f64);
+        if (yi > py) != (yj > py) && px < (xj - xi) * (py - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// 生成遮罩图的预览，被遮罩覆盖的区域使用正常颜色，其余区域使用未遮罩颜色
+pub fn preview_mask(ref_: &RgbaImage, mask: &GrayImage) -> Result<RgbaImage> {
+    let expect = (WPLACE_CHUNK_WIDTH as u32, WPLACE_CHUNK_HEIGHT as u32);
+    if ref_.dimensions() != expect {
+        return Err(anyhow::anyhow!("failed to preview mask: image size error"));
+    }
+    // 参考图与自身比较不会有差异，此时可视化结果即为整张图的遮罩预览
+    let rec = find_diffs(ref_, mask, ref_)?;
+    gen_visual_result(ref_, mask, ref_, &rec)
+}
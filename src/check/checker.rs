@@ -1,26 +1,61 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
+use async_trait::async_trait;
 use tokio::sync::mpsc::Sender;
 
-use super::Event;
+use super::{CheckerHandle, Event};
 use crate::{
     Repositories,
-    check::{RetryTimes, algorithms},
+    check::{RetryTimes, adaptive, algorithms},
     core::{
-        ImagePng,
+        ImagePng, Position,
         log::{error, info, warn},
     },
-    domains::{ChunkId, FiefId},
+    domains::{ChunkId, FiefId, ImageKey, MaintenanceMode},
     net,
 };
 
 pub const MAX_RETRY_TIMES: usize = 3;
 
+/// 检查时获取区块当前图片的来源
+#[async_trait]
+pub trait ImageSource: Sync + Send {
+    async fn fetch(&self, pos: Position) -> Result<ImagePng>;
+}
+
+/// 从 wplace 下载区块的当前图片
+struct Network;
+
+#[async_trait]
+impl ImageSource for Network {
+    async fn fetch(&self, pos: Position) -> Result<ImagePng> {
+        Ok(net::fetch_current_image(pos).await?.1)
+    }
+}
+
+/// 区块的检查结果，所有区块检查完后在同一个事务中写入
+struct ChunkResult {
+    id: ChunkId,
+    result: Option<ImagePng>,
+    /// 差异所对比的当前图片，用于接受当前状态为参考图
+    current: ImagePng,
+    diff: Option<ImagePng>,
+    diff_count: usize,
+}
+
 pub struct Checker {
     event_tx: Sender<Event>,
     repo: &'static Repositories,
     retries: HashMap<FiefId, usize>,
+    handle: CheckerHandle,
+    source: Box<dyn ImageSource>,
+    /// 上一轮检查后没有被引用的图片，连续两轮都没有被引用才会删除，
+    /// 以免删掉刚上传、还没有写入区块的图片
+    unused_images: HashSet<ImageKey>,
 }
 
 impl Checker {
@@ -29,9 +64,22 @@ impl Checker {
             repo: repositories,
             event_tx: event_sender,
             retries: HashMap::new(),
+            handle: CheckerHandle::new(),
+            source: Box::new(Network),
+            unused_images: HashSet::new(),
         }
     }
 
+    /// 使用其他来源获取区块的当前图片
+    pub fn with_source(mut self, source: impl ImageSource + 'static) -> Self {
+        self.source = Box::new(source);
+        self
+    }
+
+    pub fn handle(&self) -> CheckerHandle {
+        self.handle.clone()
+    }
+
     async fn send(&self, event: Event) {
         self.event_tx
             .send_timeout(event, Duration::from_secs(10))
@@ -40,6 +88,21 @@ impl Checker {
     }
 
     pub async fn check_one(&mut self, fief_id: FiefId) -> Result<()> {
+        let result = self.check(fief_id).await;
+        let failed = result.is_err() || self.retries.contains_key(&fief_id);
+        if let Err(e) = self
+            .repo
+            .fief()
+            .set_last_check_failed(fief_id, failed)
+            .await
+        {
+            error!("failed to save the check result of fief {}: {e}", fief_id.0);
+        }
+        self.send(Event::Checked(fief_id)).await;
+        result
+    }
+
+    async fn check(&mut self, fief_id: FiefId) -> Result<()> {
         info!("running check for fief {}", fief_id.0);
 
         let Ok(chunks) = self.repo.fief().chunks(fief_id).await else {
@@ -47,66 +110,78 @@ impl Checker {
             return Err(anyhow::anyhow!(msg));
         };
 
-        let chunk_checker = async |id: ChunkId| -> Result<bool> {
+        let chunk_checker = async |id: ChunkId| -> Result<Option<ChunkResult>> {
             let pos = self.repo.chunk().position(id).await?;
 
             let ref_ = self.repo.chunk().ref_img(id).await?;
             let Some(ref_) = ref_.map(ImagePng::try_to_rgba) else {
                 warn!("reference image of chunk {}.{} is null", fief_id.0, id.0);
                 self.send(Event::ChunkRefMissing(fief_id, id)).await;
-                return Ok(true);
+                return Ok(None);
             };
 
             let mask = self.repo.chunk().mask_img(id).await?;
             let Some(mask) = mask.map(ImagePng::try_to_gray) else {
                 warn!("mask image of chunk {}.{} is null", fief_id.0, id.0);
                 self.send(Event::ChunkMaskMissing(fief_id, id)).await;
-                return Ok(true);
+                return Ok(None);
             };
 
-            let curr = match net::fetch_current_image(pos).await {
-                Ok(curr) => curr,
+            let current = match self.source.fetch(pos).await {
+                Ok(current) => current,
                 Err(e) => {
                     warn!("{e}");
                     self.send(Event::NetworkError(e.to_string())).await;
                     return Err(e);
                 }
             };
-            let curr = curr.1.try_into()?;
+            let curr = current.clone().try_into()?;
 
             let (ref_, mask) = (ref_?, mask?);
             let rec = algorithms::find_diffs(&ref_, &mask, &curr)?;
 
             let result = algorithms::gen_visual_result(&ref_, &mask, &curr, &rec)?;
-            self.repo
-                .chunk()
-                .update_result_img(id, result.try_into().ok())
-                .await?;
-
-            self.repo
-                .chunk()
-                .update_diff(id, rec.diff_img.try_into().ok(), rec.diffs.len())
-                .await?;
-
-            Ok(rec.diffs.is_empty())
+            Ok(Some(ChunkResult {
+                id,
+                result: result.try_into().ok(),
+                current,
+                diff: rec.diff_img.try_into().ok(),
+                diff_count: rec.diffs.len(),
+            }))
         };
 
+        let mut results = vec![];
         let mut failed_chunks = vec![];
         let mut errors = vec![];
         for id in chunks {
             match chunk_checker(id).await {
-                Ok(true) => (),
-                Ok(false) => failed_chunks.push(id),
+                Ok(Some(r)) => {
+                    if r.diff_count > 0 {
+                        failed_chunks.push((id, ImageKey::of(&r.current)));
+                    }
+                    results.push(r);
+                }
+                Ok(None) => (),
                 Err(e) => errors.push(e),
             }
         }
 
+        let uow = self.repo.begin().await?;
+        for r in results {
+            uow.chunk().update_result_img(r.id, r.result).await?;
+            uow.chunk()
+                .update_current_img(r.id, Some(r.current))
+                .await?;
+            uow.chunk().update_diff(r.id, r.diff, r.diff_count).await?;
+        }
+
         if !errors.is_empty() {
             let times = self.retries.entry(fief_id).or_insert(0);
             *times = MAX_RETRY_TIMES.min(*times) + 1;
             if *times > MAX_RETRY_TIMES {
-                self.repo.fief().update_last_check(fief_id, None).await?;
+                uow.fief().update_last_check(fief_id, None).await?;
             }
+            uow.commit().await?;
             let event = Event::CheckFailed(fief_id, RetryTimes(*times - 1));
             self.send(event).await;
             error!("failed to check fief {}: {:?}", fief_id.0, errors);
@@ -114,9 +189,35 @@ impl Checker {
         }
 
         self.retries.remove(&fief_id);
-        self.repo.fief().update_last_check(fief_id, None).await?;
+        uow.fief().update_last_check(fief_id, None).await?;
+
+        let fief = uow.fief().fief_by_id(fief_id).await?;
+        if fief.adaptive {
+            let (interval, streak) = adaptive::next_interval(&fief, !failed_chunks.is_empty());
+            if interval != fief.effective_interval {
+                info!(
+                    "effective interval of fief {} is now {} minutes",
+                    fief_id.0,
+                    interval.num_minutes()
+                );
+            }
+            uow.fief()
+                .update_adaptive_state(fief_id, interval, streak)
+                .await?;
+        }
+        uow.commit().await?;
+
         if !failed_chunks.is_empty() {
             info!("there are abnormal pixels in fief {}", fief_id.0);
+            let now = chrono::Utc::now();
+            let windows = self.repo.fief().maintenance_windows(fief_id).await?;
+            if windows
+                .iter()
+                .any(|w| w.rule.mode == MaintenanceMode::MuteAlerts && w.rule.contains(now))
+            {
+                info!("fief {} is under maintenance, alert muted", fief_id.0);
+                return Ok(());
+            }
             self.send(Event::DiffFound(fief_id, failed_chunks)).await;
         } else {
             info!("fief {} has no problem", fief_id.0);
@@ -132,11 +233,48 @@ impl Checker {
             return Err(anyhow::anyhow!("failed to get fiefs to check"));
         };
 
+        let start = Instant::now();
+        let queue = fiefs.iter().map(|f| f.id).collect();
+        self.handle.update_status(|s| s.queue = queue);
+
         for fief in fiefs {
+            self.handle.update_status(|s| s.checking = Some(fief.id));
             self.check_one(fief.id).await.ok();
+            self.handle
+                .update_status(|s| s.queue.retain(|id| *id != fief.id));
         }
 
+        self.handle.update_status(|s| {
+            s.checking = None;
+            s.last_pass_duration = Some(start.elapsed());
+            s.last_pass_at = Some(chrono::Utc::now());
+        });
         net::clear_cache();
+        self.collect_image_garbage().await;
         Ok(())
     }
+
+    /// 删除被替换掉、且在上一轮检查后就已经不再被引用的图片
+    async fn collect_image_garbage(&mut self) {
+        let unused = match self.repo.unreferenced_images().await {
+            Ok(unused) => unused,
+            Err(e) => {
+                error!("failed to find unused images: {e}");
+                return;
+            }
+        };
+
+        let mut removed = 0;
+        for key in unused.intersection(&self.unused_images) {
+            match self.repo.images().remove(key).await {
+                Ok(true) => removed += 1,
+                Ok(false) => {}
+                Err(e) => error!("failed to remove image {}: {e}", key.0),
+            }
+        }
+        if removed > 0 {
+            info!("removed {removed} unused image(s)");
+        }
+        self.unused_images = unused.difference(&self.unused_images).cloned().collect();
+    }
 }
@@ -0,0 +1,68 @@
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+use crate::domains::FiefId;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckerStatus {
+    /// 本轮检查中尚未完成的领地（包含正在检查的领地）
+    pub queue: Vec<FiefId>,
+    pub checking: Option<FiefId>,
+    pub last_pass_duration: Option<Duration>,
+    pub last_pass_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    paused: AtomicBool,
+    run_once: Notify,
+    status: Mutex<CheckerStatus>,
+}
+
+/// 用于在运行时控制检查器的句柄，可以被任意克隆并在线程间共享。
+#[derive(Clone, Default)]
+pub struct CheckerHandle(Arc<Shared>);
+
+impl CheckerHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 暂停定期检查，返回检查器在此之前是否处于运行状态。
+    pub fn pause(&self) -> bool {
+        !self.0.paused.swap(true, Ordering::SeqCst)
+    }
+
+    /// 恢复定期检查，返回检查器在此之前是否处于暂停状态。
+    pub fn resume(&self) -> bool {
+        self.0.paused.swap(false, Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.paused.load(Ordering::SeqCst)
+    }
+
+    /// 请求检查器立即执行一轮检查，暂停状态下同样有效。
+    pub fn run_once(&self) {
+        self.0.run_once.notify_one();
+    }
+
+    pub async fn run_once_requested(&self) {
+        self.0.run_once.notified().await;
+    }
+
+    pub fn status(&self) -> CheckerStatus {
+        self.0.status.lock().unwrap().clone()
+    }
+
+    pub(super) fn update_status(&self, f: impl FnOnce(&mut CheckerStatus)) {
+        f(&mut self.0.status.lock().unwrap());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::domains::{ChunkId, FiefId};
+use crate::domains::{ChunkId, FiefId, ImageKey};
 
 #[derive(Debug, Clone)]
 pub struct RetryTimes(pub usize);
@@ -7,8 +7,11 @@ pub struct RetryTimes(pub usize);
 pub enum Event {
     CheckFailed(FiefId, RetryTimes),
     CheckSuccess(FiefId),
-    DiffFound(FiefId, Vec<ChunkId>),
+    /// 发现差异的区块，以及检查时获取到的当前图片
+    DiffFound(FiefId, Vec<(ChunkId, ImageKey)>),
     NetworkError(String),
     ChunkRefMissing(FiefId, ChunkId),
     ChunkMaskMissing(FiefId, ChunkId),
+    /// 每次检查领地之后发送，无论结果如何，用于更新状态面板
+    Checked(FiefId),
 }
@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use image::{GrayImage, RgbaImage};
+use rayon::prelude::*;
+
+use crate::core::{ImagePng, WPLACE_CHUNK_HEIGHT, WPLACE_CHUNK_WIDTH};
+
+/// wplace.live 的调色板（不含透明色）
+pub const WPLACE_PALETTE: [[u8; 3]; 63] = [
+    // 免费颜色
+    [0x00, 0x00, 0x00],
+    [0x3c, 0x3c, 0x3c],
+    [0x78, 0x78, 0x78],
+    [0xd2, 0xd2, 0xd2],
+    [0xff, 0xff, 0xff],
+    [0x60, 0x00, 0x18],
+    [0xed, 0x1c, 0x24],
+    [0xff, 0x7f, 0x27],
+    [0xf6, 0xaa, 0x09],
+    [0xf9, 0xdd, 0x3b],
+    [0xff, 0xfa, 0xbc],
+    [0x0e, 0xb9, 0x68],
+    [0x13, 0xe6, 0x7b],
+    [0x87, 0xff, 0x5e],
+    [0x0c, 0x81, 0x6e],
+    [0x10, 0xae, 0xa6],
+    [0x13, 0xe1, 0xbe],
+    [0x28, 0x50, 0x9e],
+    [0x40, 0x93, 0xe4],
+    [0x60, 0xf7, 0xf2],
+    [0x6b, 0x50, 0xf6],
+    [0x99, 0xb1, 0xfb],
+    [0x78, 0x0c, 0x99],
+    [0xaa, 0x38, 0xb9],
+    [0xe0, 0x9f, 0xf9],
+    [0xcb, 0x00, 0x7a],
+    [0xec, 0x1f, 0x80],
+    [0xf3, 0x8d, 0xa9],
+    [0x68, 0x46, 0x34],
+    [0x95, 0x68, 0x2a],
+    [0xf8, 0xb2, 0x77],
+    // 付费颜色
+    [0xaa, 0xaa, 0xaa],
+    [0xa5, 0x0e, 0x1e],
+    [0xfa, 0x80, 0x72],
+    [0xe4, 0x5c, 0x1a],
+    [0xd6, 0xb5, 0x94],
+    [0x9c, 0x84, 0x31],
+    [0xc5, 0xad, 0x31],
+    [0xe8, 0xd4, 0x5f],
+    [0x4a, 0x6b, 0x3a],
+    [0x5a, 0x94, 0x4a],
+    [0x84, 0xc5, 0x73],
+    [0x0f, 0x79, 0x9f],
+    [0xbb, 0xfa, 0xf2],
+    [0x7d, 0xc7, 0xff],
+    [0x4d, 0x31, 0xb8],
+    [0x4a, 0x42, 0x84],
+    [0x7a, 0x71, 0xc4],
+    [0xb5, 0xae, 0xf1],
+    [0xdb, 0xa4, 0x63],
+    [0xd1, 0x80, 0x51],
+    [0xff, 0xc5, 0xa5],
+    [0x9b, 0x52, 0x49],
+    [0xd1, 0x80, 0x78],
+    [0xfa, 0xb6, 0xa4],
+    [0x7b, 0x63, 0x52],
+    [0x9c, 0x84, 0x6b],
+    [0x33, 0x39, 0x41],
+    [0x6d, 0x75, 0x8d],
+    [0xb3, 0xb9, 0xd1],
+    [0x6d, 0x64, 0x3f],
+    [0x94, 0x8c, 0x6b],
+    [0xcd, 0xc5, 0x9e],
+];
+
+/// wplace.live 返回的图片中透明像素的值
+const TRANSPARENT: [u8; 4] = [0, 0, 0, 0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Issue {
+    /// 图片尺寸与区块尺寸不一致，无法自动修复
+    WrongSize { width: u32, height: u32 },
+    /// 遮罩图中既不是纯黑也不是纯白的像素
+    NonBinaryMask { pixels: usize },
+    /// 参考图中不属于调色板的像素（包括半透明像素）
+    OffPalette { pixels: usize, colors: usize },
+}
+
+impl Issue {
+    pub fn is_fixable(&self) -> bool {
+        !matches!(self, Issue::WrongSize { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validated {
+    /// 可以保存的图片，发现问题且未能修复时为 `None`
+    pub img: Option<ImagePng>,
+    pub issues: Vec<Issue>,
+    /// 图片是否经过了自动修复
+    pub fixed: bool,
+}
+
+fn check_size(width: u32, height: u32) -> Option<Issue> {
+    let expect = (WPLACE_CHUNK_WIDTH as u32, WPLACE_CHUNK_HEIGHT as u32);
+    (expect != (width, height)).then_some(Issue::WrongSize { width, height })
+}
+
+fn is_in_palette(px: [u8; 4]) -> bool {
+    match px[3] {
+        0 => px == TRANSPARENT,
+        0xFF => WPLACE_PALETTE.contains(&[px[0], px[1], px[2]]),
+        _ => false,
+    }
+}
+
+fn nearest_in_palette(px: [u8; 4]) -> [u8; 4] {
+    if px[3] < 0x80 {
+        return TRANSPARENT;
+    }
+    let dist = |c: &[u8; 3]| {
+        (0..3)
+            .map(|i| (c[i] as i32 - px[i] as i32).pow(2))
+            .sum::<i32>()
+    };
+    let [r, g, b] = *WPLACE_PALETTE.iter().min_by_key(|c| dist(c)).unwrap();
+    [r, g, b, 0xFF]
+}
+
+/// 检查参考图的尺寸和颜色
+pub fn check_ref(img: &RgbaImage) -> Vec<Issue> {
+    let mut issues = vec![];
+    issues.extend(check_size(img.width(), img.height()));
+
+    let off_palette = img
+        .par_pixels()
+        .filter(|px| !is_in_palette(px.0))
+        .map(|px| px.0)
+        .collect::<Vec<_>>();
+    if !off_palette.is_empty() {
+        let colors = off_palette.iter().collect::<HashSet<_>>().len();
+        issues.push(Issue::OffPalette {
+            pixels: off_palette.len(),
+            colors,
+        });
+    }
+    issues
+}
+
+/// 检查遮罩图的尺寸以及是否只包含纯黑和纯白像素
+pub fn check_mask(img: &GrayImage) -> Vec<Issue> {
+    let mut issues = vec![];
+    issues.extend(check_size(img.width(), img.height()));
+
+    let pixels = img
+        .par_pixels()
+        .filter(|px| !matches!(px.0[0], 0x00 | 0xFF))
+        .count();
+    if pixels > 0 {
+        issues.push(Issue::NonBinaryMask { pixels });
+    }
+    issues
+}
+
+/// 将参考图的每个像素替换为调色板中最接近的颜色
+pub fn quantize_ref(img: &mut RgbaImage) {
+    img.par_pixels_mut()
+        .filter(|px| !is_in_palette(px.0))
+        .for_each(|px| px.0 = nearest_in_palette(px.0));
+}
+
+/// 以 50% 灰度为阈值将遮罩图二值化
+pub fn threshold_mask(img: &mut GrayImage) {
+    img.par_pixels_mut()
+        .for_each(|px| px.0[0] = if px.0[0] >= 0x80 { 0xFF } else { 0x00 });
+}
+
+fn finish(
+    img: ImagePng,
+    issues: Vec<Issue>,
+    fix: bool,
+    encode: impl FnOnce() -> Result<ImagePng>,
+) -> Result<Validated> {
+    if issues.is_empty() {
+        return Ok(Validated {
+            img: Some(img),
+            issues,
+            fixed: false,
+        });
+    }
+    if !fix || !issues.iter().all(Issue::is_fixable) {
+        return Ok(Validated {
+            img: None,
+            issues,
+            fixed: false,
+        });
+    }
+    Ok(Validated {
+        img: Some(encode()?),
+        issues,
+        fixed: true,
+    })
+}
+
+/// 解码并检查上传的参考图，`fix` 为真时尝试将颜色量化到调色板
+pub fn validate_ref(img: ImagePng, fix: bool) -> Result<Validated> {
+    let mut decoded = img.clone().try_to_rgba()?;
+    let issues = check_ref(&decoded);
+    finish(img, issues, fix, || {
+        quantize_ref(&mut decoded);
+        ImagePng::try_from_rgba(decoded)
+    })
+}
+
+/// 解码并检查上传的遮罩图，`fix` 为真时尝试将其二值化
+pub fn validate_mask(img: ImagePng, fix: bool) -> Result<Validated> {
+    let mut decoded = img.clone().try_to_gray()?;
+    let issues = check_mask(&decoded);
+    finish(img, issues, fix, || {
+        threshold_mask(&mut decoded);
+        ImagePng::try_from_gray(decoded)
+    })
+}
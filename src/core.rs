@@ -61,6 +61,10 @@ impl ImagePng {
         raw_data.pipe(Self)
     }
 
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
     fn into_reader(self) -> ImageReader<Cursor<Vec<u8>>> {
         self.0
             .pipe(Cursor::new)
@@ -17,12 +17,14 @@ pub struct Config {
     pub check: CheckConfig,
     pub notification: NotificationConfig,
     pub visualization: VisualizationConfig,
+    pub storage: StorageConfig,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct CommonConfig {
     pub database_url: String,
     pub discord_token: String,
+    pub locale: String,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -36,6 +38,9 @@ pub struct NetworkConfig {
 pub struct CheckConfig {
     pub minimum_interval_min: usize,
     pub default_interval_min: usize,
+    pub adaptive_clean_checks: usize,
+    pub adaptive_quiet_hours: usize,
+    pub adaptive_maximum_interval_min: usize,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -56,6 +61,20 @@ pub struct VisualizationConfig {
     pub vertical_margin: usize,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageBackend {
+    #[serde(alias = "sqlite")]
+    Database,
+    Filesystem,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct StorageConfig {
+    pub image_backend: ImageBackend,
+    pub image_dir: String,
+}
+
 static CONFIG_DOC: LazyLock<RwLock<DocumentMut>> = LazyLock::new(|| {
     let mut file = std::fs::File::open(CONFIG_PATH).unwrap_or_else(|e| {
         error!("failed to open configuration file: {e}");
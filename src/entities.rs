@@ -2,13 +2,34 @@ mod user;
 pub use user::User;
 
 mod fief;
-pub use fief::Fief;
+pub use fief::{Fief, FiefSummary};
 
 mod member;
 pub use member::Member;
 
+mod fief_role;
+pub use fief_role::FiefRole;
+
+mod invitation;
+pub use invitation::Invitation;
+
+mod role_grant;
+pub use role_grant::RoleGrant;
+
 mod chunk;
-pub use chunk::{ChunkWithoutImgs, Position};
+pub use chunk::{ChunkSummary, ChunkWithoutImgs, Position};
+
+mod image_version;
+pub use image_version::ChunkImageVersion;
+
+mod maintenance_window;
+pub use maintenance_window::MaintenanceWindow;
+
+mod audit_entry;
+pub use audit_entry::AuditEntry;
+
+mod dashboard;
+pub use dashboard::Dashboard;
 
 pub type CurrentDb = sqlx::Sqlite;
 pub type CurrentRow = <CurrentDb as sqlx::Database>::Row;
@@ -0,0 +1,20 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub time: chrono::DateTime<chrono::Utc>,
+    pub actor_id: Option<i64>,
+    pub fief_id: Option<i64>,
+    pub action: i64,
+    pub target: Option<String>,
+    pub before_value: Option<String>,
+    pub after_value: Option<String>,
+}
+
+mod test {
+
+    #[test]
+    fn it_can_be_compiled() {
+        let _ = <super::AuditEntry as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::AuditEntry as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+    }
+}
@@ -18,11 +18,22 @@ pub struct ChunkWithoutImgs {
     pub diff_count: i64,
 }
 
+#[derive(Debug, sqlx::FromRow)]
+pub struct ChunkSummary {
+    #[sqlx(flatten)]
+    pub chunk: ChunkWithoutImgs,
+    pub has_ref: bool,
+    pub has_mask: bool,
+}
+
 #[cfg(test)]
 mod test {
 
     #[test]
     fn it_can_be_compiled() {
         let _ = <super::ChunkWithoutImgs as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::ChunkWithoutImgs as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+        let _ = <super::ChunkSummary as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::ChunkSummary as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
     }
 }
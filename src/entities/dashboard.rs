@@ -0,0 +1,16 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct Dashboard {
+    pub id: i64,
+    pub fief_id: Option<i64>,
+    pub channel_id: i64,
+    pub message_id: i64,
+}
+
+mod test {
+
+    #[test]
+    fn it_can_be_compiled() {
+        let _ = <super::Dashboard as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::Dashboard as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+    }
+}
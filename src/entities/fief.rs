@@ -4,8 +4,23 @@ pub struct Fief {
     pub name: String,
     pub check_interval_min: i64,
     pub last_check: chrono::DateTime<chrono::Utc>,
+    pub last_check_failed: bool,
     pub skip_check_until: chrono::DateTime<chrono::Utc>,
     pub should_check_now: bool,
+    pub adaptive: bool,
+    pub effective_interval_min: i64,
+    pub clean_streak: i64,
+    pub image_retention: i64,
+    pub owner_id: Option<i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct FiefSummary {
+    #[sqlx(flatten)]
+    pub fief: Fief,
+    pub chunk_count: i64,
+    pub member_count: i64,
+    pub diff_count: i64,
 }
 
 mod test {
@@ -13,5 +28,8 @@ mod test {
     #[test]
     fn it_can_be_compiled() {
         let _ = <super::Fief as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::Fief as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+        let _ = <super::FiefSummary as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::FiefSummary as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
     }
 }
@@ -0,0 +1,16 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct FiefRole {
+    pub id: i64,
+    pub fief_id: i64,
+    pub name: String,
+    pub permissions: i64,
+}
+
+mod test {
+
+    #[test]
+    fn it_can_be_compiled() {
+        let _ = <super::FiefRole as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::FiefRole as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct ChunkImageVersion {
+    pub id: i64,
+    pub chunk_id: i64,
+    pub kind: i64,
+    pub is_empty: bool,
+    pub author_id: Option<i64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+mod test {
+
+    #[test]
+    fn it_can_be_compiled() {
+        let _ = <super::ChunkImageVersion as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::ChunkImageVersion as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+    }
+}
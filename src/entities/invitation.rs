@@ -0,0 +1,20 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct Invitation {
+    pub id: i64,
+    pub fief_id: i64,
+    pub user_id: i64,
+    pub inviter_id: Option<i64>,
+    pub permissions: Option<i64>,
+    pub fief_role_id: Option<i64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+mod test {
+
+    #[test]
+    fn it_can_be_compiled() {
+        let _ = <super::Invitation as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::Invitation as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct MaintenanceWindow {
+    pub id: i64,
+    pub fief_id: i64,
+    pub weekday: Option<i64>,
+    pub start_min: i64,
+    pub duration_min: i64,
+    pub mode: i64,
+}
+
+mod test {
+
+    #[test]
+    fn it_can_be_compiled() {
+        let _ = <super::MaintenanceWindow as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::MaintenanceWindow as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+    }
+}
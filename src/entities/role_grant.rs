@@ -0,0 +1,15 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct RoleGrant {
+    pub role_id: i64,
+    pub fief_id: i64,
+    pub permissions: i64,
+}
+
+mod test {
+
+    #[test]
+    fn it_can_be_compiled() {
+        let _ = <super::RoleGrant as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::RoleGrant as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
+    }
+}
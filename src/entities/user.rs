@@ -11,5 +11,6 @@ mod test {
     #[test]
     fn it_can_be_compiled() {
         let _ = <super::User as sqlx::FromRow<super::super::CurrentRow>>::from_row;
+        let _ = <super::User as sqlx::FromRow<sqlx::postgres::PgRow>>::from_row;
     }
 }
@@ -1,16 +1,18 @@
 pub mod entities;
 
 pub mod repos;
-pub use repos::Repositories;
+pub use repos::{RepoError, Repositories};
 
 pub mod domains {
     pub use crate::repos::domains::*;
 }
 
 pub mod app;
+pub mod archive;
 pub mod bot;
 pub mod check;
 pub mod core;
+pub mod templates;
 pub use core::{
     config::{cfg, init_cfg, save_cfg_with},
     net,
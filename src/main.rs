@@ -1,7 +1,13 @@
+use std::path::Path;
+
 use wmonitor::{
-    Repositories, app, cfg,
-    core::{get_or_env, log::*},
+    Repositories, app,
+    archive::{self, OnConflict},
+    cfg,
+    core::{config::ImageBackend, get_or_env, log::*},
     init_cfg,
+    repos::{ImageStorage, image_store},
+    save_cfg_with, templates,
 };
 
 #[tokio::main]
@@ -18,14 +24,131 @@ async fn main() -> anyhow::Result<()> {
     init_cfg();
     info!("loaded configurations:\n{:#?}", cfg());
 
-    let discord_token = get_or_env(&cfg().common.discord_token, "", "DISCORD_TOKEN");
     let database_url = get_or_env(&cfg().common.database_url, "", "DATABASE_URL");
+    let repo = Repositories::connect_with(&database_url, image_storage(None)).await?;
+    let expired = repo.user().remove_expired_invitations().await?;
+    if expired > 0 {
+        info!("removed {expired} expired invitation(s)");
+    }
+
+    let args = std::env::args().collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [_, "import", fief_name, path] => return import_templates(&repo, fief_name, path).await,
+        [_, "export", fief_name, path] => return export_fief(&repo, fief_name, path).await,
+        [_, "restore", path, rest @ ..] => return restore_fief(&repo, path, rest).await,
+        [_, "migrate-images", target] => return migrate_images(&repo, &database_url, target).await,
+        _ => {}
+    }
+
+    let discord_token = get_or_env(&cfg().common.discord_token, "", "DISCORD_TOKEN");
     let wmonitor = app::WMonitor::builder()
         .discord_token(discord_token)
-        .repo(Repositories::from_sqlx(&database_url).await?)
+        .repo(repo)
         .build();
 
     wmonitor.run().await?;
     info!("WMonitor has been closed successfully");
     Ok(())
 }
+
+/// `wmonitor import <领地名> <模板文件>`：导入模板，领地不存在时会自动创建
+async fn import_templates(repo: &Repositories, fief_name: &str, path: &str) -> anyhow::Result<()> {
+    let data = std::fs::read(path)?;
+    let file_name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path);
+    let templates = templates::parse(file_name, &data)?;
+
+    let fief_id = match repo.fief().id(fief_name).await {
+        Ok(id) => id,
+        Err(_) => {
+            info!("creating fief `{fief_name}`");
+            repo.fief()
+                .create(fief_name, None)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("failed to create fief `{fief_name}`"))?
+        }
+    };
+
+    for i in templates::import(repo, fief_id, &templates, None).await? {
+        let action = if i.created { "created" } else { "merged into" };
+        info!(
+            "{action} chunk `{}` at ({}, {}) with {} pixels",
+            i.name, i.tile.x, i.tile.y, i.pixels
+        );
+    }
+    info!(
+        "imported {} template(s) into fief `{fief_name}`",
+        templates.len()
+    );
+    Ok(())
+}
+
+/// `wmonitor export <领地名> <归档文件>`
+async fn export_fief(repo: &Repositories, fief_name: &str, path: &str) -> anyhow::Result<()> {
+    let id = repo.fief().id(fief_name).await?;
+    std::fs::write(path, archive::export(repo, id).await?)?;
+    info!("exported fief `{fief_name}` to `{path}`");
+    Ok(())
+}
+
+/// `wmonitor restore <归档文件> [领地名] [--rename | --replace]`
+async fn restore_fief(repo: &Repositories, path: &str, rest: &[&str]) -> anyhow::Result<()> {
+    let on_conflict = match rest.last() {
+        Some(&"--rename") => OnConflict::Rename,
+        Some(&"--replace") => OnConflict::Replace,
+        _ => OnConflict::Abort,
+    };
+    let name = rest.first().filter(|n| !n.starts_with("--")).copied();
+
+    let data = std::fs::read(path)?;
+    match archive::restore(repo, &data, name, on_conflict, None).await? {
+        Some(r) => info!(
+            "restored fief `{}` with {} chunk(s) and {} member(s)",
+            r.name, r.chunks, r.members
+        ),
+        None => warn!("fief already exists, use `--rename` or `--replace` to restore anyway"),
+    }
+    Ok(())
+}
+
+fn image_storage(backend: Option<ImageBackend>) -> ImageStorage {
+    let cfg = &cfg().storage;
+    match backend.unwrap_or(cfg.image_backend) {
+        ImageBackend::Database => ImageStorage::Database,
+        ImageBackend::Filesystem => ImageStorage::Filesystem(cfg.image_dir.clone().into()),
+    }
+}
+
+/// `wmonitor migrate-images <database|filesystem>`：移动图片并修改配置
+async fn migrate_images(
+    repo: &Repositories,
+    database_url: &str,
+    target: &str,
+) -> anyhow::Result<()> {
+    let backend = match target {
+        "database" => ImageBackend::Database,
+        "filesystem" => ImageBackend::Filesystem,
+        _ => return Err(anyhow::anyhow!("unknown image backend `{target}`")),
+    };
+    if backend == cfg().storage.image_backend {
+        info!("images are already stored in `{target}`");
+        return Ok(());
+    }
+
+    let to = Repositories::connect_with(database_url, image_storage(Some(backend))).await?;
+    let count = image_store::copy_images(repo.images(), to.images(), false).await?;
+    save_cfg_with(|doc| doc["storage"]["image_backend"] = toml_edit::value(target));
+    info!("copied {count} image(s) into `{target}`");
+
+    // 配置已经指向新的位置，此时再删除原来的图片，中途失败也不会丢失图片
+    for key in repo.images().keys().await? {
+        if to.images().contains(&key).await? {
+            repo.images().remove(&key).await?;
+        }
+    }
+    info!("removed the copied image(s) from the old storage");
+    Ok(())
+}
@@ -1,42 +1,288 @@
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashSet, path::PathBuf, str::FromStr, sync::Arc};
 
-use anyhow::Result;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use sqlx::{
+    Postgres, Sqlite,
+    postgres::PgPool,
+    sqlite::{SqliteConnectOptions, SqlitePool},
+};
+
+use crate::core::log::info;
+pub mod conn;
+pub mod error;
+pub mod fs_image_store;
+pub mod memory_repos;
 pub mod sqlx_repos;
 
+mod audit;
 mod chunk;
+mod dashboard;
 mod fief;
+pub mod image_store;
+mod unit_of_work;
 mod user;
+pub use error::RepoError;
+use error::Result;
+pub use unit_of_work::UnitOfWork;
 
 pub mod domains {
-    pub use super::{chunk::domains::*, fief::domains::*, user::domains::*};
+    pub use super::{
+        audit::domains::*, chunk::domains::*, dashboard::domains::*, fief::domains::*,
+        image_store::domains::*, user::domains::*,
+    };
 }
 
 pub mod traits {
-    pub use super::{chunk::ChunkRepo, fief::FiefRepo, user::UserRepo};
+    pub use super::{
+        audit::AuditRepo, chunk::ChunkRepo, dashboard::DashboardRepo, fief::FiefRepo,
+        image_store::ImageStore, user::UserRepo,
+    };
+}
+
+/// 图片的存储位置
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ImageStorage {
+    /// 保存在数据库的 `ImageBlobs` 表中
+    Database,
+    /// 保存在指定目录下
+    Filesystem(PathBuf),
+}
+
+/// 仓库使用的数据库连接，`Repositories::begin` 据此开始事务
+#[derive(Clone)]
+enum Backend {
+    Sqlite(conn::Conn<Sqlite>),
+    Postgres(conn::Conn<Postgres>),
+    Memory(Arc<std::sync::Mutex<memory_repos::MemoryDb>>),
+    /// 工作单元中的内存副本
+    MemoryTx(Arc<std::sync::Mutex<memory_repos::MemoryDb>>),
 }
 
 pub struct Repositories {
     user: Box<dyn traits::UserRepo>,
     chunk: Box<dyn traits::ChunkRepo>,
     fief: Box<dyn traits::FiefRepo>,
+    audit: Box<dyn traits::AuditRepo>,
+    dashboard: Box<dyn traits::DashboardRepo>,
+    images: Arc<dyn traits::ImageStore>,
+    backend: Backend,
+    /// 图片是否保存在数据库中（此时图片的写入也在事务中）
+    images_in_db: bool,
 }
 
 impl Repositories {
-    pub async fn from_sqlx(url: &str) -> Result<Self> {
+    /// 根据 `url` 的协议选择数据库，`postgres://` 或 `postgresql://` 使用
+    /// PostgreSQL，`memory:` 只保存在内存中（用于试运行），其余使用 SQLite
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        Self::connect_with(url, ImageStorage::Database).await
+    }
+
+    pub async fn connect_with(url: &str, storage: ImageStorage) -> anyhow::Result<Self> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            Self::from_postgres_with(url, storage).await
+        } else if url.starts_with("memory:") {
+            Ok(Self::in_memory_with(storage))
+        } else {
+            Self::from_sqlx_with(url, storage).await
+        }
+    }
+
+    pub async fn from_sqlx(url: &str) -> anyhow::Result<Self> {
+        Self::from_sqlx_with(url, ImageStorage::Database).await
+    }
+
+    pub async fn from_sqlx_with(url: &str, storage: ImageStorage) -> anyhow::Result<Self> {
         let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
         let pool = Arc::new(SqlitePool::connect_with(options).await?);
 
         sqlx::migrate!("db/migrations").run(&*pool).await?;
 
-        use sqlx_repos::*;
-        Ok(Self {
-            user: Box::new(SqlxUserRepo::new(Arc::clone(&pool))),
-            fief: Box::new(SqlxFiefRepo::new(Arc::clone(&pool))),
-            chunk: Box::new(SqlxChunkRepo::new(Arc::clone(&pool))),
+        let backend = Backend::Sqlite(Arc::clone(&pool).into());
+        let repo = Self::build(backend, storage);
+        let chunk = sqlx_repos::SqlxChunkRepo::new(Arc::clone(&pool), Arc::clone(&repo.images));
+        let migrated = chunk.migrate_legacy_blobs().await?;
+        if migrated > 0 {
+            info!("moved {migrated} legacy image(s) into image storage");
+        }
+        Ok(repo)
+    }
+
+    pub async fn from_postgres(url: &str) -> anyhow::Result<Self> {
+        Self::from_postgres_with(url, ImageStorage::Database).await
+    }
+
+    pub async fn from_postgres_with(url: &str, storage: ImageStorage) -> anyhow::Result<Self> {
+        let pool = Arc::new(PgPool::connect(url).await?);
+
+        sqlx::migrate!("db/migrations_pg").run(&*pool).await?;
+
+        Ok(Self::build(Backend::Postgres(pool.into()), storage))
+    }
+
+    pub fn in_memory() -> Self {
+        Self::in_memory_with(ImageStorage::Database)
+    }
+
+    /// 所有数据只保存在内存中，`ImageStorage::Database` 时图片也保存在内存中
+    pub fn in_memory_with(storage: ImageStorage) -> Self {
+        Self::build(Backend::Memory(memory_repos::MemoryDb::shared()), storage)
+    }
+
+    fn build(backend: Backend, storage: ImageStorage) -> Self {
+        let images_in_db = storage == ImageStorage::Database;
+        let images: Arc<dyn traits::ImageStore> = match storage {
+            ImageStorage::Database => Self::db_image_store(&backend),
+            ImageStorage::Filesystem(root) => Arc::new(fs_image_store::FsImageStore::new(root)),
+        };
+        Self::with_images(backend, images, images_in_db)
+    }
+
+    fn db_image_store(backend: &Backend) -> Arc<dyn traits::ImageStore> {
+        match backend {
+            Backend::Sqlite(conn) => Arc::new(sqlx_repos::SqlxImageStore::new(conn.clone())),
+            Backend::Postgres(conn) => Arc::new(sqlx_repos::SqlxImageStore::new(conn.clone())),
+            Backend::Memory(_) | Backend::MemoryTx(_) => {
+                Arc::new(memory_repos::InMemoryImageStore::new())
+            }
+        }
+    }
+
+    fn with_images(
+        backend: Backend,
+        images: Arc<dyn traits::ImageStore>,
+        images_in_db: bool,
+    ) -> Self {
+        let (user, fief, chunk): (
+            Box<dyn traits::UserRepo>,
+            Box<dyn traits::FiefRepo>,
+            Box<dyn traits::ChunkRepo>,
+        ) = match &backend {
+            Backend::Sqlite(conn) => {
+                use sqlx_repos::*;
+                (
+                    Box::new(SqlxUserRepo::new(conn.clone())),
+                    Box::new(SqlxFiefRepo::new(conn.clone())),
+                    Box::new(SqlxChunkRepo::new(conn.clone(), Arc::clone(&images))),
+                )
+            }
+            Backend::Postgres(conn) => {
+                use sqlx_repos::*;
+                (
+                    Box::new(SqlxUserRepo::new(conn.clone())),
+                    Box::new(SqlxFiefRepo::new(conn.clone())),
+                    Box::new(SqlxChunkRepo::new(conn.clone(), Arc::clone(&images))),
+                )
+            }
+            Backend::Memory(db) | Backend::MemoryTx(db) => {
+                use memory_repos::*;
+                (
+                    Box::new(InMemoryUserRepo::new(Arc::clone(db))),
+                    Box::new(InMemoryFiefRepo::new(Arc::clone(db))),
+                    Box::new(InMemoryChunkRepo::new(Arc::clone(db), Arc::clone(&images))),
+                )
+            }
+        };
+
+        let audit: Box<dyn traits::AuditRepo> = match &backend {
+            Backend::Sqlite(conn) => Box::new(sqlx_repos::SqlxAuditRepo::new(conn.clone())),
+            Backend::Postgres(conn) => Box::new(sqlx_repos::SqlxAuditRepo::new(conn.clone())),
+            Backend::Memory(db) | Backend::MemoryTx(db) => {
+                Box::new(memory_repos::InMemoryAuditRepo::new(Arc::clone(db)))
+            }
+        };
+
+        let dashboard: Box<dyn traits::DashboardRepo> = match &backend {
+            Backend::Sqlite(conn) => Box::new(sqlx_repos::SqlxDashboardRepo::new(conn.clone())),
+            Backend::Postgres(conn) => Box::new(sqlx_repos::SqlxDashboardRepo::new(conn.clone())),
+            Backend::Memory(db) | Backend::MemoryTx(db) => {
+                Box::new(memory_repos::InMemoryDashboardRepo::new(Arc::clone(db)))
+            }
+        };
+
+        Self {
+            user,
+            chunk,
+            fief,
+            audit,
+            dashboard,
+            images,
+            backend,
+            images_in_db,
+        }
+    }
+
+    /// 开始一个工作单元，其中的仓库操作在同一个事务中执行
+    pub async fn begin(&self) -> Result<UnitOfWork> {
+        use conn::Conn;
+        use unit_of_work::{Finish, MemoryTx};
+
+        let (backend, finish): (Backend, Box<dyn Finish>) = match &self.backend {
+            Backend::Sqlite(Conn::Pool(pool)) => {
+                let tx = Arc::new(tokio::sync::Mutex::new(Some(pool.begin().await?)));
+                (Backend::Sqlite(Conn::Tx(Arc::clone(&tx))), Box::new(tx))
+            }
+            Backend::Postgres(Conn::Pool(pool)) => {
+                let tx = Arc::new(tokio::sync::Mutex::new(Some(pool.begin().await?)));
+                (Backend::Postgres(Conn::Tx(Arc::clone(&tx))), Box::new(tx))
+            }
+            Backend::Memory(db) => {
+                let base = memory_repos::lock(db).clone();
+                let working = Arc::new(std::sync::Mutex::new(base.clone()));
+                let tx = MemoryTx {
+                    target: Arc::clone(db),
+                    base,
+                    working: Arc::clone(&working),
+                };
+                (Backend::MemoryTx(working), Box::new(tx))
+            }
+            _ => {
+                return Err(RepoError::Invalid(
+                    "nested units of work are not supported".into(),
+                ));
+            }
+        };
+
+        // 内存中的图片存储没有事务，继续使用原来的
+        let images = match &backend {
+            Backend::Sqlite(_) | Backend::Postgres(_) if self.images_in_db => {
+                Self::db_image_store(&backend)
+            }
+            _ => Arc::clone(&self.images),
+        };
+        Ok(UnitOfWork {
+            repo: Self::with_images(backend, images, self.images_in_db),
+            finish,
         })
     }
 
+    /// 将检查时获取到的图片设为区块的参考图，图片已经被清理时返回 `false`
+    pub async fn accept_checked_img(
+        &self,
+        id: domains::ChunkId,
+        key: &domains::ImageKey,
+        author: domains::UserId,
+    ) -> Result<bool> {
+        let Some(img) = self.images.get(key).await? else {
+            return Ok(false);
+        };
+        self.chunk
+            .update_ref_img(id, Some(img), Some(author))
+            .await?;
+        Ok(true)
+    }
+
+    /// 图片存储中不再被任何区块或历史版本引用的图片
+    pub async fn unreferenced_images(&self) -> Result<HashSet<domains::ImageKey>> {
+        let in_use = self
+            .chunk
+            .referenced_image_keys()
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let keys = self.images.keys().await?.into_iter();
+        Ok(keys.filter(|key| !in_use.contains(key)).collect())
+    }
+
     pub fn user(&self) -> &dyn traits::UserRepo {
         &*self.user
     }
@@ -48,4 +294,16 @@ impl Repositories {
     pub fn chunk(&self) -> &dyn traits::ChunkRepo {
         &*self.chunk
     }
+
+    pub fn audit(&self) -> &dyn traits::AuditRepo {
+        &*self.audit
+    }
+
+    pub fn dashboard(&self) -> &dyn traits::DashboardRepo {
+        &*self.dashboard
+    }
+
+    pub fn images(&self) -> &dyn traits::ImageStore {
+        &*self.images
+    }
 }
@@ -0,0 +1,226 @@
+use async_trait::async_trait;
+
+use crate::{domains::FiefId, repos::error::Result};
+
+pub(super) mod domains {
+    use serde::{Deserialize, Serialize};
+
+    use crate::domains::{FiefId, UserId};
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct AuditId(pub i64);
+
+    impl From<i64> for AuditId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub enum AuditAction {
+        FiefCreate,
+        FiefDelete,
+        FiefRename,
+        FiefTransfer,
+        /// 检查间隔、自适应、历史版本数量、维护时间段等设置
+        FiefSettings,
+        /// 启用或禁用领地的定期检查
+        FiefCheck,
+        ChunkAdd,
+        ChunkDelete,
+        /// 区块的名字或坐标
+        ChunkEdit,
+        /// 参考图或遮罩图
+        ChunkImage,
+        MemberJoin,
+        MemberLeave,
+        /// 成员、身份组或职位的权限
+        PermissionChange,
+        AdminChange,
+        /// 暂停或恢复检查器
+        CheckerPause,
+    }
+
+    impl AuditAction {
+        pub const ALL: [Self; 15] = [
+            Self::FiefCreate,
+            Self::FiefDelete,
+            Self::FiefRename,
+            Self::FiefTransfer,
+            Self::FiefSettings,
+            Self::FiefCheck,
+            Self::ChunkAdd,
+            Self::ChunkDelete,
+            Self::ChunkEdit,
+            Self::ChunkImage,
+            Self::MemberJoin,
+            Self::MemberLeave,
+            Self::PermissionChange,
+            Self::AdminChange,
+            Self::CheckerPause,
+        ];
+
+        /// 保存在数据库中的编号，只能在末尾添加新的操作
+        pub fn code(self) -> i64 {
+            Self::ALL
+                .iter()
+                .position(|a| *a == self)
+                .unwrap_or_default() as i64
+        }
+
+        pub fn from_code(code: i64) -> Option<Self> {
+            Self::ALL.get(usize::try_from(code).ok()?).copied()
+        }
+    }
+
+    /// 操作记录中的值，用户输入的名字等原样保存，其余保存为 `locales/` 中
+    /// 文本的 id 与参数，显示时才按照查看者的语言翻译
+    #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+    #[serde(untagged)]
+    pub enum AuditValue {
+        Text(String),
+        Message {
+            id: String,
+            args: Vec<(String, AuditValue)>,
+        },
+    }
+
+    impl AuditValue {
+        /// 保存时文本 id 的前缀，用户输入中不会出现控制字符
+        const MESSAGE_PREFIX: char = '\u{1}';
+
+        pub fn msg(id: &str) -> Self {
+            Self::Message {
+                id: id.to_owned(),
+                args: vec![],
+            }
+        }
+
+        /// 添加文本的参数，原样显示的值没有参数
+        pub fn arg(self, name: &str, value: impl Into<AuditValue>) -> Self {
+            match self {
+                Self::Message { id, mut args } => {
+                    args.push((name.to_owned(), value.into()));
+                    Self::Message { id, args }
+                }
+                text => text,
+            }
+        }
+
+        /// 保存到数据库中的形式，原样显示的值不做修改
+        pub fn encode(&self) -> String {
+            match self {
+                Self::Text(s) => s.clone(),
+                message => {
+                    let json = serde_json::to_string(message).unwrap_or_default();
+                    format!("{}{json}", Self::MESSAGE_PREFIX)
+                }
+            }
+        }
+
+        pub fn decode(s: String) -> Self {
+            s.strip_prefix(Self::MESSAGE_PREFIX)
+                .and_then(|json| serde_json::from_str(json).ok())
+                .unwrap_or(Self::Text(s))
+        }
+    }
+
+    impl From<String> for AuditValue {
+        fn from(value: String) -> Self {
+            Self::Text(value)
+        }
+    }
+
+    impl From<&String> for AuditValue {
+        fn from(value: &String) -> Self {
+            Self::Text(value.clone())
+        }
+    }
+
+    impl From<&str> for AuditValue {
+        fn from(value: &str) -> Self {
+            Self::Text(value.to_owned())
+        }
+    }
+
+    /// 一次改变状态的操作
+    #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+    pub struct AuditRecord {
+        pub actor: Option<UserId>,
+        /// 领地被删除后记录仍然保留
+        pub fief_id: Option<FiefId>,
+        pub action: AuditAction,
+        /// 被操作的对象，例如区块名或用户
+        pub target: Option<AuditValue>,
+        pub before: Option<AuditValue>,
+        pub after: Option<AuditValue>,
+    }
+
+    impl AuditRecord {
+        pub fn new(action: AuditAction) -> Self {
+            Self {
+                actor: None,
+                fief_id: None,
+                action,
+                target: None,
+                before: None,
+                after: None,
+            }
+        }
+
+        pub fn fief(self, fief_id: FiefId) -> Self {
+            Self {
+                fief_id: Some(fief_id),
+                ..self
+            }
+        }
+
+        pub fn target(self, target: impl Into<AuditValue>) -> Self {
+            Self {
+                target: Some(target.into()),
+                ..self
+            }
+        }
+
+        pub fn before(self, before: impl Into<AuditValue>) -> Self {
+            Self {
+                before: Some(before.into()),
+                ..self
+            }
+        }
+
+        pub fn after(self, after: impl Into<AuditValue>) -> Self {
+            Self {
+                after: Some(after.into()),
+                ..self
+            }
+        }
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+    pub struct AuditEntry {
+        pub id: AuditId,
+        pub time: chrono::DateTime<chrono::Utc>,
+        pub record: AuditRecord,
+    }
+}
+use domains::*;
+
+#[async_trait]
+pub trait AuditRepo: Sync + Send {
+    // [C] Create
+    async fn record(&self, record: AuditRecord) -> Result<AuditId>;
+
+    // [R] Read
+    /// 领地的操作记录，从新到旧排列
+    async fn by_fief(
+        &self,
+        fief_id: FiefId,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>>;
+    async fn count_by_fief(&self, fief_id: FiefId) -> Result<usize>;
+    /// 所有操作记录，从新到旧排列
+    async fn all(&self, offset: usize, limit: usize) -> Result<Vec<AuditEntry>>;
+    async fn count(&self) -> Result<usize>;
+}
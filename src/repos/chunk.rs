@@ -1,15 +1,18 @@
-use anyhow::Result;
 use async_trait::async_trait;
 
 use crate::{
     core::{ImagePng, Position},
-    domains::FiefId,
+    domains::{FiefId, ImageKey, UserId},
+    repos::error::Result,
 };
 
 pub(super) mod domains {
     use serde::{Deserialize, Serialize};
 
-    use crate::{core::Position, domains::FiefId};
+    use crate::{
+        core::Position,
+        domains::{FiefId, UserId},
+    };
 
     #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
     pub struct ChunkId(pub i64);
@@ -27,6 +30,42 @@ pub(super) mod domains {
         pub fief_id: FiefId,
         pub position: Position,
     }
+
+    /// 区块及其不包含图片的附加信息
+    #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+    pub struct ChunkSummary {
+        pub chunk: Chunk,
+        pub diff_count: usize,
+        pub has_ref: bool,
+        pub has_mask: bool,
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct ImageVersionId(pub i64);
+
+    impl From<i64> for ImageVersionId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub enum ImageKind {
+        Reference,
+        Mask,
+    }
+
+    /// 参考图或遮罩图的一次修改记录（不包含图片本身）
+    #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+    pub struct ImageVersion {
+        pub id: ImageVersionId,
+        pub chunk_id: ChunkId,
+        pub kind: ImageKind,
+        /// 该版本是否清除了图片
+        pub is_empty: bool,
+        pub author: Option<UserId>,
+        pub created_at: chrono::DateTime<chrono::Utc>,
+    }
 }
 use domains::*;
 
@@ -39,6 +78,10 @@ pub trait ChunkRepo: Sync + Send {
     // - self or fields
     async fn chunk_by_id(&self, id: ChunkId) -> Result<Chunk>;
     async fn chunk_by_name(&self, fief_id: FiefId, name: &str) -> Result<Chunk>;
+    /// 领地内的所有区块，按 id 排列
+    async fn summaries_by_fief(&self, fief_id: FiefId) -> Result<Vec<ChunkSummary>>;
+    /// 领地内所有区块的名字，按名字排列
+    async fn names_by_fief(&self, fief_id: FiefId) -> Result<Vec<String>>;
     async fn fief_id(&self, id: ChunkId) -> Result<FiefId>;
     async fn name(&self, id: ChunkId) -> Result<String>;
     async fn id(&self, fief_id: FiefId, name: &str) -> Result<ChunkId>;
@@ -47,18 +90,43 @@ pub trait ChunkRepo: Sync + Send {
     async fn mask_img(&self, id: ChunkId) -> Result<Option<ImagePng>>;
     async fn diff_img(&self, id: ChunkId) -> Result<Option<ImagePng>>;
     async fn result_img(&self, id: ChunkId) -> Result<Option<ImagePng>>;
+    /// 最近一次检查时从 wplace.live 获取的图片，即差异所对比的状态
+    async fn current_img(&self, id: ChunkId) -> Result<Option<ImagePng>>;
     async fn diff_count(&self, id: ChunkId) -> Result<usize>;
+    /// 按时间从新到旧排列
+    async fn image_versions(&self, id: ChunkId, kind: ImageKind) -> Result<Vec<ImageVersion>>;
+    async fn version_img(&self, version_id: ImageVersionId) -> Result<Option<ImagePng>>;
     // - related
-    // *PASS*
+    /// 所有区块及其历史版本引用的图片
+    async fn referenced_image_keys(&self) -> Result<Vec<ImageKey>>;
 
     // [U] Update
     // - self or fields
-    async fn update_ref_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()>;
-    async fn update_mask_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()>;
+    async fn update_ref_img(
+        &self,
+        id: ChunkId,
+        img: Option<ImagePng>,
+        author: Option<UserId>,
+    ) -> Result<()>;
+    async fn update_mask_img(
+        &self,
+        id: ChunkId,
+        img: Option<ImagePng>,
+        author: Option<UserId>,
+    ) -> Result<()>;
     async fn update_result_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()>;
+    async fn update_current_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()>;
     async fn update_diff(&self, id: ChunkId, img: Option<ImagePng>, count: usize) -> Result<()>;
     async fn set_position(&self, id: ChunkId, pos: Position) -> Result<()>;
     async fn rename(&self, id: ChunkId, name: &str) -> Result<()>;
+    /// 将图片恢复为指定的历史版本（同时记录为一个新版本），
+    /// 版本不属于该区块时返回 `false`
+    async fn rollback_img(
+        &self,
+        id: ChunkId,
+        version_id: ImageVersionId,
+        author: Option<UserId>,
+    ) -> Result<bool>;
     // - related
     // *PASS*
 
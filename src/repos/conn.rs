@@ -0,0 +1,79 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use sqlx::{Database, Pool, Transaction, pool::PoolConnection};
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::repos::error::{RepoError, Result};
+
+pub(super) type SharedTx<DB> = Arc<Mutex<Option<Transaction<'static, DB>>>>;
+
+/// 仓库执行语句时使用的连接：连接池，或工作单元中的事务
+pub enum Conn<DB: Database> {
+    Pool(Arc<Pool<DB>>),
+    Tx(SharedTx<DB>),
+}
+
+impl<DB: Database> Clone for Conn<DB> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Pool(pool) => Self::Pool(Arc::clone(pool)),
+            Self::Tx(tx) => Self::Tx(Arc::clone(tx)),
+        }
+    }
+}
+
+impl<DB: Database> From<Arc<Pool<DB>>> for Conn<DB> {
+    fn from(pool: Arc<Pool<DB>>) -> Self {
+        Self::Pool(pool)
+    }
+}
+
+impl<DB: Database> Conn<DB> {
+    /// 事务中的语句依次执行，返回的连接在语句结束前不要跨越其他仓库调用
+    pub async fn acquire(&self) -> Result<ConnGuard<'_, DB>> {
+        match self {
+            Self::Pool(pool) => Ok(ConnGuard::Pool(pool.acquire().await?)),
+            Self::Tx(tx) => {
+                let guard = tx.lock().await;
+                if guard.is_none() {
+                    return Err(RepoError::Invalid(
+                        "transaction has already been finished".into(),
+                    ));
+                }
+                Ok(ConnGuard::Tx(guard))
+            }
+        }
+    }
+}
+
+pub enum ConnGuard<'a, DB: Database> {
+    Pool(PoolConnection<DB>),
+    Tx(MutexGuard<'a, Option<Transaction<'static, DB>>>),
+}
+
+impl<DB: Database> Deref for ConnGuard<'_, DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Tx(tx) => tx
+                .as_ref()
+                .expect("transaction should be checked in acquire"),
+        }
+    }
+}
+
+impl<DB: Database> DerefMut for ConnGuard<'_, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Tx(tx) => tx
+                .as_mut()
+                .expect("transaction should be checked in acquire"),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+
+use crate::{domains::FiefId, repos::error::Result};
+
+pub(super) mod domains {
+    use serde::{Deserialize, Serialize};
+
+    use crate::domains::FiefId;
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct DashboardId(pub i64);
+
+    impl From<i64> for DashboardId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
+    /// 每轮检查后原地编辑的状态面板消息
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct Dashboard {
+        pub id: DashboardId,
+        /// 为 `None` 时显示所有领地
+        pub fief_id: Option<FiefId>,
+        /// Discord 频道与消息的 id
+        pub channel_id: i64,
+        pub message_id: i64,
+    }
+}
+use domains::*;
+
+#[async_trait]
+pub trait DashboardRepo: Sync + Send {
+    // [C] Create
+    async fn create(
+        &self,
+        fief_id: Option<FiefId>,
+        channel_id: i64,
+        message_id: i64,
+    ) -> Result<DashboardId>;
+
+    // [R] Read
+    /// 按 id 排列
+    async fn all(&self) -> Result<Vec<Dashboard>>;
+    /// 显示该领地的状态面板，包括显示所有领地的
+    async fn showing(&self, fief_id: FiefId) -> Result<Vec<Dashboard>>;
+
+    // [D] Delete
+    async fn remove(&self, id: DashboardId) -> Result<bool>;
+}
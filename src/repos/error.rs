@@ -0,0 +1,68 @@
+use std::fmt;
+
+pub type Result<T, E = RepoError> = std::result::Result<T, E>;
+
+/// 仓库操作的错误，指令据此给出准确的提示
+#[derive(Debug)]
+pub enum RepoError {
+    /// 要查找的记录不存在
+    NotFound,
+    /// 违反唯一约束，例如名字已被占用
+    Conflict,
+    /// 引用的记录不存在，例如向不存在的领地添加区块
+    ForeignKey,
+    /// 参数不合法，不会写入数据
+    Invalid(String),
+    /// 数据库或图片存储本身出错，通常需要稍后重试
+    Storage(Box<dyn std::error::Error + Send + Sync>),
+    /// 存储中的数据无法解析
+    Decode(String),
+}
+
+impl RepoError {
+    pub fn storage(msg: impl Into<String>) -> Self {
+        Self::Storage(msg.into().into())
+    }
+}
+
+impl fmt::Display for RepoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "record not found"),
+            Self::Conflict => write!(f, "record already exists"),
+            Self::ForeignKey => write!(f, "referenced record does not exist"),
+            Self::Invalid(msg) => write!(f, "invalid argument: {msg}"),
+            Self::Storage(e) => write!(f, "storage error: {e}"),
+            Self::Decode(msg) => write!(f, "failed to decode stored data: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for RepoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Storage(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for RepoError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => Self::NotFound,
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => Self::Conflict,
+            sqlx::Error::Database(ref db) if db.is_foreign_key_violation() => Self::ForeignKey,
+            sqlx::Error::ColumnDecode { .. }
+            | sqlx::Error::Decode(_)
+            | sqlx::Error::ColumnNotFound(_) => Self::Decode(e.to_string()),
+            e => Self::Storage(Box::new(e)),
+        }
+    }
+}
+
+impl From<std::io::Error> for RepoError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Storage(Box::new(e))
+    }
+}
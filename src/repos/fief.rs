@@ -1,11 +1,15 @@
-use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::domains::{ChunkId, UserId};
+use crate::{
+    domains::{ChunkId, Member, Permissions, RoleId, UserId},
+    repos::error::Result,
+};
 
 pub(super) mod domains {
     use serde::{Deserialize, Serialize};
 
+    use crate::domains::{Permissions, RoleId, UserId};
+
     #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
     pub struct FiefId(pub i64);
 
@@ -21,7 +25,110 @@ pub(super) mod domains {
         pub name: String,
         pub check_interval: chrono::Duration,
         pub last_check: chrono::DateTime<chrono::Utc>,
+        /// 最近一次检查是否失败（出现网络错误或仍在重试）
+        pub last_check_failed: bool,
         pub skip_check_until: chrono::DateTime<chrono::Utc>,
+        /// 是否根据检查结果自动调整检查间隔
+        pub adaptive: bool,
+        /// 调度器实际使用的检查间隔，非自适应模式下等于 `check_interval`
+        pub effective_interval: chrono::Duration,
+        /// 连续无异常的检查次数
+        pub clean_streak: usize,
+        /// 每个区块除当前版本外保留的参考图和遮罩图历史版本数量
+        pub image_retention: usize,
+        /// 领地的所有者，拥有全部权限且不能被移出或收回权限
+        pub owner: Option<UserId>,
+    }
+
+    impl Fief {
+        /// 下一次定期自动检查的时间（不考虑手动检查请求）
+        pub fn next_check(&self) -> chrono::DateTime<chrono::Utc> {
+            (self.last_check + self.effective_interval).max(self.skip_check_until)
+        }
+    }
+
+    /// 领地及其区块、成员的统计信息
+    #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+    pub struct FiefSummary {
+        pub fief: Fief,
+        pub chunk_count: usize,
+        pub member_count: usize,
+        pub diff_count: usize,
+    }
+
+    /// 授予某个 Discord 身份组的领地权限，该身份组的成员均视为领地成员
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+    pub struct RoleGrant {
+        pub role_id: RoleId,
+        pub fief_id: FiefId,
+        pub permissions: Permissions,
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct FiefRoleId(pub i64);
+
+    impl From<i64> for FiefRoleId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
+    /// 领地内自定义的职位，修改职位的权限会影响所有担任该职位的成员
+    #[derive(PartialEq, Eq, Debug, Clone, Hash)]
+    pub struct FiefRole {
+        pub id: FiefRoleId,
+        pub fief_id: FiefId,
+        pub name: String,
+        pub permissions: Permissions,
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct MaintenanceWindowId(pub i64);
+
+    impl From<i64> for MaintenanceWindowId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub enum MaintenanceMode {
+        /// 维护期间跳过定期自动检查
+        SkipCheck,
+        /// 维护期间照常检查，但不发送异常警报
+        MuteAlerts,
+    }
+
+    /// 每周（或每天）重复的维护时间段，时间均为 UTC
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct MaintenanceRule {
+        /// 为 `None` 时表示每天
+        pub weekday: Option<chrono::Weekday>,
+        pub start: chrono::NaiveTime,
+        /// 不超过 24 小时
+        pub duration: chrono::Duration,
+        pub mode: MaintenanceMode,
+    }
+
+    impl MaintenanceRule {
+        pub fn contains(&self, time: chrono::DateTime<chrono::Utc>) -> bool {
+            use chrono::Datelike;
+
+            let today = time.date_naive();
+            [today.pred_opt(), Some(today)]
+                .into_iter()
+                .flatten()
+                .filter(|d| self.weekday.is_none_or(|w| d.weekday() == w))
+                .map(|d| d.and_time(self.start).and_utc())
+                .any(|start| start <= time && time < start + self.duration)
+        }
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+    pub struct MaintenanceWindow {
+        pub id: MaintenanceWindowId,
+        pub fief_id: FiefId,
+        pub rule: MaintenanceRule,
     }
 }
 use domains::*;
@@ -34,6 +141,20 @@ pub trait FiefRepo: Sync + Send {
         name: &str,
         check_interval: Option<chrono::Duration>,
     ) -> Result<Option<FiefId>>;
+    /// 身份组已有授权时返回 `false`
+    async fn grant_role(
+        &self,
+        id: FiefId,
+        role_id: RoleId,
+        permissions: Option<Permissions>,
+    ) -> Result<bool>;
+    /// 领地内已有同名职位时返回 `None`
+    async fn create_fief_role(
+        &self,
+        id: FiefId,
+        name: &str,
+        permissions: Permissions,
+    ) -> Result<Option<FiefRoleId>>;
 
     // [R] Read
     // - self or fields
@@ -43,11 +164,26 @@ pub trait FiefRepo: Sync + Send {
     async fn fief_by_name(&self, name: &str) -> Result<Fief>;
     async fn fiefs_to_check(&self) -> Result<Vec<Fief>>;
     async fn all(&self) -> Result<Vec<Fief>>;
+    /// 所有领地及其统计信息，按 id 排列
+    async fn summaries(&self) -> Result<Vec<FiefSummary>>;
+    /// 所有领地的名字，按名字排列
+    async fn names(&self) -> Result<Vec<String>>;
+    /// 用户作为所有者、成员或通过身份组所属的领地的名字，按名字排列
+    async fn names_of(&self, id: UserId, roles: &[RoleId]) -> Result<Vec<String>>;
     // - related
     async fn members(&self, id: FiefId) -> Result<Vec<UserId>>;
+    async fn members_with_perms(&self, id: FiefId) -> Result<Vec<Member>>;
+    /// 所有领地的成员关系，按领地 id 排列
+    async fn all_members(&self) -> Result<Vec<Member>>;
     async fn chunks(&self, id: FiefId) -> Result<Vec<ChunkId>>;
     async fn chunk_count(&self, id: FiefId) -> Result<usize>;
     async fn diff_count(&self, id: FiefId) -> Result<usize>;
+    async fn maintenance_windows(&self, id: FiefId) -> Result<Vec<MaintenanceWindow>>;
+    /// 按身份组 id 排列
+    async fn role_grants(&self, id: FiefId) -> Result<Vec<RoleGrant>>;
+    /// 按职位 id 排列
+    async fn fief_roles(&self, id: FiefId) -> Result<Vec<FiefRole>>;
+    async fn fief_role_by_name(&self, id: FiefId, name: &str) -> Result<FiefRole>;
 
     // [U] Update
     // - self or fields
@@ -56,7 +192,16 @@ pub trait FiefRepo: Sync + Send {
         id: FiefId,
         date: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<()>;
+    async fn set_last_check_failed(&self, id: FiefId, failed: bool) -> Result<()>;
     async fn set_check_interval(&self, id: FiefId, interval: chrono::Duration) -> Result<()>;
+    async fn set_adaptive(&self, id: FiefId, adaptive: bool) -> Result<()>;
+    async fn update_adaptive_state(
+        &self,
+        id: FiefId,
+        effective_interval: chrono::Duration,
+        clean_streak: usize,
+    ) -> Result<()>;
+    async fn set_image_retention(&self, id: FiefId, retention: usize) -> Result<()>;
     async fn mark_should_check_now(&self, id: FiefId) -> Result<()>;
     async fn skip_check(&self, id: FiefId) -> Result<()>;
     async fn keep_check(&self, id: FiefId) -> Result<()>;
@@ -66,11 +211,35 @@ pub trait FiefRepo: Sync + Send {
         dur: chrono::Duration,
         from: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<()>;
+    async fn set_owner(&self, id: FiefId, owner: Option<UserId>) -> Result<()>;
     async fn rename(&self, id: FiefId, name: &str) -> Result<()>;
     // - related
-    // *PASS*
+    async fn add_maintenance_window(
+        &self,
+        id: FiefId,
+        rule: MaintenanceRule,
+    ) -> Result<Option<MaintenanceWindowId>>;
+    async fn set_role_permissions(
+        &self,
+        id: FiefId,
+        role_id: RoleId,
+        permissions: Permissions,
+    ) -> Result<()>;
+    async fn set_fief_role_permissions(
+        &self,
+        role_id: FiefRoleId,
+        permissions: Permissions,
+    ) -> Result<()>;
 
     // [D] Delete
     async fn remove_by_id(&self, id: FiefId) -> Result<bool>;
     async fn remove_by_name(&self, name: &str) -> Result<bool>;
+    async fn remove_maintenance_window(
+        &self,
+        id: FiefId,
+        window_id: MaintenanceWindowId,
+    ) -> Result<bool>;
+    async fn revoke_role(&self, id: FiefId, role_id: RoleId) -> Result<bool>;
+    /// 担任该职位的成员会失去职位，但保留自身的权限
+    async fn remove_fief_role(&self, role_id: FiefRoleId) -> Result<bool>;
 }
@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use crate::{
+    core::ImagePng,
+    domains::ImageKey,
+    repos::{
+        error::{RepoError, Result},
+        traits::ImageStore,
+    },
+};
+
+/// 将图片保存为 `<根目录>/<哈希前两位>/<哈希>.png`
+pub struct FsImageStore {
+    root: PathBuf,
+}
+
+impl FsImageStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_of(&self, key: &ImageKey) -> Result<PathBuf> {
+        let valid = key.0.len() > 2 && key.0.bytes().all(|b| b.is_ascii_hexdigit());
+        if !valid {
+            return Err(RepoError::Invalid(format!("invalid image key: {}", key.0)));
+        }
+        Ok(self.root.join(&key.0[..2]).join(format!("{}.png", key.0)))
+    }
+}
+
+#[async_trait]
+impl ImageStore for FsImageStore {
+    // [C] Create
+    async fn put(&self, img: &ImagePng) -> Result<ImageKey> {
+        let key = ImageKey::of(img);
+        let path = self.path_of(&key)?;
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(key);
+        }
+
+        tokio::fs::create_dir_all(path.parent().unwrap()).await?;
+        // 先写入临时文件再重命名，避免留下不完整的图片
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, img.as_bytes()).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(key)
+    }
+
+    // [R] Read
+    async fn get(&self, key: &ImageKey) -> Result<Option<ImagePng>> {
+        match tokio::fs::read(self.path_of(key)?).await {
+            Ok(data) => Ok(Some(ImagePng::new(data))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn contains(&self, key: &ImageKey) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.path_of(key)?).await?)
+    }
+
+    async fn keys(&self) -> Result<Vec<ImageKey>> {
+        let mut keys = vec![];
+        let mut dirs = match tokio::fs::read_dir(&self.root).await {
+            Ok(dirs) => dirs,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(keys),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(dir) = dirs.next_entry().await? {
+            if !dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut files = tokio::fs::read_dir(dir.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                let name = file.file_name();
+                if let Some(hash) = name.to_str().and_then(|n| n.strip_suffix(".png")) {
+                    keys.push(ImageKey(hash.to_owned()));
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    // [D] Delete
+    async fn remove(&self, key: &ImageKey) -> Result<bool> {
+        match tokio::fs::remove_file(self.path_of(key)?).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
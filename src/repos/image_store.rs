@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+
+use crate::{
+    core::ImagePng,
+    repos::error::{RepoError, Result},
+};
+
+pub(super) mod domains {
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    use crate::core::ImagePng;
+
+    /// 图片内容的 SHA-256（小写十六进制），相同内容的图片只会保存一份
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Hash, Serialize, Deserialize)]
+    pub struct ImageKey(pub String);
+
+    impl ImageKey {
+        pub fn of(img: &ImagePng) -> Self {
+            let hash = Sha256::digest(img.as_bytes());
+            Self(hash.iter().map(|b| format!("{b:02x}")).collect())
+        }
+    }
+
+    impl From<String> for ImageKey {
+        fn from(value: String) -> Self {
+            Self(value)
+        }
+    }
+}
+use domains::*;
+
+/// 按内容寻址的图片存储
+#[async_trait]
+pub trait ImageStore: Sync + Send {
+    // [C] Create
+    /// 保存图片并返回其哈希，已存在时不会重复写入
+    async fn put(&self, img: &ImagePng) -> Result<ImageKey>;
+
+    // [R] Read
+    async fn get(&self, key: &ImageKey) -> Result<Option<ImagePng>>;
+    async fn contains(&self, key: &ImageKey) -> Result<bool>;
+    async fn keys(&self) -> Result<Vec<ImageKey>>;
+
+    // [D] Delete
+    async fn remove(&self, key: &ImageKey) -> Result<bool>;
+}
+
+/// 将 `from` 中的所有图片复制到 `to`，`remove` 为真时复制后从 `from`
+/// 中删除，返回复制的数量
+pub async fn copy_images(
+    from: &dyn ImageStore,
+    to: &dyn ImageStore,
+    remove: bool,
+) -> Result<usize> {
+    let mut count = 0;
+    for key in from.keys().await? {
+        let Some(img) = from.get(&key).await? else {
+            continue;
+        };
+        if to.put(&img).await? != key {
+            return Err(RepoError::Decode(format!("image {} is corrupted", key.0)));
+        }
+        if remove {
+            from.remove(&key).await?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicI64, Ordering},
+    },
+};
+
+use crate::domains::{
+    AuditEntry, AuditId, Chunk, ChunkId, Dashboard, DashboardId, Fief, FiefId, FiefRole,
+    FiefRoleId, ImageKey, ImageVersion, ImageVersionId, Invitation, InvitationId,
+    MaintenanceWindow, MaintenanceWindowId, Permissions, RoleId, UserId,
+};
+
+mod audit;
+pub use audit::InMemoryAuditRepo;
+
+mod chunk;
+pub use chunk::InMemoryChunkRepo;
+
+mod dashboard;
+pub use dashboard::InMemoryDashboardRepo;
+
+mod fief;
+pub use fief::InMemoryFiefRepo;
+
+mod image_store;
+pub use image_store::InMemoryImageStore;
+
+mod user;
+pub use user::InMemoryUserRepo;
+
+#[derive(Clone, PartialEq)]
+pub(super) struct FiefRow {
+    fief: Fief,
+    should_check_now: bool,
+}
+
+#[derive(Clone, PartialEq)]
+pub(super) struct ChunkRow {
+    chunk: Chunk,
+    ref_key: Option<ImageKey>,
+    mask_key: Option<ImageKey>,
+    diff_key: Option<ImageKey>,
+    result_key: Option<ImageKey>,
+    curr_key: Option<ImageKey>,
+    diff_count: usize,
+}
+
+#[derive(Clone, PartialEq)]
+pub(super) struct VersionRow {
+    version: ImageVersion,
+    key: Option<ImageKey>,
+}
+
+/// 各个内存仓库共享的表，删除时与数据库一样级联删除相关的行
+#[derive(Default, Clone)]
+pub struct MemoryDb {
+    users: BTreeMap<UserId, bool>,
+    members: BTreeMap<(UserId, FiefId), Permissions>,
+    role_grants: BTreeMap<(RoleId, FiefId), Permissions>,
+    fief_roles: BTreeMap<FiefRoleId, FiefRole>,
+    invitations: BTreeMap<InvitationId, Invitation>,
+    /// 成员担任的职位，对应 `Members.role_id`
+    member_roles: BTreeMap<(UserId, FiefId), FiefRoleId>,
+    fiefs: BTreeMap<FiefId, FiefRow>,
+    chunks: BTreeMap<ChunkId, ChunkRow>,
+    windows: BTreeMap<MaintenanceWindowId, MaintenanceWindow>,
+    versions: BTreeMap<ImageVersionId, VersionRow>,
+    /// 操作记录不随领地删除
+    audit: BTreeMap<AuditId, AuditEntry>,
+    dashboards: BTreeMap<DashboardId, Dashboard>,
+    /// 与 `AUTOINCREMENT` 一样，每张表单独计数，删除后不会重复使用 id
+    last_fief_id: IdSequence,
+    last_chunk_id: IdSequence,
+    last_window_id: IdSequence,
+    last_fief_role_id: IdSequence,
+    last_invitation_id: IdSequence,
+    last_version_id: IdSequence,
+    last_audit_id: IdSequence,
+    last_dashboard_id: IdSequence,
+}
+
+impl MemoryDb {
+    pub fn shared() -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    fn remove_user(&mut self, id: UserId) -> bool {
+        self.members.retain(|(user_id, _), _| *user_id != id);
+        self.member_roles.retain(|(user_id, _), _| *user_id != id);
+        self.invitations.retain(|_, i| i.user_id != id);
+        self.users.remove(&id).is_some()
+    }
+
+    fn remove_fief(&mut self, id: FiefId) -> bool {
+        self.members.retain(|(_, fief_id), _| *fief_id != id);
+        self.member_roles.retain(|(_, fief_id), _| *fief_id != id);
+        self.invitations.retain(|_, i| i.fief_id != id);
+        self.role_grants.retain(|(_, fief_id), _| *fief_id != id);
+        self.fief_roles.retain(|_, r| r.fief_id != id);
+        self.windows.retain(|_, w| w.fief_id != id);
+        self.dashboards.retain(|_, d| d.fief_id != Some(id));
+        let chunks = self
+            .chunks
+            .values()
+            .filter(|c| c.chunk.fief_id == id)
+            .map(|c| c.chunk.id)
+            .collect::<Vec<_>>();
+        for chunk_id in chunks {
+            self.remove_chunk(chunk_id);
+        }
+        self.fiefs.remove(&id).is_some()
+    }
+
+    fn remove_chunk(&mut self, id: ChunkId) -> bool {
+        self.versions.retain(|_, v| v.version.chunk_id != id);
+        self.chunks.remove(&id).is_some()
+    }
+
+    /// 将 `working` 相对于 `base` 的修改应用到当前的数据上，`base` 之后由其他
+    /// 操作写入的行会被保留，两边修改了同一行时以 `working` 为准
+    pub(super) fn merge(&mut self, base: &Self, working: &Self) {
+        merge_table(&mut self.users, &base.users, &working.users);
+        merge_table(&mut self.members, &base.members, &working.members);
+        merge_table(
+            &mut self.role_grants,
+            &base.role_grants,
+            &working.role_grants,
+        );
+        merge_table(&mut self.fief_roles, &base.fief_roles, &working.fief_roles);
+        merge_table(
+            &mut self.invitations,
+            &base.invitations,
+            &working.invitations,
+        );
+        merge_table(
+            &mut self.member_roles,
+            &base.member_roles,
+            &working.member_roles,
+        );
+        merge_table(&mut self.fiefs, &base.fiefs, &working.fiefs);
+        merge_table(&mut self.chunks, &base.chunks, &working.chunks);
+        merge_table(&mut self.windows, &base.windows, &working.windows);
+        merge_table(&mut self.versions, &base.versions, &working.versions);
+        merge_table(&mut self.audit, &base.audit, &working.audit);
+        merge_table(&mut self.dashboards, &base.dashboards, &working.dashboards);
+    }
+}
+
+fn merge_table<K, V>(target: &mut BTreeMap<K, V>, base: &BTreeMap<K, V>, working: &BTreeMap<K, V>)
+where
+    K: Ord + Clone,
+    V: Clone + PartialEq,
+{
+    for key in base.keys().filter(|k| !working.contains_key(k)) {
+        target.remove(key);
+    }
+    for (key, value) in working {
+        if base.get(key) != Some(value) {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// 自增的 id，复制出的副本共用同一个计数，工作单元与其他操作不会分配到相同的 id
+#[derive(Default, Clone)]
+struct IdSequence(Arc<AtomicI64>);
+
+impl IdSequence {
+    fn next(&self) -> i64 {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+pub(super) fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
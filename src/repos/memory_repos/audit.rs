@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use super::{MemoryDb, lock};
+use crate::{
+    domains::{AuditEntry, AuditId, AuditRecord, FiefId},
+    repos::{error::Result, traits::AuditRepo},
+};
+
+pub struct InMemoryAuditRepo(Arc<Mutex<MemoryDb>>);
+
+impl InMemoryAuditRepo {
+    pub fn new(db: Arc<Mutex<MemoryDb>>) -> Self {
+        Self(db)
+    }
+
+    fn page(
+        &self,
+        filter: impl Fn(&AuditEntry) -> bool,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<AuditEntry> {
+        let db = lock(&self.0);
+        db.audit
+            .values()
+            .rev()
+            .filter(|e| filter(e))
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AuditRepo for InMemoryAuditRepo {
+    // [C] Create
+    async fn record(&self, record: AuditRecord) -> Result<AuditId> {
+        let mut db = lock(&self.0);
+        let id = AuditId(db.last_audit_id.next());
+        let entry = AuditEntry {
+            id,
+            time: chrono::Utc::now(),
+            record,
+        };
+        db.audit.insert(id, entry);
+        Ok(id)
+    }
+
+    // [R] Read
+    async fn by_fief(
+        &self,
+        fief_id: FiefId,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>> {
+        Ok(self.page(|e| e.record.fief_id == Some(fief_id), offset, limit))
+    }
+
+    async fn count_by_fief(&self, fief_id: FiefId) -> Result<usize> {
+        let db = lock(&self.0);
+        Ok(db
+            .audit
+            .values()
+            .filter(|e| e.record.fief_id == Some(fief_id))
+            .count())
+    }
+
+    async fn all(&self, offset: usize, limit: usize) -> Result<Vec<AuditEntry>> {
+        Ok(self.page(|_| true, offset, limit))
+    }
+
+    async fn count(&self) -> Result<usize> {
+        Ok(lock(&self.0).audit.len())
+    }
+}
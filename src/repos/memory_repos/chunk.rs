@@ -0,0 +1,394 @@
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+
+use super::{ChunkRow, MemoryDb, VersionRow, lock};
+use crate::{
+    core::{ImagePng, Position},
+    domains::{
+        Chunk, ChunkId, ChunkSummary, FiefId, ImageKey, ImageKind, ImageVersion, ImageVersionId,
+        UserId,
+    },
+    repos::{
+        error::{RepoError, Result},
+        traits::{ChunkRepo, ImageStore},
+    },
+};
+
+pub struct InMemoryChunkRepo(Arc<Mutex<MemoryDb>>, Arc<dyn ImageStore>);
+
+impl InMemoryChunkRepo {
+    pub fn new(db: Arc<Mutex<MemoryDb>>, images: Arc<dyn ImageStore>) -> Self {
+        Self(db, images)
+    }
+
+    fn find<T>(&self, id: ChunkId, f: impl FnOnce(&ChunkRow) -> T) -> Result<T> {
+        let db = lock(&self.0);
+        let row = db.chunks.get(&id).ok_or(RepoError::NotFound)?;
+        Ok(f(row))
+    }
+
+    fn find_by_name<T>(
+        &self,
+        fief_id: FiefId,
+        name: &str,
+        f: impl FnOnce(&Chunk) -> T,
+    ) -> Result<T> {
+        let db = lock(&self.0);
+        let row = db
+            .chunks
+            .values()
+            .find(|c| c.chunk.fief_id == fief_id && c.chunk.name == name)
+            .ok_or(RepoError::NotFound)?;
+        Ok(f(&row.chunk))
+    }
+
+    fn update(&self, id: ChunkId, f: impl FnOnce(&mut ChunkRow)) {
+        if let Some(row) = lock(&self.0).chunks.get_mut(&id) {
+            f(row);
+        }
+    }
+
+    async fn store(&self, img: Option<ImagePng>) -> Result<Option<ImageKey>> {
+        match img {
+            Some(img) => Ok(Some(self.1.put(&img).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn load(&self, key: Option<ImageKey>) -> Result<Option<ImagePng>> {
+        let Some(key) = key else {
+            return Ok(None);
+        };
+        match self.1.get(&key).await? {
+            Some(img) => Ok(Some(img)),
+            None => Err(RepoError::storage(format!(
+                "image {} is missing from storage",
+                key.0
+            ))),
+        }
+    }
+
+    /// 更新参考图或遮罩图，记录新版本并按照领地的保留数量清理旧版本
+    fn update_versioned_img(
+        &self,
+        id: ChunkId,
+        kind: ImageKind,
+        key: Option<ImageKey>,
+        author: Option<UserId>,
+    ) -> Result<()> {
+        let mut db = lock(&self.0);
+        let row = db.chunks.get_mut(&id).ok_or(RepoError::NotFound)?;
+        match kind {
+            ImageKind::Reference => row.ref_key = key.clone(),
+            ImageKind::Mask => row.mask_key = key.clone(),
+        }
+        let fief_id = row.chunk.fief_id;
+
+        let version_id = ImageVersionId(db.last_version_id.next());
+        let version = ImageVersion {
+            id: version_id,
+            chunk_id: id,
+            kind,
+            is_empty: key.is_none(),
+            author,
+            created_at: chrono::Utc::now(),
+        };
+        db.versions.insert(version_id, VersionRow { version, key });
+
+        // 当前版本之外再保留 `retention` 个历史版本
+        let retention = db.fiefs.get(&fief_id).map_or(1, |f| f.fief.image_retention);
+        let expired = db
+            .versions
+            .values()
+            .rev()
+            .filter(|v| v.version.chunk_id == id && v.version.kind == kind)
+            .skip(retention.max(1) + 1)
+            .map(|v| v.version.id)
+            .collect::<Vec<_>>();
+        for version_id in expired {
+            db.versions.remove(&version_id);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChunkRepo for InMemoryChunkRepo {
+    // [C] Create
+    async fn create(&self, name: &str, fief_id: FiefId, pos: Position) -> Result<Option<ChunkId>> {
+        let mut db = lock(&self.0);
+        let exists = db
+            .chunks
+            .values()
+            .any(|c| c.chunk.fief_id == fief_id && c.chunk.name == name);
+        if !db.fiefs.contains_key(&fief_id) {
+            return Err(RepoError::ForeignKey);
+        }
+        if exists {
+            return Ok(None);
+        }
+
+        let id = ChunkId(db.last_chunk_id.next());
+        let chunk = Chunk {
+            id,
+            name: name.to_owned(),
+            fief_id,
+            position: pos,
+        };
+        db.chunks.insert(
+            id,
+            ChunkRow {
+                chunk,
+                ref_key: None,
+                mask_key: None,
+                diff_key: None,
+                result_key: None,
+                curr_key: None,
+                diff_count: 0,
+            },
+        );
+        Ok(Some(id))
+    }
+
+    // [R] Read
+    // - self or fields
+    async fn chunk_by_id(&self, id: ChunkId) -> Result<Chunk> {
+        self.find(id, |c| c.chunk.clone())
+    }
+
+    async fn chunk_by_name(&self, fief_id: FiefId, name: &str) -> Result<Chunk> {
+        self.find_by_name(fief_id, name, Chunk::clone)
+    }
+
+    async fn summaries_by_fief(&self, fief_id: FiefId) -> Result<Vec<ChunkSummary>> {
+        let db = lock(&self.0);
+        Ok(db
+            .chunks
+            .values()
+            .filter(|c| c.chunk.fief_id == fief_id)
+            .map(|c| ChunkSummary {
+                chunk: c.chunk.clone(),
+                diff_count: c.diff_count,
+                has_ref: c.ref_key.is_some(),
+                has_mask: c.mask_key.is_some(),
+            })
+            .collect())
+    }
+
+    async fn names_by_fief(&self, fief_id: FiefId) -> Result<Vec<String>> {
+        let db = lock(&self.0);
+        let mut names: Vec<_> = db
+            .chunks
+            .values()
+            .filter(|c| c.chunk.fief_id == fief_id)
+            .map(|c| c.chunk.name.clone())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn fief_id(&self, id: ChunkId) -> Result<FiefId> {
+        self.find(id, |c| c.chunk.fief_id)
+    }
+
+    async fn name(&self, id: ChunkId) -> Result<String> {
+        self.find(id, |c| c.chunk.name.clone())
+    }
+
+    async fn id(&self, fief_id: FiefId, name: &str) -> Result<ChunkId> {
+        self.find_by_name(fief_id, name, |c| c.id)
+    }
+
+    async fn position(&self, id: ChunkId) -> Result<Position> {
+        self.find(id, |c| c.chunk.position)
+    }
+
+    async fn ref_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+        let key = self.find(id, |c| c.ref_key.clone())?;
+        self.load(key).await
+    }
+
+    async fn mask_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+        let key = self.find(id, |c| c.mask_key.clone())?;
+        self.load(key).await
+    }
+
+    async fn diff_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+        let key = self.find(id, |c| c.diff_key.clone())?;
+        self.load(key).await
+    }
+
+    async fn result_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+        let key = self.find(id, |c| c.result_key.clone())?;
+        self.load(key).await
+    }
+
+    async fn current_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+        let key = self.find(id, |c| c.curr_key.clone())?;
+        self.load(key).await
+    }
+
+    async fn diff_count(&self, id: ChunkId) -> Result<usize> {
+        self.find(id, |c| c.diff_count)
+    }
+
+    async fn image_versions(&self, id: ChunkId, kind: ImageKind) -> Result<Vec<ImageVersion>> {
+        let db = lock(&self.0);
+        Ok(db
+            .versions
+            .values()
+            .rev()
+            .filter(|v| v.version.chunk_id == id && v.version.kind == kind)
+            .map(|v| v.version.clone())
+            .collect())
+    }
+
+    async fn version_img(&self, version_id: ImageVersionId) -> Result<Option<ImagePng>> {
+        let key = lock(&self.0)
+            .versions
+            .get(&version_id)
+            .map(|v| v.key.clone())
+            .ok_or(RepoError::NotFound)?;
+        self.load(key).await
+    }
+
+    // - related
+    async fn referenced_image_keys(&self) -> Result<Vec<ImageKey>> {
+        let db = lock(&self.0);
+        let chunks = db.chunks.values().flat_map(|c| {
+            [
+                &c.ref_key,
+                &c.mask_key,
+                &c.diff_key,
+                &c.result_key,
+                &c.curr_key,
+            ]
+            .into_iter()
+            .flatten()
+        });
+        let versions = db.versions.values().filter_map(|v| v.key.as_ref());
+        let keys = chunks.chain(versions).cloned().collect::<BTreeSet<_>>();
+        Ok(keys.into_iter().collect())
+    }
+
+    // [U] Update
+    // - self or fields
+    async fn update_ref_img(
+        &self,
+        id: ChunkId,
+        img: Option<ImagePng>,
+        author: Option<UserId>,
+    ) -> Result<()> {
+        let key = self.store(img).await?;
+        self.update_versioned_img(id, ImageKind::Reference, key, author)
+    }
+
+    async fn update_mask_img(
+        &self,
+        id: ChunkId,
+        img: Option<ImagePng>,
+        author: Option<UserId>,
+    ) -> Result<()> {
+        let key = self.store(img).await?;
+        self.update_versioned_img(id, ImageKind::Mask, key, author)
+    }
+
+    async fn update_result_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()> {
+        let key = self.store(img).await?;
+        self.update(id, |c| c.result_key = key);
+        Ok(())
+    }
+
+    async fn update_current_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()> {
+        let key = self.store(img).await?;
+        self.update(id, |c| c.curr_key = key);
+        Ok(())
+    }
+
+    async fn update_diff(&self, id: ChunkId, img: Option<ImagePng>, count: usize) -> Result<()> {
+        let key = self.store(img).await?;
+        self.update(id, |c| {
+            c.diff_key = key;
+            c.diff_count = count;
+        });
+        Ok(())
+    }
+
+    async fn set_position(&self, id: ChunkId, pos: Position) -> Result<()> {
+        self.update(id, |c| c.chunk.position = pos);
+        Ok(())
+    }
+
+    async fn rename(&self, id: ChunkId, name: &str) -> Result<()> {
+        let mut db = lock(&self.0);
+        let Some(fief_id) = db.chunks.get(&id).map(|c| c.chunk.fief_id) else {
+            return Ok(());
+        };
+        let exists = db
+            .chunks
+            .values()
+            .any(|c| c.chunk.fief_id == fief_id && c.chunk.name == name);
+        if exists {
+            return Err(RepoError::Conflict);
+        }
+        if let Some(row) = db.chunks.get_mut(&id) {
+            row.chunk.name = name.to_owned();
+        }
+        Ok(())
+    }
+
+    async fn rollback_img(
+        &self,
+        id: ChunkId,
+        version_id: ImageVersionId,
+        author: Option<UserId>,
+    ) -> Result<bool> {
+        let version = lock(&self.0)
+            .versions
+            .get(&version_id)
+            .filter(|v| v.version.chunk_id == id)
+            .map(|v| (v.version.kind, v.key.clone()));
+
+        let Some((kind, key)) = version else {
+            return Ok(false);
+        };
+        self.update_versioned_img(id, kind, key, author)?;
+        Ok(true)
+    }
+
+    // - related
+    // *PASS*
+
+    // [D] Delete
+    async fn remove_by_id(&self, id: ChunkId) -> Result<bool> {
+        Ok(lock(&self.0).remove_chunk(id))
+    }
+
+    async fn remove_by_name(&self, fief_id: FiefId, name: &str) -> Result<bool> {
+        let mut db = lock(&self.0);
+        let id = db
+            .chunks
+            .values()
+            .find(|c| c.chunk.fief_id == fief_id && c.chunk.name == name)
+            .map(|c| c.chunk.id);
+        Ok(id.is_some_and(|id| db.remove_chunk(id)))
+    }
+
+    async fn remove_all_by_fief(&self, fief_id: FiefId) -> Result<bool> {
+        let mut db = lock(&self.0);
+        let ids = db
+            .chunks
+            .values()
+            .filter(|c| c.chunk.fief_id == fief_id)
+            .map(|c| c.chunk.id)
+            .collect::<Vec<_>>();
+        for id in &ids {
+            db.remove_chunk(*id);
+        }
+        Ok(!ids.is_empty())
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use super::{MemoryDb, lock};
+use crate::{
+    domains::{Dashboard, DashboardId, FiefId},
+    repos::{
+        error::{RepoError, Result},
+        traits::DashboardRepo,
+    },
+};
+
+pub struct InMemoryDashboardRepo(Arc<Mutex<MemoryDb>>);
+
+impl InMemoryDashboardRepo {
+    pub fn new(db: Arc<Mutex<MemoryDb>>) -> Self {
+        Self(db)
+    }
+}
+
+#[async_trait]
+impl DashboardRepo for InMemoryDashboardRepo {
+    // [C] Create
+    async fn create(
+        &self,
+        fief_id: Option<FiefId>,
+        channel_id: i64,
+        message_id: i64,
+    ) -> Result<DashboardId> {
+        let mut db = lock(&self.0);
+        if fief_id.is_some_and(|id| !db.fiefs.contains_key(&id)) {
+            return Err(RepoError::ForeignKey);
+        }
+        let id = DashboardId(db.last_dashboard_id.next());
+        let dashboard = Dashboard {
+            id,
+            fief_id,
+            channel_id,
+            message_id,
+        };
+        db.dashboards.insert(id, dashboard);
+        Ok(id)
+    }
+
+    // [R] Read
+    async fn all(&self) -> Result<Vec<Dashboard>> {
+        Ok(lock(&self.0).dashboards.values().copied().collect())
+    }
+
+    async fn showing(&self, fief_id: FiefId) -> Result<Vec<Dashboard>> {
+        let db = lock(&self.0);
+        Ok(db
+            .dashboards
+            .values()
+            .filter(|d| d.fief_id.is_none_or(|id| id == fief_id))
+            .copied()
+            .collect())
+    }
+
+    // [D] Delete
+    async fn remove(&self, id: DashboardId) -> Result<bool> {
+        Ok(lock(&self.0).dashboards.remove(&id).is_some())
+    }
+}
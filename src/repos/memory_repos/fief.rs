@@ -0,0 +1,546 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::TimeZone;
+
+use super::{FiefRow, MemoryDb, lock};
+use crate::{
+    cfg,
+    domains::{
+        ChunkId, Fief, FiefId, FiefRole, FiefRoleId, FiefSummary, MaintenanceMode, MaintenanceRule,
+        MaintenanceWindow, MaintenanceWindowId, Member, Permissions, RoleGrant, RoleId, UserId,
+    },
+    repos::{
+        error::{RepoError, Result},
+        traits::FiefRepo,
+    },
+};
+
+pub struct InMemoryFiefRepo(Arc<Mutex<MemoryDb>>);
+
+impl InMemoryFiefRepo {
+    pub fn new(db: Arc<Mutex<MemoryDb>>) -> Self {
+        Self(db)
+    }
+
+    fn find<T>(&self, id: FiefId, f: impl FnOnce(&Fief) -> T) -> Result<T> {
+        let db = lock(&self.0);
+        let row = db.fiefs.get(&id).ok_or(RepoError::NotFound)?;
+        Ok(f(&row.fief))
+    }
+
+    fn find_by_name<T>(&self, name: &str, f: impl FnOnce(&Fief) -> T) -> Result<T> {
+        let db = lock(&self.0);
+        let row = db
+            .fiefs
+            .values()
+            .find(|r| r.fief.name == name)
+            .ok_or(RepoError::NotFound)?;
+        Ok(f(&row.fief))
+    }
+
+    fn update(&self, id: FiefId, f: impl FnOnce(&mut FiefRow)) {
+        if let Some(row) = lock(&self.0).fiefs.get_mut(&id) {
+            f(row);
+        }
+    }
+}
+
+fn minimum_interval() -> chrono::Duration {
+    chrono::Duration::minutes(cfg().check.minimum_interval_min as i64)
+}
+
+/// 与数据库一样只保存到分钟
+fn whole_minutes(d: chrono::Duration) -> chrono::Duration {
+    chrono::Duration::minutes(d.num_minutes())
+}
+
+#[async_trait]
+impl FiefRepo for InMemoryFiefRepo {
+    // [C] Create
+    async fn create(
+        &self,
+        name: &str,
+        check_interval: Option<chrono::Duration>,
+    ) -> Result<Option<FiefId>> {
+        let check_interval = check_interval
+            .map(whole_minutes)
+            .unwrap_or(chrono::Duration::minutes(
+                cfg().check.default_interval_min as i64,
+            ))
+            .max(minimum_interval());
+        let ago = chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap();
+
+        let mut db = lock(&self.0);
+        if db.fiefs.values().any(|r| r.fief.name == name) {
+            return Ok(None);
+        }
+        let id = FiefId(db.last_fief_id.next());
+        let fief = Fief {
+            id,
+            name: name.to_owned(),
+            check_interval,
+            last_check: ago,
+            last_check_failed: false,
+            skip_check_until: ago,
+            adaptive: false,
+            effective_interval: check_interval,
+            clean_streak: 0,
+            image_retention: 10,
+            owner: None,
+        };
+        db.fiefs.insert(
+            id,
+            FiefRow {
+                fief,
+                should_check_now: false,
+            },
+        );
+        Ok(Some(id))
+    }
+
+    async fn grant_role(
+        &self,
+        id: FiefId,
+        role_id: RoleId,
+        permissions: Option<Permissions>,
+    ) -> Result<bool> {
+        let mut db = lock(&self.0);
+        if !db.fiefs.contains_key(&id) {
+            return Err(RepoError::ForeignKey);
+        }
+        if db.role_grants.contains_key(&(role_id, id)) {
+            return Ok(false);
+        }
+        db.role_grants
+            .insert((role_id, id), permissions.unwrap_or(Permissions::NONE));
+        Ok(true)
+    }
+
+    async fn create_fief_role(
+        &self,
+        id: FiefId,
+        name: &str,
+        permissions: Permissions,
+    ) -> Result<Option<FiefRoleId>> {
+        let mut db = lock(&self.0);
+        if !db.fiefs.contains_key(&id) {
+            return Err(RepoError::ForeignKey);
+        }
+        if db
+            .fief_roles
+            .values()
+            .any(|r| r.fief_id == id && r.name == name)
+        {
+            return Ok(None);
+        }
+        let role_id = FiefRoleId(db.last_fief_role_id.next());
+        db.fief_roles.insert(
+            role_id,
+            FiefRole {
+                id: role_id,
+                fief_id: id,
+                name: name.to_owned(),
+                permissions,
+            },
+        );
+        Ok(Some(role_id))
+    }
+
+    // [R] Read
+    // - self or fields
+    async fn name(&self, id: FiefId) -> Result<String> {
+        self.find(id, |f| f.name.clone())
+    }
+
+    async fn id(&self, name: &str) -> Result<FiefId> {
+        self.find_by_name(name, |f| f.id)
+    }
+
+    async fn fief_by_id(&self, id: FiefId) -> Result<Fief> {
+        self.find(id, Fief::clone)
+    }
+
+    async fn fief_by_name(&self, name: &str) -> Result<Fief> {
+        self.find_by_name(name, Fief::clone)
+    }
+
+    async fn fiefs_to_check(&self) -> Result<Vec<Fief>> {
+        let now = chrono::Utc::now();
+        let db = lock(&self.0);
+        let skipped = db
+            .windows
+            .values()
+            .filter(|w| w.rule.mode == MaintenanceMode::SkipCheck && w.rule.contains(now))
+            .map(|w| w.fief_id)
+            .collect::<Vec<_>>();
+
+        Ok(db
+            .fiefs
+            .values()
+            .filter(|r| {
+                let f = &r.fief;
+                let due = f.last_check + f.effective_interval < now && f.skip_check_until < now;
+                r.should_check_now || (due && !skipped.contains(&f.id))
+            })
+            .map(|r| r.fief.clone())
+            .collect())
+    }
+
+    async fn all(&self) -> Result<Vec<Fief>> {
+        let db = lock(&self.0);
+        Ok(db.fiefs.values().map(|r| r.fief.clone()).collect())
+    }
+
+    async fn summaries(&self) -> Result<Vec<FiefSummary>> {
+        let db = lock(&self.0);
+        Ok(db
+            .fiefs
+            .values()
+            .map(|r| {
+                let id = r.fief.id;
+                let chunks = db.chunks.values().filter(|c| c.chunk.fief_id == id);
+                FiefSummary {
+                    fief: r.fief.clone(),
+                    chunk_count: chunks.clone().count(),
+                    member_count: db.members.keys().filter(|(_, f)| *f == id).count(),
+                    diff_count: chunks.map(|c| c.diff_count).sum(),
+                }
+            })
+            .collect())
+    }
+
+    // - related
+    async fn names(&self) -> Result<Vec<String>> {
+        let db = lock(&self.0);
+        let mut names: Vec<_> = db.fiefs.values().map(|r| r.fief.name.clone()).collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn names_of(&self, id: UserId, roles: &[RoleId]) -> Result<Vec<String>> {
+        let db = lock(&self.0);
+        let mut names: Vec<_> = db
+            .fiefs
+            .values()
+            .filter(|r| {
+                let fief_id = r.fief.id;
+                r.fief.owner == Some(id)
+                    || db.members.contains_key(&(id, fief_id))
+                    || roles
+                        .iter()
+                        .any(|&role| db.role_grants.contains_key(&(role, fief_id)))
+            })
+            .map(|r| r.fief.name.clone())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn members(&self, id: FiefId) -> Result<Vec<UserId>> {
+        let db = lock(&self.0);
+        Ok(db
+            .members
+            .keys()
+            .filter(|(_, fief_id)| *fief_id == id)
+            .map(|(user_id, _)| *user_id)
+            .collect())
+    }
+
+    async fn members_with_perms(&self, id: FiefId) -> Result<Vec<Member>> {
+        let mut members = self.all_members().await?;
+        members.retain(|m| m.fief_id == id);
+        Ok(members)
+    }
+
+    async fn all_members(&self) -> Result<Vec<Member>> {
+        let db = lock(&self.0);
+        let mut members = db
+            .members
+            .iter()
+            .map(|(&(user_id, fief_id), &permissions)| Member {
+                user_id,
+                fief_id,
+                permissions,
+            })
+            .collect::<Vec<_>>();
+        members.sort_by_key(|m| (m.fief_id, m.user_id));
+        Ok(members)
+    }
+
+    async fn chunks(&self, id: FiefId) -> Result<Vec<ChunkId>> {
+        let db = lock(&self.0);
+        Ok(db
+            .chunks
+            .values()
+            .filter(|c| c.chunk.fief_id == id)
+            .map(|c| c.chunk.id)
+            .collect())
+    }
+
+    async fn chunk_count(&self, id: FiefId) -> Result<usize> {
+        let db = lock(&self.0);
+        Ok(db.chunks.values().filter(|c| c.chunk.fief_id == id).count())
+    }
+
+    async fn diff_count(&self, id: FiefId) -> Result<usize> {
+        let db = lock(&self.0);
+        Ok(db
+            .chunks
+            .values()
+            .filter(|c| c.chunk.fief_id == id)
+            .map(|c| c.diff_count)
+            .sum())
+    }
+
+    async fn maintenance_windows(&self, id: FiefId) -> Result<Vec<MaintenanceWindow>> {
+        let db = lock(&self.0);
+        Ok(db
+            .windows
+            .values()
+            .filter(|w| w.fief_id == id)
+            .cloned()
+            .collect())
+    }
+
+    async fn role_grants(&self, id: FiefId) -> Result<Vec<RoleGrant>> {
+        let db = lock(&self.0);
+        Ok(db
+            .role_grants
+            .iter()
+            .filter(|((_, fief_id), _)| *fief_id == id)
+            .map(|(&(role_id, fief_id), &permissions)| RoleGrant {
+                role_id,
+                fief_id,
+                permissions,
+            })
+            .collect())
+    }
+
+    async fn fief_roles(&self, id: FiefId) -> Result<Vec<FiefRole>> {
+        let db = lock(&self.0);
+        Ok(db
+            .fief_roles
+            .values()
+            .filter(|r| r.fief_id == id)
+            .cloned()
+            .collect())
+    }
+
+    async fn fief_role_by_name(&self, id: FiefId, name: &str) -> Result<FiefRole> {
+        let db = lock(&self.0);
+        db.fief_roles
+            .values()
+            .find(|r| r.fief_id == id && r.name == name)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    // [U] Update
+    // - self or fields
+    async fn update_last_check(
+        &self,
+        id: FiefId,
+        date: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<()> {
+        let date = date.unwrap_or(chrono::Utc::now());
+        self.update(id, |r| {
+            r.fief.last_check = date;
+            r.should_check_now = false;
+        });
+        Ok(())
+    }
+
+    async fn set_last_check_failed(&self, id: FiefId, failed: bool) -> Result<()> {
+        self.update(id, |r| r.fief.last_check_failed = failed);
+        Ok(())
+    }
+
+    async fn set_check_interval(&self, id: FiefId, interval: chrono::Duration) -> Result<()> {
+        let interval = whole_minutes(interval).max(minimum_interval());
+        self.update(id, |r| {
+            r.fief.check_interval = interval;
+            r.fief.effective_interval = interval;
+            r.fief.clean_streak = 0;
+        });
+        Ok(())
+    }
+
+    async fn set_adaptive(&self, id: FiefId, adaptive: bool) -> Result<()> {
+        self.update(id, |r| {
+            r.fief.adaptive = adaptive;
+            r.fief.effective_interval = r.fief.check_interval;
+            r.fief.clean_streak = 0;
+        });
+        Ok(())
+    }
+
+    async fn update_adaptive_state(
+        &self,
+        id: FiefId,
+        effective_interval: chrono::Duration,
+        clean_streak: usize,
+    ) -> Result<()> {
+        let effective_interval = whole_minutes(effective_interval).max(minimum_interval());
+        self.update(id, |r| {
+            r.fief.effective_interval = effective_interval;
+            r.fief.clean_streak = clean_streak;
+        });
+        Ok(())
+    }
+
+    async fn set_image_retention(&self, id: FiefId, retention: usize) -> Result<()> {
+        self.update(id, |r| r.fief.image_retention = retention.max(1));
+        Ok(())
+    }
+
+    async fn mark_should_check_now(&self, id: FiefId) -> Result<()> {
+        self.update(id, |r| r.should_check_now = true);
+        Ok(())
+    }
+
+    async fn skip_check(&self, id: FiefId) -> Result<()> {
+        let until = chrono::Utc.with_ymd_and_hms(2077, 1, 1, 0, 0, 0).unwrap();
+        self.update(id, |r| r.fief.skip_check_until = until);
+        Ok(())
+    }
+
+    async fn keep_check(&self, id: FiefId) -> Result<()> {
+        let until = chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap();
+        self.update(id, |r| r.fief.skip_check_until = until);
+        Ok(())
+    }
+
+    async fn skip_check_for(
+        &self,
+        id: FiefId,
+        dur: chrono::Duration,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<()> {
+        let until = from.unwrap_or(chrono::Utc::now()) + dur;
+        self.update(id, |r| r.fief.skip_check_until = until);
+        Ok(())
+    }
+
+    async fn set_owner(&self, id: FiefId, owner: Option<UserId>) -> Result<()> {
+        self.update(id, |r| r.fief.owner = owner);
+        Ok(())
+    }
+
+    async fn rename(&self, id: FiefId, name: &str) -> Result<()> {
+        let mut db = lock(&self.0);
+        if db
+            .fiefs
+            .values()
+            .any(|r| r.fief.name == name && r.fief.id != id)
+        {
+            return Err(RepoError::Conflict);
+        }
+        if let Some(row) = db.fiefs.get_mut(&id) {
+            row.fief.name = name.to_owned();
+        }
+        Ok(())
+    }
+
+    // - related
+    async fn add_maintenance_window(
+        &self,
+        id: FiefId,
+        rule: MaintenanceRule,
+    ) -> Result<Option<MaintenanceWindowId>> {
+        use chrono::Timelike;
+
+        let duration = rule.duration.num_minutes();
+        if duration <= 0 || duration > 24 * 60 {
+            return Err(RepoError::Invalid(
+                "duration must be within 24 hours".into(),
+            ));
+        }
+
+        let mut db = lock(&self.0);
+        if !db.fiefs.contains_key(&id) {
+            return Err(RepoError::ForeignKey);
+        }
+        let window_id = MaintenanceWindowId(db.last_window_id.next());
+        let rule = MaintenanceRule {
+            start: chrono::NaiveTime::from_hms_opt(rule.start.hour(), rule.start.minute(), 0)
+                .unwrap_or(rule.start),
+            duration: whole_minutes(rule.duration),
+            ..rule
+        };
+        db.windows.insert(
+            window_id,
+            MaintenanceWindow {
+                id: window_id,
+                fief_id: id,
+                rule,
+            },
+        );
+        Ok(Some(window_id))
+    }
+
+    async fn set_role_permissions(
+        &self,
+        id: FiefId,
+        role_id: RoleId,
+        permissions: Permissions,
+    ) -> Result<()> {
+        if let Some(perms) = lock(&self.0).role_grants.get_mut(&(role_id, id)) {
+            *perms = permissions;
+        }
+        Ok(())
+    }
+
+    async fn set_fief_role_permissions(
+        &self,
+        role_id: FiefRoleId,
+        permissions: Permissions,
+    ) -> Result<()> {
+        if let Some(role) = lock(&self.0).fief_roles.get_mut(&role_id) {
+            role.permissions = permissions;
+        }
+        Ok(())
+    }
+
+    // [D] Delete
+    async fn remove_by_id(&self, id: FiefId) -> Result<bool> {
+        Ok(lock(&self.0).remove_fief(id))
+    }
+
+    async fn remove_by_name(&self, name: &str) -> Result<bool> {
+        let mut db = lock(&self.0);
+        let id = db
+            .fiefs
+            .values()
+            .find(|r| r.fief.name == name)
+            .map(|r| r.fief.id);
+        Ok(id.is_some_and(|id| db.remove_fief(id)))
+    }
+
+    async fn remove_maintenance_window(
+        &self,
+        id: FiefId,
+        window_id: MaintenanceWindowId,
+    ) -> Result<bool> {
+        let mut db = lock(&self.0);
+        if db.windows.get(&window_id).is_none_or(|w| w.fief_id != id) {
+            return Ok(false);
+        }
+        Ok(db.windows.remove(&window_id).is_some())
+    }
+
+    async fn revoke_role(&self, id: FiefId, role_id: RoleId) -> Result<bool> {
+        Ok(lock(&self.0).role_grants.remove(&(role_id, id)).is_some())
+    }
+
+    async fn remove_fief_role(&self, role_id: FiefRoleId) -> Result<bool> {
+        let mut db = lock(&self.0);
+        db.member_roles.retain(|_, r| *r != role_id);
+        for i in db.invitations.values_mut() {
+            if i.fief_role == Some(role_id) {
+                i.fief_role = None;
+            }
+        }
+        Ok(db.fief_roles.remove(&role_id).is_some())
+    }
+}
@@ -0,0 +1,52 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use async_trait::async_trait;
+
+use super::lock;
+use crate::{
+    core::ImagePng,
+    domains::ImageKey,
+    repos::{error::Result, traits::ImageStore},
+};
+
+#[derive(Default)]
+pub struct InMemoryImageStore(Mutex<BTreeMap<ImageKey, ImagePng>>);
+
+impl InMemoryImageStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ImageStore for InMemoryImageStore {
+    // [C] Create
+    async fn put(&self, img: &ImagePng) -> Result<ImageKey> {
+        let key = ImageKey::of(img);
+        let mut images = lock(&self.0);
+        images.entry(key.clone()).or_insert_with(|| img.clone());
+        Ok(key)
+    }
+
+    // [R] Read
+    async fn get(&self, key: &ImageKey) -> Result<Option<ImagePng>> {
+        let images = lock(&self.0);
+        Ok(images.get(key).cloned())
+    }
+
+    async fn contains(&self, key: &ImageKey) -> Result<bool> {
+        let images = lock(&self.0);
+        Ok(images.contains_key(key))
+    }
+
+    async fn keys(&self) -> Result<Vec<ImageKey>> {
+        let images = lock(&self.0);
+        Ok(images.keys().cloned().collect())
+    }
+
+    // [D] Delete
+    async fn remove(&self, key: &ImageKey) -> Result<bool> {
+        let mut images = lock(&self.0);
+        Ok(images.remove(key).is_some())
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use super::{MemoryDb, lock};
+use crate::{
+    domains::{FiefId, FiefRole, FiefRoleId, Invitation, InvitationId, Permissions, User, UserId},
+    repos::{
+        error::{RepoError, Result},
+        traits::UserRepo,
+    },
+};
+
+pub struct InMemoryUserRepo(Arc<Mutex<MemoryDb>>);
+
+impl InMemoryUserRepo {
+    pub fn new(db: Arc<Mutex<MemoryDb>>) -> Self {
+        Self(db)
+    }
+
+    fn users_where(&self, f: impl Fn(bool) -> bool) -> Vec<User> {
+        lock(&self.0)
+            .users
+            .iter()
+            .filter(|(_, is_admin)| f(**is_admin))
+            .map(|(id, is_admin)| User {
+                id: *id,
+                is_admin: *is_admin,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl UserRepo for InMemoryUserRepo {
+    // [C] Create
+    async fn create(&self, id: UserId, is_admin: bool) -> Result<Option<UserId>> {
+        let mut db = lock(&self.0);
+        if db.users.contains_key(&id) {
+            return Ok(None);
+        }
+        db.users.insert(id, is_admin);
+        Ok(Some(id))
+    }
+
+    async fn join(&self, id: UserId, fief_id: FiefId, p: Option<Permissions>) -> Result<bool> {
+        let mut db = lock(&self.0);
+        if !db.users.contains_key(&id) || !db.fiefs.contains_key(&fief_id) {
+            return Err(RepoError::ForeignKey);
+        }
+        if db.members.contains_key(&(id, fief_id)) {
+            return Ok(false);
+        }
+        db.members
+            .insert((id, fief_id), p.unwrap_or(Permissions::NONE));
+        Ok(true)
+    }
+
+    async fn invite(
+        &self,
+        id: UserId,
+        fief_id: FiefId,
+        p: Option<Permissions>,
+        role: Option<FiefRoleId>,
+        inviter: Option<UserId>,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<InvitationId>> {
+        let now = chrono::Utc::now();
+        let mut db = lock(&self.0);
+        if !db.users.contains_key(&id) || !db.fiefs.contains_key(&fief_id) {
+            return Err(RepoError::ForeignKey);
+        }
+        let existing = db
+            .invitations
+            .values()
+            .find(|i| i.user_id == id && i.fief_id == fief_id)
+            .copied();
+        if let Some(existing) = existing {
+            if !existing.is_expired(now) {
+                return Ok(None);
+            }
+            db.invitations.remove(&existing.id);
+        }
+
+        let invitation_id = InvitationId(db.last_invitation_id.next());
+        let invitation = Invitation {
+            id: invitation_id,
+            fief_id,
+            user_id: id,
+            inviter,
+            permissions: p,
+            fief_role: role,
+            created_at: now,
+            expires_at,
+        };
+        db.invitations.insert(invitation_id, invitation);
+        Ok(Some(invitation_id))
+    }
+
+    // [R] Read
+    // - self or fields
+    async fn user_by_id(&self, id: UserId) -> Result<User> {
+        let db = lock(&self.0);
+        let is_admin = db.users.get(&id).ok_or(RepoError::NotFound)?;
+        Ok(User {
+            id,
+            is_admin: *is_admin,
+        })
+    }
+
+    async fn all(&self) -> Result<Vec<User>> {
+        Ok(self.users_where(|_| true))
+    }
+
+    async fn admins(&self) -> Result<Vec<User>> {
+        Ok(self.users_where(|is_admin| is_admin))
+    }
+
+    async fn non_admins(&self) -> Result<Vec<User>> {
+        Ok(self.users_where(|is_admin| !is_admin))
+    }
+
+    // - related
+    async fn fiefs(&self, id: UserId) -> Result<Vec<FiefId>> {
+        let db = lock(&self.0);
+        Ok(db
+            .members
+            .keys()
+            .filter(|(user_id, _)| *user_id == id)
+            .map(|(_, fief_id)| *fief_id)
+            .collect())
+    }
+
+    async fn is_member_of(&self, id: UserId, fief_id: FiefId) -> Result<bool> {
+        Ok(lock(&self.0).members.contains_key(&(id, fief_id)))
+    }
+
+    async fn permissions_in(&self, id: UserId, fief_id: FiefId) -> Result<Permissions> {
+        let db = lock(&self.0);
+        db.members
+            .get(&(id, fief_id))
+            .copied()
+            .ok_or(RepoError::NotFound)
+    }
+
+    async fn effective_permissions_in(&self, id: UserId, fief_id: FiefId) -> Result<Permissions> {
+        let db = lock(&self.0);
+        let p = db
+            .members
+            .get(&(id, fief_id))
+            .copied()
+            .ok_or(RepoError::NotFound)?;
+        let role = db
+            .member_roles
+            .get(&(id, fief_id))
+            .and_then(|r| db.fief_roles.get(r));
+        Ok(role.map_or(p, |r| p | r.permissions))
+    }
+
+    async fn fief_role_in(&self, id: UserId, fief_id: FiefId) -> Result<Option<FiefRole>> {
+        let db = lock(&self.0);
+        Ok(db
+            .member_roles
+            .get(&(id, fief_id))
+            .and_then(|r| db.fief_roles.get(r))
+            .cloned())
+    }
+
+    async fn invitation_by_id(&self, invitation_id: InvitationId) -> Result<Invitation> {
+        let db = lock(&self.0);
+        db.invitations
+            .get(&invitation_id)
+            .copied()
+            .ok_or(RepoError::NotFound)
+    }
+
+    async fn invitations(&self, id: UserId) -> Result<Vec<Invitation>> {
+        let now = chrono::Utc::now();
+        let db = lock(&self.0);
+        Ok(db
+            .invitations
+            .values()
+            .filter(|i| i.user_id == id && !i.is_expired(now))
+            .copied()
+            .collect())
+    }
+
+    // [U] Update
+    // - self or fields
+    async fn set_admin(&self, id: UserId, is_admin: bool) -> Result<()> {
+        if let Some(admin) = lock(&self.0).users.get_mut(&id) {
+            *admin = is_admin;
+        }
+        Ok(())
+    }
+
+    // - related
+    async fn set_permissions_in(&self, id: UserId, fief_id: FiefId, p: Permissions) -> Result<()> {
+        if let Some(perms) = lock(&self.0).members.get_mut(&(id, fief_id)) {
+            *perms = p;
+        }
+        Ok(())
+    }
+
+    async fn set_fief_role_in(
+        &self,
+        id: UserId,
+        fief_id: FiefId,
+        role_id: Option<FiefRoleId>,
+    ) -> Result<bool> {
+        let mut db = lock(&self.0);
+        if !db.members.contains_key(&(id, fief_id)) {
+            return Ok(false);
+        }
+        match role_id {
+            Some(role_id) => {
+                if db
+                    .fief_roles
+                    .get(&role_id)
+                    .is_none_or(|r| r.fief_id != fief_id)
+                {
+                    return Ok(false);
+                }
+                db.member_roles.insert((id, fief_id), role_id);
+            }
+            None => {
+                db.member_roles.remove(&(id, fief_id));
+            }
+        }
+        Ok(true)
+    }
+
+    // [D] Delete
+    async fn leave(&self, id: UserId, fief_id: FiefId) -> Result<bool> {
+        let mut db = lock(&self.0);
+        db.member_roles.remove(&(id, fief_id));
+        Ok(db.members.remove(&(id, fief_id)).is_some())
+    }
+
+    async fn remove_by_id(&self, id: UserId) -> Result<bool> {
+        Ok(lock(&self.0).remove_user(id))
+    }
+
+    async fn remove_invitation(&self, invitation_id: InvitationId) -> Result<bool> {
+        Ok(lock(&self.0).invitations.remove(&invitation_id).is_some())
+    }
+
+    async fn remove_expired_invitations(&self) -> Result<usize> {
+        let now = chrono::Utc::now();
+        let mut db = lock(&self.0);
+        let before = db.invitations.len();
+        db.invitations.retain(|_, i| !i.is_expired(now));
+        Ok(before - db.invitations.len())
+    }
+}
@@ -1,21 +1,111 @@
+//! SQLite 与 PostgreSQL 共用的仓库实现
+//!
+//! 两种数据库的查询写在同一份代码中，由 `for_each_dialect!` 分别展开，
+//! 语法无法统一的部分放在 `Dialect` 中
+
+use async_trait::async_trait;
+use sqlx::{Database, Postgres, Sqlite, query::QueryAs};
+
+use super::{
+    conn::Conn,
+    error::{RepoError, Result},
+};
+
+/// 将其中的各项分别为 SQLite 和 PostgreSQL 展开一次，`DB` 为对应的数据库类型
+macro_rules! for_each_dialect {
+    ($($item:item)*) => {
+        const _: () = {
+            type DB = sqlx::Sqlite;
+            $($item)*
+        };
+        const _: () = {
+            type DB = sqlx::Postgres;
+            $($item)*
+        };
+    };
+}
+
+mod audit;
+pub use audit::SqlxAuditRepo;
+
 mod chunk;
 pub use chunk::SqlxChunkRepo;
 
+mod dashboard;
+pub use dashboard::SqlxDashboardRepo;
+
 mod fief;
 pub use fief::SqlxFiefRepo;
 
+mod image_store;
+pub use image_store::SqlxImageStore;
+
 mod user;
-use sqlx::sqlite::SqliteQueryResult;
 pub use user::SqlxUserRepo;
-pub fn conv_create_result<Id>(
-    result: Result<SqliteQueryResult, sqlx::Error>,
-) -> Result<Option<Id>, sqlx::Error>
+
+type InsertQuery<'q, DB> = QueryAs<'q, DB, (i64,), <DB as Database>::Arguments<'q>>;
+
+/// 两种数据库语法或行为不同的部分
+#[async_trait]
+pub trait Dialect: Database {
+    /// 已经到了检查时间、且没有被暂停检查的领地
+    const FIEF_DUE: &'static str;
+
+    /// 执行 `INSERT ... RETURNING`，违反约束时不影响所在事务中的其他语句
+    async fn insert_returning(
+        conn: &Conn<Self>,
+        query: InsertQuery<'_, Self>,
+    ) -> Result<Result<(i64,), sqlx::Error>>;
+}
+
+#[async_trait]
+impl Dialect for Sqlite {
+    const FIEF_DUE: &'static str = "datetime(last_check, '+' || effective_interval_min || ' \
+                                    minutes') < datetime('now')
+        AND (skip_check_until IS NULL OR datetime(skip_check_until) < datetime('now'))";
+
+    async fn insert_returning(
+        conn: &Conn<Self>,
+        query: InsertQuery<'_, Self>,
+    ) -> Result<Result<(i64,), sqlx::Error>> {
+        Ok(query.fetch_one(&mut *conn.acquire().await?).await)
+    }
+}
+
+#[async_trait]
+impl Dialect for Postgres {
+    const FIEF_DUE: &'static str = "last_check + effective_interval_min * INTERVAL '1 minute' < \
+                                    NOW()
+        AND (skip_check_until IS NULL OR skip_check_until < NOW())";
+
+    /// PostgreSQL 中失败的语句会让整个事务中止，插入在保存点中执行，
+    /// 违反约束时只回滚这一条语句
+    async fn insert_returning(
+        conn: &Conn<Self>,
+        query: InsertQuery<'_, Self>,
+    ) -> Result<Result<(i64,), sqlx::Error>> {
+        let mut conn = conn.acquire().await?;
+        let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+        let result = query.fetch_one(&mut *tx).await;
+        match result {
+            Ok(_) => tx.commit().await?,
+            Err(_) => tx.rollback().await?,
+        }
+        Ok(result)
+    }
+}
+
+/// 插入语句 `RETURNING` 新行的 id，违反唯一约束说明记录已经存在，返回
+/// `None`，其余错误照常返回
+pub fn conv_create_result<Id>(result: Result<(i64,), sqlx::Error>) -> Result<Option<Id>>
 where
     Id: From<i64>,
 {
-    match &result {
-        Ok(r) => Ok(Some(r.last_insert_rowid().into())),
-        Err(sqlx::Error::Database(_)) => Ok(None),
-        _ => Err(result.unwrap_err()),
+    match result {
+        Ok((id,)) => Ok(Some(id.into())),
+        Err(e) => match RepoError::from(e) {
+            RepoError::Conflict => Ok(None),
+            e => Err(e),
+        },
     }
 }
@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use sqlx::Database;
+
+use crate::{
+    domains::{AuditAction, AuditEntry, AuditId, AuditRecord, AuditValue, FiefId, UserId},
+    entities,
+    repos::{
+        conn::Conn,
+        error::{RepoError, Result},
+        traits::AuditRepo,
+    },
+};
+
+pub struct SqlxAuditRepo<DB: Database>(Conn<DB>);
+
+impl<DB: Database> SqlxAuditRepo<DB> {
+    pub fn new(conn: impl Into<Conn<DB>>) -> Self {
+        Self(conn.into())
+    }
+}
+
+fn conv_audit_entry(e: entities::AuditEntry) -> Result<AuditEntry> {
+    let action = AuditAction::from_code(e.action)
+        .ok_or_else(|| RepoError::Decode(format!("unknown audit action: {}", e.action)))?;
+    Ok(AuditEntry {
+        id: AuditId(e.id),
+        time: e.time,
+        record: AuditRecord {
+            actor: e.actor_id.map(UserId),
+            fief_id: e.fief_id.map(FiefId),
+            action,
+            target: e.target.map(AuditValue::decode),
+            before: e.before_value.map(AuditValue::decode),
+            after: e.after_value.map(AuditValue::decode),
+        },
+    })
+}
+
+for_each_dialect! {
+    #[async_trait]
+    impl AuditRepo for SqlxAuditRepo<DB> {
+        // [C] Create
+        async fn record(&self, record: AuditRecord) -> Result<AuditId> {
+            let (id,): (i64,) = sqlx::query_as(
+                "INSERT INTO AuditLog
+                (time, actor_id, fief_id, action, target, before_value, after_value)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING id",
+            )
+            .bind(chrono::Utc::now())
+            .bind(record.actor.map(|a| a.0))
+            .bind(record.fief_id.map(|f| f.0))
+            .bind(record.action.code())
+            .bind(record.target.as_ref().map(AuditValue::encode))
+            .bind(record.before.as_ref().map(AuditValue::encode))
+            .bind(record.after.as_ref().map(AuditValue::encode))
+            .fetch_one(&mut *self.0.acquire().await?)
+            .await?;
+
+            Ok(AuditId(id))
+        }
+
+        // [R] Read
+        async fn by_fief(
+            &self,
+            fief_id: FiefId,
+            offset: usize,
+            limit: usize,
+        ) -> Result<Vec<AuditEntry>> {
+            let entries: Vec<entities::AuditEntry> = sqlx::query_as(
+                "SELECT * FROM AuditLog WHERE fief_id = $1
+                ORDER BY id DESC LIMIT $2 OFFSET $3",
+            )
+            .bind(fief_id.0)
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&mut *self.0.acquire().await?)
+            .await?;
+
+            entries.into_iter().map(conv_audit_entry).collect()
+        }
+
+        async fn count_by_fief(&self, fief_id: FiefId) -> Result<usize> {
+            let result: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM AuditLog WHERE fief_id = $1")
+                .bind(fief_id.0)
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.0 as usize)
+        }
+
+        async fn all(&self, offset: usize, limit: usize) -> Result<Vec<AuditEntry>> {
+            let entries: Vec<entities::AuditEntry> =
+                sqlx::query_as("SELECT * FROM AuditLog ORDER BY id DESC LIMIT $1 OFFSET $2")
+                    .bind(limit as i64)
+                    .bind(offset as i64)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+
+            entries.into_iter().map(conv_audit_entry).collect()
+        }
+
+        async fn count(&self) -> Result<usize> {
+            let result: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM AuditLog")
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.0 as usize)
+        }
+    }
+}
@@ -1,314 +1,626 @@
 use std::sync::Arc;
 
-use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::sqlite::SqlitePool;
+use sqlx::{Database, Sqlite};
 
+use super::Dialect;
 use crate::{
     core::{ImagePng, Position},
-    domains::{Chunk, ChunkId, FiefId},
+    domains::{
+        Chunk, ChunkId, ChunkSummary, FiefId, ImageKey, ImageKind, ImageVersion, ImageVersionId,
+        UserId,
+    },
     entities,
-    repos::traits::ChunkRepo,
+    repos::{
+        conn::Conn,
+        error::{RepoError, Result},
+        traits::{ChunkRepo, ImageStore},
+    },
 };
 
-pub struct SqlxChunkRepo(Arc<SqlitePool>);
+/// 图片本身保存在 `ImageStore` 中，`Chunks` 表只记录图片的哈希
+pub struct SqlxChunkRepo<DB: Database>(Conn<DB>, Arc<dyn ImageStore>);
 
-impl SqlxChunkRepo {
-    pub fn new(pool: Arc<SqlitePool>) -> Self {
-        Self(pool)
+impl<DB: Database> SqlxChunkRepo<DB> {
+    pub fn new(conn: impl Into<Conn<DB>>, images: Arc<dyn ImageStore>) -> Self {
+        Self(conn.into(), images)
+    }
+
+    async fn store(&self, img: Option<ImagePng>) -> Result<Option<ImageKey>> {
+        match img {
+            Some(img) => Ok(Some(self.1.put(&img).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn load(&self, key: Option<String>) -> Result<Option<ImagePng>> {
+        let Some(key) = key.map(ImageKey) else {
+            return Ok(None);
+        };
+        match self.1.get(&key).await? {
+            Some(img) => Ok(Some(img)),
+            None => Err(RepoError::storage(format!(
+                "image {} is missing from storage",
+                key.0
+            ))),
+        }
     }
 }
 
-#[allow(unused)]
-#[async_trait]
-impl ChunkRepo for SqlxChunkRepo {
-    // [C] Create
-    async fn create(&self, name: &str, fief_id: FiefId, pos: Position) -> Result<Option<ChunkId>> {
-        let result: Vec<(i64,)> = sqlx::query_as(
+impl SqlxChunkRepo<Sqlite> {
+    /// 将旧版本保存在 BLOB 列中的图片移入图片存储，返回移动的图片数量
+    pub async fn migrate_legacy_blobs(&self) -> Result<usize> {
+        let mut count = 0;
+        let ids: Vec<(i64,)> = sqlx::query_as(
             "SELECT id FROM Chunks
-            WHERE fief_id = $1 AND name = $2",
+            WHERE img_ref IS NOT NULL OR img_mask IS NOT NULL
+                OR img_diff IS NOT NULL OR img_result IS NOT NULL",
         )
-        .bind(fief_id.0)
-        .bind(name)
-        .fetch_all(&*self.0)
+        .fetch_all(&mut *self.0.acquire().await?)
         .await?;
+        for (id,) in ids {
+            type Blob = Option<Vec<u8>>;
+            let (ref_, mask, diff, result): (Blob, Blob, Blob, Blob) = sqlx::query_as(
+                "SELECT img_ref, img_mask, img_diff, img_result FROM Chunks WHERE id = $1",
+            )
+            .bind(id)
+            .fetch_one(&mut *self.0.acquire().await?)
+            .await?;
 
-        if !result.is_empty() {
-            return Ok(None);
+            count += [&ref_, &mask, &diff, &result]
+                .iter()
+                .filter(|b| b.is_some())
+                .count();
+            let mut keys = vec![];
+            for blob in [ref_, mask, diff, result] {
+                keys.push(self.store(blob.map(ImagePng::new)).await?.map(|k| k.0));
+            }
+            sqlx::query(
+                "UPDATE Chunks
+                SET ref_key = COALESCE($1, ref_key), mask_key = COALESCE($2, mask_key),
+                    diff_key = COALESCE($3, diff_key), result_key = COALESCE($4, result_key),
+                    img_ref = NULL, img_mask = NULL, img_diff = NULL, img_result = NULL
+                WHERE id = $5",
+            )
+            .bind(&keys[0])
+            .bind(&keys[1])
+            .bind(&keys[2])
+            .bind(&keys[3])
+            .bind(id)
+            .execute(&mut *self.0.acquire().await?)
+            .await?;
         }
 
-        let result = sqlx::query(
-            "INSERT INTO Chunks
-            (name, fief_id, pos_x, pos_y, diff_count)
-            VALUES ($1, $2, $3, $4, $5)",
-        )
-        .bind(name)
-        .bind(fief_id.0)
-        .bind(pos.x as i64)
-        .bind(pos.y as i64)
-        .bind(0)
-        .execute(&*self.0)
-        .await;
-
-        Ok(super::conv_create_result(result)?)
+        let ids: Vec<(i64,)> =
+            sqlx::query_as("SELECT id FROM ChunkImageVersions WHERE img IS NOT NULL")
+                .fetch_all(&mut *self.0.acquire().await?)
+                .await?;
+        for (id,) in ids {
+            let (img,): (Vec<u8>,) =
+                sqlx::query_as("SELECT img FROM ChunkImageVersions WHERE id = $1")
+                    .bind(id)
+                    .fetch_one(&mut *self.0.acquire().await?)
+                    .await?;
+            let key = self.1.put(&ImagePng::new(img)).await?;
+            sqlx::query("UPDATE ChunkImageVersions SET img_key = $1, img = NULL WHERE id = $2")
+                .bind(key.0)
+                .bind(id)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            count += 1;
+        }
+        Ok(count)
     }
+}
 
-    // [R] Read
-    // - self or fields
-    async fn chunk_by_id(&self, id: ChunkId) -> Result<Chunk> {
-        let result: entities::ChunkWithoutImgs = sqlx::query_as(
-            "SELECT id, name, fief_id, pos_x, pos_y, diff_count
-            FROM Chunks
-            WHERE id = $1",
-        )
-        .bind(id.0)
-        .fetch_one(&*self.0)
-        .await?;
+fn kind_to_i64(kind: ImageKind) -> i64 {
+    match kind {
+        ImageKind::Reference => 0,
+        ImageKind::Mask => 1,
+    }
+}
 
-        let position = Position {
-            x: result.position.x as usize,
-            y: result.position.y as usize,
-        };
-        Ok(Chunk {
-            id: ChunkId(result.id),
-            name: result.name,
-            fief_id: FiefId(result.fief_id),
-            position,
-        })
+fn kind_from_i64(kind: i64) -> Result<ImageKind> {
+    match kind {
+        0 => Ok(ImageKind::Reference),
+        1 => Ok(ImageKind::Mask),
+        _ => Err(RepoError::Decode(
+            "failed to parse image kind from database".into(),
+        )),
     }
+}
 
-    async fn chunk_by_name(&self, fief_id: FiefId, name: &str) -> Result<Chunk> {
-        let result: entities::ChunkWithoutImgs = sqlx::query_as(
-            "SELECT id, name, fief_id, pos_x, pos_y, diff_count
-            FROM Chunks
-            WHERE fief_id = $1 AND name = $2",
-        )
-        .bind(fief_id.0)
-        .bind(name)
-        .fetch_one(&*self.0)
-        .await?;
+for_each_dialect! {
+    impl SqlxChunkRepo<DB> {
+        async fn img_in_column(&self, id: ChunkId, sql: &str) -> Result<Option<ImagePng>> {
+            let result: (Option<String>,) = sqlx::query_as(sql)
+                .bind(id.0)
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
 
-        let position = Position {
-            x: result.position.x as usize,
-            y: result.position.y as usize,
-        };
-        Ok(Chunk {
-            id: ChunkId(result.id),
-            name: result.name,
-            fief_id: FiefId(result.fief_id),
-            position,
-        })
-    }
+            self.load(result.0).await
+        }
 
-    async fn fief_id(&self, id: ChunkId) -> Result<FiefId> {
-        let result: (i64,) = sqlx::query_as("SELECT fief_id FROM Chunks WHERE id = $1")
+        /// 更新参考图或遮罩图，记录新版本并按照领地的保留数量清理旧版本，
+        /// 当前版本不计入保留数量
+        async fn update_versioned_img(
+            &self,
+            id: ChunkId,
+            kind: ImageKind,
+            key: Option<ImageKey>,
+            author: Option<UserId>,
+        ) -> Result<()> {
+            let key = key.map(|k| k.0);
+            let mut conn = self.0.acquire().await?;
+            let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+
+            let sql = match kind {
+                ImageKind::Reference => "UPDATE Chunks SET ref_key = $1 WHERE id = $2",
+                ImageKind::Mask => "UPDATE Chunks SET mask_key = $1 WHERE id = $2",
+            };
+            let result = sqlx::query(sql)
+                .bind(key.as_ref())
+                .bind(id.0)
+                .execute(&mut *tx)
+                .await?;
+            if result.rows_affected() == 0 {
+                return Err(RepoError::NotFound);
+            }
+
+            sqlx::query(
+                "INSERT INTO ChunkImageVersions
+                (chunk_id, kind, img_key, author_id, created_at)
+                VALUES ($1, $2, $3, $4, $5)",
+            )
             .bind(id.0)
-            .fetch_one(&*self.0)
+            .bind(kind_to_i64(kind))
+            .bind(key)
+            .bind(author.map(|a| a.0))
+            .bind(chrono::Utc::now())
+            .execute(&mut *tx)
             .await?;
 
-        Ok(FiefId(result.0))
-    }
-
-    async fn name(&self, id: ChunkId) -> Result<String> {
-        let result: (String,) = sqlx::query_as("SELECT name FROM Chunks WHERE id = $1")
+            sqlx::query(
+                "DELETE FROM ChunkImageVersions
+                WHERE chunk_id = $1 AND kind = $2 AND id NOT IN (
+                    SELECT id FROM ChunkImageVersions
+                    WHERE chunk_id = $1 AND kind = $2
+                    ORDER BY id DESC
+                    LIMIT (
+                        SELECT CASE WHEN f.image_retention > 1 THEN f.image_retention ELSE 1 END + 1
+                        FROM Fiefs f
+                        JOIN Chunks c ON c.fief_id = f.id
+                        WHERE c.id = $1
+                    )
+                )",
+            )
             .bind(id.0)
-            .fetch_one(&*self.0)
+            .bind(kind_to_i64(kind))
+            .execute(&mut *tx)
             .await?;
 
-        Ok(result.0)
+            tx.commit().await?;
+            Ok(())
+        }
     }
 
-    async fn id(&self, fief_id: FiefId, name: &str) -> Result<ChunkId> {
-        let result: (i64,) = sqlx::query_as(
-            "SELECT id FROM Chunks
-            WHERE fief_id = $1 AND name = $2",
-        )
-        .bind(fief_id.0)
-        .bind(name)
-        .fetch_one(&*self.0)
-        .await?;
+    #[allow(unused)]
+    #[async_trait]
+    impl ChunkRepo for SqlxChunkRepo<DB> {
+        // [C] Create
+        async fn create(
+            &self,
+            name: &str,
+            fief_id: FiefId,
+            pos: Position,
+        ) -> Result<Option<ChunkId>> {
+            let result: Vec<(i64,)> = sqlx::query_as(
+                "SELECT id FROM Chunks
+                WHERE fief_id = $1 AND name = $2",
+            )
+            .bind(fief_id.0)
+            .bind(name)
+            .fetch_all(&mut *self.0.acquire().await?)
+            .await?;
 
-        Ok(ChunkId(result.0))
-    }
+            if !result.is_empty() {
+                return Ok(None);
+            }
 
-    async fn position(&self, id: ChunkId) -> Result<Position> {
-        let result: entities::Position =
-            sqlx::query_as("SELECT pos_x, pos_y FROM Chunks WHERE id = $1")
-                .bind(id.0)
-                .fetch_one(&*self.0)
-                .await?;
+            let query = sqlx::query_as(
+                "INSERT INTO Chunks
+                (name, fief_id, pos_x, pos_y, diff_count)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id",
+            )
+            .bind(name)
+            .bind(fief_id.0)
+            .bind(pos.x as i64)
+            .bind(pos.y as i64)
+            .bind(0_i64);
+            let result = DB::insert_returning(&self.0, query).await?;
 
-        Ok(Position {
-            x: result.x as usize,
-            y: result.y as usize,
-        })
-    }
+            Ok(super::conv_create_result(result)?)
+        }
 
-    async fn ref_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
-        let result: (Option<Vec<u8>>,) = sqlx::query_as("SELECT img_ref FROM Chunks WHERE id = $1")
+        // [R] Read
+        // - self or fields
+        async fn chunk_by_id(&self, id: ChunkId) -> Result<Chunk> {
+            let result: entities::ChunkWithoutImgs = sqlx::query_as(
+                "SELECT id, name, fief_id, pos_x, pos_y, diff_count
+                FROM Chunks
+                WHERE id = $1",
+            )
             .bind(id.0)
-            .fetch_one(&*self.0)
+            .fetch_one(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(result.0.map(ImagePng::new))
-    }
+            let position = Position {
+                x: result.position.x as usize,
+                y: result.position.y as usize,
+            };
+            Ok(Chunk {
+                id: ChunkId(result.id),
+                name: result.name,
+                fief_id: FiefId(result.fief_id),
+                position,
+            })
+        }
 
-    async fn mask_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
-        let result: (Option<Vec<u8>>,) =
-            sqlx::query_as("SELECT img_mask FROM Chunks WHERE id = $1")
-                .bind(id.0)
-                .fetch_one(&*self.0)
-                .await?;
+        async fn chunk_by_name(&self, fief_id: FiefId, name: &str) -> Result<Chunk> {
+            let result: entities::ChunkWithoutImgs = sqlx::query_as(
+                "SELECT id, name, fief_id, pos_x, pos_y, diff_count
+                FROM Chunks
+                WHERE fief_id = $1 AND name = $2",
+            )
+            .bind(fief_id.0)
+            .bind(name)
+            .fetch_one(&mut *self.0.acquire().await?)
+            .await?;
 
-        Ok(result.0.map(ImagePng::new))
-    }
+            let position = Position {
+                x: result.position.x as usize,
+                y: result.position.y as usize,
+            };
+            Ok(Chunk {
+                id: ChunkId(result.id),
+                name: result.name,
+                fief_id: FiefId(result.fief_id),
+                position,
+            })
+        }
 
-    async fn result_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
-        let result: (Option<Vec<u8>>,) =
-            sqlx::query_as("SELECT img_result FROM Chunks WHERE id = $1")
+        async fn summaries_by_fief(&self, fief_id: FiefId) -> Result<Vec<ChunkSummary>> {
+            let summaries: Vec<entities::ChunkSummary> = sqlx::query_as(
+                "SELECT id, name, fief_id, pos_x, pos_y, diff_count,
+                ref_key IS NOT NULL AS has_ref, mask_key IS NOT NULL AS has_mask
+                FROM Chunks
+                WHERE fief_id = $1
+                ORDER BY id",
+            )
+            .bind(fief_id.0)
+            .fetch_all(&mut *self.0.acquire().await?)
+            .await?;
+
+            Ok(summaries
+                .into_iter()
+                .map(|s| ChunkSummary {
+                    chunk: Chunk {
+                        id: ChunkId(s.chunk.id),
+                        name: s.chunk.name,
+                        fief_id: FiefId(s.chunk.fief_id),
+                        position: Position {
+                            x: s.chunk.position.x as usize,
+                            y: s.chunk.position.y as usize,
+                        },
+                    },
+                    diff_count: s.chunk.diff_count as usize,
+                    has_ref: s.has_ref,
+                    has_mask: s.has_mask,
+                })
+                .collect())
+        }
+
+        async fn names_by_fief(&self, fief_id: FiefId) -> Result<Vec<String>> {
+            Ok(
+                sqlx::query_as("SELECT name FROM Chunks WHERE fief_id = $1 ORDER BY name")
+                    .bind(fief_id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?
+                    .into_iter()
+                    .map(|n: (String,)| n.0)
+                    .collect(),
+            )
+        }
+
+        async fn fief_id(&self, id: ChunkId) -> Result<FiefId> {
+            let result: (i64,) = sqlx::query_as("SELECT fief_id FROM Chunks WHERE id = $1")
                 .bind(id.0)
-                .fetch_one(&*self.0)
+                .fetch_one(&mut *self.0.acquire().await?)
                 .await?;
 
-        Ok(result.0.map(ImagePng::new))
-    }
+            Ok(FiefId(result.0))
+        }
 
-    async fn diff_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
-        let result: (Option<Vec<u8>>,) =
-            sqlx::query_as("SELECT img_diff FROM Chunks WHERE id = $1")
+        async fn name(&self, id: ChunkId) -> Result<String> {
+            let result: (String,) = sqlx::query_as("SELECT name FROM Chunks WHERE id = $1")
                 .bind(id.0)
-                .fetch_one(&*self.0)
+                .fetch_one(&mut *self.0.acquire().await?)
                 .await?;
 
-        Ok(result.0.map(ImagePng::new))
-    }
+            Ok(result.0)
+        }
 
-    async fn diff_count(&self, id: ChunkId) -> Result<usize> {
-        let result: (i64,) = sqlx::query_as("SELECT diff_count FROM Chunks WHERE id = $1")
-            .bind(id.0)
-            .fetch_one(&*self.0)
+        async fn id(&self, fief_id: FiefId, name: &str) -> Result<ChunkId> {
+            let result: (i64,) = sqlx::query_as(
+                "SELECT id FROM Chunks
+                WHERE fief_id = $1 AND name = $2",
+            )
+            .bind(fief_id.0)
+            .bind(name)
+            .fetch_one(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(result.0 as usize)
-    }
+            Ok(ChunkId(result.0))
+        }
 
-    // - related
-    // *PASS*
+        async fn position(&self, id: ChunkId) -> Result<Position> {
+            let result: entities::Position =
+                sqlx::query_as("SELECT pos_x, pos_y FROM Chunks WHERE id = $1")
+                    .bind(id.0)
+                    .fetch_one(&mut *self.0.acquire().await?)
+                    .await?;
+
+            Ok(Position {
+                x: result.x as usize,
+                y: result.y as usize,
+            })
+        }
 
-    // [U] Update
-    // - self or fields
-    async fn update_ref_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()> {
-        sqlx::query("UPDATE Chunks SET img_ref = $1 WHERE id = $2")
-            .bind(img.map(ImagePng::into_inner))
-            .bind(id.0)
-            .execute(&*self.0)
-            .await?;
+        async fn ref_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+            self.img_in_column(id, "SELECT ref_key FROM Chunks WHERE id = $1")
+                .await
+        }
 
-        Ok(())
-    }
+        async fn mask_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+            self.img_in_column(id, "SELECT mask_key FROM Chunks WHERE id = $1")
+                .await
+        }
+
+        async fn result_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+            self.img_in_column(id, "SELECT result_key FROM Chunks WHERE id = $1")
+                .await
+        }
+
+        async fn current_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+            self.img_in_column(id, "SELECT curr_key FROM Chunks WHERE id = $1")
+                .await
+        }
 
-    async fn update_mask_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()> {
-        sqlx::query("UPDATE Chunks SET img_mask = $1 WHERE id = $2")
-            .bind(img.map(ImagePng::into_inner))
+        async fn diff_img(&self, id: ChunkId) -> Result<Option<ImagePng>> {
+            self.img_in_column(id, "SELECT diff_key FROM Chunks WHERE id = $1")
+                .await
+        }
+
+        async fn diff_count(&self, id: ChunkId) -> Result<usize> {
+            let result: (i64,) = sqlx::query_as("SELECT diff_count FROM Chunks WHERE id = $1")
+                .bind(id.0)
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.0 as usize)
+        }
+
+        async fn image_versions(&self, id: ChunkId, kind: ImageKind) -> Result<Vec<ImageVersion>> {
+            let versions: Vec<entities::ChunkImageVersion> = sqlx::query_as(
+                "SELECT id, chunk_id, kind, img_key IS NULL AS is_empty, author_id, created_at
+                FROM ChunkImageVersions
+                WHERE chunk_id = $1 AND kind = $2
+                ORDER BY id DESC",
+            )
             .bind(id.0)
-            .execute(&*self.0)
+            .bind(kind_to_i64(kind))
+            .fetch_all(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(())
-    }
+            Ok(versions
+                .into_iter()
+                .map(|v| ImageVersion {
+                    id: ImageVersionId(v.id),
+                    chunk_id: ChunkId(v.chunk_id),
+                    kind,
+                    is_empty: v.is_empty,
+                    author: v.author_id.map(UserId),
+                    created_at: v.created_at,
+                })
+                .collect())
+        }
 
-    async fn update_result_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()> {
-        sqlx::query("UPDATE Chunks SET img_result = $1 WHERE id = $2")
-            .bind(img.map(ImagePng::into_inner))
-            .bind(id.0)
-            .execute(&*self.0)
+        async fn version_img(&self, version_id: ImageVersionId) -> Result<Option<ImagePng>> {
+            let result: (Option<String>,) =
+                sqlx::query_as("SELECT img_key FROM ChunkImageVersions WHERE id = $1")
+                    .bind(version_id.0)
+                    .fetch_one(&mut *self.0.acquire().await?)
+                    .await?;
+
+            self.load(result.0).await
+        }
+
+        // - related
+        async fn referenced_image_keys(&self) -> Result<Vec<ImageKey>> {
+            let result: Vec<(String,)> = sqlx::query_as(
+                "SELECT ref_key FROM Chunks WHERE ref_key IS NOT NULL
+                UNION SELECT mask_key FROM Chunks WHERE mask_key IS NOT NULL
+                UNION SELECT diff_key FROM Chunks WHERE diff_key IS NOT NULL
+                UNION SELECT result_key FROM Chunks WHERE result_key IS NOT NULL
+                UNION SELECT curr_key FROM Chunks WHERE curr_key IS NOT NULL
+                UNION SELECT img_key FROM ChunkImageVersions WHERE img_key IS NOT NULL",
+            )
+            .fetch_all(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(())
-    }
+            Ok(result.into_iter().map(|r| ImageKey(r.0)).collect())
+        }
 
-    async fn update_diff(&self, id: ChunkId, img: Option<ImagePng>, count: usize) -> Result<()> {
-        sqlx::query(
-            "UPDATE Chunks
-            SET img_diff = $1, diff_count = $2
-            WHERE id = $3",
-        )
-        .bind(img.map(ImagePng::into_inner))
-        .bind(count as i64)
-        .bind(id.0)
-        .execute(&*self.0)
-        .await?;
+        // [U] Update
+        // - self or fields
+        async fn update_ref_img(
+            &self,
+            id: ChunkId,
+            img: Option<ImagePng>,
+            author: Option<UserId>,
+        ) -> Result<()> {
+            let key = self.store(img).await?;
+            self.update_versioned_img(id, ImageKind::Reference, key, author)
+                .await
+        }
 
-        Ok(())
-    }
+        async fn update_mask_img(
+            &self,
+            id: ChunkId,
+            img: Option<ImagePng>,
+            author: Option<UserId>,
+        ) -> Result<()> {
+            let key = self.store(img).await?;
+            self.update_versioned_img(id, ImageKind::Mask, key, author)
+                .await
+        }
 
-    async fn set_position(&self, id: ChunkId, pos: Position) -> Result<()> {
-        sqlx::query(
-            "UPDATE Chunks
-            SET pos_x = $1, pos_y = $2
-            WHERE id = $3",
-        )
-        .bind(pos.x as i64)
-        .bind(pos.y as i64)
-        .bind(id.0)
-        .execute(&*self.0)
-        .await?;
+        async fn update_result_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()> {
+            let key = self.store(img).await?;
+            sqlx::query("UPDATE Chunks SET result_key = $1 WHERE id = $2")
+                .bind(key.map(|k| k.0))
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
 
-        Ok(())
-    }
+            Ok(())
+        }
 
-    async fn rename(&self, id: ChunkId, name: &str) -> Result<()> {
-        let result: Vec<(i64,)> = sqlx::query_as(
-            "SELECT id FROM Chunks
-            WHERE fief_id = (SELECT fief_id FROM Chunks WHERE id = $1) AND name = $2",
-        )
-        .bind(id.0)
-        .bind(name)
-        .fetch_all(&*self.0)
-        .await?;
+        async fn update_current_img(&self, id: ChunkId, img: Option<ImagePng>) -> Result<()> {
+            let key = self.store(img).await?;
+            sqlx::query("UPDATE Chunks SET curr_key = $1 WHERE id = $2")
+                .bind(key.map(|k| k.0))
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
 
-        if !result.is_empty() {
-            return Err(anyhow::anyhow!("name {name} already exists"));
+            Ok(())
         }
 
-        sqlx::query("UPDATE Chunks SET name = $1 WHERE id = $2")
-            .bind(name)
+        async fn update_diff(
+            &self,
+            id: ChunkId,
+            img: Option<ImagePng>,
+            count: usize,
+        ) -> Result<()> {
+            let key = self.store(img).await?;
+            sqlx::query(
+                "UPDATE Chunks
+                SET diff_key = $1, diff_count = $2
+                WHERE id = $3",
+            )
+            .bind(key.map(|k| k.0))
+            .bind(count as i64)
             .bind(id.0)
-            .execute(&*self.0)
+            .execute(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(())
-    }
-
-    // - related
-    // *PASS*
+            Ok(())
+        }
 
-    // [D] Delete
-    async fn remove_by_id(&self, id: ChunkId) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM Chunks WHERE id = $1")
+        async fn set_position(&self, id: ChunkId, pos: Position) -> Result<()> {
+            sqlx::query(
+                "UPDATE Chunks
+                SET pos_x = $1, pos_y = $2
+                WHERE id = $3",
+            )
+            .bind(pos.x as i64)
+            .bind(pos.y as i64)
             .bind(id.0)
-            .execute(&*self.0)
+            .execute(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(result.rows_affected() == 1)
-    }
+            Ok(())
+        }
 
-    async fn remove_by_name(&self, fief_id: FiefId, name: &str) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM Chunks WHERE fief_id = $1 AND name = $2")
-            .bind(fief_id.0)
+        async fn rename(&self, id: ChunkId, name: &str) -> Result<()> {
+            let result: Vec<(i64,)> = sqlx::query_as(
+                "SELECT id FROM Chunks
+                WHERE fief_id = (SELECT fief_id FROM Chunks WHERE id = $1) AND name = $2",
+            )
+            .bind(id.0)
             .bind(name)
-            .execute(&*self.0)
+            .fetch_all(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(result.rows_affected() == 1)
-    }
+            if !result.is_empty() {
+                return Err(RepoError::Conflict);
+            }
 
-    async fn remove_all_by_fief(&self, fief_id: FiefId) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM Chunks WHERE fief_id = $1")
-            .bind(fief_id.0)
-            .execute(&*self.0)
+            sqlx::query("UPDATE Chunks SET name = $1 WHERE id = $2")
+                .bind(name)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(())
+        }
+
+        async fn rollback_img(
+            &self,
+            id: ChunkId,
+            version_id: ImageVersionId,
+            author: Option<UserId>,
+        ) -> Result<bool> {
+            let result: Option<(i64, Option<String>)> = sqlx::query_as(
+                "SELECT kind, img_key FROM ChunkImageVersions
+                WHERE id = $1 AND chunk_id = $2",
+            )
+            .bind(version_id.0)
+            .bind(id.0)
+            .fetch_optional(&mut *self.0.acquire().await?)
             .await?;
 
-        Ok(result.rows_affected() >= 1)
+            let Some((kind, key)) = result else {
+                return Ok(false);
+            };
+            let kind = kind_from_i64(kind)?;
+            self.update_versioned_img(id, kind, key.map(ImageKey), author)
+                .await?;
+            Ok(true)
+        }
+
+        // - related
+        // *PASS*
+
+        // [D] Delete
+        async fn remove_by_id(&self, id: ChunkId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Chunks WHERE id = $1")
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_by_name(&self, fief_id: FiefId, name: &str) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Chunks WHERE fief_id = $1 AND name = $2")
+                .bind(fief_id.0)
+                .bind(name)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_all_by_fief(&self, fief_id: FiefId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Chunks WHERE fief_id = $1")
+                .bind(fief_id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.rows_affected() >= 1)
+        }
     }
 }
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use sqlx::Database;
+
+use crate::{
+    domains::{Dashboard, DashboardId, FiefId},
+    entities,
+    repos::{conn::Conn, error::Result, traits::DashboardRepo},
+};
+
+pub struct SqlxDashboardRepo<DB: Database>(Conn<DB>);
+
+impl<DB: Database> SqlxDashboardRepo<DB> {
+    pub fn new(conn: impl Into<Conn<DB>>) -> Self {
+        Self(conn.into())
+    }
+}
+
+fn conv_dashboard(d: entities::Dashboard) -> Dashboard {
+    Dashboard {
+        id: DashboardId(d.id),
+        fief_id: d.fief_id.map(FiefId),
+        channel_id: d.channel_id,
+        message_id: d.message_id,
+    }
+}
+
+for_each_dialect! {
+    #[async_trait]
+    impl DashboardRepo for SqlxDashboardRepo<DB> {
+        // [C] Create
+        async fn create(
+            &self,
+            fief_id: Option<FiefId>,
+            channel_id: i64,
+            message_id: i64,
+        ) -> Result<DashboardId> {
+            let (id,): (i64,) = sqlx::query_as(
+                "INSERT INTO Dashboards (fief_id, channel_id, message_id) VALUES ($1, $2, $3)
+                RETURNING id",
+            )
+            .bind(fief_id.map(|f| f.0))
+            .bind(channel_id)
+            .bind(message_id)
+            .fetch_one(&mut *self.0.acquire().await?)
+            .await?;
+
+            Ok(DashboardId(id))
+        }
+
+        // [R] Read
+        async fn all(&self) -> Result<Vec<Dashboard>> {
+            let dashboards: Vec<entities::Dashboard> =
+                sqlx::query_as("SELECT * FROM Dashboards ORDER BY id")
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+
+            Ok(dashboards.into_iter().map(conv_dashboard).collect())
+        }
+
+        async fn showing(&self, fief_id: FiefId) -> Result<Vec<Dashboard>> {
+            let dashboards: Vec<entities::Dashboard> = sqlx::query_as(
+                "SELECT * FROM Dashboards WHERE fief_id IS NULL OR fief_id = $1 ORDER BY id",
+            )
+            .bind(fief_id.0)
+            .fetch_all(&mut *self.0.acquire().await?)
+            .await?;
+
+            Ok(dashboards.into_iter().map(conv_dashboard).collect())
+        }
+
+        // [D] Delete
+        async fn remove(&self, id: DashboardId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Dashboards WHERE id = $1")
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.rows_affected() > 0)
+        }
+    }
+}
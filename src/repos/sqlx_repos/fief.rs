@@ -1,283 +1,683 @@
-use std::sync::Arc;
-
-use anyhow::Result;
 use async_trait::async_trait;
 use chrono::TimeZone;
-use sqlx::sqlite::SqlitePool;
+use sqlx::Database;
 
+use super::Dialect;
 use crate::{
     cfg,
-    domains::{ChunkId, Fief, FiefId, UserId},
+    domains::{
+        ChunkId, Fief, FiefId, FiefRole, FiefRoleId, FiefSummary, MaintenanceMode, MaintenanceRule,
+        MaintenanceWindow, MaintenanceWindowId, Member, Permissions, RoleGrant, RoleId, UserId,
+    },
     entities,
-    repos::traits::FiefRepo,
+    repos::{
+        conn::Conn,
+        error::{RepoError, Result},
+        traits::FiefRepo,
+    },
 };
 
-pub struct SqlxFiefRepo(Arc<SqlitePool>);
+pub struct SqlxFiefRepo<DB: Database>(Conn<DB>);
 
-impl SqlxFiefRepo {
-    pub fn new(pool: Arc<SqlitePool>) -> Self {
-        Self(pool)
+impl<DB: Database> SqlxFiefRepo<DB> {
+    pub fn new(conn: impl Into<Conn<DB>>) -> Self {
+        Self(conn.into())
     }
 }
 
-#[allow(unused)]
-#[async_trait]
-impl FiefRepo for SqlxFiefRepo {
-    // [C] Create
-    async fn create(
-        &self,
-        name: &str,
-        check_interval: Option<chrono::Duration>,
-    ) -> Result<Option<FiefId>> {
-        let check_interval = check_interval
-            .map(|i| i.num_minutes())
-            .unwrap_or(cfg().check.default_interval_min as i64);
-        let min_interval = cfg().check.minimum_interval_min as i64;
-        let ago = chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap();
-
-        let result = sqlx::query(
-            "INSERT INTO Fiefs
-            (name, check_interval_min, last_check, skip_check_until, should_check_now)
-            VALUES ($1, $2, $3, $4, $5)",
-        )
-        .bind(name)
-        .bind(check_interval.max(min_interval))
-        .bind(ago)
-        .bind(ago)
-        .bind(false)
-        .execute(&*self.0)
-        .await;
-
-        Ok(super::conv_create_result(result)?)
+fn conv_fief(f: entities::Fief) -> Fief {
+    Fief {
+        id: FiefId(f.id),
+        name: f.name,
+        check_interval: chrono::Duration::minutes(f.check_interval_min),
+        last_check: f.last_check,
+        last_check_failed: f.last_check_failed,
+        skip_check_until: f.skip_check_until,
+        adaptive: f.adaptive,
+        effective_interval: chrono::Duration::minutes(f.effective_interval_min),
+        clean_streak: f.clean_streak as usize,
+        image_retention: f.image_retention as usize,
+        owner: f.owner_id.map(UserId),
     }
+}
 
-    // [R] Read
-    // - self or fields
-    async fn name(&self, id: FiefId) -> Result<String> {
-        let result: (String,) = sqlx::query_as("SELECT name FROM Fiefs WHERE id = $1")
-            .bind(id.0)
-            .fetch_one(&*self.0)
-            .await?;
+fn conv_member(m: entities::Member) -> Result<Member> {
+    let permissions = Permissions::from_bits(m.permissions).ok_or(RepoError::Decode(
+        "failed to parse permissions from database".into(),
+    ))?;
+    Ok(Member {
+        user_id: UserId(m.user_id),
+        fief_id: FiefId(m.fief_id),
+        permissions,
+    })
+}
 
-        Ok(result.0)
-    }
+fn conv_role_grant(g: entities::RoleGrant) -> Result<RoleGrant> {
+    let permissions = Permissions::from_bits(g.permissions).ok_or(RepoError::Decode(
+        "failed to parse permissions from database".into(),
+    ))?;
+    Ok(RoleGrant {
+        role_id: RoleId(g.role_id),
+        fief_id: FiefId(g.fief_id),
+        permissions,
+    })
+}
 
-    async fn id(&self, name: &str) -> Result<FiefId> {
-        let result: (i64,) = sqlx::query_as("SELECT id FROM Fiefs WHERE name = $1")
-            .bind(name)
-            .fetch_one(&*self.0)
-            .await?;
+pub(super) fn conv_fief_role(r: entities::FiefRole) -> Result<FiefRole> {
+    let permissions = Permissions::from_bits(r.permissions).ok_or(RepoError::Decode(
+        "failed to parse permissions from database".into(),
+    ))?;
+    Ok(FiefRole {
+        id: FiefRoleId(r.id),
+        fief_id: FiefId(r.fief_id),
+        name: r.name,
+        permissions,
+    })
+}
 
-        Ok(FiefId(result.0))
-    }
+fn conv_maintenance_window(w: entities::MaintenanceWindow) -> Result<MaintenanceWindow> {
+    let weekday = match w.weekday {
+        Some(d) => Some(
+            chrono::Weekday::try_from(d as u8)
+                .map_err(|_| RepoError::Decode("failed to parse weekday from database".into()))?,
+        ),
+        None => None,
+    };
+    let start = chrono::NaiveTime::from_num_seconds_from_midnight_opt(w.start_min as u32 * 60, 0)
+        .ok_or(RepoError::Decode(
+        "failed to parse start time from database".into(),
+    ))?;
+    let mode = match w.mode {
+        0 => MaintenanceMode::SkipCheck,
+        1 => MaintenanceMode::MuteAlerts,
+        _ => {
+            return Err(RepoError::Decode(
+                "failed to parse maintenance mode from database".into(),
+            ));
+        }
+    };
+
+    Ok(MaintenanceWindow {
+        id: MaintenanceWindowId(w.id),
+        fief_id: FiefId(w.fief_id),
+        rule: MaintenanceRule {
+            weekday,
+            start,
+            duration: chrono::Duration::minutes(w.duration_min),
+            mode,
+        },
+    })
+}
 
-    async fn fief_by_id(&self, id: FiefId) -> Result<Fief> {
-        let r: entities::Fief = sqlx::query_as("SELECT * FROM Fiefs WHERE id = $1")
+for_each_dialect! {
+    #[allow(unused)]
+    #[async_trait]
+    impl FiefRepo for SqlxFiefRepo<DB> {
+        // [C] Create
+        async fn create(
+            &self,
+            name: &str,
+            check_interval: Option<chrono::Duration>,
+        ) -> Result<Option<FiefId>> {
+            let check_interval = check_interval
+                .map(|i| i.num_minutes())
+                .unwrap_or(cfg().check.default_interval_min as i64);
+            let min_interval = cfg().check.minimum_interval_min as i64;
+            let ago = chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap();
+
+            let query = sqlx::query_as(
+                "INSERT INTO Fiefs
+                (name, check_interval_min, last_check, skip_check_until, should_check_now,
+                effective_interval_min)
+                VALUES ($1, $2, $3, $4, $5, $2)
+                RETURNING id",
+            )
+            .bind(name)
+            .bind(check_interval.max(min_interval))
+            .bind(ago)
+            .bind(ago)
+            .bind(false);
+            let result = DB::insert_returning(&self.0, query).await?;
+
+            Ok(super::conv_create_result(result)?)
+        }
+
+        async fn grant_role(
+            &self,
+            id: FiefId,
+            role_id: RoleId,
+            permissions: Option<Permissions>,
+        ) -> Result<bool> {
+            let permissions = permissions.unwrap_or(Permissions::NONE);
+            let query = sqlx::query_as(
+                "INSERT INTO RoleGrants (role_id, fief_id, permissions) VALUES ($1, $2, $3)
+                RETURNING role_id",
+            )
+            .bind(role_id.0)
+            .bind(id.0)
+            .bind(permissions.bits());
+            let result = DB::insert_returning(&self.0, query).await?;
+            Ok(super::conv_create_result::<i64>(result)?.is_some())
+        }
+
+        async fn create_fief_role(
+            &self,
+            id: FiefId,
+            name: &str,
+            permissions: Permissions,
+        ) -> Result<Option<FiefRoleId>> {
+            let query = sqlx::query_as(
+                "INSERT INTO FiefRoles (fief_id, name, permissions) VALUES ($1, $2, $3)
+                RETURNING id",
+            )
             .bind(id.0)
-            .fetch_one(&*self.0)
-            .await?;
-
-        Ok(Fief {
-            id: FiefId(r.id),
-            name: r.name,
-            check_interval: chrono::Duration::minutes(r.check_interval_min),
-            last_check: r.last_check,
-            skip_check_until: r.skip_check_until,
-        })
-    }
-
-    async fn fief_by_name(&self, name: &str) -> Result<Fief> {
-        let r: entities::Fief = sqlx::query_as("SELECT * FROM Fiefs WHERE name = $1")
             .bind(name)
-            .fetch_one(&*self.0)
-            .await?;
+            .bind(permissions.bits());
+            let result = DB::insert_returning(&self.0, query).await?;
+            Ok(super::conv_create_result(result)?)
+        }
+
+        // [R] Read
+        // - self or fields
+        async fn name(&self, id: FiefId) -> Result<String> {
+            let result: (String,) = sqlx::query_as("SELECT name FROM Fiefs WHERE id = $1")
+                .bind(id.0)
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
 
-        Ok(Fief {
-            id: FiefId(r.id),
-            name: r.name,
-            check_interval: chrono::Duration::minutes(r.check_interval_min),
-            last_check: r.last_check,
-            skip_check_until: r.skip_check_until,
-        })
-    }
+            Ok(result.0)
+        }
 
-    async fn fiefs_to_check(&self) -> Result<Vec<Fief>> {
-        Ok(sqlx::query_as(
-            "SELECT * FROM Fiefs
-            WHERE should_check_now = TRUE OR
-            (datetime(last_check, '+' || check_interval_min || ' minutes') < datetime('now')
-            AND (skip_check_until IS NULL OR datetime(skip_check_until) < datetime('now')))",
-        )
-        .fetch_all(&*self.0)
-        .await?
-        .into_iter()
-        .map(|f: entities::Fief| Fief {
-            id: FiefId(f.id),
-            name: f.name,
-            check_interval: chrono::Duration::minutes(f.check_interval_min),
-            last_check: f.last_check,
-            skip_check_until: f.skip_check_until,
-        })
-        .collect())
-    }
+        async fn id(&self, name: &str) -> Result<FiefId> {
+            let result: (i64,) = sqlx::query_as("SELECT id FROM Fiefs WHERE name = $1")
+                .bind(name)
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
 
-    async fn all(&self) -> Result<Vec<Fief>> {
-        Ok(sqlx::query_as("SELECT * FROM Fiefs")
-            .fetch_all(&*self.0)
-            .await?
-            .into_iter()
-            .map(|f: entities::Fief| Fief {
-                id: FiefId(f.id),
-                name: f.name,
-                check_interval: chrono::Duration::minutes(f.check_interval_min),
-                last_check: f.last_check,
-                skip_check_until: f.skip_check_until,
-            })
-            .collect())
-    }
+            Ok(FiefId(result.0))
+        }
 
-    // - related
-    async fn members(&self, id: FiefId) -> Result<Vec<UserId>> {
-        Ok(
-            sqlx::query_as("SELECT user_id FROM Members WHERE fief_id = $1")
+        async fn fief_by_id(&self, id: FiefId) -> Result<Fief> {
+            let r: entities::Fief = sqlx::query_as("SELECT * FROM Fiefs WHERE id = $1")
                 .bind(id.0)
-                .fetch_all(&*self.0)
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(conv_fief(r))
+        }
+
+        async fn fief_by_name(&self, name: &str) -> Result<Fief> {
+            let r: entities::Fief = sqlx::query_as("SELECT * FROM Fiefs WHERE name = $1")
+                .bind(name)
+                .fetch_one(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(conv_fief(r))
+        }
+
+        async fn fiefs_to_check(&self) -> Result<Vec<Fief>> {
+            let sql = format!(
+                "SELECT * FROM Fiefs WHERE should_check_now = TRUE OR ({})",
+                DB::FIEF_DUE
+            );
+            let fiefs: Vec<entities::Fief> = sqlx::query_as(&sql)
+                .fetch_all(&mut *self.0.acquire().await?)
+                .await?;
+
+            let windows: Vec<entities::MaintenanceWindow> =
+                sqlx::query_as("SELECT * FROM MaintenanceWindows WHERE mode = $1")
+                    .bind(0_i64)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+            let now = chrono::Utc::now();
+            let mut skipped = vec![];
+            for w in windows {
+                let w = conv_maintenance_window(w)?;
+                if w.rule.contains(now) {
+                    skipped.push(w.fief_id.0);
+                }
+            }
+
+            Ok(fiefs
+                .into_iter()
+                .filter(|f| f.should_check_now || !skipped.contains(&f.id))
+                .map(conv_fief)
+                .collect())
+        }
+
+        async fn all(&self) -> Result<Vec<Fief>> {
+            Ok(sqlx::query_as("SELECT * FROM Fiefs")
+                .fetch_all(&mut *self.0.acquire().await?)
                 .await?
                 .into_iter()
-                .map(|uid: (i64,)| UserId(uid.0))
-                .collect(),
-        )
-    }
-
-    async fn chunks(&self, id: FiefId) -> Result<Vec<ChunkId>> {
-        Ok(sqlx::query_as("SELECT id FROM Chunks WHERE fief_id = $1")
-            .bind(id.0)
-            .fetch_all(&*self.0)
-            .await?
-            .into_iter()
-            .map(|cid: (i64,)| ChunkId(cid.0))
-            .collect())
-    }
-
-    async fn chunk_count(&self, id: FiefId) -> Result<usize> {
-        let result: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) AS chunk_count
-            FROM Chunks
-            WHERE fief_id = $1",
-        )
-        .bind(id.0)
-        .fetch_one(&*self.0)
-        .await?;
-
-        Ok(result.0 as usize)
-    }
-
-    async fn diff_count(&self, id: FiefId) -> Result<usize> {
-        let result: (i64,) = sqlx::query_as(
-            "SELECT COALESCE(SUM(diff_count), 0) AS total_diff_count
-            FROM Chunks
-            WHERE fief_id = $1",
-        )
-        .bind(id.0)
-        .fetch_one(&*self.0)
-        .await?;
-
-        Ok(result.0 as usize)
-    }
-
-    // [U] Update
-    // - self or fields
-    async fn update_last_check(
-        &self,
-        id: FiefId,
-        date: Option<chrono::DateTime<chrono::Utc>>,
-    ) -> Result<()> {
-        let date = date.unwrap_or(chrono::Utc::now());
-        sqlx::query("UPDATE Fiefs SET last_check = $1, should_check_now = FALSE WHERE id = $2")
-            .bind(date)
-            .bind(id.0)
-            .execute(&*self.0)
+                .map(conv_fief)
+                .collect())
+        }
+
+        async fn summaries(&self) -> Result<Vec<FiefSummary>> {
+            let summaries: Vec<entities::FiefSummary> = sqlx::query_as(
+                "SELECT f.*,
+                (SELECT COUNT(*) FROM Chunks c WHERE c.fief_id = f.id) AS chunk_count,
+                (SELECT COUNT(*) FROM Members m WHERE m.fief_id = f.id) AS member_count,
+                (SELECT CAST(COALESCE(SUM(c.diff_count), 0) AS BIGINT) FROM Chunks c
+                    WHERE c.fief_id = f.id)
+                    AS diff_count
+                FROM Fiefs f
+                ORDER BY f.id",
+            )
+            .fetch_all(&mut *self.0.acquire().await?)
             .await?;
-        Ok(())
-    }
 
-    async fn set_check_interval(&self, id: FiefId, interval: chrono::Duration) -> Result<()> {
-        let min_interval = cfg().check.minimum_interval_min as i64;
-        sqlx::query("UPDATE Fiefs SET check_interval_min = $1 WHERE id = $2")
-            .bind(interval.num_minutes().max(min_interval))
+            Ok(summaries
+                .into_iter()
+                .map(|s| FiefSummary {
+                    fief: conv_fief(s.fief),
+                    chunk_count: s.chunk_count as usize,
+                    member_count: s.member_count as usize,
+                    diff_count: s.diff_count as usize,
+                })
+                .collect())
+        }
+
+        // - related
+        async fn names(&self) -> Result<Vec<String>> {
+            Ok(sqlx::query_as("SELECT name FROM Fiefs ORDER BY name")
+                .fetch_all(&mut *self.0.acquire().await?)
+                .await?
+                .into_iter()
+                .map(|n: (String,)| n.0)
+                .collect())
+        }
+
+        async fn names_of(&self, id: UserId, roles: &[RoleId]) -> Result<Vec<String>> {
+            let mut query =
+                sqlx::QueryBuilder::<DB>::new("SELECT name FROM Fiefs f WHERE f.owner_id = ");
+            query.push_bind(id.0);
+            query.push(
+                " OR EXISTS (SELECT 1 FROM Members m WHERE m.fief_id = f.id AND m.user_id = ",
+            );
+            query.push_bind(id.0);
+            query.push(")");
+            if !roles.is_empty() {
+                query.push(
+                    " OR EXISTS (SELECT 1 FROM RoleGrants r WHERE r.fief_id = f.id AND r.role_id \
+                     IN (",
+                );
+                let mut list = query.separated(", ");
+                for role in roles {
+                    list.push_bind(role.0);
+                }
+                query.push("))");
+            }
+            query.push(" ORDER BY name");
+
+            Ok(query
+                .build_query_as()
+                .fetch_all(&mut *self.0.acquire().await?)
+                .await?
+                .into_iter()
+                .map(|n: (String,)| n.0)
+                .collect())
+        }
+
+        async fn members(&self, id: FiefId) -> Result<Vec<UserId>> {
+            Ok(
+                sqlx::query_as("SELECT user_id FROM Members WHERE fief_id = $1")
+                    .bind(id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?
+                    .into_iter()
+                    .map(|uid: (i64,)| UserId(uid.0))
+                    .collect(),
+            )
+        }
+
+        async fn members_with_perms(&self, id: FiefId) -> Result<Vec<Member>> {
+            let members: Vec<entities::Member> =
+                sqlx::query_as("SELECT * FROM Members WHERE fief_id = $1 ORDER BY user_id")
+                    .bind(id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+
+            members.into_iter().map(conv_member).collect()
+        }
+
+        async fn all_members(&self) -> Result<Vec<Member>> {
+            let members: Vec<entities::Member> =
+                sqlx::query_as("SELECT * FROM Members ORDER BY fief_id, user_id")
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+
+            members.into_iter().map(conv_member).collect()
+        }
+
+        async fn chunks(&self, id: FiefId) -> Result<Vec<ChunkId>> {
+            Ok(sqlx::query_as("SELECT id FROM Chunks WHERE fief_id = $1")
+                .bind(id.0)
+                .fetch_all(&mut *self.0.acquire().await?)
+                .await?
+                .into_iter()
+                .map(|cid: (i64,)| ChunkId(cid.0))
+                .collect())
+        }
+
+        async fn chunk_count(&self, id: FiefId) -> Result<usize> {
+            let result: (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) AS chunk_count
+                FROM Chunks
+                WHERE fief_id = $1",
+            )
             .bind(id.0)
-            .execute(&*self.0)
+            .fetch_one(&mut *self.0.acquire().await?)
             .await?;
-        Ok(())
-    }
 
-    async fn skip_check(&self, id: FiefId) -> Result<()> {
-        sqlx::query("UPDATE Fiefs SET skip_check_until = $1 WHERE id = $2")
-            .bind(chrono::Utc.with_ymd_and_hms(2077, 1, 1, 0, 0, 0).unwrap())
-            .bind(id.0)
-            .execute(&*self.0)
-            .await?;
-        Ok(())
-    }
+            Ok(result.0 as usize)
+        }
 
-    async fn keep_check(&self, id: FiefId) -> Result<()> {
-        sqlx::query("UPDATE Fiefs SET skip_check_until = $1 WHERE id = $2")
-            .bind(chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap())
+        async fn diff_count(&self, id: FiefId) -> Result<usize> {
+            let result: (i64,) = sqlx::query_as(
+                "SELECT CAST(COALESCE(SUM(diff_count), 0) AS BIGINT) AS total_diff_count
+                FROM Chunks
+                WHERE fief_id = $1",
+            )
             .bind(id.0)
-            .execute(&*self.0)
+            .fetch_one(&mut *self.0.acquire().await?)
             .await?;
-        Ok(())
-    }
 
-    async fn mark_should_check_now(&self, id: FiefId) -> Result<()> {
-        sqlx::query("UPDATE Fiefs SET should_check_now = TRUE WHERE id = $1")
+            Ok(result.0 as usize)
+        }
+
+        async fn maintenance_windows(&self, id: FiefId) -> Result<Vec<MaintenanceWindow>> {
+            let windows: Vec<entities::MaintenanceWindow> =
+                sqlx::query_as("SELECT * FROM MaintenanceWindows WHERE fief_id = $1 ORDER BY id")
+                    .bind(id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+
+            windows.into_iter().map(conv_maintenance_window).collect()
+        }
+
+        async fn role_grants(&self, id: FiefId) -> Result<Vec<RoleGrant>> {
+            let grants: Vec<entities::RoleGrant> =
+                sqlx::query_as("SELECT * FROM RoleGrants WHERE fief_id = $1 ORDER BY role_id")
+                    .bind(id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+
+            grants.into_iter().map(conv_role_grant).collect()
+        }
+
+        async fn fief_roles(&self, id: FiefId) -> Result<Vec<FiefRole>> {
+            let roles: Vec<entities::FiefRole> =
+                sqlx::query_as("SELECT * FROM FiefRoles WHERE fief_id = $1 ORDER BY id")
+                    .bind(id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+
+            roles.into_iter().map(conv_fief_role).collect()
+        }
+
+        async fn fief_role_by_name(&self, id: FiefId, name: &str) -> Result<FiefRole> {
+            let role: entities::FiefRole =
+                sqlx::query_as("SELECT * FROM FiefRoles WHERE fief_id = $1 AND name = $2")
+                    .bind(id.0)
+                    .bind(name)
+                    .fetch_one(&mut *self.0.acquire().await?)
+                    .await?;
+
+            conv_fief_role(role)
+        }
+
+        // [U] Update
+        // - self or fields
+        async fn update_last_check(
+            &self,
+            id: FiefId,
+            date: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<()> {
+            let date = date.unwrap_or(chrono::Utc::now());
+            sqlx::query("UPDATE Fiefs SET last_check = $1, should_check_now = FALSE WHERE id = $2")
+                .bind(date)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn set_last_check_failed(&self, id: FiefId, failed: bool) -> Result<()> {
+            sqlx::query("UPDATE Fiefs SET last_check_failed = $1 WHERE id = $2")
+                .bind(failed)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn set_check_interval(&self, id: FiefId, interval: chrono::Duration) -> Result<()> {
+            let min_interval = cfg().check.minimum_interval_min as i64;
+            sqlx::query(
+                "UPDATE Fiefs
+                SET check_interval_min = $1, effective_interval_min = $1, clean_streak = 0
+                WHERE id = $2",
+            )
+            .bind(interval.num_minutes().max(min_interval))
             .bind(id.0)
-            .execute(&*self.0)
+            .execute(&mut *self.0.acquire().await?)
             .await?;
-        Ok(())
-    }
-
-    async fn skip_check_for(
-        &self,
-        id: FiefId,
-        dur: chrono::Duration,
-        from: Option<chrono::DateTime<chrono::Utc>>,
-    ) -> Result<()> {
-        let datetime = from.unwrap_or(chrono::Utc::now()) + dur;
-        sqlx::query("UPDATE Fiefs SET skip_check_until = $1 WHERE id = $2")
-            .bind(datetime)
+            Ok(())
+        }
+
+        async fn set_adaptive(&self, id: FiefId, adaptive: bool) -> Result<()> {
+            sqlx::query(
+                "UPDATE Fiefs
+                SET adaptive = $1, effective_interval_min = check_interval_min, clean_streak = 0
+                WHERE id = $2",
+            )
+            .bind(adaptive)
             .bind(id.0)
-            .execute(&*self.0)
+            .execute(&mut *self.0.acquire().await?)
             .await?;
-        Ok(())
-    }
-
-    async fn rename(&self, id: FiefId, name: &str) -> Result<()> {
-        sqlx::query("UPDATE Fiefs SET name = $1 WHERE id = $2")
-            .bind(name)
+            Ok(())
+        }
+
+        async fn update_adaptive_state(
+            &self,
+            id: FiefId,
+            effective_interval: chrono::Duration,
+            clean_streak: usize,
+        ) -> Result<()> {
+            let min_interval = cfg().check.minimum_interval_min as i64;
+            sqlx::query(
+                "UPDATE Fiefs
+                SET effective_interval_min = $1, clean_streak = $2
+                WHERE id = $3",
+            )
+            .bind(effective_interval.num_minutes().max(min_interval))
+            .bind(clean_streak as i64)
             .bind(id.0)
-            .execute(&*self.0)
+            .execute(&mut *self.0.acquire().await?)
             .await?;
-        Ok(())
-    }
+            Ok(())
+        }
 
-    // - related
-    // *PASS*
+        async fn set_image_retention(&self, id: FiefId, retention: usize) -> Result<()> {
+            sqlx::query("UPDATE Fiefs SET image_retention = $1 WHERE id = $2")
+                .bind(retention.max(1) as i64)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn skip_check(&self, id: FiefId) -> Result<()> {
+            sqlx::query("UPDATE Fiefs SET skip_check_until = $1 WHERE id = $2")
+                .bind(chrono::Utc.with_ymd_and_hms(2077, 1, 1, 0, 0, 0).unwrap())
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn keep_check(&self, id: FiefId) -> Result<()> {
+            sqlx::query("UPDATE Fiefs SET skip_check_until = $1 WHERE id = $2")
+                .bind(chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap())
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
 
-    // [D] Delete
-    async fn remove_by_id(&self, id: FiefId) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM Fiefs WHERE id = $1")
+        async fn mark_should_check_now(&self, id: FiefId) -> Result<()> {
+            sqlx::query("UPDATE Fiefs SET should_check_now = TRUE WHERE id = $1")
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn skip_check_for(
+            &self,
+            id: FiefId,
+            dur: chrono::Duration,
+            from: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<()> {
+            let datetime = from.unwrap_or(chrono::Utc::now()) + dur;
+            sqlx::query("UPDATE Fiefs SET skip_check_until = $1 WHERE id = $2")
+                .bind(datetime)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn set_owner(&self, id: FiefId, owner: Option<UserId>) -> Result<()> {
+            sqlx::query("UPDATE Fiefs SET owner_id = $1 WHERE id = $2")
+                .bind(owner.map(|u| u.0))
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn rename(&self, id: FiefId, name: &str) -> Result<()> {
+            sqlx::query("UPDATE Fiefs SET name = $1 WHERE id = $2")
+                .bind(name)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        // - related
+        async fn add_maintenance_window(
+            &self,
+            id: FiefId,
+            rule: MaintenanceRule,
+        ) -> Result<Option<MaintenanceWindowId>> {
+            use chrono::Timelike;
+
+            let duration = rule.duration.num_minutes();
+            if duration <= 0 || duration > 24 * 60 {
+                return Err(RepoError::Invalid(
+                    "duration must be within 24 hours".into(),
+                ));
+            }
+
+            let query = sqlx::query_as(
+                "INSERT INTO MaintenanceWindows
+                (fief_id, weekday, start_min, duration_min, mode)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id",
+            )
             .bind(id.0)
-            .execute(&*self.0)
-            .await?;
-        Ok(result.rows_affected() == 1)
-    }
-
-    async fn remove_by_name(&self, name: &str) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM Fiefs WHERE name = $1")
-            .bind(name)
-            .execute(&*self.0)
+            .bind(rule.weekday.map(|w| w.num_days_from_monday() as i64))
+            .bind((rule.start.hour() * 60 + rule.start.minute()) as i64)
+            .bind(duration)
+            .bind(match rule.mode {
+                MaintenanceMode::SkipCheck => 0_i64,
+                MaintenanceMode::MuteAlerts => 1,
+            });
+            let result = DB::insert_returning(&self.0, query).await?;
+
+            Ok(super::conv_create_result(result)?)
+        }
+
+        async fn set_role_permissions(
+            &self,
+            id: FiefId,
+            role_id: RoleId,
+            permissions: Permissions,
+        ) -> Result<()> {
+            sqlx::query(
+                "UPDATE RoleGrants SET permissions = $1 WHERE role_id = $2 AND fief_id = $3",
+            )
+            .bind(permissions.bits())
+            .bind(role_id.0)
+            .bind(id.0)
+            .execute(&mut *self.0.acquire().await?)
             .await?;
-        Ok(result.rows_affected() == 1)
+            Ok(())
+        }
+
+        async fn set_fief_role_permissions(
+            &self,
+            role_id: FiefRoleId,
+            permissions: Permissions,
+        ) -> Result<()> {
+            sqlx::query("UPDATE FiefRoles SET permissions = $1 WHERE id = $2")
+                .bind(permissions.bits())
+                .bind(role_id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        // [D] Delete
+        async fn remove_by_id(&self, id: FiefId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Fiefs WHERE id = $1")
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_by_name(&self, name: &str) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Fiefs WHERE name = $1")
+                .bind(name)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_maintenance_window(
+            &self,
+            id: FiefId,
+            window_id: MaintenanceWindowId,
+        ) -> Result<bool> {
+            let result =
+                sqlx::query("DELETE FROM MaintenanceWindows WHERE id = $1 AND fief_id = $2")
+                    .bind(window_id.0)
+                    .bind(id.0)
+                    .execute(&mut *self.0.acquire().await?)
+                    .await?;
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn revoke_role(&self, id: FiefId, role_id: RoleId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM RoleGrants WHERE role_id = $1 AND fief_id = $2")
+                .bind(role_id.0)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_fief_role(&self, role_id: FiefRoleId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM FiefRoles WHERE id = $1")
+                .bind(role_id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        }
     }
 }
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use sqlx::Database;
+
+use crate::{
+    core::ImagePng,
+    domains::ImageKey,
+    repos::{conn::Conn, error::Result, traits::ImageStore},
+};
+
+/// 将图片保存在数据库的 `ImageBlobs` 表中
+pub struct SqlxImageStore<DB: Database>(Conn<DB>);
+
+impl<DB: Database> SqlxImageStore<DB> {
+    pub fn new(conn: impl Into<Conn<DB>>) -> Self {
+        Self(conn.into())
+    }
+}
+
+for_each_dialect! {
+    #[async_trait]
+    impl ImageStore for SqlxImageStore<DB> {
+        // [C] Create
+        async fn put(&self, img: &ImagePng) -> Result<ImageKey> {
+            let key = ImageKey::of(img);
+            sqlx::query(
+                "INSERT INTO ImageBlobs (hash, img) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            )
+            .bind(&key.0)
+            .bind(img.as_bytes())
+            .execute(&mut *self.0.acquire().await?)
+            .await?;
+
+            Ok(key)
+        }
+
+        // [R] Read
+        async fn get(&self, key: &ImageKey) -> Result<Option<ImagePng>> {
+            let result: Option<(Vec<u8>,)> =
+                sqlx::query_as("SELECT img FROM ImageBlobs WHERE hash = $1")
+                    .bind(&key.0)
+                    .fetch_optional(&mut *self.0.acquire().await?)
+                    .await?;
+
+            Ok(result.map(|r| ImagePng::new(r.0)))
+        }
+
+        async fn contains(&self, key: &ImageKey) -> Result<bool> {
+            let result: (bool,) =
+                sqlx::query_as("SELECT EXISTS(SELECT 1 FROM ImageBlobs WHERE hash = $1)")
+                    .bind(&key.0)
+                    .fetch_one(&mut *self.0.acquire().await?)
+                    .await?;
+
+            Ok(result.0)
+        }
+
+        async fn keys(&self) -> Result<Vec<ImageKey>> {
+            let result: Vec<(String,)> = sqlx::query_as("SELECT hash FROM ImageBlobs")
+                .fetch_all(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.into_iter().map(|r| ImageKey(r.0)).collect())
+        }
+
+        // [D] Delete
+        async fn remove(&self, key: &ImageKey) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM ImageBlobs WHERE hash = $1")
+                .bind(&key.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(result.rows_affected() == 1)
+        }
+    }
+}
@@ -1,172 +1,330 @@
-use std::sync::Arc;
-
-use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::sqlite::SqlitePool;
+use sqlx::Database;
 
+use super::Dialect;
 use crate::{
-    domains::{FiefId, Permissions, User, UserId},
+    domains::{FiefId, FiefRole, FiefRoleId, Invitation, InvitationId, Permissions, User, UserId},
     entities,
-    repos::traits::UserRepo,
+    repos::{
+        conn::Conn,
+        error::{RepoError, Result},
+        traits::UserRepo,
+    },
 };
 
-pub struct SqlxUserRepo(Arc<SqlitePool>);
+pub struct SqlxUserRepo<DB: Database>(Conn<DB>);
 
-impl SqlxUserRepo {
-    pub fn new(pool: Arc<SqlitePool>) -> Self {
-        Self(pool)
+impl<DB: Database> SqlxUserRepo<DB> {
+    pub fn new(conn: impl Into<Conn<DB>>) -> Self {
+        Self(conn.into())
     }
 }
 
-#[allow(unused)]
-#[async_trait]
-impl UserRepo for SqlxUserRepo {
-    // [C] Create
-    async fn create(&self, id: UserId, is_admin: bool) -> Result<Option<UserId>> {
-        let result = sqlx::query("INSERT INTO Users (id, is_admin) VALUES ($1, $2)")
-            .bind(id.0)
-            .bind(is_admin)
-            .execute(&*self.0)
-            .await;
-        Ok(super::conv_create_result(result)?)
+fn conv_invitation(i: entities::Invitation) -> Invitation {
+    Invitation {
+        id: InvitationId(i.id),
+        fief_id: FiefId(i.fief_id),
+        user_id: UserId(i.user_id),
+        inviter: i.inviter_id.map(UserId),
+        permissions: i.permissions.map(Permissions::from_bits_truncate),
+        fief_role: i.fief_role_id.map(FiefRoleId),
+        created_at: i.created_at,
+        expires_at: i.expires_at,
     }
+}
 
-    async fn join(&self, id: UserId, fief_id: FiefId, p: Option<Permissions>) -> Result<bool> {
-        let permissions = p.unwrap_or(Permissions::NONE);
-        let result =
-            sqlx::query("INSERT INTO Members (user_id, fief_id, permissions) VALUES ($1, $2, $3)")
-                .bind(id.0)
-                .bind(fief_id.0)
-                .bind(permissions.bits())
-                .execute(&*self.0)
-                .await;
-        Ok(super::conv_create_result::<i64>(result)?.is_some())
-    }
+for_each_dialect! {
+    #[allow(unused)]
+    #[async_trait]
+    impl UserRepo for SqlxUserRepo<DB> {
+        // [C] Create
+        async fn create(&self, id: UserId, is_admin: bool) -> Result<Option<UserId>> {
+            let query =
+                sqlx::query_as("INSERT INTO Users (id, is_admin) VALUES ($1, $2) RETURNING id")
+                    .bind(id.0)
+                    .bind(is_admin);
+            let result = DB::insert_returning(&self.0, query).await?;
+            Ok(super::conv_create_result(result)?)
+        }
 
-    // [R] Read
-    // - self or fields
-    async fn user_by_id(&self, id: UserId) -> Result<User> {
-        let result: entities::User = sqlx::query_as("SELECT * FROM Users WHERE id = $1")
+        async fn join(&self, id: UserId, fief_id: FiefId, p: Option<Permissions>) -> Result<bool> {
+            let permissions = p.unwrap_or(Permissions::NONE);
+            let query = sqlx::query_as(
+                "INSERT INTO Members (user_id, fief_id, permissions) VALUES ($1, $2, $3)
+                RETURNING user_id",
+            )
             .bind(id.0)
-            .fetch_one(&*self.0)
-            .await?;
-
-        Ok(User {
-            id: UserId(result.id),
-            is_admin: result.is_admin,
-        })
-    }
+            .bind(fief_id.0)
+            .bind(permissions.bits());
+            let result = DB::insert_returning(&self.0, query).await?;
+            Ok(super::conv_create_result::<i64>(result)?.is_some())
+        }
 
-    async fn all(&self) -> Result<Vec<User>> {
-        let users: Vec<entities::User> = sqlx::query_as("SELECT * FROM Users")
-            .fetch_all(&*self.0)
+        async fn invite(
+            &self,
+            id: UserId,
+            fief_id: FiefId,
+            p: Option<Permissions>,
+            role: Option<FiefRoleId>,
+            inviter: Option<UserId>,
+            expires_at: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Option<InvitationId>> {
+            let now = chrono::Utc::now();
+            let existing: Option<(i64, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+                "SELECT id, expires_at FROM Invitations WHERE user_id = $1 AND fief_id = $2",
+            )
+            .bind(id.0)
+            .bind(fief_id.0)
+            .fetch_optional(&mut *self.0.acquire().await?)
             .await?;
-        Ok(users
-            .into_iter()
-            .map(|u| User {
-                id: UserId(u.id),
-                is_admin: u.is_admin,
-            })
-            .collect())
-    }
+            if let Some((invitation_id, old_expires_at)) = existing {
+                if old_expires_at > now {
+                    return Ok(None);
+                }
+                self.remove_invitation(InvitationId(invitation_id)).await?;
+            }
+
+            let query = sqlx::query_as(
+                "INSERT INTO Invitations
+                (fief_id, user_id, inviter_id, permissions, fief_role_id, created_at, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+            )
+            .bind(fief_id.0)
+            .bind(id.0)
+            .bind(inviter.map(|u| u.0))
+            .bind(p.map(|p| p.bits()))
+            .bind(role.map(|r| r.0))
+            .bind(now)
+            .bind(expires_at);
+            let result = DB::insert_returning(&self.0, query).await?;
+            Ok(super::conv_create_result(result)?)
+        }
 
-    async fn admins(&self) -> Result<Vec<User>> {
-        let users: Vec<entities::User> =
-            sqlx::query_as("SELECT * FROM Users WHERE is_admin = TRUE")
-                .fetch_all(&*self.0)
+        // [R] Read
+        // - self or fields
+        async fn user_by_id(&self, id: UserId) -> Result<User> {
+            let result: entities::User = sqlx::query_as("SELECT * FROM Users WHERE id = $1")
+                .bind(id.0)
+                .fetch_one(&mut *self.0.acquire().await?)
                 .await?;
-        Ok(users
-            .into_iter()
-            .map(|u| User {
-                id: UserId(u.id),
-                is_admin: u.is_admin,
+
+            Ok(User {
+                id: UserId(result.id),
+                is_admin: result.is_admin,
             })
-            .collect())
-    }
+        }
 
-    async fn non_admins(&self) -> Result<Vec<User>> {
-        let users: Vec<entities::User> =
-            sqlx::query_as("SELECT * FROM Users WHERE is_admin = FALSE")
-                .fetch_all(&*self.0)
+        async fn all(&self) -> Result<Vec<User>> {
+            let users: Vec<entities::User> = sqlx::query_as("SELECT * FROM Users")
+                .fetch_all(&mut *self.0.acquire().await?)
                 .await?;
-        Ok(users
-            .into_iter()
-            .map(|u| User {
-                id: UserId(u.id),
-                is_admin: u.is_admin,
-            })
-            .collect())
-    }
+            Ok(users
+                .into_iter()
+                .map(|u| User {
+                    id: UserId(u.id),
+                    is_admin: u.is_admin,
+                })
+                .collect())
+        }
 
-    // - related
-    async fn fiefs(&self, id: UserId) -> Result<Vec<FiefId>> {
-        let fiefs: Vec<(i64,)> = sqlx::query_as("SELECT fief_id FROM Members WHERE user_id = $1")
-            .bind(id.0)
-            .fetch_all(&*self.0)
-            .await?;
-        Ok(fiefs.into_iter().map(|f| FiefId(f.0)).collect())
-    }
+        async fn admins(&self) -> Result<Vec<User>> {
+            let users: Vec<entities::User> =
+                sqlx::query_as("SELECT * FROM Users WHERE is_admin = TRUE")
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+            Ok(users
+                .into_iter()
+                .map(|u| User {
+                    id: UserId(u.id),
+                    is_admin: u.is_admin,
+                })
+                .collect())
+        }
 
-    async fn is_member_of(&self, id: UserId, fief_id: FiefId) -> Result<bool> {
-        let (result,): (bool,) = sqlx::query_as(
-            "SELECT EXISTS(SELECT 1 FROM Members WHERE user_id = $1 AND fief_id = $2)",
-        )
-        .bind(id.0)
-        .bind(fief_id.0)
-        .fetch_one(&*self.0)
-        .await?;
-        Ok(result)
-    }
+        async fn non_admins(&self) -> Result<Vec<User>> {
+            let users: Vec<entities::User> =
+                sqlx::query_as("SELECT * FROM Users WHERE is_admin = FALSE")
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+            Ok(users
+                .into_iter()
+                .map(|u| User {
+                    id: UserId(u.id),
+                    is_admin: u.is_admin,
+                })
+                .collect())
+        }
 
-    async fn permissions_in(&self, id: UserId, fief_id: FiefId) -> Result<Permissions> {
-        let (p,): (i64,) =
-            sqlx::query_as("SELECT permissions FROM Members WHERE user_id = $1 AND fief_id = $2")
-                .bind(id.0)
-                .bind(fief_id.0)
-                .fetch_one(&*self.0)
-                .await?;
-        Ok(Permissions::from_bits(p)
-            .ok_or(anyhow::anyhow!("failed to parse permissions from database"))?)
-    }
+        // - related
+        async fn fiefs(&self, id: UserId) -> Result<Vec<FiefId>> {
+            let fiefs: Vec<(i64,)> =
+                sqlx::query_as("SELECT fief_id FROM Members WHERE user_id = $1")
+                    .bind(id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+            Ok(fiefs.into_iter().map(|f| FiefId(f.0)).collect())
+        }
 
-    // [U] Update
-    // - self or fields
-    async fn set_admin(&self, id: UserId, is_admin: bool) -> Result<()> {
-        sqlx::query("UPDATE Users SET is_admin = $1 WHERE id = $2")
-            .bind(is_admin)
+        async fn is_member_of(&self, id: UserId, fief_id: FiefId) -> Result<bool> {
+            let (result,): (bool,) = sqlx::query_as(
+                "SELECT EXISTS(SELECT 1 FROM Members WHERE user_id = $1 AND fief_id = $2)",
+            )
             .bind(id.0)
-            .execute(&*self.0)
+            .bind(fief_id.0)
+            .fetch_one(&mut *self.0.acquire().await?)
             .await?;
+            Ok(result)
+        }
 
-        Ok(())
-    }
+        async fn permissions_in(&self, id: UserId, fief_id: FiefId) -> Result<Permissions> {
+            let (p,): (i64,) = sqlx::query_as(
+                "SELECT permissions FROM Members WHERE user_id = $1 AND fief_id = $2",
+            )
+            .bind(id.0)
+            .bind(fief_id.0)
+            .fetch_one(&mut *self.0.acquire().await?)
+            .await?;
+            Ok(Permissions::from_bits(p).ok_or(RepoError::Decode(
+                "failed to parse permissions from database".into(),
+            ))?)
+        }
 
-    // - related
-    async fn set_permissions_in(&self, id: UserId, fief_id: FiefId, p: Permissions) -> Result<()> {
-        sqlx::query("UPDATE Members SET permissions = $1 WHERE user_id = $2 AND fief_id = $3")
-            .bind(p.bits())
+        async fn effective_permissions_in(
+            &self,
+            id: UserId,
+            fief_id: FiefId,
+        ) -> Result<Permissions> {
+            let (p,): (i64,) = sqlx::query_as(
+                "SELECT m.permissions | COALESCE(r.permissions, 0) FROM Members m
+                LEFT JOIN FiefRoles r ON r.id = m.role_id
+                WHERE m.user_id = $1 AND m.fief_id = $2",
+            )
             .bind(id.0)
             .bind(fief_id.0)
-            .execute(&*self.0)
+            .fetch_one(&mut *self.0.acquire().await?)
             .await?;
-        Ok(())
-    }
+            Ok(Permissions::from_bits(p).ok_or(RepoError::Decode(
+                "failed to parse permissions from database".into(),
+            ))?)
+        }
 
-    // [D] Delete
-    async fn leave(&self, id: UserId, fief_id: FiefId) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM Members WHERE user_id = $1 AND fief_id = $2")
+        async fn fief_role_in(&self, id: UserId, fief_id: FiefId) -> Result<Option<FiefRole>> {
+            let role: Option<entities::FiefRole> = sqlx::query_as(
+                "SELECT r.* FROM Members m
+                JOIN FiefRoles r ON r.id = m.role_id
+                WHERE m.user_id = $1 AND m.fief_id = $2",
+            )
             .bind(id.0)
             .bind(fief_id.0)
-            .execute(&*self.0)
+            .fetch_optional(&mut *self.0.acquire().await?)
             .await?;
-        Ok(result.rows_affected() == 1)
-    }
+            role.map(super::fief::conv_fief_role).transpose()
+        }
+
+        async fn invitation_by_id(&self, invitation_id: InvitationId) -> Result<Invitation> {
+            let result: entities::Invitation =
+                sqlx::query_as("SELECT * FROM Invitations WHERE id = $1")
+                    .bind(invitation_id.0)
+                    .fetch_one(&mut *self.0.acquire().await?)
+                    .await?;
+            Ok(conv_invitation(result))
+        }
 
-    async fn remove_by_id(&self, id: UserId) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM Users WHERE id = $1")
+        async fn invitations(&self, id: UserId) -> Result<Vec<Invitation>> {
+            let result: Vec<entities::Invitation> =
+                sqlx::query_as("SELECT * FROM Invitations WHERE user_id = $1 ORDER BY id")
+                    .bind(id.0)
+                    .fetch_all(&mut *self.0.acquire().await?)
+                    .await?;
+            let now = chrono::Utc::now();
+            Ok(result
+                .into_iter()
+                .map(conv_invitation)
+                .filter(|i| !i.is_expired(now))
+                .collect())
+        }
+
+        // [U] Update
+        // - self or fields
+        async fn set_admin(&self, id: UserId, is_admin: bool) -> Result<()> {
+            sqlx::query("UPDATE Users SET is_admin = $1 WHERE id = $2")
+                .bind(is_admin)
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+
+            Ok(())
+        }
+
+        // - related
+        async fn set_permissions_in(
+            &self,
+            id: UserId,
+            fief_id: FiefId,
+            p: Permissions,
+        ) -> Result<()> {
+            sqlx::query("UPDATE Members SET permissions = $1 WHERE user_id = $2 AND fief_id = $3")
+                .bind(p.bits())
+                .bind(id.0)
+                .bind(fief_id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(())
+        }
+
+        async fn set_fief_role_in(
+            &self,
+            id: UserId,
+            fief_id: FiefId,
+            role_id: Option<FiefRoleId>,
+        ) -> Result<bool> {
+            let result = sqlx::query(
+                "UPDATE Members SET role_id = CAST($1 AS BIGINT) WHERE user_id = $2 AND fief_id = \
+                 $3
+                AND (CAST($1 AS BIGINT) IS NULL
+                    OR EXISTS(SELECT 1 FROM FiefRoles WHERE id = $1 AND fief_id = $3))",
+            )
+            .bind(role_id.map(|r| r.0))
             .bind(id.0)
-            .execute(&*self.0)
+            .bind(fief_id.0)
+            .execute(&mut *self.0.acquire().await?)
             .await?;
-        Ok(result.rows_affected() == 1)
+            Ok(result.rows_affected() == 1)
+        }
+
+        // [D] Delete
+        async fn leave(&self, id: UserId, fief_id: FiefId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Members WHERE user_id = $1 AND fief_id = $2")
+                .bind(id.0)
+                .bind(fief_id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_by_id(&self, id: UserId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Users WHERE id = $1")
+                .bind(id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_invitation(&self, invitation_id: InvitationId) -> Result<bool> {
+            let result = sqlx::query("DELETE FROM Invitations WHERE id = $1")
+                .bind(invitation_id.0)
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        }
+
+        async fn remove_expired_invitations(&self) -> Result<usize> {
+            // SQLite 中的时间与参数都是 UTC 的 RFC 3339 文本，可以直接比较
+            let result = sqlx::query("DELETE FROM Invitations WHERE expires_at <= $1")
+                .bind(chrono::Utc::now())
+                .execute(&mut *self.0.acquire().await?)
+                .await?;
+            Ok(result.rows_affected() as usize)
+        }
     }
 }
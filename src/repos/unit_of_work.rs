@@ -0,0 +1,82 @@
+use std::{ops::Deref, sync::Arc};
+
+use async_trait::async_trait;
+use sqlx::Database;
+
+use super::{Repositories, conn::SharedTx, memory_repos::MemoryDb};
+use crate::repos::error::{RepoError, Result};
+
+/// 提交或回滚工作单元中的修改
+#[async_trait]
+pub(super) trait Finish: Send + Sync {
+    async fn commit(self: Box<Self>) -> Result<()>;
+    async fn rollback(self: Box<Self>) -> Result<()>;
+}
+
+#[async_trait]
+impl<DB: Database> Finish for SharedTx<DB> {
+    async fn commit(self: Box<Self>) -> Result<()> {
+        let tx = self.lock().await.take();
+        let tx =
+            tx.ok_or_else(|| RepoError::Invalid("transaction has already been finished".into()))?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<()> {
+        let tx = self.lock().await.take();
+        let tx =
+            tx.ok_or_else(|| RepoError::Invalid("transaction has already been finished".into()))?;
+        tx.rollback().await?;
+        Ok(())
+    }
+}
+
+/// 内存仓库在副本上修改，提交时只把副本相对于 `base` 的修改合并回原来的数据，
+/// 期间其他操作写入的数据不会丢失
+pub(super) struct MemoryTx {
+    pub(super) target: Arc<std::sync::Mutex<MemoryDb>>,
+    pub(super) base: MemoryDb,
+    pub(super) working: Arc<std::sync::Mutex<MemoryDb>>,
+}
+
+#[async_trait]
+impl Finish for MemoryTx {
+    async fn commit(self: Box<Self>) -> Result<()> {
+        let working = super::memory_repos::lock(&self.working);
+        super::memory_repos::lock(&self.target).merge(&self.base, &working);
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// 由 `Repositories::begin` 开始的工作单元，其中的修改在 `commit` 后才会生效，
+/// 未提交就被丢弃时自动回滚
+///
+/// 图片保存在文件系统中时不受事务控制，回滚后多出的图片由检查器在之后的
+/// 检查中清理
+pub struct UnitOfWork {
+    pub(super) repo: Repositories,
+    pub(super) finish: Box<dyn Finish>,
+}
+
+impl UnitOfWork {
+    pub async fn commit(self) -> Result<()> {
+        self.finish.commit().await
+    }
+
+    pub async fn rollback(self) -> Result<()> {
+        self.finish.rollback().await
+    }
+}
+
+impl Deref for UnitOfWork {
+    type Target = Repositories;
+
+    fn deref(&self) -> &Self::Target {
+        &self.repo
+    }
+}
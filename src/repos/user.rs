@@ -1,12 +1,17 @@
-use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::domains::FiefId;
+use crate::{
+    domains::{FiefId, FiefRole, FiefRoleId},
+    repos::error::Result,
+};
 
 pub(super) mod domains {
     use bitflags::bitflags;
+    use chrono::{DateTime, Utc};
     use serde::{Deserialize, Serialize};
 
+    use crate::domains::{FiefId, FiefRoleId};
+
     #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
     pub struct UserId(pub i64);
 
@@ -16,12 +21,60 @@ pub(super) mod domains {
         }
     }
 
+    /// Discord 身份组的 id
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct RoleId(pub i64);
+
+    impl From<i64> for RoleId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
     #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
     pub struct User {
         pub id: UserId,
         pub is_admin: bool,
     }
 
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+    pub struct InvitationId(pub i64);
+
+    impl From<i64> for InvitationId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
+    /// 等待用户同意的领地邀请，同意后用户才会成为领地成员
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+    pub struct Invitation {
+        pub id: InvitationId,
+        pub fief_id: FiefId,
+        pub user_id: UserId,
+        pub inviter: Option<UserId>,
+        /// 同意后授予的权限，为空时使用默认权限
+        pub permissions: Option<Permissions>,
+        /// 同意后担任的职位
+        pub fief_role: Option<FiefRoleId>,
+        pub created_at: DateTime<Utc>,
+        pub expires_at: DateTime<Utc>,
+    }
+
+    impl Invitation {
+        pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+            self.expires_at <= now
+        }
+    }
+
+    /// 用户在领地中的成员关系
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+    pub struct Member {
+        pub user_id: UserId,
+        pub fief_id: FiefId,
+        pub permissions: Permissions,
+    }
+
     bitflags! {
         #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
         pub struct Permissions: i64 {
@@ -57,6 +110,17 @@ pub trait UserRepo: Sync + Send {
     // [C] Create
     async fn create(&self, id: UserId, is_admin: bool) -> Result<Option<UserId>>;
     async fn join(&self, id: UserId, fief_id: FiefId, p: Option<Permissions>) -> Result<bool>;
+    /// 已有未过期的邀请时返回 `None`，已过期的邀请会被替换，`p`
+    /// 和 `role` 为同意后授予的权限和担任的职位
+    async fn invite(
+        &self,
+        id: UserId,
+        fief_id: FiefId,
+        p: Option<Permissions>,
+        role: Option<FiefRoleId>,
+        inviter: Option<UserId>,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<InvitationId>>;
 
     // [R] Read
     // - self or fields
@@ -67,15 +131,33 @@ pub trait UserRepo: Sync + Send {
     // - related
     async fn fiefs(&self, id: UserId) -> Result<Vec<FiefId>>;
     async fn is_member_of(&self, id: UserId, fief_id: FiefId) -> Result<bool>;
+    /// 成员自身的权限，不包括职位的权限
     async fn permissions_in(&self, id: UserId, fief_id: FiefId) -> Result<Permissions>;
+    /// 成员自身的权限与职位权限的并集
+    async fn effective_permissions_in(&self, id: UserId, fief_id: FiefId) -> Result<Permissions>;
+    /// 不是领地成员或没有职位时返回 `None`
+    async fn fief_role_in(&self, id: UserId, fief_id: FiefId) -> Result<Option<FiefRole>>;
+    async fn invitation_by_id(&self, invitation_id: InvitationId) -> Result<Invitation>;
+    /// 用户未过期的邀请，按邀请的先后排列
+    async fn invitations(&self, id: UserId) -> Result<Vec<Invitation>>;
 
     // [U] Update
     // - self or fields
     async fn set_admin(&self, id: UserId, is_admin: bool) -> Result<()>;
     // - related
     async fn set_permissions_in(&self, id: UserId, fief_id: FiefId, p: Permissions) -> Result<()>;
+    /// 不是领地成员或职位不属于该领地时返回 `false`
+    async fn set_fief_role_in(
+        &self,
+        id: UserId,
+        fief_id: FiefId,
+        role_id: Option<FiefRoleId>,
+    ) -> Result<bool>;
 
     // [D] Delete
     async fn remove_by_id(&self, id: UserId) -> Result<bool>;
     async fn leave(&self, id: UserId, fief_id: FiefId) -> Result<bool>;
+    async fn remove_invitation(&self, invitation_id: InvitationId) -> Result<bool>;
+    /// 返回删除的过期邀请数量
+    async fn remove_expired_invitations(&self) -> Result<usize>;
 }
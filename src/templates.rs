@@ -0,0 +1,322 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use image::{GrayImage, Luma, RgbaImage};
+use serde_json::Value;
+
+use crate::{
+    Repositories,
+    core::{ImagePng, Position, WPLACE_CHUNK_HEIGHT, WPLACE_CHUNK_WIDTH},
+    domains::{ChunkId, FiefId, UserId},
+};
+
+/// Blue Marble 保存模板时每个像素放大的倍数，只有中心像素是模板的颜色
+const BLUE_MARBLE_DRAW_MULT: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    /// 模板左上角在整个画布上的像素坐标
+    pub origin: Position,
+    pub img: RgbaImage,
+}
+
+/// 模板落在某个区块内的部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TilePiece {
+    pub tile: Position,
+    pub ref_: RgbaImage,
+    pub mask: GrayImage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Imported {
+    pub chunk_id: ChunkId,
+    pub name: String,
+    pub tile: Position,
+    /// 导入的像素数量
+    pub pixels: usize,
+    /// 是否新建了区块，否则合并到了已有区块
+    pub created: bool,
+}
+
+fn global(tile: [usize; 2], pixel: [usize; 2]) -> Position {
+    Position::new(
+        tile[0] * WPLACE_CHUNK_WIDTH + pixel[0],
+        tile[1] * WPLACE_CHUNK_HEIGHT + pixel[1],
+    )
+}
+
+/// 取出字符串中的所有整数
+fn numbers(s: &str) -> Vec<usize> {
+    s.split(|c: char| !c.is_ascii_digit())
+        .filter(|n| !n.is_empty())
+        .filter_map(|n| n.parse().ok())
+        .collect()
+}
+
+fn decode_base64_png(data: &str) -> Result<RgbaImage> {
+    // 可能带有 `data:image/png;base64,` 前缀
+    let data = data.rsplit(',').next().unwrap_or(data);
+    let bytes = STANDARD.decode(data.trim())?;
+    ImagePng::new(bytes).try_to_rgba()
+}
+
+/// 根据文件名选择格式并解析模板
+pub fn parse(file_name: &str, data: &[u8]) -> Result<Vec<Template>> {
+    let lower = file_name.to_lowercase();
+    if lower.ends_with(".json") {
+        parse_json(data)
+    } else if lower.ends_with(".png") {
+        Ok(vec![parse_png(file_name, data)?])
+    } else {
+        Err(anyhow!("unsupported template file: {file_name}"))
+    }
+}
+
+/// 解析文件名以 `<区块 X>_<区块 Y>_<像素 X>_<像素 Y>.png` 结尾的模板图片
+pub fn parse_png(file_name: &str, data: &[u8]) -> Result<Template> {
+    let stem = file_name.rsplit_once('.').map_or(file_name, |(s, _)| s);
+    let nums = numbers(stem);
+    let [tx, ty, px, py] = nums
+        .get(nums.len().saturating_sub(4)..)
+        .and_then(|n| n.try_into().ok())
+        .ok_or_else(|| anyhow!("failed to find coordinates in file name: {file_name}"))?;
+
+    let name = stem
+        .trim_end_matches(|c: char| c.is_ascii_digit() || "_-, ".contains(c))
+        .to_owned();
+    Ok(Template {
+        name: if name.is_empty() {
+            stem.to_owned()
+        } else {
+            name
+        },
+        origin: global([tx, ty], [px, py]),
+        img: ImagePng::new(data.to_vec()).try_to_rgba()?,
+    })
+}
+
+/// 解析 Blue Marble 或 Overlay Pro 导出的 JSON
+pub fn parse_json(data: &[u8]) -> Result<Vec<Template>> {
+    let json: Value = serde_json::from_slice(data)?;
+    if let Some(templates) = json.get("templates").and_then(Value::as_object) {
+        return templates
+            .values()
+            .map(parse_blue_marble)
+            .try_fold(vec![], |mut acc, t| {
+                acc.extend(t?);
+                Ok(acc)
+            });
+    }
+
+    let overlays = match &json {
+        Value::Array(overlays) => overlays.iter().collect(),
+        _ => match json.get("overlays").and_then(Value::as_array) {
+            Some(overlays) => overlays.iter().collect(),
+            None => vec![&json],
+        },
+    };
+    overlays.into_iter().map(parse_overlay_pro).collect()
+}
+
+fn parse_blue_marble(t: &Value) -> Result<Vec<Template>> {
+    let name = t["name"].as_str().unwrap_or("template").to_owned();
+    let tiles = t["tiles"]
+        .as_object()
+        .ok_or_else(|| anyhow!("failed to parse Blue Marble template: missing tiles"))?;
+
+    let mult = BLUE_MARBLE_DRAW_MULT;
+    let mut templates = vec![];
+    for (key, data) in tiles {
+        let [tx, ty, px, py] = numbers(key)
+            .try_into()
+            .map_err(|_| anyhow!("failed to parse Blue Marble tile key: {key}"))?;
+        let data = data
+            .as_str()
+            .ok_or_else(|| anyhow!("failed to parse Blue Marble tile: {key}"))?;
+        let scaled = decode_base64_png(data)?;
+        let img = RgbaImage::from_fn(scaled.width() / mult, scaled.height() / mult, |x, y| {
+            *scaled.get_pixel(x * mult + mult / 2, y * mult + mult / 2)
+        });
+        templates.push(Template {
+            name: name.clone(),
+            origin: global([tx, ty], [px, py]),
+            img,
+        });
+    }
+    Ok(templates)
+}
+
+fn parse_overlay_pro(o: &Value) -> Result<Template> {
+    let name = o["name"].as_str().unwrap_or("overlay").to_owned();
+    let data = o["imageBase64"]
+        .as_str()
+        .ok_or_else(|| anyhow!("failed to parse Overlay Pro overlay: missing imageBase64"))?;
+    let url = o["pixelUrl"]
+        .as_str()
+        .ok_or_else(|| anyhow!("failed to parse Overlay Pro overlay: missing pixelUrl"))?;
+
+    // 形如 `https://backend.wplace.live/s0/pixel/<区块 X>/<区块 Y>?x=<像素 X>&y=<像素 Y>`
+    let coords = url.rsplit_once("/pixel/").map_or(url, |(_, c)| c);
+    let [tx, ty, px, py] = numbers(coords)
+        .get(..4)
+        .and_then(|n| n.try_into().ok())
+        .ok_or_else(|| anyhow!("failed to parse Overlay Pro pixel url: {url}"))?;
+
+    let Position { x, y } = global([tx, ty], [px, py]);
+    let offset = |key| o[key].as_i64().unwrap_or(0);
+    let (x, y) = (x as i64 + offset("offsetX"), y as i64 + offset("offsetY"));
+    if x < 0 || y < 0 {
+        return Err(anyhow!(
+            "failed to parse Overlay Pro overlay: position out of range"
+        ));
+    }
+
+    Ok(Template {
+        name,
+        origin: Position::new(x as usize, y as usize),
+        img: decode_base64_png(data)?,
+    })
+}
+
+/// 将模板按照区块切分，同一区块内的多个模板会合并在一起
+pub fn split_into_tiles(templates: &[Template]) -> Vec<TilePiece> {
+    let (w, h) = (WPLACE_CHUNK_WIDTH, WPLACE_CHUNK_HEIGHT);
+    let mut pieces = BTreeMap::<(usize, usize), TilePiece>::new();
+
+    for t in templates {
+        for (x, y, px) in t.img.enumerate_pixels() {
+            if px.0[3] == 0 {
+                continue;
+            }
+            let (gx, gy) = (t.origin.x + x as usize, t.origin.y + y as usize);
+            let piece = pieces.entry((gx / w, gy / h)).or_insert_with(|| TilePiece {
+                tile: Position::new(gx / w, gy / h),
+                ref_: RgbaImage::new(w as u32, h as u32),
+                mask: GrayImage::new(w as u32, h as u32),
+            });
+            let (lx, ly) = ((gx % w) as u32, (gy % h) as u32);
+            piece.ref_.put_pixel(lx, ly, *px);
+            piece.mask.put_pixel(lx, ly, Luma([0xFF]));
+        }
+    }
+    pieces.into_values().collect()
+}
+
+/// 新区块以区块坐标命名，名字已被领地内其他位置的区块占用时添加数字后缀
+fn unique_name(taken: &HashSet<String>, tile: Position) -> String {
+    let base = format!("{}-{}", tile.x, tile.y);
+    (1..)
+        .map(|i| match i {
+            1 => base.clone(),
+            i => format!("{base}_{i}"),
+        })
+        .find(|name| !taken.contains(name))
+        .expect("there are always unused names")
+}
+
+/// 将模板导入领地：为每个覆盖到的区块设置参考图和遮罩图，
+/// 领地内已有相同位置的区块时将模板合并到原有的图片上，
+/// 所有区块一起导入或一起失败
+pub async fn import(
+    repo: &Repositories,
+    fief_id: FiefId,
+    templates: &[Template],
+    author: Option<UserId>,
+) -> Result<Vec<Imported>> {
+    let uow = repo.begin().await?;
+    let imported = import_in(&uow, fief_id, templates, author).await?;
+    uow.commit().await?;
+    Ok(imported)
+}
+
+async fn import_in(
+    repo: &Repositories,
+    fief_id: FiefId,
+    templates: &[Template],
+    author: Option<UserId>,
+) -> Result<Vec<Imported>> {
+    let existing = repo
+        .chunk()
+        .summaries_by_fief(fief_id)
+        .await?
+        .into_iter()
+        .map(|s| s.chunk)
+        .collect::<Vec<_>>();
+    let mut taken = existing
+        .iter()
+        .map(|c| c.name.clone())
+        .collect::<HashSet<_>>();
+
+    let mut imported = vec![];
+    for piece in split_into_tiles(templates) {
+        let TilePiece {
+            tile,
+            mut ref_,
+            mut mask,
+        } = piece;
+        let pixels = mask.pixels().filter(|px| px.0[0] == 0xFF).count();
+
+        let (chunk_id, name, created) = match existing.iter().find(|c| c.position == tile) {
+            Some(chunk) => {
+                merge(repo, chunk.id, &mut ref_, &mut mask).await?;
+                (chunk.id, chunk.name.clone(), false)
+            }
+            None => {
+                let name = unique_name(&taken, tile);
+                taken.insert(name.clone());
+                let id = repo
+                    .chunk()
+                    .create(&name, fief_id, tile)
+                    .await?
+                    .ok_or_else(|| anyhow!("failed to create chunk {name}"))?;
+                (id, name, true)
+            }
+        };
+
+        let ref_ = ImagePng::try_from_rgba(ref_)?;
+        let mask = ImagePng::try_from_gray(mask)?;
+        repo.chunk()
+            .update_ref_img(chunk_id, Some(ref_), author)
+            .await?;
+        repo.chunk()
+            .update_mask_img(chunk_id, Some(mask), author)
+            .await?;
+        imported.push(Imported {
+            chunk_id,
+            name,
+            tile,
+            pixels,
+            created,
+        });
+    }
+    Ok(imported)
+}
+
+/// 将区块原有的参考图和遮罩图垫在模板下方
+async fn merge(
+    repo: &Repositories,
+    id: ChunkId,
+    ref_: &mut RgbaImage,
+    mask: &mut GrayImage,
+) -> Result<()> {
+    let size = ref_.dimensions();
+    let old_ref = repo.chunk().ref_img(id).await?.map(|i| i.try_to_rgba());
+    let old_mask = repo.chunk().mask_img(id).await?.map(|i| i.try_to_gray());
+    let (Some(Ok(old_ref)), Some(Ok(old_mask))) = (old_ref, old_mask) else {
+        return Ok(());
+    };
+    if old_ref.dimensions() != size || old_mask.dimensions() != size {
+        return Ok(());
+    }
+
+    for (x, y, px) in mask.enumerate_pixels_mut() {
+        if px.0[0] != 0xFF && old_mask.get_pixel(x, y).0[0] == 0xFF {
+            *px = Luma([0xFF]);
+            ref_.put_pixel(x, y, *old_ref.get_pixel(x, y));
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,9 @@
+mod test_archive;
 mod test_checker;
+mod test_dashboard;
+mod test_i18n;
 mod test_net;
-mod test_sqlx_repos;
+mod test_pages;
+mod test_perms;
+mod test_repos;
+mod test_templates;
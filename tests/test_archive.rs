@@ -0,0 +1,244 @@
+use wmonitor::{
+    Repositories,
+    archive::{self, OnConflict},
+    core::ImagePng,
+    domains::{MaintenanceMode, MaintenanceRule, Permissions, RoleId, UserId},
+};
+
+async fn sample_repo() -> Repositories {
+    let repo = Repositories::from_sqlx("sqlite::memory:").await.unwrap();
+    let interval = Some(chrono::Duration::minutes(30));
+    let fief_id = repo.fief().create("协会横幅", interval).await.unwrap();
+    let fief_id = fief_id.unwrap();
+    repo.fief().set_adaptive(fief_id, true).await.unwrap();
+    repo.fief().set_image_retention(fief_id, 3).await.unwrap();
+    repo.fief().skip_check(fief_id).await.unwrap();
+    let rule = MaintenanceRule {
+        weekday: Some(chrono::Weekday::Sun),
+        start: chrono::NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+        duration: chrono::Duration::hours(2),
+        mode: MaintenanceMode::MuteAlerts,
+    };
+    repo.fief()
+        .add_maintenance_window(fief_id, rule)
+        .await
+        .unwrap();
+
+    let pos = [114, 514].into();
+    let id = repo.chunk().create("左侧", fief_id, pos).await.unwrap();
+    let id = id.unwrap();
+    let img = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk().update_ref_img(id, img, None).await.unwrap();
+    let pos = [115, 514].into();
+    repo.chunk().create("右侧", fief_id, pos).await.unwrap();
+
+    repo.user().create(UserId(1919810), false).await.unwrap();
+    repo.user()
+        .join(UserId(1919810), fief_id, Some(Permissions::CHUNK_ALL))
+        .await
+        .unwrap();
+    repo.fief()
+        .set_owner(fief_id, Some(UserId(1919810)))
+        .await
+        .unwrap();
+    repo.fief()
+        .grant_role(fief_id, RoleId(114514), Some(Permissions::CHUNK_EDIT))
+        .await
+        .unwrap();
+    let role = repo
+        .fief()
+        .create_fief_role(fief_id, "画师", Permissions::MEMBER_INVITE)
+        .await
+        .unwrap();
+    repo.user()
+        .set_fief_role_in(UserId(1919810), fief_id, role)
+        .await
+        .unwrap();
+    repo
+}
+
+#[tokio::test]
+async fn export_and_read() {
+    let repo = sample_repo().await;
+    let fief_id = repo.fief().id("协会横幅").await.unwrap();
+
+    let data = archive::export(&repo, fief_id).await.unwrap();
+    let (manifest, files) = archive::read(&data).unwrap();
+    assert_eq!(manifest.version, archive::ARCHIVE_VERSION);
+    assert_eq!(manifest.fief.name, "协会横幅");
+    assert_eq!(manifest.fief.check_interval_min, 30);
+    assert!(manifest.fief.skip_check_until.is_some());
+    assert!(manifest.fief.adaptive);
+    assert_eq!(manifest.fief.image_retention, 3);
+    assert_eq!(manifest.fief.maintenance_windows.len(), 1);
+    assert_eq!(manifest.fief.owner, Some(UserId(1919810)));
+    assert_eq!(manifest.chunks.len(), 2);
+    assert_eq!(manifest.members.len(), 1);
+    assert_eq!(
+        manifest.members[0].permissions,
+        Permissions::CHUNK_ALL.bits()
+    );
+    assert_eq!(manifest.members[0].role.as_deref(), Some("画师"));
+    assert_eq!(
+        manifest.roles,
+        vec![archive::RoleEntry {
+            name: "画师".to_owned(),
+            permissions: Permissions::MEMBER_INVITE.bits(),
+        }]
+    );
+    assert_eq!(manifest.role_grants.len(), 1);
+    assert_eq!(manifest.role_grants[0].role_id, RoleId(114514));
+    assert_eq!(
+        manifest.role_grants[0].permissions,
+        Permissions::CHUNK_EDIT.bits()
+    );
+    // 旧版本的归档没有记录职位和身份组的授权，只记录了是否暂停检查
+    let mut old = serde_json::to_value(&manifest).unwrap();
+    let fields = old.as_object_mut().unwrap();
+    fields.remove("roles");
+    fields.remove("role_grants");
+    fields["members"][0].as_object_mut().unwrap().remove("role");
+    let fief = fields["fief"].as_object_mut().unwrap();
+    fief.remove("skip_check_until");
+    fief.insert("check_enabled".to_owned(), false.into());
+    let old: archive::Manifest = serde_json::from_value(old).unwrap();
+    assert!(old.roles.is_empty() && old.role_grants.is_empty());
+    assert_eq!(old.members[0].role, None);
+    assert_eq!(old.fief.skip_check_until, None);
+    assert_eq!(old.fief.check_enabled, Some(false));
+
+    let left = manifest.chunks.iter().find(|c| c.name == "左侧").unwrap();
+    let ref_path = left.ref_img.as_ref().unwrap();
+    assert_eq!(files[ref_path], vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    assert_eq!(left.mask_img, None);
+
+    archive::read(b"not an archive").unwrap_err();
+}
+
+#[tokio::test]
+async fn restore() {
+    let repo = sample_repo().await;
+    let fief_id = repo.fief().id("协会横幅").await.unwrap();
+    let data = archive::export(&repo, fief_id).await.unwrap();
+
+    let other = Repositories::from_sqlx("sqlite::memory:").await.unwrap();
+    let restored = archive::restore(&other, &data, None, OnConflict::Abort, None);
+    let restored = restored.await.unwrap().unwrap();
+    assert_eq!(restored.name, "协会横幅");
+    assert_eq!((restored.chunks, restored.members), (2, 1));
+
+    let fief = other.fief().fief_by_id(restored.fief_id).await.unwrap();
+    assert_eq!(fief.check_interval, chrono::Duration::minutes(30));
+    assert!(fief.adaptive);
+    assert_eq!(fief.image_retention, 3);
+    assert!(fief.skip_check_until > chrono::Utc::now());
+    assert_eq!(fief.owner, Some(UserId(1919810)));
+    let windows = other.fief().maintenance_windows(fief.id).await.unwrap();
+    assert_eq!(windows.len(), 1);
+
+    let chunk = other.chunk().chunk_by_name(fief.id, "左侧").await.unwrap();
+    assert_eq!(chunk.position, [114, 514].into());
+    let ref_ = other.chunk().ref_img(chunk.id).await.unwrap();
+    assert_eq!(ref_, Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE])));
+    let perms = other.user().permissions_in(UserId(1919810), fief.id).await;
+    assert_eq!(perms.unwrap(), Permissions::CHUNK_ALL);
+    let role = other.user().fief_role_in(UserId(1919810), fief.id).await;
+    let role = role.unwrap().unwrap();
+    assert_eq!(role.name, "画师");
+    assert_eq!(role.permissions, Permissions::MEMBER_INVITE);
+    let grants = other.fief().role_grants(fief.id).await.unwrap();
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].role_id, RoleId(114514));
+    assert_eq!(grants[0].permissions, Permissions::CHUNK_EDIT);
+}
+
+#[tokio::test]
+async fn restore_by_user() {
+    let repo = sample_repo().await;
+    let fief_id = repo.fief().id("协会横幅").await.unwrap();
+    let data = archive::export(&repo, fief_id).await.unwrap();
+
+    // 归档内记录的所有者被忽略，恢复领地的用户成为所有者
+    let other = Repositories::from_sqlx("sqlite::memory:").await.unwrap();
+    let restored = archive::restore(&other, &data, None, OnConflict::Abort, Some(UserId(42)));
+    let restored = restored.await.unwrap().unwrap();
+    let fief = other.fief().fief_by_id(restored.fief_id).await.unwrap();
+    assert_eq!(fief.owner, Some(UserId(42)));
+    let perms = other.user().permissions_in(UserId(42), fief.id).await;
+    assert_eq!(perms.unwrap(), Permissions::ALL);
+
+    // 归档内的成员需要同意邀请才会加入，同意后保留原有的权限
+    assert_eq!((restored.members, restored.invited), (1, 1));
+    let member = other.user().is_member_of(UserId(1919810), fief.id).await;
+    assert!(!member.unwrap());
+    let invitations = other.user().invitations(UserId(1919810)).await.unwrap();
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0].fief_id, fief.id);
+    assert_eq!(invitations[0].inviter, Some(UserId(42)));
+    assert_eq!(invitations[0].permissions, Some(Permissions::CHUNK_ALL));
+    let role = other
+        .fief()
+        .fief_role_by_name(fief.id, "画师")
+        .await
+        .unwrap();
+    assert_eq!(invitations[0].fief_role, Some(role.id));
+}
+
+#[tokio::test]
+async fn restore_conflict() {
+    let repo = sample_repo().await;
+    let fief_id = repo.fief().id("协会横幅").await.unwrap();
+    let data = archive::export(&repo, fief_id).await.unwrap();
+
+    let restored = archive::restore(&repo, &data, None, OnConflict::Abort, None);
+    assert!(restored.await.unwrap().is_none());
+
+    let restored = archive::restore(&repo, &data, None, OnConflict::Rename, None);
+    assert_eq!(restored.await.unwrap().unwrap().name, "协会横幅-2");
+    let restored = archive::restore(&repo, &data, None, OnConflict::Rename, None);
+    assert_eq!(restored.await.unwrap().unwrap().name, "协会横幅-3");
+
+    let restored = archive::restore(&repo, &data, Some("新横幅"), OnConflict::Abort, None);
+    assert_eq!(restored.await.unwrap().unwrap().name, "新横幅");
+
+    let restored = archive::restore(&repo, &data, None, OnConflict::Replace, None);
+    let restored = restored.await.unwrap().unwrap();
+    assert_ne!(restored.fief_id, fief_id);
+    assert_eq!(repo.fief().id("协会横幅").await.unwrap(), restored.fief_id);
+    assert_eq!(repo.fief().all().await.unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn restore_timed_skip() {
+    let repo = sample_repo().await;
+    let fief_id = repo.fief().id("协会横幅").await.unwrap();
+    let hours = chrono::Duration::hours(2);
+    repo.fief()
+        .skip_check_for(fief_id, hours, None)
+        .await
+        .unwrap();
+    let until = repo
+        .fief()
+        .fief_by_id(fief_id)
+        .await
+        .unwrap()
+        .skip_check_until;
+    let data = archive::export(&repo, fief_id).await.unwrap();
+
+    // 暂停一段时间的领地恢复后在同一时间恢复检查，而不是一直暂停
+    let other = Repositories::from_sqlx("sqlite::memory:").await.unwrap();
+    let restored = archive::restore(&other, &data, None, OnConflict::Abort, None);
+    let restored = restored.await.unwrap().unwrap();
+    let fief = other.fief().fief_by_id(restored.fief_id).await.unwrap();
+    assert_eq!(fief.skip_check_until, until);
+
+    // 没有暂停检查的领地
+    repo.fief().keep_check(fief_id).await.unwrap();
+    let data = archive::export(&repo, fief_id).await.unwrap();
+    let (manifest, _) = archive::read(&data).unwrap();
+    assert_eq!(manifest.fief.skip_check_until, None);
+    let restored = archive::restore(&other, &data, Some("新横幅"), OnConflict::Abort, None);
+    let restored = restored.await.unwrap().unwrap();
+    let fief = other.fief().fief_by_id(restored.fief_id).await.unwrap();
+    assert!(fief.skip_check_until < chrono::Utc::now());
+}
@@ -1,5 +1,9 @@
 use image::{GrayImage, RgbaImage};
-use wmonitor::check::algorithms::find_diffs;
+use wmonitor::{
+    cfg,
+    check::{adaptive, algorithms::find_diffs},
+    domains::{Fief, FiefId},
+};
 
 #[test]
 fn algorithms() {
@@ -13,3 +17,275 @@ fn algorithms() {
     assert_eq!(rec.diff_img, expect_diff_img);
     assert_eq!(rec.diffs.len(), 1);
 }
+
+#[tokio::test]
+async fn checker_handle() {
+    use std::time::Duration;
+
+    use wmonitor::check::CheckerHandle;
+
+    let handle = CheckerHandle::new();
+    assert!(!handle.is_paused());
+    assert!(handle.pause());
+    assert!(!handle.pause());
+    assert!(handle.is_paused());
+    assert!(handle.resume());
+    assert!(!handle.resume());
+
+    handle.clone().run_once();
+    let requested = tokio::time::timeout(Duration::from_secs(1), handle.run_once_requested());
+    requested.await.unwrap();
+    assert_eq!(handle.status(), Default::default());
+}
+
+#[test]
+fn adaptive_interval() {
+    let minutes = |m: usize| chrono::Duration::minutes(m as i64);
+    let (min, max, clean_checks, quiet_hours) = {
+        let cfg = &cfg().check;
+        (
+            minutes(cfg.minimum_interval_min),
+            minutes(cfg.adaptive_maximum_interval_min),
+            cfg.adaptive_clean_checks,
+            cfg.adaptive_quiet_hours,
+        )
+    };
+    let base = min * 4;
+    let mut fief = Fief {
+        id: FiefId(1),
+        name: "协会横幅".to_owned(),
+        check_interval: base,
+        last_check: chrono::Utc::now(),
+        last_check_failed: false,
+        skip_check_until: chrono::Utc::now(),
+        adaptive: true,
+        effective_interval: base,
+        clean_streak: 0,
+        image_retention: 10,
+        owner: None,
+    };
+    let step = |fief: &mut Fief, diff_found| {
+        let (interval, streak) = adaptive::next_interval(fief, diff_found);
+        fief.effective_interval = interval;
+        fief.clean_streak = streak;
+    };
+
+    step(&mut fief, true);
+    assert_eq!(fief.effective_interval, min * 2);
+    step(&mut fief, true);
+    assert_eq!(fief.effective_interval, min);
+    step(&mut fief, true);
+    assert_eq!(fief.effective_interval, min);
+
+    for _ in 1..clean_checks {
+        step(&mut fief, false);
+        assert_eq!(fief.effective_interval, min);
+    }
+    step(&mut fief, false);
+    assert_eq!(fief.effective_interval, min * 2);
+    for _ in 0..clean_checks {
+        step(&mut fief, false);
+    }
+    assert_eq!(fief.effective_interval, base);
+    assert_eq!(fief.clean_streak, 0);
+
+    let quiet_checks = quiet_hours * 60 / (4 * cfg().check.minimum_interval_min);
+    for _ in 1..quiet_checks {
+        step(&mut fief, false);
+        assert_eq!(fief.effective_interval, base);
+    }
+    step(&mut fief, false);
+    let relaxed = (base * 2).min(max).max(base);
+    assert_eq!(fief.effective_interval, relaxed);
+
+    assert!(relaxed > base);
+
+    // 放慢后发现异常，间隔不应长于设置值
+    step(&mut fief, true);
+    assert_eq!(fief.effective_interval, (base / 2).max(min));
+}
+
+#[test]
+fn validation() {
+    use image::{Luma, Rgba};
+    use wmonitor::{
+        check::validation::{self, Issue, WPLACE_PALETTE},
+        core::ImagePng,
+    };
+
+    let [r, g, b] = WPLACE_PALETTE[6];
+    let mut ref_ = RgbaImage::from_pixel(1000, 1000, Rgba([r, g, b, 0xFF]));
+    ref_.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+    assert!(validation::check_ref(&ref_).is_empty());
+
+    ref_.put_pixel(1, 0, Rgba([r, g, b.wrapping_add(1), 0xFF]));
+    ref_.put_pixel(2, 0, Rgba([r, g, b, 0x10]));
+    let issues = validation::check_ref(&ref_);
+    assert_eq!(
+        issues,
+        [Issue::OffPalette {
+            pixels: 2,
+            colors: 2
+        }]
+    );
+
+    let png = ImagePng::try_from_rgba(ref_).unwrap();
+    let rejected = validation::validate_ref(png.clone(), false).unwrap();
+    assert_eq!(rejected.img, None);
+    let fixed = validation::validate_ref(png, true).unwrap();
+    assert!(fixed.fixed);
+    let fixed = fixed.img.unwrap().try_to_rgba().unwrap();
+    assert!(validation::check_ref(&fixed).is_empty());
+    assert_eq!(fixed.get_pixel(1, 0), &Rgba([r, g, b, 0xFF]));
+    assert_eq!(fixed.get_pixel(2, 0), &Rgba([0, 0, 0, 0]));
+
+    let mut mask = GrayImage::new(1000, 1000);
+    mask.put_pixel(0, 0, Luma([0xC0]));
+    mask.put_pixel(1, 0, Luma([0x40]));
+    let issues = validation::check_mask(&mask);
+    assert_eq!(issues, [Issue::NonBinaryMask { pixels: 2 }]);
+    validation::threshold_mask(&mut mask);
+    assert!(validation::check_mask(&mask).is_empty());
+    assert_eq!(mask.get_pixel(0, 0), &Luma([0xFF]));
+    assert_eq!(mask.get_pixel(1, 0), &Luma([0x00]));
+
+    let small = ImagePng::try_from_gray(GrayImage::new(2, 2)).unwrap();
+    let rejected = validation::validate_mask(small, true).unwrap();
+    assert_eq!(rejected.img, None);
+    assert_eq!(
+        rejected.issues,
+        [Issue::WrongSize {
+            width: 2,
+            height: 2
+        }]
+    );
+}
+
+#[test]
+fn automask() {
+    use image::Rgba;
+    use wmonitor::{
+        check::automask::{MaskShape, build_mask, preview_mask},
+        core::Position,
+    };
+
+    let (red, blue) = (
+        Rgba([0xed, 0x1c, 0x24, 0xff]),
+        Rgba([0x40, 0x93, 0xe4, 0xff]),
+    );
+    let mut ref_ = RgbaImage::new(8, 8);
+    for x in 0..4 {
+        for y in 0..4 {
+            ref_.put_pixel(x, y, red);
+        }
+    }
+    ref_.put_pixel(6, 6, red);
+    ref_.put_pixel(7, 7, blue);
+    let count = |shape| {
+        let mask = build_mask(&ref_, &shape).unwrap();
+        mask.pixels().filter(|px| px.0[0] == 0xff).count()
+    };
+
+    assert_eq!(count(MaskShape::Opaque), 18);
+    assert_eq!(count(MaskShape::Colors(vec![[0x40, 0x93, 0xe4]])), 1);
+    assert_eq!(count(MaskShape::FloodFill(Position::new(1, 1))), 16);
+    assert_eq!(count(MaskShape::FloodFill(Position::new(5, 5))), 64 - 18);
+    let rect = MaskShape::Rect(Position::new(5, 6), Position::new(2, 3));
+    assert_eq!(count(rect), 4 * 4);
+    let triangle = vec![
+        Position::new(0, 0),
+        Position::new(8, 0),
+        Position::new(0, 8),
+    ];
+    assert_eq!(count(MaskShape::Polygon(triangle)), 28);
+
+    build_mask(&ref_, &MaskShape::FloodFill(Position::new(8, 0))).unwrap_err();
+    let line = vec![Position::new(0, 0), Position::new(8, 8)];
+    build_mask(&ref_, &MaskShape::Polygon(line)).unwrap_err();
+
+    let mask = build_mask(&ref_, &MaskShape::Opaque).unwrap();
+    preview_mask(&ref_, &mask).unwrap_err();
+    let ref_ = RgbaImage::new(1000, 1000);
+    let mask = build_mask(&ref_, &MaskShape::Opaque).unwrap();
+    let preview = preview_mask(&ref_, &mask).unwrap();
+    assert_eq!(preview.dimensions(), (1000, 1000));
+}
+
+#[tokio::test]
+async fn accept_checked_image() {
+    use std::sync::{Arc, Mutex};
+
+    use image::{GrayImage, Luma, Rgba};
+    use wmonitor::{
+        Repositories,
+        check::{Checker, Event, ImageSource},
+        core::{ImagePng, Position},
+        domains::{ImageKey, UserId},
+    };
+
+    struct Captures(Arc<Mutex<ImagePng>>);
+
+    #[async_trait::async_trait]
+    impl ImageSource for Captures {
+        async fn fetch(&self, _: Position) -> anyhow::Result<ImagePng> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    let repo = Repositories::from_sqlx("sqlite::memory:").await.unwrap();
+    let repo: &'static Repositories = Box::leak(Box::new(repo));
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap();
+    let fief_id = fief_id.unwrap();
+    let id = repo.chunk().create("左侧", fief_id, [114, 514].into());
+    let id = id.await.unwrap().unwrap();
+    repo.user().create(UserId(1919810), false).await.unwrap();
+
+    // 参考图全为白色，两次检查分别有一个不同的像素被涂黑
+    let capture = |x: u32| {
+        let img = RgbaImage::from_fn(1000, 1000, |px, py| match (px, py) == (x, 0) {
+            true => Rgba([0x00, 0x00, 0x00, 0xff]),
+            false => Rgba([0xff, 0xff, 0xff, 0xff]),
+        });
+        ImagePng::try_from_rgba(img).unwrap()
+    };
+    let ref_ = ImagePng::try_from_rgba(RgbaImage::from_pixel(1000, 1000, Rgba([0xff; 4]))).unwrap();
+    let mask = ImagePng::try_from_gray(GrayImage::from_pixel(1000, 1000, Luma([0xff]))).unwrap();
+    repo.chunk()
+        .update_ref_img(id, Some(ref_), None)
+        .await
+        .unwrap();
+    repo.chunk()
+        .update_mask_img(id, Some(mask), None)
+        .await
+        .unwrap();
+
+    let (first, second) = (capture(0), capture(1));
+    let current = Arc::new(Mutex::new(first.clone()));
+    let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+    let mut checker = Checker::new(repo, tx).with_source(Captures(current.clone()));
+    checker.check_one(fief_id).await.unwrap();
+    *current.lock().unwrap() = second.clone();
+    checker.check_one(fief_id).await.unwrap();
+    drop(checker);
+
+    let mut found = vec![];
+    while let Some(event) = rx.recv().await {
+        if let Event::DiffFound(_, chunks) = event {
+            found.push(chunks);
+        }
+    }
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0], vec![(id, ImageKey::of(&first))]);
+    assert_eq!(repo.chunk().current_img(id).await.unwrap(), Some(second));
+
+    // 在第二轮检查后接受第一次通知，参考图是第一次检查到的图片
+    let (_, key) = &found[0][0];
+    let accepted = repo.accept_checked_img(id, key, UserId(1919810)).await;
+    assert!(accepted.unwrap());
+    assert_eq!(repo.chunk().ref_img(id).await.unwrap(), Some(first));
+
+    // 已经被清理的图片不能再被接受
+    let gone = ImageKey::of(&ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    let accepted = repo.accept_checked_img(id, &gone, UserId(1919810)).await;
+    assert!(!accepted.unwrap());
+}
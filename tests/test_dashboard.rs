@@ -0,0 +1,22 @@
+use image::{Rgba, RgbaImage};
+use wmonitor::bot::dashboard::thumbnail_grid;
+
+#[test]
+fn thumbnails() {
+    const RED: Rgba<u8> = Rgba([0xff, 0x00, 0x00, 0xff]);
+    const GREEN: Rgba<u8> = Rgba([0x00, 0xff, 0x00, 0xff]);
+    let img = RgbaImage::from_pixel(20, 10, Rgba([0x00, 0x00, 0xff, 0xff]));
+
+    let tiles = [(Some(img), RED), (None, GREEN), (None, GREEN)];
+    let grid = thumbnail_grid(&tiles, 10);
+    // 3 张缩略图排成 2x2，每格为 10 像素加上两侧各 3 像素的边框
+    assert_eq!(grid.dimensions(), (32, 32));
+    assert_eq!(*grid.get_pixel(0, 0), RED);
+    assert_eq!(*grid.get_pixel(16, 0), GREEN);
+    assert_eq!(*grid.get_pixel(0, 16), GREEN);
+    // 宽图缩小为 10x5 并垂直居中
+    assert_eq!(*grid.get_pixel(8, 8), Rgba([0x00, 0x00, 0xff, 0xff]));
+    assert_ne!(*grid.get_pixel(8, 3), Rgba([0x00, 0x00, 0xff, 0xff]));
+
+    assert_eq!(thumbnail_grid(&[], 10).dimensions(), (16, 16));
+}
@@ -0,0 +1,50 @@
+use fluent::FluentArgs;
+use wmonitor::bot::i18n::{self, Locale};
+
+#[test]
+fn catalogues_complete() {
+    let en = i18n::message_ids(Locale::EnUs);
+    let missing = i18n::message_ids(Locale::ZhCn)
+        .into_iter()
+        .filter(|id| !en.contains(id))
+        .collect::<Vec<_>>();
+    assert!(missing.is_empty(), "missing in en-US: {missing:?}");
+}
+
+#[test]
+fn from_discord() {
+    assert_eq!(Locale::from_discord("zh-CN"), Some(Locale::ZhCn));
+    assert_eq!(Locale::from_discord("zh-TW"), Some(Locale::ZhCn));
+    assert_eq!(Locale::from_discord("en-US"), Some(Locale::EnUs));
+    assert_eq!(Locale::from_discord("en-GB"), Some(Locale::EnUs));
+    assert_eq!(Locale::from_discord("ja"), None);
+
+    let pick = Locale::pick(Some("en-US"), Some("zh-CN"));
+    assert_eq!(pick, Locale::EnUs);
+    let pick = Locale::pick(Some("ja"), Some("zh-CN"));
+    assert_eq!(pick, Locale::ZhCn);
+}
+
+#[test]
+fn get() {
+    let mut args = FluentArgs::new();
+    args.set("name", "协会");
+    assert_eq!(
+        i18n::get(Locale::ZhCn, "error-fief-not-found", &args),
+        "错误：领地 **协会** 不存在。"
+    );
+    assert_eq!(
+        i18n::get(Locale::EnUs, "error-fief-not-found", &args),
+        "Error: fief **协会** does not exist."
+    );
+
+    // 引用的文本使用相同的参数
+    args.set("chunk", "a");
+    args.set("kind", "mask");
+    assert_eq!(
+        i18n::get(Locale::ZhCn, "history-title", &args),
+        "# 区块 a 的遮罩图历史"
+    );
+
+    assert_eq!(i18n::get(Locale::EnUs, "no-such-id", &args), "no-such-id");
+}
@@ -0,0 +1,71 @@
+use wmonitor::bot::pages::{Pages, Row, Sort, Status, View};
+
+fn numbered(n: usize) -> Pages {
+    let mut pages = Pages::new("# 标题");
+    for i in 0..n {
+        pages.push(Row::new(format!("- 第 {i} 项")));
+    }
+    pages
+}
+
+#[test]
+fn split_by_rows() {
+    let pages = numbered(40).render(&View::default());
+    assert_eq!(pages.len(), 3);
+    assert!(pages.iter().all(|p| p.starts_with("# 标题\n")));
+    assert!(pages[0].contains("第 14 项") && !pages[0].contains("第 15 项"));
+    assert!(pages[2].contains("第 39 项"));
+
+    assert!(numbered(0).render(&View::default()).is_empty());
+}
+
+#[test]
+fn split_by_length() {
+    let mut pages = Pages::new("# 标题");
+    pages.section("## 小标题");
+    for i in 0..5 {
+        pages.push(Row::new(format!("{i}{}", "长".repeat(700))));
+    }
+    let rendered = pages.render(&View::default());
+    assert_eq!(rendered.len(), 3);
+    for page in &rendered {
+        assert!(page.chars().count() <= 1900);
+        // 换页后重复小标题
+        assert!(page.starts_with("# 标题\n## 小标题\n"));
+    }
+
+    // 单项过长时截断
+    let mut pages = Pages::new("# 标题");
+    pages.push(Row::new("长".repeat(5000)));
+    let rendered = pages.render(&View::default());
+    assert_eq!(rendered.len(), 1);
+    assert!(rendered[0].chars().count() <= 1900);
+}
+
+#[test]
+fn filter_and_sort() {
+    let mut pages = Pages::new("# 领地");
+    pages.section("## 区块");
+    pages.push(Row::new("a").status(Status::Ok));
+    pages.push(Row::new("b").status(Status::Damaged).diff(3));
+    pages.push(Row::new("c").status(Status::MissingRef));
+    pages.push(Row::new("d").status(Status::Damaged).diff(10));
+    pages.section("## 成员");
+    pages.push(Row::new("e"));
+
+    let view = View {
+        sort: Sort::Status,
+        ..Default::default()
+    };
+    let rendered = pages.render(&view);
+    assert_eq!(rendered, ["# 领地\n## 区块\nb\nd\nc\na\n## 成员\ne\n"]);
+
+    let view = View {
+        problems_only: true,
+        sort: Sort::Diff,
+        ..Default::default()
+    };
+    assert_eq!(pages.count(&view), 3);
+    let rendered = pages.render(&view);
+    assert_eq!(rendered, ["# 领地\n## 区块\nd\nb\nc\n"]);
+}
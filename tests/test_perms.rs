@@ -0,0 +1,52 @@
+use wmonitor::{
+    Repositories,
+    bot::perms::has_perms,
+    domains::{Permissions, RoleId, UserId},
+};
+
+#[tokio::test]
+async fn permissions() {
+    let repo = Repositories::in_memory();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let [owner, admin, member, stranger] = [1, 2, 3, 4].map(UserId);
+    for id in [owner, member, stranger] {
+        repo.user().create(id, false).await.unwrap();
+    }
+    repo.user().create(admin, true).await.unwrap();
+    repo.fief().set_owner(fief_id, Some(owner)).await.unwrap();
+    let perms = Some(Permissions::CHUNK_EDIT);
+    repo.user().join(member, fief_id, perms).await.unwrap();
+
+    let check = |id, roles: &'static [RoleId], perms| has_perms(&repo, id, roles, fief_id, perms);
+    assert!(check(owner, &[], Permissions::ALL).await.unwrap());
+    assert!(check(admin, &[], Permissions::ALL).await.unwrap());
+    assert!(check(member, &[], Permissions::CHUNK_EDIT).await.unwrap());
+    assert!(check(member, &[], Permissions::NONE).await.unwrap());
+    assert!(!check(member, &[], Permissions::CHUNK_DELETE).await.unwrap());
+
+    // 不属于领地的用户即使不要求任何权限也会被拒绝
+    assert!(!check(stranger, &[], Permissions::NONE).await.unwrap());
+    assert!(!check(UserId(5), &[], Permissions::NONE).await.unwrap());
+    assert!(
+        !check(stranger, &[RoleId(10)], Permissions::NONE)
+            .await
+            .unwrap()
+    );
+
+    // 所属身份组被授予了领地权限时视为属于领地
+    let none = Some(Permissions::NONE);
+    repo.fief()
+        .grant_role(fief_id, RoleId(10), none)
+        .await
+        .unwrap();
+    assert!(
+        check(stranger, &[RoleId(10)], Permissions::NONE)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !check(stranger, &[RoleId(10)], Permissions::CHUNK_EDIT)
+            .await
+            .unwrap()
+    );
+}
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use wmonitor::Repositories;
+
+/// 为每个 `async fn(Repositories)` 生成内存、SQLite 和 PostgreSQL 三组测试，
+/// 保证各个实现的行为一致，没有设置 `WMONITOR_TEST_POSTGRES_URL` 时跳过
+/// PostgreSQL
+macro_rules! repo_tests {
+    ($($name:ident),* $(,)?) => {
+        mod memory {
+            $(
+                #[tokio::test]
+                async fn $name() {
+                    super::$name(wmonitor::Repositories::in_memory()).await;
+                }
+            )*
+        }
+
+        mod sqlite {
+            $(
+                #[tokio::test]
+                async fn $name() {
+                    super::$name(crate::test_repos::new_repo().await).await;
+                }
+            )*
+        }
+
+        mod postgres {
+            $(
+                #[tokio::test]
+                async fn $name() {
+                    use crate::test_repos::{drop_pg_schema, new_pg_repo};
+                    if let Some((repo, schema)) = new_pg_repo().await {
+                        super::$name(repo).await;
+                        drop_pg_schema(&schema).await;
+                    }
+                }
+            )*
+        }
+    };
+}
+
+mod test_audit;
+mod test_chunk;
+mod test_dashboard;
+mod test_fief;
+mod test_image_store;
+mod test_unit_of_work;
+mod test_user;
+
+async fn new_repo() -> Repositories {
+    Repositories::from_sqlx("sqlite::memory:").await.unwrap()
+}
+
+/// 每个测试使用单独的 schema，互不干扰
+async fn new_pg_repo() -> Option<(Repositories, String)> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let url = std::env::var("WMONITOR_TEST_POSTGRES_URL").ok()?;
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    let schema = format!("wmonitor_test_{}_{n}", std::process::id());
+
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    let sql = format!("DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}");
+    sqlx::raw_sql(&sql).execute(&pool).await.unwrap();
+    pool.close().await;
+
+    let sep = if url.contains('?') { '&' } else { '?' };
+    let url = format!("{url}{sep}options[search_path]={schema}");
+    Some((Repositories::from_postgres(&url).await.unwrap(), schema))
+}
+
+async fn drop_pg_schema(schema: &str) {
+    let url = std::env::var("WMONITOR_TEST_POSTGRES_URL").unwrap();
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    let sql = format!("DROP SCHEMA IF EXISTS {schema} CASCADE");
+    sqlx::raw_sql(&sql).execute(&pool).await.unwrap();
+    pool.close().await;
+}
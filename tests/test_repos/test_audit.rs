@@ -0,0 +1,84 @@
+use wmonitor::{
+    Repositories,
+    domains::{AuditAction, AuditEntry, AuditRecord, AuditValue, FiefId, UserId},
+};
+
+// [C] Create
+async fn record(repo: Repositories) {
+    let record = AuditRecord {
+        actor: Some(UserId(114514)),
+        ..AuditRecord::new(AuditAction::FiefRename)
+            .fief(FiefId(1))
+            .before("协会横幅")
+            .after("布莉姬特")
+    };
+    let id1 = repo.audit().record(record.clone()).await.unwrap();
+    // 需要翻译的值保存为文本的 id 与参数
+    let interval = AuditValue::msg("audit-value-minutes").arg("minutes", "30");
+    let record2 = AuditRecord::new(AuditAction::FiefSettings)
+        .target(AuditValue::msg("audit-value-check-interval"))
+        .after(interval);
+    let id2 = repo.audit().record(record2.clone()).await.unwrap();
+    assert!(id1 < id2);
+
+    let result = repo.audit().all(0, 10).await.unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].id, id2);
+    assert_eq!(result[0].record.fief_id, None);
+    assert_eq!(result[0].record.actor, None);
+    assert_eq!(result[0].record, record2);
+    assert_eq!(result[1].id, id1);
+    assert_eq!(result[1].record, record);
+}
+
+// [R] Read
+async fn by_fief(repo: Repositories) {
+    for i in 0..5 {
+        let record = AuditRecord::new(AuditAction::ChunkAdd)
+            .fief(FiefId(1))
+            .target(i.to_string());
+        repo.audit().record(record).await.unwrap();
+    }
+    let record = AuditRecord::new(AuditAction::ChunkAdd).fief(FiefId(2));
+    repo.audit().record(record).await.unwrap();
+
+    let targets = |entries: Vec<AuditEntry>| {
+        entries
+            .into_iter()
+            .map(|e| e.record.target.unwrap().encode())
+            .collect::<Vec<_>>()
+    };
+    let result = repo.audit().by_fief(FiefId(1), 0, 2).await.unwrap();
+    assert_eq!(targets(result), ["4", "3"]);
+    let result = repo.audit().by_fief(FiefId(1), 4, 2).await.unwrap();
+    assert_eq!(targets(result), ["0"]);
+    let result = repo.audit().by_fief(FiefId(1), 5, 2).await.unwrap();
+    assert!(result.is_empty());
+    let result = repo.audit().by_fief(FiefId(3), 0, 2).await.unwrap();
+    assert!(result.is_empty());
+}
+
+async fn count(repo: Repositories) {
+    assert_eq!(repo.audit().count().await.unwrap(), 0);
+    for fief_id in [1, 1, 2] {
+        let record = AuditRecord::new(AuditAction::MemberJoin).fief(FiefId(fief_id));
+        repo.audit().record(record).await.unwrap();
+    }
+
+    assert_eq!(repo.audit().count().await.unwrap(), 3);
+    assert_eq!(repo.audit().count_by_fief(FiefId(1)).await.unwrap(), 2);
+    assert_eq!(repo.audit().count_by_fief(FiefId(3)).await.unwrap(), 0);
+}
+
+async fn survives_fief_removal(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let record = AuditRecord::new(AuditAction::FiefDelete)
+        .fief(id)
+        .before("协会横幅");
+    repo.audit().record(record).await.unwrap();
+    repo.fief().remove_by_id(id).await.unwrap();
+
+    assert_eq!(repo.audit().count_by_fief(id).await.unwrap(), 1);
+}
+
+repo_tests!(record, by_fief, count, survives_fief_removal);
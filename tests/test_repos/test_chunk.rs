@@ -0,0 +1,645 @@
+use std::collections::HashSet;
+
+use wmonitor::{
+    RepoError, Repositories,
+    core::{ImagePng, Position},
+    domains::{ChunkId, FiefId, ImageKind, ImageVersionId, UserId},
+};
+
+// [C] Create
+async fn create(repo: Repositories) {
+    let pos = Position::new(114, 514);
+
+    let result = repo.chunk().create("左侧", FiefId(1), pos).await;
+    assert!(matches!(result, Err(RepoError::ForeignKey)));
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id = repo.chunk().create("左侧", fief_id, pos).await.unwrap();
+    assert!(id.is_some());
+
+    let id = repo.chunk().create("左侧", fief_id, pos).await.unwrap();
+    assert!(id.is_none());
+}
+
+// [R] Read
+// - self or fields
+async fn chunk_by_id(repo: Repositories) {
+    repo.chunk()
+        .chunk_by_id(ChunkId(1145141919810))
+        .await
+        .unwrap_err();
+
+    let pos = Position::new(114, 514);
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let chunk = repo.chunk().chunk_by_id(id).await.unwrap();
+    assert_eq!(chunk.fief_id, fief_id);
+    assert_eq!(chunk.name, "左侧");
+}
+
+async fn chunk_by_name(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    repo.chunk()
+        .chunk_by_name(fief_id, "左侧")
+        .await
+        .unwrap_err();
+
+    let pos = Position::new(114, 514);
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+
+    repo.chunk()
+        .chunk_by_name(FiefId(114514), "左侧")
+        .await
+        .unwrap_err();
+    let chunk = repo.chunk().chunk_by_name(fief_id, "左侧").await.unwrap();
+    assert_eq!(chunk.id, id);
+}
+
+async fn summaries_by_fief(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    assert!(
+        repo.chunk()
+            .summaries_by_fief(fief_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let pos = Position::new(114, 514);
+    let id1 = repo.chunk().create("左侧", fief_id, pos).await.unwrap();
+    let pos = Position::new(114, 515);
+    let id2 = repo.chunk().create("右侧", fief_id, pos).await.unwrap();
+    let (id1, id2) = (id1.unwrap(), id2.unwrap());
+    let img = ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    repo.chunk()
+        .update_ref_img(id2, Some(img.clone()), None)
+        .await
+        .unwrap();
+    repo.chunk()
+        .update_mask_img(id2, Some(img), None)
+        .await
+        .unwrap();
+    repo.chunk().update_diff(id2, None, 3).await.unwrap();
+
+    let other = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    let pos = Position::new(1, 1);
+    repo.chunk().create("左侧", other, pos).await.unwrap();
+
+    let actual = repo.chunk().summaries_by_fief(fief_id).await.unwrap();
+    assert_eq!(actual.len(), 2);
+    assert_eq!(
+        actual[0].chunk,
+        repo.chunk().chunk_by_id(id1).await.unwrap()
+    );
+    assert_eq!(
+        (actual[0].diff_count, actual[0].has_ref, actual[0].has_mask),
+        (0, false, false)
+    );
+    assert_eq!(actual[1].chunk.id, id2);
+    assert_eq!(actual[1].chunk.position, Position::new(114, 515));
+    assert_eq!(
+        (actual[1].diff_count, actual[1].has_ref, actual[1].has_mask),
+        (3, true, true)
+    );
+}
+
+async fn names_by_fief(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    assert!(repo.chunk().names_by_fief(id1).await.unwrap().is_empty());
+
+    let pos = Position::new(114, 514);
+    repo.chunk().create("right", id1, pos).await.unwrap();
+    repo.chunk().create("left", id1, pos).await.unwrap();
+    repo.chunk().create("middle", id2, pos).await.unwrap();
+
+    let actual = repo.chunk().names_by_fief(id1).await.unwrap();
+    assert_eq!(actual, vec!["left", "right"]);
+    let actual = repo.chunk().names_by_fief(id2).await.unwrap();
+    assert_eq!(actual, vec!["middle"]);
+}
+
+async fn fief_id(repo: Repositories) {
+    repo.chunk().fief_id(ChunkId(114514)).await.unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let expect = fief_id;
+    let actual = repo.chunk().fief_id(id).await.unwrap();
+    assert_eq!(expect, actual);
+}
+
+async fn name(repo: Repositories) {
+    repo.chunk().name(ChunkId(114514)).await.unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let name = repo.chunk().name(id).await.unwrap();
+    assert_eq!(name, "左侧");
+}
+
+async fn id(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    repo.chunk().id(fief_id, "左侧").await.unwrap_err();
+
+    let pos = Position::new(114, 514);
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+
+    repo.chunk().id(FiefId(114514), "左侧").await.unwrap_err();
+    let expect = id;
+    let actual = repo.chunk().id(fief_id, "左侧").await.unwrap();
+    assert_eq!(expect, actual);
+}
+
+async fn position(repo: Repositories) {
+    repo.chunk().position(ChunkId(114514)).await.unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let expect = pos;
+    let actual = repo.chunk().position(id).await.unwrap();
+    assert_eq!(expect, actual);
+}
+
+async fn ref_img(repo: Repositories) {
+    repo.chunk().ref_img(ChunkId(114514)).await.unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let actual = repo.chunk().ref_img(id).await.unwrap();
+    assert!(actual.is_none());
+}
+
+async fn mask_img(repo: Repositories) {
+    repo.chunk().mask_img(ChunkId(114514)).await.unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let actual = repo.chunk().mask_img(id).await.unwrap();
+    assert!(actual.is_none());
+}
+
+async fn diff_img(repo: Repositories) {
+    repo.chunk().diff_img(ChunkId(114514)).await.unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let actual = repo.chunk().diff_img(id).await.unwrap();
+    assert!(actual.is_none());
+}
+
+async fn diff_count(repo: Repositories) {
+    repo.chunk().diff_count(ChunkId(114514)).await.unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(repo.chunk().diff_count(id).await.unwrap(), 0);
+    repo.chunk().update_diff(id, None, 99).await.unwrap();
+    assert_eq!(repo.chunk().diff_count(id).await.unwrap(), 99);
+}
+
+async fn image_versions(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let versions = repo.chunk().image_versions(id, ImageKind::Reference);
+    assert!(versions.await.unwrap().is_empty());
+
+    let img = ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    let before = chrono::Utc::now();
+    repo.chunk()
+        .update_ref_img(id, Some(img.clone()), Some(UserId(1919810)))
+        .await
+        .unwrap();
+    repo.chunk().update_ref_img(id, None, None).await.unwrap();
+    repo.chunk()
+        .update_mask_img(id, Some(img), None)
+        .await
+        .unwrap();
+
+    let versions = repo.chunk().image_versions(id, ImageKind::Reference);
+    let versions = versions.await.unwrap();
+    assert_eq!(versions.len(), 2);
+    assert!(versions[0].is_empty);
+    assert_eq!(versions[0].author, None);
+    assert!(!versions[1].is_empty);
+    assert_eq!(versions[1].author, Some(UserId(1919810)));
+    assert!(versions[1].created_at >= before);
+    assert!(versions.iter().all(|v| v.kind == ImageKind::Reference));
+
+    let versions = repo.chunk().image_versions(id, ImageKind::Mask);
+    assert_eq!(versions.await.unwrap().len(), 1);
+}
+
+async fn version_img(repo: Repositories) {
+    repo.chunk()
+        .version_img(ImageVersionId(114514))
+        .await
+        .unwrap_err();
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [114, 514].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let img = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk()
+        .update_ref_img(id, img.clone(), None)
+        .await
+        .unwrap();
+    repo.chunk().update_ref_img(id, None, None).await.unwrap();
+
+    let versions = repo.chunk().image_versions(id, ImageKind::Reference);
+    let versions = versions.await.unwrap();
+    assert_eq!(
+        repo.chunk().version_img(versions[0].id).await.unwrap(),
+        None
+    );
+    assert_eq!(repo.chunk().version_img(versions[1].id).await.unwrap(), img);
+}
+
+// - related
+// *PASS*
+
+// [U] Update
+// - self or fields
+async fn update_ref_img(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(repo.chunk().ref_img(id).await.unwrap(), None);
+
+    let img = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk()
+        .update_ref_img(id, img.as_ref().cloned(), None)
+        .await
+        .unwrap();
+    assert_eq!(repo.chunk().ref_img(id).await.unwrap(), img);
+}
+
+async fn rollback_img(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let other = repo
+        .chunk()
+        .create("右侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    let rollback = repo.chunk().rollback_img(id, ImageVersionId(114514), None);
+    assert!(!rollback.await.unwrap());
+
+    let old = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk()
+        .update_ref_img(id, old.clone(), None)
+        .await
+        .unwrap();
+    let new = Some(ImagePng::new(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+    repo.chunk()
+        .update_ref_img(id, new, Some(UserId(114514)))
+        .await
+        .unwrap();
+
+    let versions = repo.chunk().image_versions(id, ImageKind::Reference);
+    let old_version = versions.await.unwrap()[1].id;
+    let rollback = repo.chunk().rollback_img(other, old_version, None);
+    assert!(!rollback.await.unwrap());
+
+    let rollback = repo
+        .chunk()
+        .rollback_img(id, old_version, Some(UserId(1919810)));
+    assert!(rollback.await.unwrap());
+    assert_eq!(repo.chunk().ref_img(id).await.unwrap(), old);
+    assert_eq!(repo.chunk().mask_img(id).await.unwrap(), None);
+
+    let versions = repo.chunk().image_versions(id, ImageKind::Reference);
+    let versions = versions.await.unwrap();
+    assert_eq!(versions.len(), 3);
+    assert_eq!(versions[0].author, Some(UserId(1919810)));
+}
+
+async fn image_retention(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    repo.fief().set_image_retention(fief_id, 3).await.unwrap();
+
+    for i in 0..5u8 {
+        let img = Some(ImagePng::new(vec![i]));
+        repo.chunk().update_ref_img(id, img, None).await.unwrap();
+    }
+    repo.chunk().update_mask_img(id, None, None).await.unwrap();
+
+    let versions = repo.chunk().image_versions(id, ImageKind::Reference);
+    let versions = versions.await.unwrap();
+    // 当前版本之外保留 3 个历史版本
+    assert_eq!(versions.len(), 4);
+    let oldest = repo.chunk().version_img(versions[3].id).await.unwrap();
+    assert_eq!(oldest, Some(ImagePng::new(vec![1])));
+
+    let versions = repo.chunk().image_versions(id, ImageKind::Mask);
+    assert_eq!(versions.await.unwrap().len(), 1);
+
+    // 只保留 1 个历史版本时仍然可以回滚
+    repo.fief().set_image_retention(fief_id, 1).await.unwrap();
+    let img = Some(ImagePng::new(vec![5]));
+    repo.chunk().update_ref_img(id, img, None).await.unwrap();
+    let versions = repo.chunk().image_versions(id, ImageKind::Reference);
+    let versions = versions.await.unwrap();
+    assert_eq!(versions.len(), 2);
+    let rollback = repo.chunk().rollback_img(id, versions[1].id, None);
+    assert!(rollback.await.unwrap());
+    let ref_img = repo.chunk().ref_img(id).await.unwrap();
+    assert_eq!(ref_img, Some(ImagePng::new(vec![4])));
+}
+
+async fn update_mask_img(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(repo.chunk().mask_img(id).await.unwrap(), None);
+
+    let img = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk()
+        .update_mask_img(id, img.as_ref().cloned(), None)
+        .await
+        .unwrap();
+    assert_eq!(repo.chunk().mask_img(id).await.unwrap(), img);
+}
+
+async fn update_diff(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(repo.chunk().diff_img(id).await.unwrap(), None);
+    assert_eq!(repo.chunk().diff_count(id).await.unwrap(), 0);
+
+    let img = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk()
+        .update_diff(id, img.as_ref().cloned(), 114514)
+        .await
+        .unwrap();
+    assert_eq!(repo.chunk().diff_img(id).await.unwrap(), img);
+    assert_eq!(repo.chunk().diff_count(id).await.unwrap(), 114514);
+}
+
+async fn update_current_img(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(repo.chunk().current_img(id).await.unwrap(), None);
+
+    let img = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk()
+        .update_current_img(id, img.as_ref().cloned())
+        .await
+        .unwrap();
+    assert_eq!(repo.chunk().current_img(id).await.unwrap(), img);
+    assert_eq!(repo.chunk().result_img(id).await.unwrap(), None);
+
+    let keys = repo.chunk().referenced_image_keys().await.unwrap();
+    assert_eq!(keys.len(), 1);
+}
+
+async fn set_position(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(repo.chunk().position(id).await.unwrap(), [0, 0].into());
+
+    repo.chunk().set_position(id, [1, 1].into()).await.unwrap();
+    assert_eq!(repo.chunk().position(id).await.unwrap(), [1, 1].into());
+}
+
+async fn rename(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = [0, 0].into();
+    repo.chunk().create("右侧", fief_id, pos).await.unwrap();
+    let id = repo
+        .chunk()
+        .create("左侧", fief_id, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(repo.chunk().name(id).await.unwrap(), "左侧");
+
+    repo.chunk().rename(id, "左上").await.unwrap();
+    assert_eq!(repo.chunk().name(id).await.unwrap(), "左上");
+
+    let result = repo.chunk().rename(id, "右侧").await;
+    assert!(matches!(result, Err(RepoError::Conflict)));
+}
+
+// - related
+// *PASS*
+
+// [D] Delete
+async fn remove_by_id(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos1 = Position::new(114, 514);
+    let pos2 = Position::new(114, 515);
+    let id1 = repo
+        .chunk()
+        .create("左侧", fief_id, pos1)
+        .await
+        .unwrap()
+        .unwrap();
+    let id2 = repo
+        .chunk()
+        .create("右侧", fief_id, pos2)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let result = repo.chunk().remove_by_id(id1).await.unwrap();
+    assert!(result);
+
+    let expect = HashSet::<ChunkId>::from_iter([id2]);
+    let actual = repo.fief().chunks(fief_id).await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect());
+
+    let result = repo.chunk().remove_by_id(id1).await.unwrap();
+    assert!(!result);
+}
+
+async fn remove_by_name(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos1 = Position::new(114, 514);
+    let pos2 = Position::new(114, 515);
+    let _ = repo
+        .chunk()
+        .create("左侧", fief_id, pos1)
+        .await
+        .unwrap()
+        .unwrap();
+    let id = repo
+        .chunk()
+        .create("右侧", fief_id, pos2)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let result = repo.chunk().remove_by_name(fief_id, "左侧").await.unwrap();
+    assert!(result);
+
+    let expect = HashSet::<ChunkId>::from_iter([id]);
+    let actual = repo.fief().chunks(fief_id).await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect());
+
+    let result = repo.chunk().remove_by_name(fief_id, "左侧").await.unwrap();
+    assert!(!result);
+}
+
+async fn remove_all_by_fief(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos1 = Position::new(114, 514);
+    let pos2 = Position::new(114, 515);
+    repo.chunk().create("左侧", fief_id, pos1).await.unwrap();
+    repo.chunk().create("右侧", fief_id, pos2).await.unwrap();
+
+    let errid = FiefId(1145141919810);
+    let result = repo.chunk().remove_all_by_fief(errid).await.unwrap();
+    assert!(!result);
+    assert_eq!(repo.fief().chunks(fief_id).await.unwrap().len(), 2);
+
+    let result = repo.chunk().remove_all_by_fief(fief_id).await.unwrap();
+    assert!(result);
+    assert!(repo.fief().chunks(fief_id).await.unwrap().is_empty());
+
+    let result = repo.chunk().remove_all_by_fief(fief_id).await.unwrap();
+    assert!(!result);
+}
+
+repo_tests!(
+    create,
+    chunk_by_id,
+    chunk_by_name,
+    summaries_by_fief,
+    names_by_fief,
+    fief_id,
+    name,
+    id,
+    position,
+    ref_img,
+    mask_img,
+    diff_img,
+    diff_count,
+    image_versions,
+    version_img,
+    update_ref_img,
+    rollback_img,
+    image_retention,
+    update_mask_img,
+    update_diff,
+    update_current_img,
+    set_position,
+    rename,
+    remove_by_id,
+    remove_by_name,
+    remove_all_by_fief,
+);
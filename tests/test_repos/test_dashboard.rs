@@ -0,0 +1,59 @@
+use wmonitor::{
+    RepoError, Repositories,
+    domains::{Dashboard, DashboardId, FiefId},
+};
+
+// [C] Create
+async fn create(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id1 = repo.dashboard().create(Some(fief_id), 1, 2).await.unwrap();
+    let id2 = repo.dashboard().create(None, 1, 3).await.unwrap();
+    assert!(id1 < id2);
+
+    let result = repo.dashboard().all().await.unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].id, id1);
+    assert_eq!(result[0].fief_id, Some(fief_id));
+    assert_eq!((result[0].channel_id, result[0].message_id), (1, 2));
+    assert_eq!(result[1].fief_id, None);
+
+    let result = repo.dashboard().create(Some(FiefId(114514)), 1, 4).await;
+    assert!(matches!(result, Err(RepoError::ForeignKey)));
+}
+
+// [R] Read
+async fn showing(repo: Repositories) {
+    let a = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let b = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    let id_a = repo.dashboard().create(Some(a), 1, 2).await.unwrap();
+    let id_b = repo.dashboard().create(Some(b), 1, 3).await.unwrap();
+    let id_all = repo.dashboard().create(None, 1, 4).await.unwrap();
+
+    let ids = |dashboards: Vec<Dashboard>| dashboards.into_iter().map(|d| d.id).collect::<Vec<_>>();
+    let result = repo.dashboard().showing(a).await.unwrap();
+    assert_eq!(ids(result), [id_a, id_all]);
+    let result = repo.dashboard().showing(b).await.unwrap();
+    assert_eq!(ids(result), [id_b, id_all]);
+}
+
+// [D] Delete
+async fn remove(repo: Repositories) {
+    let id = repo.dashboard().create(None, 1, 2).await.unwrap();
+    assert!(repo.dashboard().remove(id).await.unwrap());
+    assert!(!repo.dashboard().remove(id).await.unwrap());
+    assert!(!repo.dashboard().remove(DashboardId(114514)).await.unwrap());
+    assert!(repo.dashboard().all().await.unwrap().is_empty());
+}
+
+async fn removed_with_fief(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.dashboard().create(Some(fief_id), 1, 2).await.unwrap();
+    let id = repo.dashboard().create(None, 1, 3).await.unwrap();
+    repo.fief().remove_by_id(fief_id).await.unwrap();
+
+    let result = repo.dashboard().all().await.unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, id);
+}
+
+repo_tests!(create, showing, remove, removed_with_fief);
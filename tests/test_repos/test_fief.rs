@@ -0,0 +1,978 @@
+use std::collections::HashSet;
+
+use chrono::TimeZone;
+use wmonitor::{
+    RepoError, Repositories, cfg,
+    core::{ImagePng, Position},
+    domains::{
+        ChunkId, Fief, FiefId, MaintenanceMode, MaintenanceRule, MaintenanceWindowId, Member,
+        Permissions, RoleGrant, RoleId, UserId,
+    },
+};
+
+// [C] Create
+async fn create(repo: Repositories) {
+    repo.fief().fief_by_name("协会横幅").await.unwrap_err();
+
+    let id = repo
+        .fief()
+        .create("协会横幅", Some(chrono::Duration::seconds(419)))
+        .await
+        .unwrap();
+    assert!(id.is_some());
+
+    let result = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    let min = cfg().check.minimum_interval_min as i64;
+    assert_eq!(result.check_interval, chrono::Duration::minutes(6.max(min)));
+
+    let id = repo.fief().create("协会横幅", None).await.unwrap();
+    assert!(id.is_none());
+}
+
+async fn grant_role(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let result = repo.fief().grant_role(id, RoleId(114514), None).await;
+    assert!(result.unwrap());
+    let result = repo.fief().grant_role(id, RoleId(114514), None).await;
+    assert!(!result.unwrap());
+
+    let result = repo
+        .fief()
+        .grant_role(FiefId(1919810), RoleId(114514), None);
+    assert!(matches!(result.await, Err(RepoError::ForeignKey)));
+}
+
+async fn create_fief_role(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    let p = Permissions::CHUNK_EDIT;
+
+    let result = repo.fief().create_fief_role(id1, "画师", p).await.unwrap();
+    assert!(result.is_some());
+    let result = repo.fief().create_fief_role(id1, "画师", p).await.unwrap();
+    assert!(result.is_none());
+
+    // 不同领地可以有同名职位
+    let result = repo.fief().create_fief_role(id2, "画师", p).await.unwrap();
+    assert!(result.is_some());
+
+    let result = repo.fief().create_fief_role(FiefId(1919810), "画师", p);
+    assert!(matches!(result.await, Err(RepoError::ForeignKey)));
+}
+
+// [R] Read
+// - self or fields
+async fn name(repo: Repositories) {
+    repo.fief().create("协会横幅", None).await.unwrap();
+    repo.fief().create("布莉姬特", None).await.unwrap();
+
+    let id1 = repo.fief().id("协会横幅").await.unwrap();
+    let id2 = repo.fief().id("布莉姬特").await.unwrap();
+    let name1 = repo.fief().name(id1).await.unwrap();
+    let name2 = repo.fief().name(id2).await.unwrap();
+    assert_eq!(name1, "协会横幅".to_owned());
+    assert_eq!(name2, "布莉姬特".to_owned());
+
+    let result = repo.fief().name(FiefId(1145141919810)).await;
+    assert!(matches!(result, Err(RepoError::NotFound)));
+}
+
+async fn id(repo: Repositories) {
+    repo.fief().create("协会横幅", None).await.unwrap();
+    repo.fief().create("布莉姬特", None).await.unwrap();
+
+    let id1 = repo.fief().id("协会横幅").await.unwrap();
+    let id2 = repo.fief().id("布莉姬特").await.unwrap();
+    assert_ne!(id1, id2);
+
+    repo.fief().id("初音未来").await.unwrap_err();
+}
+
+async fn fief_by_id(repo: Repositories) {
+    repo.fief()
+        .fief_by_id(FiefId(1145141919810))
+        .await
+        .unwrap_err();
+
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert_eq!(fief.name, "协会横幅".to_owned());
+}
+
+async fn fief_by_name(repo: Repositories) {
+    repo.fief().fief_by_name("协会横幅").await.unwrap_err();
+
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let fief = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    assert_eq!(fief.id, id);
+}
+
+async fn all(repo: Repositories) {
+    let expect = HashSet::new();
+    let actual = repo.fief().all().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect());
+
+    repo.fief().create("协会横幅", None).await.unwrap();
+    repo.fief().create("布莉姬特", None).await.unwrap();
+
+    let expect = HashSet::<String>::from_iter(["协会横幅".to_owned(), "布莉姬特".to_owned()]);
+    let actual = repo.fief().all().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+}
+
+async fn names(repo: Repositories) {
+    assert!(repo.fief().names().await.unwrap().is_empty());
+
+    // 不同数据库对非 ASCII 字符的排序规则不同
+    repo.fief().create("banner", None).await.unwrap();
+    repo.fief().create("bridget", None).await.unwrap();
+    repo.fief().create("avatar", None).await.unwrap();
+
+    let actual = repo.fief().names().await.unwrap();
+    assert_eq!(actual, vec!["avatar", "banner", "bridget"]);
+}
+
+async fn names_of(repo: Repositories) {
+    let id1 = repo.fief().create("banner", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("bridget", None).await.unwrap().unwrap();
+    let id3 = repo.fief().create("avatar", None).await.unwrap().unwrap();
+    repo.fief().create("unrelated", None).await.unwrap();
+    let user = UserId(114514);
+    repo.user().create(user, false).await.unwrap();
+
+    let actual = repo.fief().names_of(user, &[]).await.unwrap();
+    assert!(actual.is_empty());
+
+    repo.user().join(user, id1, None).await.unwrap();
+    repo.fief().set_owner(id2, Some(user)).await.unwrap();
+    repo.fief()
+        .grant_role(id3, RoleId(1919810), None)
+        .await
+        .unwrap();
+
+    let actual = repo.fief().names_of(user, &[]).await.unwrap();
+    assert_eq!(actual, vec!["banner", "bridget"]);
+
+    let roles = [RoleId(1), RoleId(1919810)];
+    let actual = repo.fief().names_of(user, &roles).await.unwrap();
+    assert_eq!(actual, vec!["avatar", "banner", "bridget"]);
+
+    let actual = repo.fief().names_of(UserId(1), &roles).await.unwrap();
+    assert_eq!(actual, vec!["avatar"]);
+}
+
+async fn summaries(repo: Repositories) {
+    assert!(repo.fief().summaries().await.unwrap().is_empty());
+
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    let pos = Position::new(114, 514);
+    let chunk_id = repo
+        .chunk()
+        .create("左侧", id1, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    repo.chunk().update_diff(chunk_id, None, 3).await.unwrap();
+    let pos = Position::new(114, 515);
+    let chunk_id = repo
+        .chunk()
+        .create("右侧", id1, pos)
+        .await
+        .unwrap()
+        .unwrap();
+    repo.chunk().update_diff(chunk_id, None, 4).await.unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().join(UserId(114514), id2, None).await.unwrap();
+
+    let actual = repo.fief().summaries().await.unwrap();
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[0].fief, repo.fief().fief_by_id(id1).await.unwrap());
+    assert_eq!(
+        (
+            actual[0].chunk_count,
+            actual[0].member_count,
+            actual[0].diff_count
+        ),
+        (2, 0, 7)
+    );
+    assert_eq!(actual[1].fief.id, id2);
+    assert_eq!(
+        (
+            actual[1].chunk_count,
+            actual[1].member_count,
+            actual[1].diff_count
+        ),
+        (0, 1, 0)
+    );
+}
+
+async fn fiefs_to_check(repo: Repositories) {
+    let expect = HashSet::new();
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect());
+
+    repo.fief().create("协会横幅", None).await.unwrap();
+    repo.fief().create("布莉姬特", None).await.unwrap();
+    let expect = HashSet::<String>::from_iter(["协会横幅".to_owned(), "布莉姬特".to_owned()]);
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+
+    let id1 = repo.fief().id("协会横幅").await.unwrap();
+    let id2 = repo.fief().id("布莉姬特").await.unwrap();
+    repo.fief().update_last_check(id1, None).await.unwrap();
+    let expect = HashSet::<String>::from_iter(["布莉姬特".to_owned()]);
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+
+    repo.fief().skip_check(id2).await.unwrap();
+    let expect = HashSet::new();
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect());
+
+    repo.fief().keep_check(id2).await.unwrap();
+    let expect = HashSet::<String>::from_iter(["布莉姬特".to_owned()]);
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+}
+
+async fn fiefs_to_check_with_maintenance(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+
+    let now = chrono::Utc::now();
+    let rule = MaintenanceRule {
+        weekday: None,
+        start: (now - chrono::Duration::minutes(30)).time(),
+        duration: chrono::Duration::hours(1),
+        mode: MaintenanceMode::SkipCheck,
+    };
+    repo.fief().add_maintenance_window(id1, rule).await.unwrap();
+    let rule = MaintenanceRule {
+        mode: MaintenanceMode::MuteAlerts,
+        ..rule
+    };
+    repo.fief().add_maintenance_window(id2, rule).await.unwrap();
+
+    let expect = HashSet::<String>::from_iter(["布莉姬特".to_owned()]);
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+
+    repo.fief().mark_should_check_now(id1).await.unwrap();
+    let expect = HashSet::<String>::from_iter(["协会横幅".to_owned(), "布莉姬特".to_owned()]);
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+}
+
+#[test]
+fn maintenance_rule_contains() {
+    let at = |d, h, m| chrono::Utc.with_ymd_and_hms(2025, 9, d, h, m, 0).unwrap();
+    let time = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+    // 2025-09-06 is a Saturday
+    let rule = MaintenanceRule {
+        weekday: Some(chrono::Weekday::Sat),
+        start: time(20, 0),
+        duration: chrono::Duration::hours(2),
+        mode: MaintenanceMode::SkipCheck,
+    };
+    assert!(!rule.contains(at(6, 19, 59)));
+    assert!(rule.contains(at(6, 20, 0)));
+    assert!(rule.contains(at(6, 21, 59)));
+    assert!(!rule.contains(at(6, 22, 0)));
+    assert!(!rule.contains(at(5, 21, 0)));
+
+    let rule = MaintenanceRule {
+        start: time(23, 0),
+        ..rule
+    };
+    assert!(rule.contains(at(6, 23, 30)));
+    assert!(rule.contains(at(7, 0, 30)));
+    assert!(!rule.contains(at(7, 1, 0)));
+    assert!(!rule.contains(at(8, 0, 30)));
+
+    let rule = MaintenanceRule {
+        weekday: None,
+        ..rule
+    };
+    assert!(rule.contains(at(8, 0, 30)));
+}
+
+// - related
+async fn members(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let members = repo.fief().members(id).await.unwrap();
+    assert_eq!(members, vec![]);
+
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().join(UserId(114514), id, None).await.unwrap();
+    repo.user().create(UserId(1919810), true).await.unwrap();
+    repo.user().join(UserId(1919810), id, None).await.unwrap();
+    let actual = repo.fief().members(id).await.unwrap();
+    let expect = HashSet::<UserId>::from_iter([UserId(114514), UserId(1919810)]);
+    assert_eq!(expect, HashSet::from_iter(actual));
+
+    repo.user().leave(UserId(1919810), id).await.unwrap();
+    let actual = repo.fief().members(id).await.unwrap();
+    let expect = HashSet::<UserId>::from_iter([UserId(114514)]);
+    assert_eq!(expect, HashSet::from_iter(actual));
+}
+
+async fn members_with_perms(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    assert!(
+        repo.fief()
+            .members_with_perms(id1)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(repo.fief().all_members().await.unwrap().is_empty());
+
+    repo.user().create(UserId(1919810), false).await.unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let p = Permissions::CHUNK_ALL;
+    repo.user()
+        .join(UserId(1919810), id1, Some(p))
+        .await
+        .unwrap();
+    repo.user().join(UserId(114514), id1, None).await.unwrap();
+    repo.user()
+        .join(UserId(114514), id2, Some(p))
+        .await
+        .unwrap();
+
+    let member = |user_id, fief_id, permissions| Member {
+        user_id: UserId(user_id),
+        fief_id,
+        permissions,
+    };
+    let actual = repo.fief().members_with_perms(id1).await.unwrap();
+    let expect = vec![
+        member(114514, id1, Permissions::NONE),
+        member(1919810, id1, p),
+    ];
+    assert_eq!(actual, expect);
+
+    let actual = repo.fief().all_members().await.unwrap();
+    let expect = vec![
+        member(114514, id1, Permissions::NONE),
+        member(1919810, id1, p),
+        member(114514, id2, p),
+    ];
+    assert_eq!(actual, expect);
+}
+
+async fn chunks(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    assert!(repo.fief().chunks(id).await.unwrap().is_empty());
+
+    let pos = Position::new(114, 514);
+    repo.chunk().create("左侧", id, pos).await.unwrap();
+    assert_eq!(repo.fief().chunks(id).await.unwrap().len(), 1);
+
+    let pos = Position::new(114, 515);
+    repo.chunk().create("右侧", id, pos).await.unwrap();
+    assert_eq!(repo.fief().chunks(id).await.unwrap().len(), 2);
+}
+
+async fn chunk_count(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    assert_eq!(repo.fief().chunk_count(id).await.unwrap(), 0);
+
+    let pos = Position::new(114, 514);
+    repo.chunk().create("左侧", id, pos).await.unwrap();
+    assert_eq!(repo.fief().chunk_count(id).await.unwrap(), 1);
+
+    let pos = Position::new(114, 515);
+    repo.chunk().create("右侧", id, pos).await.unwrap();
+    assert_eq!(repo.fief().chunk_count(id).await.unwrap(), 2);
+}
+
+async fn diff_count(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    assert_eq!(repo.fief().diff_count(id).await.unwrap(), 0);
+
+    let pos = Position::new(114, 514);
+    let chunk_id = repo.chunk().create("左侧", id, pos).await.unwrap().unwrap();
+    repo.chunk().update_diff(chunk_id, None, 3).await.unwrap();
+    assert_eq!(repo.fief().diff_count(id).await.unwrap(), 3);
+
+    let pos = Position::new(114, 515);
+    let chunk_id = repo.chunk().create("右侧", id, pos).await.unwrap().unwrap();
+    repo.chunk().update_diff(chunk_id, None, 4).await.unwrap();
+    assert_eq!(repo.fief().diff_count(id).await.unwrap(), 7);
+}
+
+async fn maintenance_windows(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    assert!(
+        repo.fief()
+            .maintenance_windows(id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let rule = MaintenanceRule {
+        weekday: Some(chrono::Weekday::Sat),
+        start: chrono::NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+        duration: chrono::Duration::hours(2),
+        mode: MaintenanceMode::MuteAlerts,
+    };
+    let window_id = repo.fief().add_maintenance_window(id, rule).await.unwrap();
+    let windows = repo.fief().maintenance_windows(id).await.unwrap();
+    assert_eq!(windows.len(), 1);
+    assert_eq!(Some(windows[0].id), window_id);
+    assert_eq!(windows[0].fief_id, id);
+    assert_eq!(windows[0].rule, rule);
+}
+
+async fn role_grants(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    assert!(repo.fief().role_grants(id1).await.unwrap().is_empty());
+
+    let p = Permissions::CHUNK_ALL;
+    repo.fief()
+        .grant_role(id1, RoleId(1919810), Some(p))
+        .await
+        .unwrap();
+    repo.fief()
+        .grant_role(id1, RoleId(114514), None)
+        .await
+        .unwrap();
+    repo.fief()
+        .grant_role(id2, RoleId(114514), Some(p))
+        .await
+        .unwrap();
+
+    let grant = |role_id, fief_id, permissions| RoleGrant {
+        role_id: RoleId(role_id),
+        fief_id,
+        permissions,
+    };
+    let actual = repo.fief().role_grants(id1).await.unwrap();
+    let expect = vec![
+        grant(114514, id1, Permissions::NONE),
+        grant(1919810, id1, p),
+    ];
+    assert_eq!(actual, expect);
+
+    let actual = repo.fief().role_grants(id2).await.unwrap();
+    assert_eq!(actual, vec![grant(114514, id2, p)]);
+}
+
+async fn fief_roles(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    assert!(repo.fief().fief_roles(id1).await.unwrap().is_empty());
+
+    let p1 = Permissions::CHUNK_EDIT;
+    let p2 = Permissions::MEMBER_ALL;
+    let r1 = repo.fief().create_fief_role(id1, "画师", p1).await.unwrap();
+    let r2 = repo.fief().create_fief_role(id1, "军官", p2).await.unwrap();
+    repo.fief().create_fief_role(id2, "画师", p1).await.unwrap();
+
+    let actual = repo.fief().fief_roles(id1).await.unwrap();
+    let actual = actual
+        .into_iter()
+        .map(|r| (r.id, r.fief_id, r.name, r.permissions))
+        .collect::<Vec<_>>();
+    let expect = vec![
+        (r1.unwrap(), id1, "画师".to_string(), p1),
+        (r2.unwrap(), id1, "军官".to_string(), p2),
+    ];
+    assert_eq!(actual, expect);
+}
+
+async fn fief_role_by_name(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let result = repo.fief().fief_role_by_name(id, "画师").await;
+    assert!(matches!(result, Err(RepoError::NotFound)));
+
+    let p = Permissions::CHUNK_EDIT;
+    let role_id = repo.fief().create_fief_role(id, "画师", p).await.unwrap();
+    let role = repo.fief().fief_role_by_name(id, "画师").await.unwrap();
+    assert_eq!(Some(role.id), role_id);
+    assert_eq!(role.permissions, p);
+}
+
+// [U] Update
+// - self or fields
+async fn update_last_check(repo: Repositories) {
+    repo.fief().create("协会横幅", None).await.unwrap();
+    let Fief { last_check, id, .. } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    let old = last_check;
+
+    repo.fief().update_last_check(id, None).await.unwrap();
+    let Fief { last_check, .. } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    let new = last_check;
+    assert_ne!(old, new);
+}
+
+async fn set_last_check_failed(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    assert!(!repo.fief().fief_by_id(id).await.unwrap().last_check_failed);
+
+    repo.fief().set_last_check_failed(id, true).await.unwrap();
+    assert!(repo.fief().fief_by_id(id).await.unwrap().last_check_failed);
+
+    repo.fief().set_last_check_failed(id, false).await.unwrap();
+    assert!(!repo.fief().fief_by_id(id).await.unwrap().last_check_failed);
+}
+
+async fn set_check_interval(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    repo.fief()
+        .set_check_interval(id, chrono::Duration::nanoseconds(1))
+        .await
+        .unwrap();
+    let Fief { check_interval, .. } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    let new = check_interval;
+
+    let min = cfg().check.minimum_interval_min as i64;
+    assert_eq!(new.num_minutes(), min);
+
+    repo.fief()
+        .set_check_interval(id, chrono::Duration::weeks(1))
+        .await
+        .unwrap();
+    let Fief { check_interval, .. } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    let new = check_interval;
+    assert_eq!(new.num_minutes(), chrono::Duration::weeks(1).num_minutes());
+}
+
+async fn set_adaptive(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert!(!fief.adaptive);
+    assert_eq!(fief.effective_interval, fief.check_interval);
+
+    repo.fief().set_adaptive(id, true).await.unwrap();
+    let min = chrono::Duration::minutes(cfg().check.minimum_interval_min as i64);
+    repo.fief().update_adaptive_state(id, min, 0).await.unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert!(fief.adaptive);
+    assert_eq!(fief.effective_interval, min);
+
+    repo.fief().set_adaptive(id, false).await.unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert!(!fief.adaptive);
+    assert_eq!(fief.effective_interval, fief.check_interval);
+}
+
+async fn set_image_retention(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    assert_eq!(
+        repo.fief().fief_by_id(id).await.unwrap().image_retention,
+        10
+    );
+
+    repo.fief().set_image_retention(id, 3).await.unwrap();
+    assert_eq!(repo.fief().fief_by_id(id).await.unwrap().image_retention, 3);
+
+    repo.fief().set_image_retention(id, 0).await.unwrap();
+    assert_eq!(repo.fief().fief_by_id(id).await.unwrap().image_retention, 1);
+}
+
+async fn update_adaptive_state(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.fief().set_adaptive(id, true).await.unwrap();
+
+    repo.fief()
+        .update_adaptive_state(id, chrono::Duration::nanoseconds(1), 2)
+        .await
+        .unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    let min = cfg().check.minimum_interval_min as i64;
+    assert_eq!(fief.effective_interval.num_minutes(), min);
+    assert_eq!(fief.clean_streak, 2);
+
+    repo.fief().update_last_check(id, None).await.unwrap();
+    repo.fief()
+        .update_adaptive_state(id, chrono::Duration::zero(), 0)
+        .await
+        .unwrap();
+    assert!(repo.fief().fiefs_to_check().await.unwrap().is_empty());
+
+    let ago = chrono::Utc::now() - chrono::Duration::minutes(min + 1);
+    repo.fief().update_last_check(id, Some(ago)).await.unwrap();
+    assert_eq!(repo.fief().fiefs_to_check().await.unwrap().len(), 1);
+
+    repo.fief()
+        .set_check_interval(id, chrono::Duration::weeks(1))
+        .await
+        .unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert_eq!(fief.effective_interval, chrono::Duration::weeks(1));
+    assert_eq!(fief.clean_streak, 0);
+    assert!(repo.fief().fiefs_to_check().await.unwrap().is_empty());
+}
+
+async fn skip_check(repo: Repositories) {
+    repo.fief().create("协会横幅", None).await.unwrap();
+    let Fief {
+        skip_check_until,
+        id,
+        ..
+    } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    assert_eq!(
+        skip_check_until,
+        chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap()
+    );
+
+    repo.fief().skip_check(id).await.unwrap();
+    let Fief {
+        skip_check_until, ..
+    } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    assert_eq!(
+        skip_check_until,
+        chrono::Utc.with_ymd_and_hms(2077, 1, 1, 0, 0, 0).unwrap()
+    );
+}
+
+async fn keep_check(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.fief().skip_check(id).await.unwrap();
+    let Fief {
+        skip_check_until, ..
+    } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    assert_eq!(
+        skip_check_until,
+        chrono::Utc.with_ymd_and_hms(2077, 1, 1, 0, 0, 0).unwrap()
+    );
+
+    repo.fief().keep_check(id).await.unwrap();
+    let Fief {
+        skip_check_until, ..
+    } = repo.fief().fief_by_name("协会横幅").await.unwrap();
+    assert_eq!(
+        skip_check_until,
+        chrono::Utc.with_ymd_and_hms(1919, 11, 4, 5, 1, 4).unwrap()
+    );
+}
+
+async fn skip_check_for(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    repo.fief()
+        .skip_check_for(id, chrono::Duration::seconds(1), None)
+        .await
+        .unwrap();
+    let expect = HashSet::new();
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect());
+
+    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    let expect = HashSet::<String>::from_iter(["协会横幅".to_owned()]);
+    let actual = repo.fief().fiefs_to_check().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+}
+
+async fn set_owner(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert_eq!(fief.owner, None);
+
+    repo.fief()
+        .set_owner(id, Some(UserId(114514)))
+        .await
+        .unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert_eq!(fief.owner, Some(UserId(114514)));
+    let summaries = repo.fief().summaries().await.unwrap();
+    assert_eq!(summaries[0].fief.owner, Some(UserId(114514)));
+
+    repo.fief().set_owner(id, None).await.unwrap();
+    let fief = repo.fief().fief_by_id(id).await.unwrap();
+    assert_eq!(fief.owner, None);
+}
+
+async fn rename(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    repo.fief().rename(id, "协会横幅#0").await.unwrap();
+    let name = repo.fief().name(id).await.unwrap();
+    assert_eq!(name, "协会横幅#0".to_owned());
+}
+
+// - related
+async fn add_maintenance_window(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let rule = MaintenanceRule {
+        weekday: None,
+        start: chrono::NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+        duration: chrono::Duration::hours(2),
+        mode: MaintenanceMode::SkipCheck,
+    };
+    let result = repo.fief().add_maintenance_window(id, rule).await.unwrap();
+    assert!(result.is_some());
+
+    let result = repo
+        .fief()
+        .add_maintenance_window(FiefId(114514), rule)
+        .await;
+    assert!(matches!(result, Err(RepoError::ForeignKey)));
+
+    let rule = MaintenanceRule {
+        duration: chrono::Duration::hours(25),
+        ..rule
+    };
+    let result = repo.fief().add_maintenance_window(id, rule).await;
+    assert!(matches!(result, Err(RepoError::Invalid(_))));
+}
+
+async fn set_role_permissions(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    repo.fief()
+        .grant_role(id1, RoleId(114514), None)
+        .await
+        .unwrap();
+    repo.fief()
+        .grant_role(id2, RoleId(114514), None)
+        .await
+        .unwrap();
+
+    let p = Permissions::FIEF_EDIT | Permissions::MEMBER_INVITE;
+    repo.fief()
+        .set_role_permissions(id1, RoleId(114514), p)
+        .await
+        .unwrap();
+
+    let grants = repo.fief().role_grants(id1).await.unwrap();
+    assert_eq!(grants[0].permissions, p);
+    let grants = repo.fief().role_grants(id2).await.unwrap();
+    assert_eq!(grants[0].permissions, Permissions::NONE);
+}
+
+async fn set_fief_role_permissions(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let role_id = repo
+        .fief()
+        .create_fief_role(id, "画师", Permissions::CHUNK_EDIT)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let p = Permissions::CHUNK_ALL | Permissions::FIEF_EDIT;
+    repo.fief()
+        .set_fief_role_permissions(role_id, p)
+        .await
+        .unwrap();
+    let role = repo.fief().fief_role_by_name(id, "画师").await.unwrap();
+    assert_eq!(role.permissions, p);
+}
+
+// [D] Delete
+async fn remove_by_id(repo: Repositories) {
+    repo.fief().create("协会横幅", None).await.unwrap();
+    let id = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+
+    use std::collections::HashSet;
+
+    let result = repo.fief().remove_by_id(id).await.unwrap();
+    assert!(result);
+
+    let expect = HashSet::<String>::from_iter(["协会横幅".to_owned()]);
+    let actual = repo.fief().all().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+
+    let result = repo.fief().remove_by_id(id).await.unwrap();
+    assert!(!result);
+}
+
+async fn remove_by_name(repo: Repositories) {
+    repo.fief().create("协会横幅", None).await.unwrap();
+    repo.fief().create("布莉姬特", None).await.unwrap();
+
+    use std::collections::HashSet;
+
+    let result = repo.fief().remove_by_name("布莉姬特").await.unwrap();
+    assert!(result);
+
+    let expect = HashSet::<String>::from_iter(["协会横幅".to_owned()]);
+    let actual = repo.fief().all().await.unwrap();
+    assert_eq!(expect, actual.into_iter().map(|f| f.name).collect());
+
+    let result = repo.fief().remove_by_name("布莉姬特").await.unwrap();
+    assert!(!result);
+}
+
+async fn remove_maintenance_window(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+
+    let rule = MaintenanceRule {
+        weekday: None,
+        start: chrono::NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+        duration: chrono::Duration::hours(2),
+        mode: MaintenanceMode::SkipCheck,
+    };
+    let window_id = repo.fief().add_maintenance_window(id1, rule).await.unwrap();
+    let window_id = window_id.unwrap();
+
+    let result = repo.fief().remove_maintenance_window(id2, window_id).await;
+    assert!(!result.unwrap());
+
+    let result = repo.fief().remove_maintenance_window(id1, window_id).await;
+    assert!(result.unwrap());
+    assert!(
+        repo.fief()
+            .maintenance_windows(id1)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let window_id = MaintenanceWindowId(1145141919810);
+    let result = repo.fief().remove_maintenance_window(id1, window_id).await;
+    assert!(!result.unwrap());
+}
+
+async fn revoke_role(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    repo.fief()
+        .grant_role(id1, RoleId(114514), None)
+        .await
+        .unwrap();
+
+    let result = repo.fief().revoke_role(id2, RoleId(114514)).await;
+    assert!(!result.unwrap());
+
+    let result = repo.fief().revoke_role(id1, RoleId(114514)).await;
+    assert!(result.unwrap());
+    assert!(repo.fief().role_grants(id1).await.unwrap().is_empty());
+
+    let result = repo.fief().revoke_role(id1, RoleId(114514)).await;
+    assert!(!result.unwrap());
+}
+
+async fn remove_fief_role(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let role_id = repo
+        .fief()
+        .create_fief_role(id, "画师", Permissions::CHUNK_EDIT)
+        .await
+        .unwrap()
+        .unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().join(UserId(114514), id, None).await.unwrap();
+    repo.user()
+        .set_fief_role_in(UserId(114514), id, Some(role_id))
+        .await
+        .unwrap();
+
+    assert!(repo.fief().remove_fief_role(role_id).await.unwrap());
+    assert!(repo.fief().fief_roles(id).await.unwrap().is_empty());
+    let role = repo.user().fief_role_in(UserId(114514), id).await.unwrap();
+    assert!(role.is_none());
+    assert!(repo.user().is_member_of(UserId(114514), id).await.unwrap());
+
+    assert!(!repo.fief().remove_fief_role(role_id).await.unwrap());
+}
+
+async fn remove_cascade(repo: Repositories) {
+    let id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().join(UserId(114514), id, None).await.unwrap();
+    let chunk_id = repo.chunk().create("左侧", id, Position::new(114, 514));
+    let chunk_id = chunk_id.await.unwrap().unwrap();
+    let img = Some(ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    repo.chunk()
+        .update_ref_img(chunk_id, img, None)
+        .await
+        .unwrap();
+    let rule = MaintenanceRule {
+        weekday: None,
+        start: chrono::NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+        duration: chrono::Duration::hours(2),
+        mode: MaintenanceMode::SkipCheck,
+    };
+    repo.fief().add_maintenance_window(id, rule).await.unwrap();
+    repo.fief()
+        .grant_role(id, RoleId(1919810), None)
+        .await
+        .unwrap();
+
+    assert!(repo.fief().remove_by_id(id).await.unwrap());
+    let fiefs = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert!(fiefs.is_empty());
+    repo.user().user_by_id(UserId(114514)).await.unwrap();
+    repo.chunk().chunk_by_id(chunk_id).await.unwrap_err();
+    let keys = repo.chunk().referenced_image_keys().await.unwrap();
+    assert!(keys.is_empty());
+    assert!(
+        repo.fief()
+            .maintenance_windows(id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(repo.fief().role_grants(id).await.unwrap().is_empty());
+    assert!(repo.fief().fief_roles(id).await.unwrap().is_empty());
+
+    // 删除后不会重复使用 id
+    let new_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    assert_ne!(new_id, id);
+    let new_chunk_id = repo.chunk().create("左侧", new_id, Position::new(114, 514));
+    let new_chunk_id: ChunkId = new_chunk_id.await.unwrap().unwrap();
+    assert_ne!(new_chunk_id, chunk_id);
+}
+
+repo_tests!(
+    create,
+    grant_role,
+    create_fief_role,
+    name,
+    id,
+    fief_by_id,
+    fief_by_name,
+    all,
+    names,
+    names_of,
+    summaries,
+    fiefs_to_check,
+    fiefs_to_check_with_maintenance,
+    members,
+    members_with_perms,
+    chunks,
+    chunk_count,
+    diff_count,
+    maintenance_windows,
+    role_grants,
+    fief_roles,
+    fief_role_by_name,
+    update_last_check,
+    set_last_check_failed,
+    set_check_interval,
+    set_adaptive,
+    set_image_retention,
+    update_adaptive_state,
+    skip_check,
+    keep_check,
+    skip_check_for,
+    set_owner,
+    rename,
+    add_maintenance_window,
+    set_role_permissions,
+    set_fief_role_permissions,
+    remove_by_id,
+    remove_by_name,
+    remove_maintenance_window,
+    revoke_role,
+    remove_fief_role,
+    remove_cascade,
+);
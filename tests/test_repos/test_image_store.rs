@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use wmonitor::{
+    Repositories,
+    core::ImagePng,
+    domains::ImageKey,
+    repos::{ImageStorage, fs_image_store::FsImageStore, image_store, traits::ImageStore},
+};
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("wmonitor-{name}-{}-{n}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+async fn check_store(store: &dyn ImageStore) {
+    let img = ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    let key = store.put(&img).await.unwrap();
+    assert_eq!(key, ImageKey::of(&img));
+    assert_eq!(store.put(&img).await.unwrap(), key);
+    assert!(store.contains(&key).await.unwrap());
+    assert_eq!(store.get(&key).await.unwrap(), Some(img));
+    assert_eq!(store.keys().await.unwrap(), vec![key.clone()]);
+
+    assert!(store.remove(&key).await.unwrap());
+    assert!(!store.remove(&key).await.unwrap());
+    assert!(!store.contains(&key).await.unwrap());
+    assert_eq!(store.get(&key).await.unwrap(), None);
+    assert!(store.keys().await.unwrap().is_empty());
+}
+
+async fn sqlx_image_store(repo: Repositories) {
+    check_store(repo.images()).await;
+}
+
+#[tokio::test]
+async fn fs_image_store() {
+    let dir = temp_dir("fs-image-store");
+    check_store(&FsImageStore::new(&dir)).await;
+    FsImageStore::new(&dir)
+        .get(&ImageKey("../cfg".to_owned()))
+        .await
+        .unwrap_err();
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+async fn copy_images(repo: Repositories) {
+    let dir = temp_dir("copy-images");
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id = repo.chunk().create("左侧", fief_id, [114, 514].into());
+    let id = id.await.unwrap().unwrap();
+    let img = ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    repo.chunk()
+        .update_ref_img(id, Some(img.clone()), None)
+        .await
+        .unwrap();
+
+    let to = FsImageStore::new(&dir);
+    let count = image_store::copy_images(repo.images(), &to, true).await;
+    assert_eq!(count.unwrap(), 1);
+    assert!(repo.images().keys().await.unwrap().is_empty());
+    assert_eq!(to.get(&ImageKey::of(&img)).await.unwrap(), Some(img));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn filesystem_storage() {
+    let dir = temp_dir("filesystem-storage");
+    let storage = ImageStorage::Filesystem(dir.clone());
+    let repo = Repositories::from_sqlx_with("sqlite::memory:", storage);
+    let repo = repo.await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id = repo.chunk().create("左侧", fief_id, [114, 514].into());
+    let id = id.await.unwrap().unwrap();
+
+    let img = ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    repo.chunk()
+        .update_mask_img(id, Some(img.clone()), None)
+        .await
+        .unwrap();
+    assert_eq!(repo.chunk().mask_img(id).await.unwrap(), Some(img.clone()));
+    assert!(
+        FsImageStore::new(&dir)
+            .contains(&ImageKey::of(&img))
+            .await
+            .unwrap()
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+async fn collect_image_garbage(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.fief().set_image_retention(fief_id, 1).await.unwrap();
+    let id = repo.chunk().create("左侧", fief_id, [114, 514].into());
+    let id = id.await.unwrap().unwrap();
+    let other = repo.chunk().create("右侧", fief_id, [115, 514].into());
+    let other = other.await.unwrap().unwrap();
+
+    // 保留当前版本以外的 1 个历史版本，最早的图片不再被引用
+    let old = ImagePng::new(vec![0xCA, 0xFE]);
+    let prev = ImagePng::new(vec![0xF0, 0x0D]);
+    let new = ImagePng::new(vec![0xBA, 0xBE]);
+    for img in [&old, &prev, &new] {
+        repo.chunk()
+            .update_ref_img(id, Some(img.clone()), None)
+            .await
+            .unwrap();
+    }
+    // 相同的图片只保存一份，仍被引用时不会被删除
+    repo.chunk()
+        .update_ref_img(other, Some(new.clone()), None)
+        .await
+        .unwrap();
+    assert_eq!(repo.images().keys().await.unwrap().len(), 3);
+    let unused = repo.unreferenced_images().await.unwrap();
+    assert_eq!(unused, [ImageKey::of(&old)].into());
+
+    for key in &unused {
+        assert!(repo.images().remove(key).await.unwrap());
+    }
+    let mut keys = repo.images().keys().await.unwrap();
+    keys.sort();
+    let mut expected = vec![ImageKey::of(&prev), ImageKey::of(&new)];
+    expected.sort();
+    assert_eq!(keys, expected);
+    assert_eq!(repo.chunk().ref_img(id).await.unwrap(), Some(new));
+    assert!(repo.unreferenced_images().await.unwrap().is_empty());
+}
+
+repo_tests!(sqlx_image_store, collect_image_garbage, copy_images,);
@@ -0,0 +1,95 @@
+use wmonitor::{
+    Repositories,
+    core::{ImagePng, Position},
+    domains::UserId,
+};
+
+async fn commit(repo: Repositories) {
+    let uow = repo.begin().await.unwrap();
+    uow.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = uow.fief().create("协会横幅", None).await.unwrap().unwrap();
+    assert!(
+        uow.user()
+            .join(UserId(114514), fief_id, None)
+            .await
+            .unwrap()
+    );
+    let pos = Position::new(114, 514);
+    let chunk_id = uow.chunk().create("左侧", fief_id, pos).await.unwrap();
+    let img = ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    uow.chunk()
+        .update_ref_img(chunk_id.unwrap(), Some(img.clone()), None)
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    assert!(
+        repo.user()
+            .is_member_of(UserId(114514), fief_id)
+            .await
+            .unwrap()
+    );
+    let chunk = repo.chunk().chunk_by_name(fief_id, "左侧").await.unwrap();
+    assert_eq!(repo.chunk().ref_img(chunk.id).await.unwrap(), Some(img));
+}
+
+async fn rollback(repo: Repositories) {
+    let uow = repo.begin().await.unwrap();
+    let fief_id = uow.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let pos = Position::new(114, 514);
+    let chunk_id = uow.chunk().create("左侧", fief_id, pos).await.unwrap();
+    let img = ImagePng::new(vec![0xCA, 0xFE, 0xBA, 0xBE]);
+    uow.chunk()
+        .update_ref_img(chunk_id.unwrap(), Some(img), None)
+        .await
+        .unwrap();
+    assert_eq!(uow.fief().all().await.unwrap().len(), 1);
+    uow.rollback().await.unwrap();
+
+    assert!(repo.fief().all().await.unwrap().is_empty());
+    repo.fief().id("协会横幅").await.unwrap_err();
+
+    // 未提交就丢弃同样会回滚
+    let uow = repo.begin().await.unwrap();
+    uow.user().create(UserId(114514), false).await.unwrap();
+    drop(uow);
+    assert!(repo.user().all().await.unwrap().is_empty());
+}
+
+async fn isolation(repo: Repositories) {
+    let uow = repo.begin().await.unwrap();
+    uow.user().create(UserId(114514), false).await.unwrap();
+    uow.commit().await.unwrap();
+
+    let uow = repo.begin().await.unwrap();
+    uow.user().create(UserId(1919810), false).await.unwrap();
+    // 约束冲突不会中断事务
+    let id = uow.user().create(UserId(114514), false).await.unwrap();
+    assert!(id.is_none());
+    uow.fief().create("协会横幅", None).await.unwrap().unwrap();
+    assert!(uow.fief().create("协会横幅", None).await.unwrap().is_none());
+    uow.commit().await.unwrap();
+
+    assert_eq!(repo.user().all().await.unwrap().len(), 2);
+    assert_eq!(repo.fief().all().await.unwrap().len(), 1);
+}
+
+async fn concurrent_writes(repo: Repositories) {
+    let uow = repo.begin().await.unwrap();
+    // 工作单元开始后在其外部写入的数据不会在提交时丢失
+    let outer = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let inner = uow.fief().create("像素画", None).await.unwrap().unwrap();
+    assert_ne!(outer, inner);
+    uow.commit().await.unwrap();
+
+    assert_eq!(repo.fief().id("协会横幅").await.unwrap(), outer);
+    assert_eq!(repo.fief().id("像素画").await.unwrap(), inner);
+}
+
+async fn nested(repo: Repositories) {
+    let uow = repo.begin().await.unwrap();
+    assert!(uow.begin().await.is_err());
+    uow.rollback().await.unwrap();
+}
+
+repo_tests!(commit, rollback, isolation, concurrent_writes, nested);
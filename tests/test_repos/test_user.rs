@@ -0,0 +1,603 @@
+use wmonitor::{
+    RepoError, Repositories,
+    domains::{FiefId, FiefRole, Permissions, User, UserId},
+};
+
+fn new_user(id: i64, is_admin: bool) -> User {
+    let id = UserId(id);
+    User { id, is_admin }
+}
+
+async fn create(repo: Repositories) {
+    let users = repo.user().all().await.unwrap();
+    assert_eq!(users, vec![]);
+
+    let id = repo.user().create(UserId(114514), true).await.unwrap();
+    assert!(id.is_some());
+
+    let users = repo.user().all().await.unwrap();
+    assert_eq!(users, vec![new_user(114514, true)]);
+
+    let id = repo.user().create(UserId(114514), false).await.unwrap();
+    assert!(id.is_none());
+
+    let users = repo.user().all().await.unwrap();
+    assert_eq!(users, vec![new_user(114514, true)]);
+}
+
+async fn user_by_id(repo: Repositories) {
+    let result = repo.user().user_by_id(UserId(114514)).await;
+    assert!(matches!(result, Err(RepoError::NotFound)));
+
+    repo.user().create(UserId(114514), true).await.unwrap();
+
+    let user = repo.user().user_by_id(UserId(114514)).await.unwrap();
+    assert_eq!(user, new_user(114514, true));
+}
+
+async fn all(repo: Repositories) {
+    let expect = HashSet::new();
+    let actual = repo.user().all().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect::<HashSet<User>>());
+
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().create(UserId(1919), true).await.unwrap();
+    repo.user().create(UserId(810), false).await.unwrap();
+
+    use std::collections::HashSet;
+    let expect = HashSet::<User>::from_iter([
+        new_user(114514, false),
+        new_user(1919, true),
+        new_user(810, false),
+    ]);
+    let actual = repo.user().all().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect::<HashSet<User>>());
+}
+
+async fn admins(repo: Repositories) {
+    let expect = HashSet::new();
+    let actual = repo.user().admins().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect::<HashSet<User>>());
+
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().create(UserId(1919), true).await.unwrap();
+    repo.user().create(UserId(810), false).await.unwrap();
+
+    use std::collections::HashSet;
+    let expect = HashSet::<User>::from_iter([new_user(1919, true)]);
+    let actual = repo.user().admins().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect::<HashSet<User>>());
+}
+
+async fn non_admins(repo: Repositories) {
+    let expect = HashSet::new();
+    let actual = repo.user().non_admins().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect::<HashSet<User>>());
+
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().create(UserId(1919), true).await.unwrap();
+    repo.user().create(UserId(810), false).await.unwrap();
+
+    use std::collections::HashSet;
+    let expect = HashSet::<User>::from_iter([new_user(114514, false), new_user(810, false)]);
+    let actual = repo.user().non_admins().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect::<HashSet<User>>());
+}
+
+async fn fiefs(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![]);
+
+    repo.user()
+        .join(UserId(114514), fief_id, None)
+        .await
+        .unwrap();
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![fief_id]);
+
+    repo.user().leave(UserId(114514), fief_id).await.unwrap();
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![]);
+}
+
+async fn is_member_of(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let is_member = repo
+        .user()
+        .is_member_of(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert!(!is_member);
+
+    repo.user()
+        .join(UserId(114514), fief_id, None)
+        .await
+        .unwrap();
+    let is_member = repo
+        .user()
+        .is_member_of(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert!(is_member);
+}
+
+async fn permissions_in(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let p = Permissions::CHUNK_ALL;
+    repo.user()
+        .join(UserId(114514), fief_id, Some(p))
+        .await
+        .unwrap();
+
+    let expect = Permissions::CHUNK_ADD | Permissions::CHUNK_EDIT | Permissions::CHUNK_DELETE;
+    let actual = repo
+        .user()
+        .permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, expect);
+}
+
+async fn effective_permissions_in(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let result = repo
+        .user()
+        .effective_permissions_in(UserId(114514), fief_id)
+        .await;
+    assert!(matches!(result, Err(RepoError::NotFound)));
+
+    let p = Permissions::CHUNK_EDIT;
+    repo.user()
+        .join(UserId(114514), fief_id, Some(p))
+        .await
+        .unwrap();
+    let actual = repo
+        .user()
+        .effective_permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, p);
+
+    let role_p = Permissions::FIEF_EDIT | Permissions::CHUNK_EDIT;
+    let role_id = repo.fief().create_fief_role(fief_id, "画师", role_p);
+    let role_id = role_id.await.unwrap().unwrap();
+    repo.user()
+        .set_fief_role_in(UserId(114514), fief_id, Some(role_id))
+        .await
+        .unwrap();
+    let actual = repo
+        .user()
+        .effective_permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, Permissions::FIEF_EDIT | Permissions::CHUNK_EDIT);
+    let actual = repo
+        .user()
+        .permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, p);
+
+    // 修改职位的权限会影响担任该职位的成员
+    let role_p = Permissions::CHUNK_ADD;
+    repo.fief()
+        .set_fief_role_permissions(role_id, role_p)
+        .await
+        .unwrap();
+    let actual = repo
+        .user()
+        .effective_permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, Permissions::CHUNK_ADD | Permissions::CHUNK_EDIT);
+
+    repo.fief().remove_fief_role(role_id).await.unwrap();
+    let actual = repo
+        .user()
+        .effective_permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, p);
+}
+
+async fn fief_role_in(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let role_id = repo
+        .fief()
+        .create_fief_role(fief_id, "画师", Permissions::CHUNK_EDIT)
+        .await
+        .unwrap()
+        .unwrap();
+    let role = repo
+        .user()
+        .fief_role_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert!(role.is_none());
+
+    repo.user()
+        .join(UserId(114514), fief_id, None)
+        .await
+        .unwrap();
+    let role = repo
+        .user()
+        .fief_role_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert!(role.is_none());
+
+    repo.user()
+        .set_fief_role_in(UserId(114514), fief_id, Some(role_id))
+        .await
+        .unwrap();
+    let expect = FiefRole {
+        id: role_id,
+        fief_id,
+        name: "画师".into(),
+        permissions: Permissions::CHUNK_EDIT,
+    };
+    let role = repo
+        .user()
+        .fief_role_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(role, Some(expect));
+}
+
+async fn invite(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let later = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    let inviter = Some(UserId(1919));
+    let perms = Some(Permissions::CHUNK_EDIT);
+    let role = repo
+        .fief()
+        .create_fief_role(fief_id, "画师", Permissions::NONE);
+    let role = role.await.unwrap();
+    let result = repo
+        .user()
+        .invite(UserId(114514), fief_id, perms, role, inviter, later);
+    let id = result.await.unwrap().unwrap();
+    let result = repo
+        .user()
+        .invite(UserId(114514), fief_id, None, None, None, later);
+    assert!(result.await.unwrap().is_none());
+
+    let invitation = repo.user().invitation_by_id(id).await.unwrap();
+    assert_eq!(invitation.user_id, UserId(114514));
+    assert_eq!(invitation.fief_id, fief_id);
+    assert_eq!(invitation.inviter, inviter);
+    assert_eq!(invitation.permissions, perms);
+    assert_eq!(invitation.fief_role, role);
+    assert!(!invitation.is_expired(chrono::Utc::now()));
+
+    // 职位被删除后邀请仍然有效，只是不再担任该职位
+    repo.fief().remove_fief_role(role.unwrap()).await.unwrap();
+    let invitation = repo.user().invitation_by_id(id).await.unwrap();
+    assert_eq!(invitation.fief_role, None);
+
+    let result = repo
+        .user()
+        .invite(UserId(810), fief_id, None, None, None, later);
+    assert!(matches!(result.await, Err(RepoError::ForeignKey)));
+    let result = repo
+        .user()
+        .invite(UserId(114514), FiefId(1919810), None, None, None, later);
+    assert!(matches!(result.await, Err(RepoError::ForeignKey)));
+}
+
+async fn invite_replaces_expired(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let earlier = chrono::Utc::now() - chrono::Duration::hours(1);
+    let later = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    let result = repo
+        .user()
+        .invite(UserId(114514), fief_id, None, None, None, earlier);
+    let old = result.await.unwrap().unwrap();
+    let result = repo
+        .user()
+        .invite(UserId(114514), fief_id, None, None, None, later);
+    let new = result.await.unwrap().unwrap();
+    assert_ne!(old, new);
+
+    let result = repo.user().invitation_by_id(old).await;
+    assert!(matches!(result, Err(RepoError::NotFound)));
+    let invitations = repo.user().invitations(UserId(114514)).await.unwrap();
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0].id, new);
+}
+
+async fn invitations(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    let id3 = repo.fief().create("猫猫", None).await.unwrap().unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().create(UserId(1919), false).await.unwrap();
+    let earlier = chrono::Utc::now() - chrono::Duration::hours(1);
+    let later = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    let user = UserId(114514);
+    let a = repo
+        .user()
+        .invite(user, id1, None, None, None, later)
+        .await
+        .unwrap();
+    let b = repo
+        .user()
+        .invite(user, id2, None, None, None, later)
+        .await
+        .unwrap();
+    repo.user()
+        .invite(user, id3, None, None, None, earlier)
+        .await
+        .unwrap();
+    repo.user()
+        .invite(UserId(1919), id1, None, None, None, later)
+        .await
+        .unwrap();
+
+    let result = repo.user().invitations(user).await.unwrap();
+    let ids = result.into_iter().map(|i| Some(i.id)).collect::<Vec<_>>();
+    assert_eq!(ids, vec![a, b]);
+
+    let result = repo.user().invitations(UserId(810)).await.unwrap();
+    assert!(result.is_empty());
+}
+
+async fn set_admin(repo: Repositories) {
+    repo.user().set_admin(UserId(114514), true).await.unwrap();
+
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let User { is_admin, .. } = repo.user().user_by_id(UserId(114514)).await.unwrap();
+    assert!(!is_admin);
+
+    repo.user().set_admin(UserId(114514), true).await.unwrap();
+    let User { is_admin, .. } = repo.user().user_by_id(UserId(114514)).await.unwrap();
+    assert!(is_admin);
+}
+
+async fn set_permissions_in(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.user()
+        .join(UserId(114514), fief_id, None)
+        .await
+        .unwrap();
+    let expect = Permissions::NONE;
+    let actual = repo
+        .user()
+        .permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, expect);
+
+    let p = Permissions::ALL - Permissions::MEMBER_KICK - Permissions::MEMBER_EDIT_PERMS;
+    repo.user()
+        .set_permissions_in(UserId(114514), fief_id, p)
+        .await
+        .unwrap();
+
+    let expect = Permissions::FIEF_ALL | Permissions::CHUNK_ALL | Permissions::MEMBER_INVITE;
+    let actual = repo
+        .user()
+        .permissions_in(UserId(114514), fief_id)
+        .await
+        .unwrap();
+    assert_eq!(actual, expect);
+}
+
+async fn set_fief_role_in(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    let role_id = repo
+        .fief()
+        .create_fief_role(id1, "画师", Permissions::CHUNK_EDIT)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // 不是领地成员
+    let result = repo
+        .user()
+        .set_fief_role_in(UserId(114514), id1, Some(role_id))
+        .await;
+    assert!(!result.unwrap());
+
+    repo.user().join(UserId(114514), id1, None).await.unwrap();
+    repo.user().join(UserId(114514), id2, None).await.unwrap();
+
+    // 职位不属于该领地
+    let result = repo
+        .user()
+        .set_fief_role_in(UserId(114514), id2, Some(role_id))
+        .await;
+    assert!(!result.unwrap());
+
+    let result = repo
+        .user()
+        .set_fief_role_in(UserId(114514), id1, Some(role_id))
+        .await;
+    assert!(result.unwrap());
+    let role = repo.user().fief_role_in(UserId(114514), id1).await.unwrap();
+    assert_eq!(role.map(|r| r.id), Some(role_id));
+
+    let result = repo
+        .user()
+        .set_fief_role_in(UserId(114514), id1, None)
+        .await;
+    assert!(result.unwrap());
+    let role = repo.user().fief_role_in(UserId(114514), id1).await.unwrap();
+    assert!(role.is_none());
+
+    // 重新加入领地后不再担任原来的职位
+    repo.user()
+        .set_fief_role_in(UserId(114514), id1, Some(role_id))
+        .await
+        .unwrap();
+    repo.user().leave(UserId(114514), id1).await.unwrap();
+    repo.user().join(UserId(114514), id1, None).await.unwrap();
+    let role = repo.user().fief_role_in(UserId(114514), id1).await.unwrap();
+    assert!(role.is_none());
+}
+
+async fn join(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![]);
+
+    let success = repo
+        .user()
+        .join(UserId(114514), fief_id, None)
+        .await
+        .unwrap();
+    assert!(success);
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![fief_id]);
+
+    let success = repo
+        .user()
+        .join(UserId(114514), fief_id, None)
+        .await
+        .unwrap();
+    assert!(!success);
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![fief_id]);
+
+    let result = repo.user().join(UserId(1919810), fief_id, None).await;
+    assert!(matches!(result, Err(RepoError::ForeignKey)));
+}
+
+async fn leave(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.user()
+        .join(UserId(114514), fief_id, None)
+        .await
+        .unwrap();
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![fief_id]);
+
+    let success = repo.user().leave(UserId(114514), fief_id).await.unwrap();
+    assert!(success);
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![]);
+
+    let success = repo.user().leave(UserId(114514), fief_id).await.unwrap();
+    assert!(!success);
+    let actual = repo.user().fiefs(UserId(114514)).await.unwrap();
+    assert_eq!(actual, vec![]);
+}
+
+async fn remove_by_id(repo: Repositories) {
+    repo.user().create(UserId(114514), false).await.unwrap();
+    repo.user().create(UserId(1919), true).await.unwrap();
+    repo.user().create(UserId(810), false).await.unwrap();
+
+    use std::collections::HashSet;
+
+    let result = repo.user().remove_by_id(UserId(1919)).await.unwrap();
+    assert!(result);
+
+    let expect = HashSet::<User>::from_iter([new_user(114514, false), new_user(810, false)]);
+    let actual = repo.user().all().await.unwrap();
+    assert_eq!(expect, actual.into_iter().collect::<HashSet<User>>());
+
+    let result = repo.user().remove_by_id(UserId(1919)).await.unwrap();
+    assert!(!result);
+}
+
+async fn remove_invitation(repo: Repositories) {
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let later = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    let result = repo
+        .user()
+        .invite(UserId(114514), fief_id, None, None, None, later);
+    let id = result.await.unwrap().unwrap();
+    assert!(repo.user().remove_invitation(id).await.unwrap());
+    assert!(!repo.user().remove_invitation(id).await.unwrap());
+
+    // 删除领地或用户时邀请也会被删除
+    let result = repo
+        .user()
+        .invite(UserId(114514), fief_id, None, None, None, later);
+    let id = result.await.unwrap().unwrap();
+    repo.fief().remove_by_id(fief_id).await.unwrap();
+    assert!(!repo.user().remove_invitation(id).await.unwrap());
+
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let result = repo
+        .user()
+        .invite(UserId(114514), fief_id, None, None, None, later);
+    let id = result.await.unwrap().unwrap();
+    repo.user().remove_by_id(UserId(114514)).await.unwrap();
+    assert!(!repo.user().remove_invitation(id).await.unwrap());
+}
+
+async fn remove_expired_invitations(repo: Repositories) {
+    let id1 = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+    let id2 = repo.fief().create("布莉姬特", None).await.unwrap().unwrap();
+    repo.user().create(UserId(114514), false).await.unwrap();
+    let earlier = chrono::Utc::now() - chrono::Duration::hours(1);
+    let later = chrono::Utc::now() + chrono::Duration::hours(1);
+
+    let user = UserId(114514);
+    let expired = repo
+        .user()
+        .invite(user, id1, None, None, None, earlier)
+        .await
+        .unwrap();
+    let pending = repo
+        .user()
+        .invite(user, id2, None, None, None, later)
+        .await
+        .unwrap();
+
+    assert_eq!(repo.user().remove_expired_invitations().await.unwrap(), 1);
+    let result = repo.user().invitation_by_id(expired.unwrap()).await;
+    assert!(matches!(result, Err(RepoError::NotFound)));
+    repo.user()
+        .invitation_by_id(pending.unwrap())
+        .await
+        .unwrap();
+    assert_eq!(repo.user().remove_expired_invitations().await.unwrap(), 0);
+}
+
+repo_tests!(
+    create,
+    user_by_id,
+    all,
+    admins,
+    non_admins,
+    fiefs,
+    is_member_of,
+    permissions_in,
+    effective_permissions_in,
+    fief_role_in,
+    invite,
+    invite_replaces_expired,
+    invitations,
+    set_admin,
+    set_permissions_in,
+    set_fief_role_in,
+    join,
+    leave,
+    remove_by_id,
+    remove_invitation,
+    remove_expired_invitations,
+);
@@ -0,0 +1,148 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use image::{Rgba, RgbaImage};
+use wmonitor::{
+    Repositories,
+    core::{ImagePng, Position},
+    templates::{self, Template},
+};
+
+const RED: Rgba<u8> = Rgba([0xed, 0x1c, 0x24, 0xff]);
+
+fn png(img: RgbaImage) -> Vec<u8> {
+    ImagePng::try_from_rgba(img).unwrap().into_inner()
+}
+
+#[test]
+fn parse_png() {
+    let data = png(RgbaImage::from_pixel(2, 2, RED));
+    let t = templates::parse_png("协会横幅_1023_678_12_345.png", &data).unwrap();
+    assert_eq!(t.name, "协会横幅");
+    assert_eq!(t.origin, Position::new(1023 * 1000 + 12, 678 * 1000 + 345));
+    assert_eq!(t.img.dimensions(), (2, 2));
+
+    templates::parse_png("协会横幅_12_345.png", &data).unwrap_err();
+    templates::parse("协会横幅_1_2_3_4.gif", &data).unwrap_err();
+}
+
+#[test]
+fn parse_blue_marble() {
+    // 放大 3 倍，只有中心像素是模板颜色
+    let mut scaled = RgbaImage::new(6, 3);
+    scaled.put_pixel(1, 1, RED);
+    let json = serde_json::json!({
+        "whoami": "BlueMarble",
+        "templates": {
+            "0 $Z": {
+                "name": "协会横幅",
+                "coords": "1023, 678, 12, 345",
+                "tiles": {
+                    "1023,0678,012,345": STANDARD.encode(png(scaled)),
+                },
+            },
+        },
+    });
+
+    let ts = templates::parse("bm.json", json.to_string().as_bytes()).unwrap();
+    assert_eq!(ts.len(), 1);
+    assert_eq!(ts[0].name, "协会横幅");
+    assert_eq!(
+        ts[0].origin,
+        Position::new(1023 * 1000 + 12, 678 * 1000 + 345)
+    );
+    assert_eq!(ts[0].img.dimensions(), (2, 1));
+    assert_eq!(ts[0].img.get_pixel(0, 0), &RED);
+    assert_eq!(ts[0].img.get_pixel(1, 0).0[3], 0);
+}
+
+#[test]
+fn parse_overlay_pro() {
+    let data = STANDARD.encode(png(RgbaImage::from_pixel(2, 2, RED)));
+    let overlay = serde_json::json!({
+        "name": "协会横幅",
+        "imageBase64": format!("data:image/png;base64,{data}"),
+        "pixelUrl": "https://backend.wplace.live/s0/pixel/1023/678?x=12&y=345",
+        "offsetX": -2,
+        "offsetY": 5,
+    });
+
+    let ts = templates::parse_json(overlay.to_string().as_bytes()).unwrap();
+    assert_eq!(ts.len(), 1);
+    assert_eq!(
+        ts[0].origin,
+        Position::new(1023 * 1000 + 10, 678 * 1000 + 350)
+    );
+
+    let overlays = serde_json::json!({ "overlays": [overlay.clone(), overlay] });
+    let ts = templates::parse_json(overlays.to_string().as_bytes()).unwrap();
+    assert_eq!(ts.len(), 2);
+}
+
+#[test]
+fn split_into_tiles() {
+    let t = Template {
+        name: "协会横幅".to_owned(),
+        origin: Position::new(1999, 2999),
+        img: RgbaImage::from_pixel(2, 2, RED),
+    };
+    let pieces = templates::split_into_tiles(&[t]);
+    let tiles = pieces.iter().map(|p| p.tile).collect::<Vec<_>>();
+    assert_eq!(
+        tiles,
+        [
+            Position::new(1, 2),
+            Position::new(1, 3),
+            Position::new(2, 2),
+            Position::new(2, 3)
+        ]
+    );
+    assert_eq!(pieces[0].ref_.get_pixel(999, 999), &RED);
+    assert_eq!(pieces[0].mask.get_pixel(999, 999).0[0], 0xff);
+    assert_eq!(pieces[0].mask.get_pixel(0, 0).0[0], 0);
+    assert_eq!(pieces[3].ref_.get_pixel(0, 0), &RED);
+}
+
+#[tokio::test]
+async fn import() {
+    let repo = Repositories::from_sqlx("sqlite::memory:").await.unwrap();
+    let fief_id = repo.fief().create("协会横幅", None).await.unwrap().unwrap();
+
+    let t = Template {
+        name: "协会横幅".to_owned(),
+        origin: Position::new(1999, 2000),
+        img: RgbaImage::from_pixel(2, 1, RED),
+    };
+    let imported = templates::import(&repo, fief_id, &[t], None).await.unwrap();
+    assert_eq!(imported.len(), 2);
+    assert!(imported.iter().all(|i| i.created && i.pixels == 1));
+    assert_eq!(repo.fief().chunks(fief_id).await.unwrap().len(), 2);
+
+    let t = Template {
+        name: "协会横幅".to_owned(),
+        origin: Position::new(1000, 2000),
+        img: RgbaImage::from_pixel(1, 1, RED),
+    };
+    let imported = templates::import(&repo, fief_id, &[t], None).await.unwrap();
+    assert_eq!(imported.len(), 1);
+    assert!(!imported[0].created);
+    assert_eq!(repo.fief().chunks(fief_id).await.unwrap().len(), 2);
+
+    let id = imported[0].chunk_id;
+    let mask = repo.chunk().mask_img(id).await.unwrap().unwrap();
+    let mask = mask.try_to_gray().unwrap();
+    assert_eq!(mask.pixels().filter(|px| px.0[0] == 0xff).count(), 2);
+    let ref_ = repo.chunk().ref_img(id).await.unwrap().unwrap();
+    assert_eq!(ref_.try_to_rgba().unwrap().get_pixel(999, 0), &RED);
+
+    // 区块名已被其他位置的区块占用
+    let pos = Position::new(0, 0);
+    repo.chunk().create("3-2", fief_id, pos).await.unwrap();
+    let t = Template {
+        name: "协会横幅".to_owned(),
+        origin: Position::new(3000, 2000),
+        img: RgbaImage::from_pixel(1, 1, RED),
+    };
+    let imported = templates::import(&repo, fief_id, &[t], None).await.unwrap();
+    assert_eq!(imported.len(), 1);
+    assert!(imported[0].created);
+    assert_eq!(imported[0].name, "3-2_2");
+}